@@ -110,6 +110,9 @@ pub trait AirBuilder: Sized {
     }
 }
 
+/// An `AirBuilder` which exposes the public values of the instance, such as a program hash or a
+/// claimed output, so that constraints can refer to them. Provers and verifiers observe the public
+/// values as part of the instance, before any commitment, so challenges depend on them.
 pub trait AirBuilderWithPublicValues: AirBuilder {
     type PublicVar: Into<Self::Expr> + Copy;
 
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use core::fmt::{self, Debug, Display, Formatter};
 use core::iter::{Product, Sum};
 use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
@@ -87,7 +88,14 @@ impl Serialize for BabyBear {
 impl<'de> Deserialize<'de> for BabyBear {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
         let val = u32::deserialize(d)?;
-        Ok(BabyBear::from_canonical_u32(val))
+        // Reject unreduced values, so that each element has a single encoding.
+        if val < P {
+            Ok(BabyBear::from_canonical_u32(val))
+        } else {
+            Err(serde::de::Error::custom(
+                "value is not less than the field order",
+            ))
+        }
     }
 }
 
@@ -218,7 +226,9 @@ impl Field for BabyBear {
     }
 
     fn try_inverse(&self) -> Option<Self> {
-        if self.is_zero() {
+        // With the `ct` feature, zero is only rejected after the exponentiation, so that the
+        // running time doesn't reveal whether `self` is zero.
+        if !cfg!(feature = "ct") && self.is_zero() {
             return None;
         }
 
@@ -244,7 +254,7 @@ impl Field for BabyBear {
         let p1110111111111111111111111111111 =
             p1110000111100001111000011110000 * p111000011110000111100001111;
 
-        Some(p1110111111111111111111111111111)
+        (!self.is_zero()).then_some(p1110111111111111111111111111111)
     }
 
     #[inline]
@@ -264,6 +274,19 @@ impl PrimeField for BabyBear {
     fn as_canonical_biguint(&self) -> BigUint {
         <Self as PrimeField32>::as_canonical_u32(self).into()
     }
+
+    fn num_canonical_bytes() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.as_canonical_u32().to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u32::from_le_bytes(bytes.try_into().ok()?);
+        (value < Self::ORDER_U32).then(|| Self::from_canonical_u32(value))
+    }
 }
 
 impl PrimeField64 for BabyBear {
@@ -330,7 +353,9 @@ impl Add for BabyBear {
     fn add(self, rhs: Self) -> Self {
         let mut sum = self.value + rhs.value;
         let (corr_sum, over) = sum.overflowing_sub(P);
-        if !over {
+        if cfg!(feature = "ct") {
+            sum = corr_sum.wrapping_add(correction(over));
+        } else if !over {
             sum = corr_sum;
         }
         Self { value: sum }
@@ -364,8 +389,7 @@ impl Sub for BabyBear {
     #[inline]
     fn sub(self, rhs: Self) -> Self {
         let (mut diff, over) = self.value.overflowing_sub(rhs.value);
-        let corr = if over { P } else { 0 };
-        diff = diff.wrapping_add(corr);
+        diff = diff.wrapping_add(correction(over));
         BabyBear { value: diff }
     }
 }
@@ -466,15 +490,27 @@ pub(crate) const fn monty_reduce(x: u64) -> u32 {
 
     let (x_sub_u, over) = x.overflowing_sub(u);
     let x_sub_u_hi = (x_sub_u >> MONTY_BITS) as u32;
-    let corr = if over { P } else { 0 };
-    x_sub_u_hi.wrapping_add(corr)
+    x_sub_u_hi.wrapping_add(correction(over))
+}
+
+/// `P` if `over`, and `0` otherwise. With the `ct` feature, this is computed with a mask, so that
+/// it can't be compiled to a branch on (possibly secret) field data.
+#[inline(always)]
+const fn correction(over: bool) -> u32 {
+    if cfg!(feature = "ct") {
+        P & (over as u32).wrapping_neg()
+    } else if over {
+        P
+    } else {
+        0
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use core::array;
 
-    use p3_field_testing::{test_field, test_two_adic_field};
+    use p3_field_testing::{test_field, test_prime_field, test_two_adic_field};
 
     use super::*;
 
@@ -583,8 +619,13 @@ mod tests {
         let m2_serialized = serde_json::to_string(&m2).unwrap();
         let m2_deserialized: F = serde_json::from_str(&m2_serialized).unwrap();
         assert_eq!(m2, m2_deserialized);
+
+        // Unreduced values are rejected, rather than reduced.
+        assert!(serde_json::from_str::<F>(&P.to_string()).is_err());
+        assert!(serde_json::from_str::<F>(&u32::MAX.to_string()).is_err());
     }
 
     test_field!(crate::BabyBear);
+    test_prime_field!(crate::BabyBear);
     test_two_adic_field!(crate::BabyBear);
 }
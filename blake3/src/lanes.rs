@@ -0,0 +1,171 @@
+//! BLAKE3 over several inputs at once, one per lane, for inputs made of 32-bit words.
+//!
+//! All lanes see inputs of the same length, so they go through the same sequence of compressions,
+//! and each operation below acts on `[u32; N]`, one word from each lane, in a way the compiler can
+//! vectorize.
+
+use alloc::vec::Vec;
+use core::array;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+const BLOCK_WORDS: usize = 16;
+const CHUNK_BLOCKS: usize = 16;
+
+type Lanes<const N: usize> = [u32; N];
+
+#[inline(always)]
+fn splat<const N: usize>(x: u32) -> Lanes<N> {
+    [x; N]
+}
+
+#[inline(always)]
+fn add<const N: usize>(a: Lanes<N>, b: Lanes<N>) -> Lanes<N> {
+    array::from_fn(|i| a[i].wrapping_add(b[i]))
+}
+
+#[inline(always)]
+fn xor_rotate_right<const N: usize>(a: Lanes<N>, b: Lanes<N>, n: u32) -> Lanes<N> {
+    array::from_fn(|i| (a[i] ^ b[i]).rotate_right(n))
+}
+
+#[inline(always)]
+fn g<const N: usize>(
+    state: &mut [Lanes<N>; 16],
+    (a, b, c, d): (usize, usize, usize, usize),
+    mx: Lanes<N>,
+    my: Lanes<N>,
+) {
+    state[a] = add(add(state[a], state[b]), mx);
+    state[d] = xor_rotate_right(state[d], state[a], 16);
+    state[c] = add(state[c], state[d]);
+    state[b] = xor_rotate_right(state[b], state[c], 12);
+    state[a] = add(add(state[a], state[b]), my);
+    state[d] = xor_rotate_right(state[d], state[a], 8);
+    state[c] = add(state[c], state[d]);
+    state[b] = xor_rotate_right(state[b], state[c], 7);
+}
+
+/// The BLAKE3 compression function, truncated to the chaining value.
+#[inline(always)]
+fn compress<const N: usize>(
+    cv: &[Lanes<N>; 8],
+    block: &[Lanes<N>; BLOCK_WORDS],
+    counter: u64,
+    block_len: usize,
+    flags: u32,
+) -> [Lanes<N>; 8] {
+    let mut state = [
+        cv[0],
+        cv[1],
+        cv[2],
+        cv[3],
+        cv[4],
+        cv[5],
+        cv[6],
+        cv[7],
+        splat(IV[0]),
+        splat(IV[1]),
+        splat(IV[2]),
+        splat(IV[3]),
+        splat(counter as u32),
+        splat((counter >> 32) as u32),
+        splat(block_len as u32),
+        splat(flags),
+    ];
+    let mut m = *block;
+    for round in 0..7 {
+        g(&mut state, (0, 4, 8, 12), m[0], m[1]);
+        g(&mut state, (1, 5, 9, 13), m[2], m[3]);
+        g(&mut state, (2, 6, 10, 14), m[4], m[5]);
+        g(&mut state, (3, 7, 11, 15), m[6], m[7]);
+        g(&mut state, (0, 5, 10, 15), m[8], m[9]);
+        g(&mut state, (1, 6, 11, 12), m[10], m[11]);
+        g(&mut state, (2, 7, 8, 13), m[12], m[13]);
+        g(&mut state, (3, 4, 9, 14), m[14], m[15]);
+        if round < 6 {
+            m = array::from_fn(|i| m[MSG_PERMUTATION[i]]);
+        }
+    }
+    array::from_fn(|i| array::from_fn(|lane| state[i][lane] ^ state[i + 8][lane]))
+}
+
+/// The BLAKE3 hash of each lane's input, given a word from each lane at a time, as the eight
+/// little-endian words of each digest.
+pub(crate) fn hash_lanes<const N: usize, I>(input: I) -> [Lanes<N>; 8]
+where
+    I: IntoIterator<Item = Lanes<N>>,
+{
+    let iv = IV.map(splat);
+    let mut cv_stack: Vec<[Lanes<N>; 8]> = Vec::new();
+    let mut chunk_counter = 0;
+    let mut chunk_cv = iv;
+    let mut blocks_compressed = 0;
+    let mut block = [splat(0); BLOCK_WORDS];
+    let mut block_words = 0;
+
+    let start_flag = |blocks_compressed: usize| {
+        if blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    };
+
+    for word in input {
+        // A full block followed by more input isn't the last block, so it can be compressed now.
+        if block_words == BLOCK_WORDS {
+            let flags = start_flag(blocks_compressed);
+            if blocks_compressed == CHUNK_BLOCKS - 1 {
+                // The chunk is complete. Merge it with the completed subtrees it completes.
+                let mut cv = compress(&chunk_cv, &block, chunk_counter, 64, flags | CHUNK_END);
+                chunk_counter += 1;
+                let mut total_chunks = chunk_counter;
+                while total_chunks & 1 == 0 {
+                    let left = cv_stack.pop().unwrap();
+                    cv = compress(&iv, &concat(left, cv), 0, 64, PARENT);
+                    total_chunks >>= 1;
+                }
+                cv_stack.push(cv);
+                chunk_cv = iv;
+                blocks_compressed = 0;
+            } else {
+                chunk_cv = compress(&chunk_cv, &block, chunk_counter, 64, flags);
+                blocks_compressed += 1;
+            }
+            block = [splat(0); BLOCK_WORDS];
+            block_words = 0;
+        }
+        block[block_words] = word;
+        block_words += 1;
+    }
+
+    // Compress the last block, and then the parents on the path from its chunk to the root, with
+    // the root flag on the last of these compressions.
+    let mut cv = chunk_cv;
+    let mut counter = chunk_counter;
+    let mut block_len = block_words * 4;
+    let mut flags = start_flag(blocks_compressed) | CHUNK_END;
+    while let Some(left) = cv_stack.pop() {
+        let right = compress(&cv, &block, counter, block_len, flags);
+        cv = iv;
+        block = concat(left, right);
+        counter = 0;
+        block_len = 64;
+        flags = PARENT;
+    }
+    compress(&cv, &block, counter, block_len, flags | ROOT)
+}
+
+fn concat<const N: usize>(left: [Lanes<N>; 8], right: [Lanes<N>; 8]) -> [Lanes<N>; BLOCK_WORDS] {
+    array::from_fn(|i| if i < 8 { left[i] } else { right[i - 8] })
+}
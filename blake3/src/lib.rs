@@ -8,10 +8,35 @@ use alloc::vec::Vec;
 
 use p3_symmetric::CryptographicHasher;
 
+mod lanes;
+
 /// The blake3 hash function.
+///
+/// Besides bytes, this hashes sequences of 32-bit words, taken as their little-endian bytes, to
+/// digests of eight words. It can hash `N` such inputs at once, given as `[u32; N]`, one word from
+/// each input at a time, which is how a Merkle tree whose digests are packed as `[u32; N]` hashes
+/// `N` leaves at a time.
 #[derive(Copy, Clone, Debug)]
 pub struct Blake3;
 
+impl Blake3 {
+    /// The digests of `N` inputs of the same length, hashed together, one word from each input at a
+    /// time.
+    pub fn hash_iter_batched<const N: usize, I>(&self, input: I) -> [[u8; 32]; N]
+    where
+        I: IntoIterator<Item = [u32; N]>,
+    {
+        let digests = lanes::hash_lanes(input);
+        core::array::from_fn(|lane| {
+            let mut digest = [0; 32];
+            for (bytes, word) in digest.chunks_exact_mut(4).zip(digests) {
+                bytes.copy_from_slice(&word[lane].to_le_bytes());
+            }
+            digest
+        })
+    }
+}
+
 impl CryptographicHasher<u8, [u8; 32]> for Blake3 {
     fn hash_iter<I>(&self, input: I) -> [u8; 32]
     where
@@ -32,3 +57,59 @@ impl CryptographicHasher<u8, [u8; 32]> for Blake3 {
         hasher.finalize().into()
     }
 }
+
+impl CryptographicHasher<u32, [u32; 8]> for Blake3 {
+    fn hash_iter<I>(&self, input: I) -> [u32; 8]
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        let digest: [u8; 32] = self.hash_iter(input.into_iter().flat_map(u32::to_le_bytes));
+        core::array::from_fn(|i| u32::from_le_bytes(digest[i * 4..][..4].try_into().unwrap()))
+    }
+}
+
+impl<const N: usize> CryptographicHasher<[u32; N], [[u32; N]; 8]> for Blake3 {
+    fn hash_iter<I>(&self, input: I) -> [[u32; N]; 8]
+    where
+        I: IntoIterator<Item = [u32; N]>,
+    {
+        lanes::hash_lanes(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batched_matches_single() {
+        const N: usize = 4;
+        // Lengths around the block and chunk boundaries, up to several chunks.
+        for len in [0, 3, 16, 17, 255, 256, 257, 600, 1024, 1100] {
+            let inputs: [Vec<u32>; N] = core::array::from_fn(|lane| {
+                (0..len)
+                    .map(|i| (i as u32).wrapping_mul(0x9e37_79b9) ^ lane as u32)
+                    .collect()
+            });
+            let batched =
+                Blake3.hash_iter_batched((0..len).map(|i| inputs.each_ref().map(|input| input[i])));
+            for (input, digest) in inputs.iter().zip(batched) {
+                let bytes = input
+                    .iter()
+                    .flat_map(|word| word.to_le_bytes())
+                    .collect::<Vec<_>>();
+                assert_eq!(digest, *blake3::hash(&bytes).as_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn packed_words_match_scalar_words() {
+        let input: Vec<[u32; 2]> = (0..40).map(|i| [i, i * 7]).collect();
+        let packed: [[u32; 2]; 8] = Blake3.hash_iter(input.iter().copied());
+        for lane in 0..2 {
+            let scalar: [u32; 8] = Blake3.hash_iter(input.iter().map(|words| words[lane]));
+            assert_eq!(packed.map(|words| words[lane]), scalar);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! The scalar field of the BN254 curve, defined as `F_r` where `r = 21888242871839275222246405745257275088548364400416034343698204186575808495617`.
 
+mod poseidon;
 mod poseidon2;
 
 use core::fmt;
@@ -10,7 +11,8 @@ use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use ff::{Field as FFField, PrimeField as FFPrimeField, PrimeFieldBits};
 use num_bigint::BigUint;
-use p3_field::{AbstractField, Field, Packable, PrimeField};
+use p3_field::{AbstractField, Embeds, Field, Packable, PrimeField, PrimeField64, TwoAdicField};
+pub use poseidon::{poseidon_bn254_t3, poseidon_bn254_t4, MdsMatrixBN254, PoseidonBN254};
 pub use poseidon2::DiffusionMatrixBN254;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
@@ -207,6 +209,47 @@ impl PrimeField for Bn254Fr {
         let le_bytes = repr.as_ref();
         BigUint::from_bytes_le(le_bytes)
     }
+
+    fn num_canonical_bytes() -> usize {
+        32
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.value.to_repr().as_ref().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut repr = <FFBn254Fr as FFPrimeField>::Repr::default();
+        if bytes.len() != repr.as_ref().len() {
+            return None;
+        }
+        repr.as_mut().copy_from_slice(bytes);
+        let value = FFBn254Fr::from_repr(repr);
+
+        if value.is_some().into() {
+            Some(Self::new(value.unwrap()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Every prime field of order less than `2^64` embeds in BN254's scalar field.
+impl<F: PrimeField64> Embeds<F> for Bn254Fr {
+    fn embed(value: F) -> Self {
+        Self::from_canonical_u64(value.as_canonical_u64())
+    }
+}
+
+impl TwoAdicField for Bn254Fr {
+    const TWO_ADICITY: usize = FFBn254Fr::S as usize;
+
+    fn two_adic_generator(bits: usize) -> Self {
+        assert!(bits <= Self::TWO_ADICITY);
+        // `ROOT_OF_UNITY` generates the whole 2^TWO_ADICITY group.
+        let base = Self::new(FFBn254Fr::ROOT_OF_UNITY);
+        base.exp_power_of_2(Self::TWO_ADICITY - bits)
+    }
 }
 
 impl Add for Bn254Fr {
@@ -290,7 +333,7 @@ impl Distribution<Bn254Fr> for Standard {
 #[cfg(test)]
 mod tests {
     use num_traits::One;
-    use p3_field_testing::test_field;
+    use p3_field_testing::{test_field, test_prime_field, test_two_adic_field};
 
     use super::*;
 
@@ -382,4 +425,6 @@ mod tests {
     }
 
     test_field!(crate::Bn254Fr);
+    test_prime_field!(crate::Bn254Fr);
+    test_two_adic_field!(crate::Bn254Fr);
 }
@@ -1,9 +1,11 @@
-use p3_field::{Field, PrimeField, PrimeField32, PrimeField64};
+use p3_field::{AbstractField, Field, PrimeField, PrimeField32, PrimeField64};
 use p3_maybe_rayon::prelude::*;
 use p3_symmetric::CryptographicPermutation;
 use tracing::instrument;
 
-use crate::{CanObserve, CanSampleBits, DuplexChallenger, MultiField32Challenger};
+use crate::{
+    CanObserve, CanSampleBits, DuplexChallenger, MultiField32Challenger, MultiField64Challenger,
+};
 
 pub trait GrindingChallenger:
     CanObserve<Self::Witness> + CanSampleBits<usize> + Sync + Clone
@@ -19,6 +21,24 @@ pub trait GrindingChallenger:
     }
 }
 
+/// Find a proof-of-work witness for `challenger` by trying every field element, in parallel, then
+/// observe it. This is how the challengers in this crate implement `grind`, and suits any other
+/// challenger whose witnesses are elements of a `PrimeField64`.
+#[instrument(name = "grind for proof-of-work witness", skip_all)]
+pub fn grind_by_search<C>(challenger: &mut C, bits: usize) -> C::Witness
+where
+    C: GrindingChallenger,
+    C::Witness: PrimeField64,
+{
+    let witness = (0..<C::Witness as PrimeField64>::ORDER_U64)
+        .into_par_iter()
+        .map(<C::Witness as AbstractField>::from_canonical_u64)
+        .find_any(|witness| challenger.clone().check_witness(bits, *witness))
+        .expect("failed to find witness");
+    assert!(challenger.check_witness(bits, witness));
+    witness
+}
+
 impl<F, P, const WIDTH: usize, const RATE: usize> GrindingChallenger
     for DuplexChallenger<F, P, WIDTH, RATE>
 where
@@ -27,15 +47,8 @@ where
 {
     type Witness = F;
 
-    #[instrument(name = "grind for proof-of-work witness", skip_all)]
     fn grind(&mut self, bits: usize) -> Self::Witness {
-        let witness = (0..F::ORDER_U64)
-            .into_par_iter()
-            .map(|i| F::from_canonical_u64(i))
-            .find_any(|witness| self.clone().check_witness(bits, *witness))
-            .expect("failed to find witness");
-        assert!(self.check_witness(bits, witness));
-        witness
+        grind_by_search(self, bits)
     }
 }
 
@@ -47,14 +60,46 @@ where
 {
     type Witness = F;
 
-    #[instrument(name = "grind for proof-of-work witness", skip_all)]
     fn grind(&mut self, bits: usize) -> Self::Witness {
-        let witness = (0..F::ORDER_U64)
-            .into_par_iter()
-            .map(F::from_canonical_u64)
-            .find_any(|witness| self.clone().check_witness(bits, *witness))
-            .expect("failed to find witness");
-        assert!(self.check_witness(bits, witness));
-        witness
+        grind_by_search(self, bits)
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize> GrindingChallenger for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    type Witness = F;
+
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        grind_by_search(self, bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_keccak::Keccak256Hash;
+
+    use super::*;
+    use crate::{CanSample, SerializingChallenger32};
+
+    #[test]
+    fn grinding_finds_a_checkable_witness() {
+        let mut prover = SerializingChallenger32::<BabyBear, _>::from_hasher(vec![], Keccak256Hash);
+        prover.observe(BabyBear::from_canonical_u32(42));
+        let mut verifier = prover.clone();
+
+        let witness = prover.grind(8);
+        assert!(verifier.check_witness(8, witness));
+
+        // Both observed the witness, so their transcripts continue in step.
+        let prover_sample: BabyBear = prover.sample();
+        let verifier_sample: BabyBear = verifier.sample();
+        assert_eq!(prover_sample, verifier_sample);
     }
 }
@@ -7,6 +7,7 @@ extern crate alloc;
 mod duplex_challenger;
 mod grinding_challenger;
 mod hash_challenger;
+mod logging_challenger;
 mod multi_field_challenger;
 mod serializing_challenger;
 
@@ -16,8 +17,10 @@ use core::array;
 pub use duplex_challenger::*;
 pub use grinding_challenger::*;
 pub use hash_challenger::*;
+pub use logging_challenger::*;
 pub use multi_field_challenger::*;
-use p3_field::{AbstractExtensionField, Field};
+use p3_field::{AbstractExtensionField, AbstractField, Field};
+use p3_matrix::Dimensions;
 pub use serializing_challenger::*;
 
 pub trait CanObserve<T> {
@@ -60,6 +63,44 @@ pub trait FieldChallenger<F: Field>:
         let vec = self.sample_vec(EF::D);
         EF::from_base_slice(&vec)
     }
+
+    /// Observe `value` as four 16-bit limbs, least significant first, so that its encoding has
+    /// the same width in any field, whatever its size.
+    fn observe_u64(&mut self, value: u64) {
+        for i in 0..4 {
+            self.observe(F::from_canonical_u16((value >> (16 * i)) as u16));
+        }
+    }
+
+    /// Observe `value` as a `u64`, see `observe_u64`.
+    fn observe_usize(&mut self, value: usize) {
+        self.observe_u64(value as u64);
+    }
+
+    /// Observe the dimensions of a matrix, width first.
+    fn observe_dimensions(&mut self, dims: Dimensions) {
+        self.observe_usize(dims.width);
+        self.observe_usize(dims.height);
+    }
+
+    /// Observe a domain separator, e.g. the name of a protocol phase, so that transcripts of
+    /// different phases or proofs can't collide. The separator is observed as its length followed
+    /// by its bytes, one field element each, so no separator is a prefix of another.
+    fn observe_domain_separator(&mut self, domain: &[u8]) {
+        self.observe(F::from_canonical_usize(domain.len()));
+        for &byte in domain {
+            self.observe(F::from_canonical_u8(byte));
+        }
+    }
+
+    /// Observe `value`, labelled with the domain separator `domain`.
+    fn observe_tagged<T>(&mut self, domain: &[u8], value: T)
+    where
+        Self: CanObserve<T>,
+    {
+        self.observe_domain_separator(domain);
+        CanObserve::<T>::observe(self, value);
+    }
 }
 
 impl<'a, C, T> CanObserve<T> for &'a mut C
@@ -123,4 +164,93 @@ where
     fn sample_ext_element<EF: AbstractExtensionField<F>>(&mut self) -> EF {
         (**self).sample_ext_element()
     }
+
+    #[inline(always)]
+    fn observe_u64(&mut self, value: u64) {
+        (**self).observe_u64(value)
+    }
+
+    #[inline(always)]
+    fn observe_usize(&mut self, value: usize) {
+        (**self).observe_usize(value)
+    }
+
+    #[inline(always)]
+    fn observe_dimensions(&mut self, dims: Dimensions) {
+        (**self).observe_dimensions(dims)
+    }
+
+    #[inline(always)]
+    fn observe_domain_separator(&mut self, domain: &[u8]) {
+        (**self).observe_domain_separator(domain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_keccak::Keccak256Hash;
+
+    use super::*;
+
+    type F = BabyBear;
+
+    fn challenger() -> SerializingChallenger32<F, HashChallenger<u8, Keccak256Hash, 32>> {
+        SerializingChallenger32::from_hasher(vec![], Keccak256Hash)
+    }
+
+    /// Sample after observing `1`, tagged with each of `domains` in turn.
+    fn sample_after_tags(domains: &[&[u8]]) -> F {
+        let mut challenger = challenger();
+        for domain in domains {
+            challenger.observe_tagged(domain, F::one());
+        }
+        challenger.sample()
+    }
+
+    #[test]
+    fn integers_are_observed_as_fixed_width_limbs() {
+        let mut limbs = challenger();
+        for limb in [0x5678, 0x1234, 0, 0] {
+            limbs.observe(F::from_canonical_u32(limb));
+        }
+        let expected: F = limbs.sample();
+
+        let mut value = challenger();
+        value.observe_usize(0x1234_5678);
+        let actual: F = value.sample();
+        assert_eq!(actual, expected);
+
+        let mut dims = challenger();
+        dims.observe_dimensions(Dimensions {
+            width: 3,
+            height: 1 << 20,
+        });
+        let mut explicit = challenger();
+        explicit.observe_u64(3);
+        explicit.observe_u64(1 << 20);
+        let dims_sample: F = dims.sample();
+        let explicit_sample: F = explicit.sample();
+        assert_eq!(dims_sample, explicit_sample);
+    }
+
+    #[test]
+    fn domain_separators_separate_transcripts() {
+        let commit = sample_after_tags(&[b"commit"]);
+        assert_ne!(commit, sample_after_tags(&[b"opening"]));
+
+        // Length prefixes keep the boundaries between separators and values unambiguous.
+        assert_ne!(
+            sample_after_tags(&[b"ab", b"c"]),
+            sample_after_tags(&[b"a", b"bc"])
+        );
+
+        let mut explicit = challenger();
+        explicit.observe_domain_separator(b"commit");
+        explicit.observe(F::one());
+        let explicit: F = explicit.sample();
+        assert_eq!(commit, explicit);
+    }
 }
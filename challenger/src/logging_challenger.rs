@@ -0,0 +1,288 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use p3_field::{AbstractExtensionField, Field};
+
+use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger};
+
+/// One interaction with a challenger, labelled with the protocol phase it happened in.
+///
+/// Values are kept in their `Debug` form, so that observations of any type can be logged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptEvent {
+    Observe {
+        label: &'static str,
+        value: String,
+    },
+    Sample {
+        label: &'static str,
+        value: String,
+    },
+    SampleBits {
+        label: &'static str,
+        bits: usize,
+        value: usize,
+    },
+}
+
+/// Where a `LoggingChallenger` sends its events.
+pub trait TranscriptLog {
+    fn log(&mut self, event: TranscriptEvent);
+}
+
+/// Keeps every event, e.g. of a prover's transcript.
+#[derive(Clone, Debug, Default)]
+pub struct TranscriptRecording {
+    pub events: Vec<TranscriptEvent>,
+}
+
+impl TranscriptLog for TranscriptRecording {
+    fn log(&mut self, event: TranscriptEvent) {
+        self.events.push(event);
+    }
+}
+
+/// Checks each event against a recording, e.g. checking a verifier's transcript against the
+/// prover's, and panics at the first one which differs.
+#[derive(Clone, Debug)]
+pub struct TranscriptReplay {
+    expected: Vec<TranscriptEvent>,
+    position: usize,
+}
+
+impl TranscriptLog for TranscriptReplay {
+    fn log(&mut self, event: TranscriptEvent) {
+        match self.expected.get(self.position) {
+            Some(expected) => assert_eq!(
+                &event, expected,
+                "transcript diverged from the recording at event {}",
+                self.position
+            ),
+            None => panic!(
+                "transcript continued past the end of the recording, with {:?}",
+                event
+            ),
+        }
+        self.position += 1;
+    }
+}
+
+/// Wraps a challenger, logging every observation and sample it makes.
+///
+/// To find where a prover and verifier's transcripts diverge, run the prover with a
+/// `RecordingChallenger`, then the verifier with a `ReplayChallenger` of its events, which panics
+/// at the first difference. `set_label` names the current protocol phase in the events.
+#[derive(Clone, Debug)]
+pub struct LoggingChallenger<Inner, Log> {
+    inner: Inner,
+    log: Log,
+    label: &'static str,
+}
+
+pub type RecordingChallenger<Inner> = LoggingChallenger<Inner, TranscriptRecording>;
+
+pub type ReplayChallenger<Inner> = LoggingChallenger<Inner, TranscriptReplay>;
+
+impl<Inner> RecordingChallenger<Inner> {
+    pub fn new(inner: Inner) -> Self {
+        Self {
+            inner,
+            log: TranscriptRecording::default(),
+            label: "",
+        }
+    }
+
+    pub fn events(&self) -> &[TranscriptEvent] {
+        &self.log.events
+    }
+
+    pub fn into_events(self) -> Vec<TranscriptEvent> {
+        self.log.events
+    }
+}
+
+impl<Inner> ReplayChallenger<Inner> {
+    pub fn new(inner: Inner, expected: Vec<TranscriptEvent>) -> Self {
+        Self {
+            inner,
+            log: TranscriptReplay {
+                expected,
+                position: 0,
+            },
+            label: "",
+        }
+    }
+
+    /// Check that the whole recording was replayed.
+    pub fn finish(self) {
+        assert_eq!(
+            self.log.position,
+            self.log.expected.len(),
+            "transcript ended before the recording did"
+        );
+    }
+}
+
+impl<Inner, Log> LoggingChallenger<Inner, Log> {
+    /// Label the events from now on with `label`.
+    pub fn set_label(&mut self, label: &'static str) {
+        self.label = label;
+    }
+
+    pub fn into_inner(self) -> Inner {
+        self.inner
+    }
+}
+
+impl<T, Inner, Log> CanObserve<T> for LoggingChallenger<Inner, Log>
+where
+    T: Debug,
+    Inner: CanObserve<T>,
+    Log: TranscriptLog,
+{
+    fn observe(&mut self, value: T) {
+        self.log.log(TranscriptEvent::Observe {
+            label: self.label,
+            value: format!("{:?}", value),
+        });
+        self.inner.observe(value);
+    }
+}
+
+impl<T, Inner, Log> CanSample<T> for LoggingChallenger<Inner, Log>
+where
+    T: Debug,
+    Inner: CanSample<T>,
+    Log: TranscriptLog,
+{
+    fn sample(&mut self) -> T {
+        let value = self.inner.sample();
+        self.log.log(TranscriptEvent::Sample {
+            label: self.label,
+            value: format!("{:?}", value),
+        });
+        value
+    }
+}
+
+impl<Inner, Log> CanSampleBits<usize> for LoggingChallenger<Inner, Log>
+where
+    Inner: CanSampleBits<usize>,
+    Log: TranscriptLog,
+{
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        let value = self.inner.sample_bits(bits);
+        self.log.log(TranscriptEvent::SampleBits {
+            label: self.label,
+            bits,
+            value,
+        });
+        value
+    }
+}
+
+impl<F, Inner, Log> FieldChallenger<F> for LoggingChallenger<Inner, Log>
+where
+    F: Field,
+    Inner: FieldChallenger<F>,
+    Log: TranscriptLog + Sync,
+{
+    // Log extension elements whole, rather than as their base field coefficients.
+    fn observe_ext_element<EF: AbstractExtensionField<F>>(&mut self, ext: EF) {
+        self.log.log(TranscriptEvent::Observe {
+            label: self.label,
+            value: format!("{:?}", ext.as_base_slice()),
+        });
+        self.inner.observe_ext_element(ext);
+    }
+
+    fn sample_ext_element<EF: AbstractExtensionField<F>>(&mut self) -> EF {
+        let value: EF = self.inner.sample_ext_element();
+        self.log.log(TranscriptEvent::Sample {
+            label: self.label,
+            value: format!("{:?}", value.as_base_slice()),
+        });
+        value
+    }
+}
+
+impl<Inner, Log> GrindingChallenger for LoggingChallenger<Inner, Log>
+where
+    Inner: GrindingChallenger,
+    Log: TranscriptLog + Clone + Sync,
+{
+    type Witness = Inner::Witness;
+
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        let witness = self.inner.grind(bits);
+        // Log what the verifier's `check_witness` will.
+        self.log.log(TranscriptEvent::Observe {
+            label: self.label,
+            value: format!("{:?}", witness),
+        });
+        self.log.log(TranscriptEvent::SampleBits {
+            label: self.label,
+            bits,
+            value: 0,
+        });
+        witness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+    use p3_keccak::Keccak256Hash;
+
+    use super::*;
+    use crate::{HashChallenger, SerializingChallenger32};
+
+    type F = BabyBear;
+    type Inner = SerializingChallenger32<F, HashChallenger<u8, Keccak256Hash, 32>>;
+
+    fn inner() -> Inner {
+        SerializingChallenger32::from_hasher(vec![], Keccak256Hash)
+    }
+
+    fn transcript(challenger: &mut impl FieldChallenger<F>, observed: u32) -> F {
+        challenger.observe(F::from_canonical_u32(observed));
+        challenger.sample_bits(4);
+        challenger.sample()
+    }
+
+    #[test]
+    fn replay_accepts_matching_transcript() {
+        let mut prover = RecordingChallenger::new(inner());
+        prover.set_label("commit");
+        let alpha = transcript(&mut prover, 1);
+        let events = prover.into_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[2],
+            TranscriptEvent::Sample {
+                label: "commit",
+                value: format!("{:?}", alpha),
+            }
+        );
+
+        let mut verifier = ReplayChallenger::new(inner(), events);
+        verifier.set_label("commit");
+        assert_eq!(transcript(&mut verifier, 1), alpha);
+        verifier.finish();
+    }
+
+    #[test]
+    #[should_panic(expected = "transcript diverged from the recording at event 0")]
+    fn replay_rejects_diverging_transcript() {
+        let mut prover = RecordingChallenger::new(inner());
+        transcript(&mut prover, 1);
+
+        let mut verifier = ReplayChallenger::new(inner(), prover.into_events());
+        transcript(&mut verifier, 2);
+    }
+}
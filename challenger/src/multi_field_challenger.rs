@@ -2,8 +2,11 @@ use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 
-use p3_field::{reduce_32, split_32, ExtensionField, Field, PrimeField, PrimeField32};
-use p3_symmetric::{CryptographicPermutation, Hash};
+use p3_field::{
+    reduce_32, reduce_64, split_32, split_64, ExtensionField, Field, PrimeField, PrimeField32,
+    PrimeField64,
+};
+use p3_symmetric::{CryptographicPermutation, Hash, MerkleCap};
 
 use crate::{CanObserve, CanSample, CanSampleBits, FieldChallenger};
 
@@ -132,6 +135,23 @@ where
     }
 }
 
+impl<F, PF, const N: usize, P, const WIDTH: usize> CanObserve<MerkleCap<F, PF, N>>
+    for MultiField32Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, values: MerkleCap<F, PF, N>) {
+        for pf_val in values {
+            let f_vals: Vec<F> = split_32(pf_val, self.num_f_elms);
+            for f_val in f_vals {
+                self.observe(f_val);
+            }
+        }
+    }
+}
+
 // for TrivialPcs
 impl<F, PF, P, const WIDTH: usize> CanObserve<Vec<Vec<F>>>
     for MultiField32Challenger<F, PF, P, WIDTH>
@@ -185,3 +205,260 @@ where
         rand_usize & ((1 << bits) - 1)
     }
 }
+
+/// Like `MultiField32Challenger`, but producing challenges of a 64-bit field such as Goldilocks.
+///
+/// Each sponge element absorbs as many 64-bit elements as fit below its modulus, e.g. three per
+/// BN254 scalar, so that a SNARK verifying the STARK can hash its transcript cheaply.
+///
+/// SAFETY: As with `MultiField32Challenger`, samples are random in [0, 2^64) and then reduced to
+/// be in F, which biases them by about 1/2^32 for Goldilocks.
+#[derive(Clone, Debug)]
+pub struct MultiField64Challenger<F, PF, P, const WIDTH: usize>
+where
+    F: PrimeField64,
+    PF: Field,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    sponge_state: [PF; WIDTH],
+    input_buffer: Vec<F>,
+    output_buffer: Vec<F>,
+    permutation: P,
+    num_f_elms: usize,
+}
+
+impl<F, PF, P, const WIDTH: usize> MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: Field,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    pub fn new(permutation: P) -> Result<Self, String> {
+        // Packed elements must stay below the modulus, so each sponge element takes strictly
+        // fewer than `PF::bits()` bits of them.
+        let num_f_elms = (PF::bits() - 1) / 64;
+        if num_f_elms == 0 {
+            return Err(String::from("PF must be larger than 2^64"));
+        }
+        Ok(Self {
+            sponge_state: [PF::default(); WIDTH],
+            input_buffer: vec![],
+            output_buffer: vec![],
+            permutation,
+            num_f_elms,
+        })
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize> MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn duplexing(&mut self) {
+        assert!(self.input_buffer.len() <= self.num_f_elms * WIDTH);
+
+        for (i, f_chunk) in self.input_buffer.chunks(self.num_f_elms).enumerate() {
+            self.sponge_state[i] = reduce_64(f_chunk);
+        }
+        self.input_buffer.clear();
+
+        // Apply the permutation.
+        self.permutation.permute_mut(&mut self.sponge_state);
+
+        self.output_buffer.clear();
+        for &pf_val in self.sponge_state.iter() {
+            let f_vals = split_64(pf_val, self.num_f_elms);
+            for f_val in f_vals {
+                self.output_buffer.push(f_val);
+            }
+        }
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize> FieldChallenger<F> for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+}
+
+impl<F, PF, P, const WIDTH: usize> CanObserve<F> for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, value: F) {
+        // Any buffered output is now invalid.
+        self.output_buffer.clear();
+
+        self.input_buffer.push(value);
+
+        if self.input_buffer.len() == self.num_f_elms * WIDTH {
+            self.duplexing();
+        }
+    }
+}
+
+impl<F, PF, const N: usize, P, const WIDTH: usize> CanObserve<[F; N]>
+    for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, values: [F; N]) {
+        for value in values {
+            self.observe(value);
+        }
+    }
+}
+
+impl<F, PF, const N: usize, P, const WIDTH: usize> CanObserve<Hash<F, PF, N>>
+    for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, values: Hash<F, PF, N>) {
+        for pf_val in values {
+            let f_vals: Vec<F> = split_64(pf_val, self.num_f_elms);
+            for f_val in f_vals {
+                self.observe(f_val);
+            }
+        }
+    }
+}
+
+impl<F, PF, const N: usize, P, const WIDTH: usize> CanObserve<MerkleCap<F, PF, N>>
+    for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, values: MerkleCap<F, PF, N>) {
+        for pf_val in values {
+            let f_vals: Vec<F> = split_64(pf_val, self.num_f_elms);
+            for f_val in f_vals {
+                self.observe(f_val);
+            }
+        }
+    }
+}
+
+// for TrivialPcs
+impl<F, PF, P, const WIDTH: usize> CanObserve<Vec<Vec<F>>>
+    for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn observe(&mut self, valuess: Vec<Vec<F>>) {
+        for values in valuess {
+            for value in values {
+                self.observe(value);
+            }
+        }
+    }
+}
+
+impl<F, EF, PF, P, const WIDTH: usize> CanSample<EF> for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    EF: ExtensionField<F>,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn sample(&mut self) -> EF {
+        EF::from_base_fn(|_| {
+            // If we have buffered inputs, we must perform a duplexing so that the challenge will
+            // reflect them. Or if we've run out of outputs, we must perform a duplexing to get more.
+            if !self.input_buffer.is_empty() || self.output_buffer.is_empty() {
+                self.duplexing();
+            }
+
+            self.output_buffer
+                .pop()
+                .expect("Output buffer should be non-empty")
+        })
+    }
+}
+
+impl<F, PF, P, const WIDTH: usize> CanSampleBits<usize> for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+{
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        debug_assert!(bits < (usize::BITS as usize));
+        debug_assert!((1 << bits) < F::ORDER_U64);
+        let rand_f: F = self.sample();
+        let rand_usize = rand_f.as_canonical_u64() as usize;
+        rand_usize & ((1 << bits) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_bn254_fr::Bn254Fr;
+    use p3_field::AbstractField;
+    use p3_goldilocks::Goldilocks;
+    use p3_symmetric::Permutation;
+
+    use super::*;
+
+    type F = Goldilocks;
+    type PF = Bn254Fr;
+
+    /// Not a real permutation, but it mixes every input into every output.
+    #[derive(Clone)]
+    struct TestPermutation;
+
+    impl Permutation<[PF; 3]> for TestPermutation {
+        fn permute_mut(&self, state: &mut [PF; 3]) {
+            let sum: PF = state.iter().copied().sum();
+            state
+                .iter_mut()
+                .zip(1..)
+                .for_each(|(x, i)| *x = *x * PF::from_canonical_u32(i) + sum);
+        }
+    }
+
+    impl CryptographicPermutation<[PF; 3]> for TestPermutation {}
+
+    #[test]
+    fn reduce_and_split_64_round_trip() {
+        let vals = [F::from_canonical_u64(F::ORDER_U64 - 1), F::two(), F::zero()];
+        let packed: PF = reduce_64(&vals);
+        assert_eq!(split_64::<PF, F>(packed, 3), vals.to_vec());
+    }
+
+    #[test]
+    fn multi_field_64_challenger_packs_three_per_element() {
+        let mut challenger = MultiField64Challenger::<F, PF, _, 3>::new(TestPermutation).unwrap();
+        assert_eq!(challenger.num_f_elms, 3);
+
+        challenger.observe(F::one());
+        let a: F = challenger.sample();
+        let b: F = challenger.sample();
+        assert_ne!(a, b);
+
+        // The same transcript gives the same challenges, and a different one different challenges.
+        let mut same = MultiField64Challenger::<F, PF, _, 3>::new(TestPermutation).unwrap();
+        same.observe(F::one());
+        let same_samples: Vec<F> = same.sample_vec(2);
+        assert_eq!(same_samples, vec![a, b]);
+
+        let mut other = MultiField64Challenger::<F, PF, _, 3>::new(TestPermutation).unwrap();
+        other.observe(F::two());
+        let c: F = other.sample();
+        assert_ne!(a, c);
+    }
+}
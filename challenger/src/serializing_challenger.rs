@@ -2,13 +2,12 @@ use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 use p3_field::{ExtensionField, PrimeField32, PrimeField64};
-use p3_maybe_rayon::prelude::*;
-use p3_symmetric::{CryptographicHasher, Hash};
+use p3_symmetric::{CryptographicHasher, Hash, MerkleCap};
 use p3_util::log2_ceil_u64;
-use tracing::instrument;
 
 use crate::{
-    CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger, HashChallenger,
+    grind_by_search, CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger,
+    HashChallenger,
 };
 
 /// Given a challenger that can observe and sample bytes, produces a challenger that is able to
@@ -20,6 +19,16 @@ use crate::{
 /// **Sampling**:
 /// -  Samples a field element in a prime field of size `p` by sampling uniformly an element in the
 ///    range (0..1 << log_2(p)). This avoids modulo bias.
+///
+/// Over a byte hash such as Keccak-256 or Blake3, see `from_hasher`, the transcript is simple to
+/// reproduce outside of Rust, e.g. in a Solidity verifier:
+/// - Each observed element is appended to the input as the 4 little-endian bytes of its canonical
+///   value.
+/// - To sample with no digest bytes left, the input is hashed, the digest becomes both the bytes
+///   to sample from and the new input, and sampling takes bytes from the end of the digest.
+/// - A sampled element is built from 4 sampled bytes, the first of which is the least significant.
+///   It is masked to `log2_ceil(p)` bits, and rejected and resampled if it is at least `p`.
+/// - Observing anything discards the digest bytes not yet sampled.
 #[derive(Clone, Debug)]
 pub struct SerializingChallenger32<F, Inner> {
     inner: Inner,
@@ -35,6 +44,8 @@ pub struct SerializingChallenger32<F, Inner> {
 /// **Sampling**:
 /// -  Samples a field element in a prime field of size `p` by sampling unofrmly an element in the
 ///    range (0..1 << log_2(p)). This avoids modulo bias.
+///
+/// The transcript is that of `SerializingChallenger32`, except with 8-byte elements.
 #[derive(Clone, Debug)]
 pub struct SerializingChallenger64<F, Inner> {
     inner: Inner,
@@ -67,6 +78,16 @@ impl<F: PrimeField32, Inner: CanObserve<u8>> CanObserve<F> for SerializingChalle
     }
 }
 
+impl<F: PrimeField32, const N: usize, Inner: CanObserve<u8>> CanObserve<[F; N]>
+    for SerializingChallenger32<F, Inner>
+{
+    fn observe(&mut self, values: [F; N]) {
+        for value in values {
+            self.observe(value);
+        }
+    }
+}
+
 impl<F: PrimeField32, const N: usize, Inner: CanObserve<u8>> CanObserve<Hash<F, u8, N>>
     for SerializingChallenger32<F, Inner>
 {
@@ -77,6 +98,16 @@ impl<F: PrimeField32, const N: usize, Inner: CanObserve<u8>> CanObserve<Hash<F,
     }
 }
 
+impl<F: PrimeField32, const N: usize, Inner: CanObserve<u8>> CanObserve<MerkleCap<F, u8, N>>
+    for SerializingChallenger32<F, Inner>
+{
+    fn observe(&mut self, values: MerkleCap<F, u8, N>) {
+        for value in values {
+            self.inner.observe(value);
+        }
+    }
+}
+
 impl<F, EF, Inner> CanSample<EF> for SerializingChallenger32<F, Inner>
 where
     F: PrimeField32,
@@ -86,7 +117,8 @@ where
     fn sample(&mut self) -> EF {
         let modulus = F::ORDER_U64 as u32;
         let log_size = log2_ceil_u64(F::ORDER_U64);
-        let pow_of_two_bound = (1 << log_size) - 1;
+        // Shifting down rather than up, as the bound may be `u32::MAX`.
+        let pow_of_two_bound = u32::MAX >> (32 - log_size);
         // Perform rejection sampling over the uniform range (0..log2_ceil(p))
         let sample_base = |inner: &mut Inner| loop {
             let value = u32::from_le_bytes(inner.sample_array::<4>());
@@ -120,15 +152,8 @@ where
 {
     type Witness = F;
 
-    #[instrument(name = "grind for proof-of-work witness", skip_all)]
     fn grind(&mut self, bits: usize) -> Self::Witness {
-        let witness = (0..F::ORDER_U64)
-            .into_par_iter()
-            .map(|i| F::from_canonical_u64(i))
-            .find_any(|witness| self.clone().check_witness(bits, *witness))
-            .expect("failed to find witness");
-        assert!(self.check_witness(bits, witness));
-        witness
+        grind_by_search(self, bits)
     }
 }
 
@@ -165,6 +190,16 @@ impl<F: PrimeField64, Inner: CanObserve<u8>> CanObserve<F> for SerializingChalle
     }
 }
 
+impl<F: PrimeField64, const N: usize, Inner: CanObserve<u8>> CanObserve<[F; N]>
+    for SerializingChallenger64<F, Inner>
+{
+    fn observe(&mut self, values: [F; N]) {
+        for value in values {
+            self.observe(value);
+        }
+    }
+}
+
 impl<F: PrimeField64, const N: usize, Inner: CanObserve<u8>> CanObserve<Hash<F, u8, N>>
     for SerializingChallenger64<F, Inner>
 {
@@ -175,6 +210,16 @@ impl<F: PrimeField64, const N: usize, Inner: CanObserve<u8>> CanObserve<Hash<F,
     }
 }
 
+impl<F: PrimeField64, const N: usize, Inner: CanObserve<u8>> CanObserve<MerkleCap<F, u8, N>>
+    for SerializingChallenger64<F, Inner>
+{
+    fn observe(&mut self, values: MerkleCap<F, u8, N>) {
+        for value in values {
+            self.inner.observe(value);
+        }
+    }
+}
+
 impl<F, EF, Inner> CanSample<EF> for SerializingChallenger64<F, Inner>
 where
     F: PrimeField64,
@@ -184,7 +229,8 @@ where
     fn sample(&mut self) -> EF {
         let modulus = F::ORDER_U64;
         let log_size = log2_ceil_u64(F::ORDER_U64);
-        let pow_of_two_bound = (1 << log_size) - 1;
+        // Shifting down rather than up, as the bound is `u64::MAX` for e.g. Goldilocks.
+        let pow_of_two_bound = u64::MAX >> (64 - log_size);
         // Perform rejection sampling over the uniform range (0..log2_ceil(p))
         let sample_base = |inner: &mut Inner| loop {
             let value = u64::from_le_bytes(inner.sample_array::<8>());
@@ -218,15 +264,8 @@ where
 {
     type Witness = F;
 
-    #[instrument(name = "grind for proof-of-work witness", skip_all)]
     fn grind(&mut self, bits: usize) -> Self::Witness {
-        let witness = (0..F::ORDER_U64)
-            .into_par_iter()
-            .map(|i| F::from_canonical_u64(i))
-            .find_any(|witness| self.clone().check_witness(bits, *witness))
-            .expect("failed to find witness");
-        assert!(self.check_witness(bits, witness));
-        witness
+        grind_by_search(self, bits)
     }
 }
 
@@ -236,3 +275,97 @@ where
     Inner: CanSample<u8> + CanObserve<u8> + Clone + Send + Sync,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_blake3::Blake3;
+    use p3_field::AbstractField;
+    use p3_goldilocks::Goldilocks;
+    use p3_keccak::Keccak256Hash;
+
+    use super::*;
+
+    /// The byte-level transcript described on `SerializingChallenger32`, written out by hand.
+    struct ReferenceTranscript<H> {
+        input: Vec<u8>,
+        digest: Vec<u8>,
+        hasher: H,
+    }
+
+    impl<H: CryptographicHasher<u8, [u8; 32]>> ReferenceTranscript<H> {
+        fn observe(&mut self, bytes: &[u8]) {
+            self.digest.clear();
+            self.input.extend_from_slice(bytes);
+        }
+
+        fn sample_byte(&mut self) -> u8 {
+            if self.digest.is_empty() {
+                let digest = self.hasher.hash_iter(self.input.drain(..));
+                self.input.extend_from_slice(&digest);
+                self.digest = digest.to_vec();
+            }
+            self.digest.pop().unwrap()
+        }
+
+        fn sample_u64(&mut self, num_bytes: usize, modulus: u64) -> u64 {
+            let mask = u64::MAX >> (64 - log2_ceil_u64(modulus));
+            loop {
+                let value = (0..num_bytes)
+                    .map(|i| (self.sample_byte() as u64) << (8 * i))
+                    .sum::<u64>()
+                    & mask;
+                if value < modulus {
+                    return value;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn serializing_challenger_32_matches_reference_keccak() {
+        type F = BabyBear;
+        let mut challenger = SerializingChallenger32::<F, _>::from_hasher(vec![7], Keccak256Hash);
+        let mut reference = ReferenceTranscript {
+            input: vec![7],
+            digest: vec![],
+            hasher: Keccak256Hash,
+            MerkleCap,
+        };
+
+        for round in 0..20u32 {
+            let value = F::from_canonical_u32(round * 1_000_003);
+            challenger.observe(value);
+            reference.observe(&value.as_canonical_u32().to_le_bytes());
+            for _ in 0..round % 4 {
+                let sampled: F = challenger.sample();
+                let expected = reference.sample_u64(4, F::ORDER_U64);
+                assert_eq!(sampled, F::from_canonical_u64(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn serializing_challenger_64_matches_reference_blake3() {
+        type F = Goldilocks;
+        let mut challenger = SerializingChallenger64::<F, _>::from_hasher(vec![], Blake3);
+        let mut reference = ReferenceTranscript {
+            input: vec![],
+            digest: vec![],
+            hasher: Blake3,
+        };
+
+        for round in 0..20u64 {
+            let value = F::from_canonical_u64(round.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 1);
+            challenger.observe(value);
+            reference.observe(&value.as_canonical_u64().to_le_bytes());
+            for _ in 0..round % 3 {
+                let sampled: F = challenger.sample();
+                let expected = reference.sample_u64(8, F::ORDER_U64);
+                assert_eq!(sampled, F::from_canonical_u64(expected));
+            }
+        }
+    }
+}
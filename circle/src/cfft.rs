@@ -208,13 +208,13 @@ mod tests {
         do_test_cfft(8);
     }
 
-    fn do_test_lde(log_n: usize, added_bits: usize) {
+    fn do_test_lde(log_n: usize, added_bits: usize, width: usize) {
         let n = 1 << log_n;
         let cfft = Cfft::<F>::default();
 
         let shift: Complex<F> = univariate_to_point(random()).unwrap();
 
-        let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), n, 1);
+        let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), n, width);
         let src_domain = CircleDomain { log_n, shift };
         let target_domain = CircleDomain::standard(log_n + added_bits);
 
@@ -229,6 +229,9 @@ mod tests {
 
     #[test]
     fn test_lde() {
-        do_test_lde(3, 1);
+        do_test_lde(3, 1, 1);
+        for (log_n, added_bits) in [(1, 1), (4, 2), (6, 3)] {
+            do_test_lde(log_n, added_bits, 1 << 4);
+        }
     }
 }
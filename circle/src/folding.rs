@@ -1,3 +1,4 @@
+use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 use core::marker::PhantomData;
@@ -109,6 +110,20 @@ impl<F: ComplexExtendable, EF: ExtensionField<F>, InputProof, InputError: Debug>
         twiddles = circle_bitrev_permute(&twiddles);
         fold(m, beta, &twiddles)
     }
+
+    fn final_poly_coeffs(&self, codeword: Vec<EF>, log_final_poly_len: usize) -> Vec<EF> {
+        assert_eq!(
+            log_final_poly_len, 0,
+            "circle FRI only supports a constant final polynomial"
+        );
+        let constant = codeword[0];
+        debug_assert!(codeword.iter().all(|&x| x == constant));
+        vec![constant]
+    }
+
+    fn eval_final_poly(&self, coeffs: &[EF], _index: usize, _log_height: usize) -> EF {
+        coeffs[0]
+    }
 }
 
 fn fold<F: ComplexExtendable, EF: ExtensionField<F>>(
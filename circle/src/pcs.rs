@@ -5,11 +5,11 @@ use core::marker::PhantomData;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, CanSample, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace};
+use p3_commit::{Mmcs, OpenedValues, OpenedValuesForPoint, Pcs, PolynomialSpace};
 use p3_field::extension::{Complex, ComplexExtendable};
 use p3_field::{ExtensionField, Field};
 use p3_fri::verifier::FriError;
-use p3_fri::{FriConfig, FriProof};
+use p3_fri::{FriConfig, FriProof, LowDegreeTest};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
@@ -28,9 +28,39 @@ use crate::util::{univariate_to_point, v_n};
 
 #[derive(Debug)]
 pub struct CirclePcs<Val: Field, InputMmcs, FriMmcs> {
-    pub cfft: Cfft<Val>,
-    pub mmcs: InputMmcs,
-    pub fri_config: FriConfig<FriMmcs>,
+    cfft: Cfft<Val>,
+    mmcs: InputMmcs,
+    fri_config: FriConfig<FriMmcs>,
+}
+
+impl<Val: Field, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
+    /// A circle PCS over `Val`, committing with `mmcs` and proving openings with circle FRI.
+    ///
+    /// Circle FRI only folds by 2 down to a constant, so `fri_config` must have `max_log_arity`
+    /// of 1, `log_final_poly_len` of 0 and select `LowDegreeTest::Fri`.
+    pub fn new(mmcs: InputMmcs, fri_config: FriConfig<FriMmcs>) -> Self {
+        if let Err(msg) = check_fri_config(&fri_config) {
+            panic!("{msg}");
+        }
+        Self {
+            cfft: Cfft::default(),
+            mmcs,
+            fri_config,
+        }
+    }
+}
+
+/// Check that circle FRI supports `fri_config`, returning why not otherwise.
+fn check_fri_config<M>(fri_config: &FriConfig<M>) -> Result<(), &'static str> {
+    if fri_config.max_log_arity != 1 {
+        Err("circle FRI only supports folding by 2")
+    } else if fri_config.log_final_poly_len != 0 {
+        Err("circle FRI only supports a constant final polynomial")
+    } else if fri_config.ldt != LowDegreeTest::Fri {
+        Err("circle PCS only supports FRI")
+    } else {
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -86,7 +116,8 @@ where
     Challenge: ExtensionField<Val>,
     InputMmcs: Mmcs<Val>,
     FriMmcs: Mmcs<Challenge>,
-    Challenger: CanSample<Challenge> + GrindingChallenger + CanObserve<FriMmcs::Commitment>,
+    Challenger:
+        CanSample<Challenge> + GrindingChallenger<Witness = Val> + CanObserve<FriMmcs::Commitment>,
 {
     type Domain = CircleDomain<Val>;
     type Commitment = InputMmcs::Commitment;
@@ -226,7 +257,7 @@ where
                                 // Update alpha_offset from α^i -> α^(i + 2 * width)
                                 *alpha_offset *= alpha.exp_u64(2 * mat.width() as u64);
 
-                                ps_at_zeta
+                                OpenedValuesForPoint::from(ps_at_zeta)
                             })
                             .collect()
                     })
@@ -346,21 +377,36 @@ where
                     // the point,
                     Challenge,
                     // values at the point
-                    Vec<Challenge>,
+                    OpenedValuesForPoint<Challenge>,
                 )>,
             )>,
         )>,
         proof: &Self::Proof,
         challenger: &mut Challenger,
     ) -> Result<(), Self::Error> {
+        // `new` already rejects such a config; a verifier shouldn't panic on one regardless.
+        if check_fri_config(&self.fri_config).is_err() {
+            return Err(FriError::InvalidProofShape);
+        }
+
         // Batch combination challenge
         let alpha: Challenge = challenger.sample();
         challenger.observe(proof.first_layer_commitment.clone());
         let bivariate_beta: Challenge = challenger.sample();
 
-        // +1 to account for first layer
-        let log_global_max_height =
-            proof.fri_proof.commit_phase_commits.len() + self.fri_config.log_blowup + 1;
+        // The first layer is folded before FRI, so its inputs are one bit shorter.
+        let fri_input_log_heights = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(domain, _)| domain.log_n + self.fri_config.log_blowup - 1)
+            .sorted()
+            .dedup()
+            .rev()
+            .collect_vec();
+        let log_global_max_height = fri_input_log_heights
+            .first()
+            .ok_or(FriError::InvalidProofShape)?
+            + 1;
 
         let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> =
             CircleFriGenericConfig(PhantomData);
@@ -368,6 +414,7 @@ where
         p3_fri::verifier::verify(
             &g,
             &self.fri_config,
+            &fri_input_log_heights,
             &proof.fri_proof,
             challenger,
             |index, input_proof| {
@@ -423,7 +470,13 @@ where
                             let zeta = univariate_to_point(*zeta_uni).unwrap();
 
                             *ro += *alpha_offset
-                                * deep_quotient_reduce_row(alpha, x, zeta, ps_at_x, ps_at_zeta);
+                                * deep_quotient_reduce_row(
+                                    alpha,
+                                    x,
+                                    zeta,
+                                    ps_at_x,
+                                    ps_at_zeta.values(),
+                                );
 
                             *alpha_offset *= alpha_pow_width_2;
                         }
@@ -535,15 +588,15 @@ mod tests {
             log_blowup: 1,
             num_queries: 2,
             proof_of_work_bits: 1,
+            max_log_arity: 1,
+            log_final_poly_len: 0,
+            ldt: LowDegreeTest::Fri,
+            prune_openings: false,
             mmcs: challenge_mmcs,
         };
 
         type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
-        let pcs = Pcs {
-            cfft: Cfft::default(),
-            mmcs: val_mmcs,
-            fri_config,
-        };
+        let pcs = Pcs::new(val_mmcs, fri_config);
 
         let log_n = 10;
 
@@ -562,12 +615,34 @@ mod tests {
         let mut chal = Challenger::from_hasher(vec![], byte_hash);
         let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
 
+        let claims = vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])];
         let mut chal = Challenger::from_hasher(vec![], byte_hash);
-        pcs.verify(
-            vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
-            &proof,
-            &mut chal,
-        )
-        .expect("verify err");
+        pcs.verify(claims.clone(), &proof, &mut chal)
+            .expect("verify err");
+
+        // A verifier with a config circle FRI doesn't support rejects the proof, without panicking.
+        let mut pcs = pcs;
+        pcs.fri_config.max_log_arity = 2;
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        assert!(matches!(
+            pcs.verify(claims, &proof, &mut chal),
+            Err(FriError::InvalidProofShape)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "circle FRI only supports folding by 2")]
+    fn circle_pcs_rejects_higher_arity() {
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            num_queries: 2,
+            proof_of_work_bits: 1,
+            max_log_arity: 2,
+            log_final_poly_len: 0,
+            ldt: LowDegreeTest::Fri,
+            prune_openings: false,
+            mmcs: (),
+        };
+        CirclePcs::<Mersenne31, (), ()>::new((), fri_config);
     }
 }
@@ -1,6 +1,7 @@
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 
+use itertools::izip;
 use p3_field::{ExtensionField, Field};
 use p3_matrix::extension::FlatMatrixView;
 use p3_matrix::{Dimensions, Matrix};
@@ -51,6 +52,18 @@ where
         (opened_ext_values, proof)
     }
 
+    fn prune_proofs(&self, indices: &[usize], proofs: Vec<Self::Proof>) -> Vec<Self::Proof> {
+        self.inner.prune_proofs(indices, proofs)
+    }
+
+    fn expand_proofs(
+        &self,
+        indices: &[usize],
+        proofs: Vec<Self::Proof>,
+    ) -> Result<Vec<Self::Proof>, Self::Error> {
+        self.inner.expand_proofs(indices, proofs)
+    }
+
     fn get_matrices<'a, M: Matrix<EF>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
         self.inner
             .get_matrices(prover_data)
@@ -76,14 +89,50 @@ where
                     .collect()
             })
             .collect();
-        let base_dimensions = dimensions
+        self.inner.verify_batch(
+            commit,
+            &base_dimensions(dimensions, EF::D),
+            index,
+            &opened_base_values,
+            proof,
+        )
+    }
+
+    fn verify_batches(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        openings: &[(usize, &[Vec<EF>], &Self::Proof)],
+    ) -> Result<(), Self::Error> {
+        let opened_base_values: Vec<Vec<Vec<F>>> = openings
             .iter()
-            .map(|dim| Dimensions {
-                width: dim.width * EF::D,
-                height: dim.height,
+            .map(|(_, opened_values, _)| {
+                opened_values
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .flat_map(|el| el.as_base_slice())
+                            .copied()
+                            .collect()
+                    })
+                    .collect()
             })
-            .collect::<Vec<_>>();
+            .collect();
+        let base_openings: Vec<_> = izip!(openings, &opened_base_values)
+            .map(|(&(index, _, proof), values)| (index, values.as_slice(), proof))
+            .collect();
         self.inner
-            .verify_batch(commit, &base_dimensions, index, &opened_base_values, proof)
+            .verify_batches(commit, &base_dimensions(dimensions, EF::D), &base_openings)
     }
 }
+
+/// The dimensions of matrices over the extension field, viewed as matrices over the base field.
+fn base_dimensions(dimensions: &[Dimensions], d: usize) -> Vec<Dimensions> {
+    dimensions
+        .iter()
+        .map(|dim| Dimensions {
+            width: dim.width * d,
+            height: dim.height,
+        })
+        .collect()
+}
@@ -1,4 +1,7 @@
 //! Adapters for converting between different types of commitment schemes.
 
 mod extension_mmcs;
+mod salted_mmcs;
+
 pub use extension_mmcs::*;
+pub use salted_mmcs::*;
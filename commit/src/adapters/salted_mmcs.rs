@@ -0,0 +1,226 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use itertools::{izip, Itertools};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::stack::HorizontalPair;
+use p3_matrix::{Dimensions, Matrix};
+use rand::distributions::{Distribution, Standard};
+use rand::{Rng, RngCore, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::Mmcs;
+
+/// An MMCS which appends `SALT_ELEMS` random elements to each row before committing it with
+/// `InnerMmcs`, and sends the salts of the opened rows along with each opening proof.
+///
+/// If `InnerMmcs` hashes its rows, as a Merkle tree does, the salts make the commitment and the
+/// sibling digests in opening proofs statistically independent of the rows which aren't opened,
+/// which is what a zero-knowledge PCS needs of its commitments.
+///
+/// Salts are drawn from an `R` seeded, per commitment, with a secret seed mixed with a counter, so
+/// that the MMCS can be shared between threads. `R` should be a cryptographically secure RNG.
+pub struct SaltedMmcs<T, InnerMmcs, R: SeedableRng, const SALT_ELEMS: usize> {
+    inner: InnerMmcs,
+    seed: Vec<u8>,
+    num_commits: AtomicU64,
+    _phantom: PhantomData<(T, R)>,
+}
+
+impl<T, InnerMmcs, R: SeedableRng, const SALT_ELEMS: usize>
+    SaltedMmcs<T, InnerMmcs, R, SALT_ELEMS>
+{
+    pub fn new(inner: InnerMmcs, mut seed: R::Seed) -> Self {
+        Self {
+            inner,
+            seed: seed.as_mut().to_vec(),
+            num_commits: AtomicU64::new(0),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Draw the secret seed from `rng`.
+    pub fn from_rng<G: RngCore>(inner: InnerMmcs, rng: &mut G) -> Self {
+        let mut seed = R::Seed::default();
+        rng.fill_bytes(seed.as_mut());
+        Self::new(inner, seed)
+    }
+
+    /// A fresh RNG, which no other commitment (by this MMCS) draws from.
+    fn next_rng(&self) -> R {
+        let nonce = self.num_commits.fetch_add(1, Ordering::Relaxed);
+        let mut seed = R::Seed::default();
+        seed.as_mut().copy_from_slice(&self.seed);
+        for (byte, nonce_byte) in seed.as_mut().iter_mut().zip(nonce.to_le_bytes()) {
+            *byte ^= nonce_byte;
+        }
+        R::from_seed(seed)
+    }
+}
+
+/// A clone gets a seed of its own, drawn from the original, so the two never reuse salts.
+impl<T, InnerMmcs: Clone, R: SeedableRng + RngCore, const SALT_ELEMS: usize> Clone
+    for SaltedMmcs<T, InnerMmcs, R, SALT_ELEMS>
+{
+    fn clone(&self) -> Self {
+        Self::from_rng(self.inner.clone(), &mut self.next_rng())
+    }
+}
+
+/// Leaves out the seed, which must stay secret.
+impl<T, InnerMmcs: Debug, R: SeedableRng, const SALT_ELEMS: usize> Debug
+    for SaltedMmcs<T, InnerMmcs, R, SALT_ELEMS>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SaltedMmcs")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+pub enum SaltedMmcsError<InnerMmcsError> {
+    /// The proof doesn't hold one salt, of `SALT_ELEMS` elements, for each opened row.
+    InvalidSalts,
+    InnerMmcsError(InnerMmcsError),
+}
+
+impl<T, InnerMmcs, R, const SALT_ELEMS: usize> Mmcs<T> for SaltedMmcs<T, InnerMmcs, R, SALT_ELEMS>
+where
+    T: Clone + Send + Sync + Serialize + DeserializeOwned,
+    InnerMmcs: Mmcs<T>,
+    R: SeedableRng + RngCore,
+    Standard: Distribution<T>,
+{
+    type ProverData<M> = InnerMmcs::ProverData<HorizontalPair<M, RowMajorMatrix<T>>>;
+    type Commitment = InnerMmcs::Commitment;
+    /// The salts of the opened rows, and the inner proof.
+    type Proof = (Vec<Vec<T>>, InnerMmcs::Proof);
+    type Error = SaltedMmcsError<InnerMmcs::Error>;
+
+    fn commit<M: Matrix<T>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
+        let mut rng = self.next_rng();
+        let salted = inputs
+            .into_iter()
+            .map(|mat| {
+                let salts = (&mut rng)
+                    .sample_iter(Standard)
+                    .take(mat.height() * SALT_ELEMS)
+                    .collect();
+                let salts = RowMajorMatrix::new(salts, SALT_ELEMS);
+                HorizontalPair::new::<T>(mat, salts)
+            })
+            .collect();
+        self.inner.commit(salted)
+    }
+
+    fn open_batch<M: Matrix<T>>(
+        &self,
+        index: usize,
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<T>>, Self::Proof) {
+        let (mut opened_values, proof) = self.inner.open_batch(index, prover_data);
+        let salts = opened_values
+            .iter_mut()
+            .map(|row| row.split_off(row.len() - SALT_ELEMS))
+            .collect();
+        (opened_values, (salts, proof))
+    }
+
+    fn prune_proofs(&self, indices: &[usize], proofs: Vec<Self::Proof>) -> Vec<Self::Proof> {
+        let (salts, inner_proofs): (Vec<_>, Vec<_>) = proofs.into_iter().unzip();
+        let inner_proofs = self.inner.prune_proofs(indices, inner_proofs);
+        salts.into_iter().zip(inner_proofs).collect()
+    }
+
+    fn expand_proofs(
+        &self,
+        indices: &[usize],
+        proofs: Vec<Self::Proof>,
+    ) -> Result<Vec<Self::Proof>, Self::Error> {
+        let (salts, inner_proofs): (Vec<_>, Vec<_>) = proofs.into_iter().unzip();
+        let inner_proofs = self
+            .inner
+            .expand_proofs(indices, inner_proofs)
+            .map_err(SaltedMmcsError::InnerMmcsError)?;
+        Ok(salts.into_iter().zip(inner_proofs).collect())
+    }
+
+    fn get_matrices<'a, M: Matrix<T>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
+        self.inner
+            .get_matrices(prover_data)
+            .into_iter()
+            .map(HorizontalPair::first)
+            .collect()
+    }
+
+    fn verify_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        index: usize,
+        opened_values: &[Vec<T>],
+        (salts, inner_proof): &Self::Proof,
+    ) -> Result<(), Self::Error> {
+        let salted_values = salted_rows::<_, SALT_ELEMS, _>(opened_values, salts)?;
+        self.inner
+            .verify_batch(
+                commit,
+                &salted_dimensions::<SALT_ELEMS>(dimensions),
+                index,
+                &salted_values,
+                inner_proof,
+            )
+            .map_err(SaltedMmcsError::InnerMmcsError)
+    }
+
+    fn verify_batches(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        openings: &[(usize, &[Vec<T>], &Self::Proof)],
+    ) -> Result<(), Self::Error> {
+        let salted_values = openings
+            .iter()
+            .map(|(_, opened_values, (salts, _))| {
+                salted_rows::<_, SALT_ELEMS, _>(opened_values, salts)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let salted_openings = izip!(openings, &salted_values)
+            .map(|(&(index, _, (_, inner_proof)), values)| (index, values.as_slice(), inner_proof))
+            .collect_vec();
+        self.inner
+            .verify_batches(
+                commit,
+                &salted_dimensions::<SALT_ELEMS>(dimensions),
+                &salted_openings,
+            )
+            .map_err(SaltedMmcsError::InnerMmcsError)
+    }
+}
+
+/// The opened rows with their salts appended, as committed by the inner MMCS.
+fn salted_rows<T: Clone, const SALT_ELEMS: usize, E>(
+    opened_values: &[Vec<T>],
+    salts: &[Vec<T>],
+) -> Result<Vec<Vec<T>>, SaltedMmcsError<E>> {
+    if salts.len() != opened_values.len() || salts.iter().any(|salt| salt.len() != SALT_ELEMS) {
+        return Err(SaltedMmcsError::InvalidSalts);
+    }
+    Ok(izip!(opened_values, salts)
+        .map(|(row, salt)| row.iter().chain(salt).cloned().collect())
+        .collect())
+}
+
+fn salted_dimensions<const SALT_ELEMS: usize>(dimensions: &[Dimensions]) -> Vec<Dimensions> {
+    dimensions
+        .iter()
+        .map(|dims| Dimensions {
+            width: dims.width + SALT_ELEMS,
+            height: dims.height,
+        })
+        .collect()
+}
@@ -7,7 +7,10 @@ extern crate alloc;
 mod adapters;
 mod domain;
 mod mmcs;
+mod multivariate_pcs;
+mod opened_values;
 mod pcs;
+mod routed_pcs;
 
 #[cfg(any(test, feature = "test-utils"))]
 pub mod testing;
@@ -15,4 +18,7 @@ pub mod testing;
 pub use adapters::*;
 pub use domain::*;
 pub use mmcs::*;
+pub use multivariate_pcs::*;
+pub use opened_values::*;
 pub use pcs::*;
+pub use routed_pcs::*;
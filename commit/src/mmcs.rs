@@ -44,6 +44,25 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         prover_data: &Self::ProverData<M>,
     ) -> (Vec<Vec<T>>, Self::Proof);
 
+    /// Shrink the proofs of several openings of the same commitment, at the given indices, by
+    /// leaving out whatever one proof repeats from an earlier one. `expand_proofs` recovers the
+    /// original proofs. By default, the proofs are left as they are.
+    fn prune_proofs(&self, indices: &[usize], proofs: Vec<Self::Proof>) -> Vec<Self::Proof> {
+        let _ = indices;
+        proofs
+    }
+
+    /// Recover the proofs of openings at `indices` from their pruned form, as returned by
+    /// `prune_proofs`. The recovered proofs still need to be checked with `verify_batch`.
+    fn expand_proofs(
+        &self,
+        indices: &[usize],
+        proofs: Vec<Self::Proof>,
+    ) -> Result<Vec<Self::Proof>, Self::Error> {
+        let _ = indices;
+        Ok(proofs)
+    }
+
     /// Get the matrices that were committed to.
     fn get_matrices<'a, M: Matrix<T>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M>;
 
@@ -75,4 +94,25 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         opened_values: &[Vec<T>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error>;
+
+    /// Verify several batch openings of the same commitment, each given as
+    /// `(index, opened_values, proof)` with the same meaning as in `verify_batch`.
+    ///
+    /// By default the openings are checked one at a time. Implementations can instead share the
+    /// work which overlapping openings have in common, as long as every opened row is still
+    /// checked against the commitment; they may then ignore parts of a proof which other openings
+    /// make redundant, and which `verify_batch` would check.
+    #[allow(clippy::type_complexity)]
+    fn verify_batches(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        openings: &[(usize, &[Vec<T>], &Self::Proof)],
+    ) -> Result<(), Self::Error> {
+        openings
+            .iter()
+            .try_for_each(|&(index, opened_values, proof)| {
+                self.verify_batch(commit, dimensions, index, opened_values, proof)
+            })
+    }
 }
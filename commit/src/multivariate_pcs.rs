@@ -0,0 +1,53 @@
+//! Traits for multilinear polynomial commitment schemes.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A (not necessarily hiding) polynomial commitment scheme for batches of multilinear
+/// polynomials, such as those of sumcheck-based provers.
+///
+/// A batch is given as a matrix of height `2^n`, each column holding a polynomial in `n`
+/// variables by its evaluations over the boolean hypercube: row `i` holds the evaluations at the
+/// point whose `j`th coordinate is the `j`th bit of `i`.
+pub trait MultivariatePcs<Val, Challenge, Challenger>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val>,
+{
+    /// The commitment that's sent to the verifier.
+    type Commitment: Clone + Serialize + DeserializeOwned;
+
+    /// Data that the prover stores for committed polynomials, to help the prover with opening.
+    type ProverData;
+
+    /// The opening argument.
+    type Proof: Clone + Serialize + DeserializeOwned;
+
+    type Error: Debug;
+
+    fn commit(&self, evaluations: RowMajorMatrix<Val>) -> (Self::Commitment, Self::ProverData);
+
+    /// Evaluate every committed polynomial at `point`, which has one coordinate per variable,
+    /// returning the evaluations, one per column, and a proof of them.
+    fn open(
+        &self,
+        prover_data: &Self::ProverData,
+        point: &[Challenge],
+        challenger: &mut Challenger,
+    ) -> (Vec<Challenge>, Self::Proof);
+
+    /// Check that the polynomials committed in `commit` evaluate to `values` at `point`.
+    fn verify(
+        &self,
+        commit: &Self::Commitment,
+        point: &[Challenge],
+        values: &[Challenge],
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,158 @@
+//! The values opened by a PCS, nested by round, then matrix, then point.
+//!
+//! Each level of nesting has its own type, so that rounds, matrices and points can't be mixed up.
+
+use alloc::vec::Vec;
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+use serde::{Deserialize, Serialize};
+
+macro_rules! opened_values_level {
+    ($(#[$attr:meta])* $name:ident, $item:ty, $items:ident, $item_at:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name<F>(Vec<$item>);
+
+        impl<F> $name<F> {
+            pub const fn new($items: Vec<$item>) -> Self {
+                Self($items)
+            }
+
+            pub fn len(&self) -> usize {
+                self.0.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.0.is_empty()
+            }
+
+            pub fn $items(&self) -> &[$item] {
+                &self.0
+            }
+
+            pub fn $item_at(&self, index: usize) -> Option<&$item> {
+                self.0.get(index)
+            }
+
+            pub fn iter(&self) -> slice::Iter<'_, $item> {
+                self.0.iter()
+            }
+
+            pub fn into_vec(self) -> Vec<$item> {
+                self.0
+            }
+        }
+
+        impl<F> From<Vec<$item>> for $name<F> {
+            fn from($items: Vec<$item>) -> Self {
+                Self($items)
+            }
+        }
+
+        impl<F> FromIterator<$item> for $name<F> {
+            fn from_iter<I: IntoIterator<Item = $item>>(iter: I) -> Self {
+                Self(iter.into_iter().collect())
+            }
+        }
+
+        impl<F> IntoIterator for $name<F> {
+            type Item = $item;
+            type IntoIter = alloc::vec::IntoIter<$item>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.0.into_iter()
+            }
+        }
+
+        impl<'a, F> IntoIterator for &'a $name<F> {
+            type Item = &'a $item;
+            type IntoIter = slice::Iter<'a, $item>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.0.iter()
+            }
+        }
+
+        impl<F> AsRef<[$item]> for $name<F> {
+            fn as_ref(&self) -> &[$item] {
+                &self.0
+            }
+        }
+
+        impl<F> Index<usize> for $name<F> {
+            type Output = $item;
+
+            fn index(&self, index: usize) -> &$item {
+                &self.0[index]
+            }
+        }
+
+        impl<F> IndexMut<usize> for $name<F> {
+            fn index_mut(&mut self, index: usize) -> &mut $item {
+                &mut self.0[index]
+            }
+        }
+    };
+}
+
+opened_values_level!(
+    /// For each round of commitments, the values opened from it.
+    OpenedValues,
+    OpenedValuesForRound<F>,
+    rounds,
+    round
+);
+
+opened_values_level!(
+    /// For each matrix of a round, the values opened from it.
+    OpenedValuesForRound,
+    OpenedValuesForMatrix<F>,
+    matrices,
+    matrix
+);
+
+opened_values_level!(
+    /// For each point a matrix is opened at, the values opened there.
+    OpenedValuesForMatrix,
+    OpenedValuesForPoint<F>,
+    points,
+    point
+);
+
+opened_values_level!(
+    /// For each column of a matrix, its value at a point.
+    OpenedValuesForPoint,
+    F,
+    values,
+    value
+);
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn nested_access() {
+        let opened: OpenedValues<u32> = vec![
+            vec![vec![vec![1, 2], vec![3, 4]]
+                .into_iter()
+                .map(Into::into)
+                .collect()]
+            .into_iter()
+            .collect(),
+            OpenedValuesForRound::default(),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(opened.len(), 2);
+        assert!(opened[1].is_empty());
+        assert_eq!(opened[0][0][1].values(), &[3, 4]);
+        assert_eq!(opened.round(0).and_then(|r| r.matrix(1)), None);
+        assert_eq!(opened[0].matrices()[0].points().len(), 2);
+    }
+}
@@ -3,13 +3,14 @@
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
+use p3_challenger::{CanObserve, FieldChallenger};
 use p3_field::ExtensionField;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use crate::PolynomialSpace;
+use crate::{OpenedValues, OpenedValuesForPoint, PolynomialSpace};
 
 pub type Val<D> = <D as PolynomialSpace>::Val;
 
@@ -41,6 +42,40 @@ where
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
     ) -> (Self::Commitment, Self::ProverData);
 
+    /// Commit to one stage of a multi-stage protocol, e.g. the main trace, or auxiliary columns
+    /// which depend on challenges sampled after it, and observe the stage's index followed by the
+    /// commitment, so that every challenge sampled afterwards depends on both. A stage committed
+    /// out of order, or a skipped one, then changes the transcript.
+    #[allow(clippy::type_complexity)]
+    fn commit_stage(
+        &self,
+        stage: usize,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
+        challenger: &mut Challenger,
+    ) -> (Self::Commitment, Self::ProverData)
+    where
+        Challenger: FieldChallenger<Val<Self::Domain>> + CanObserve<Self::Commitment>,
+    {
+        let (commit, data) = self.commit(evaluations);
+        self.observe_stage(stage, &commit, challenger);
+        (commit, data)
+    }
+
+    /// The verifier's counterpart to `commit_stage`, which must be called for each stage's
+    /// commitment, with the same index and in the same order, relative to other transcript
+    /// operations, as the prover.
+    fn observe_stage(
+        &self,
+        stage: usize,
+        commitment: &Self::Commitment,
+        challenger: &mut Challenger,
+    ) where
+        Challenger: FieldChallenger<Val<Self::Domain>> + CanObserve<Self::Commitment>,
+    {
+        challenger.observe_usize(stage);
+        challenger.observe(commitment.clone());
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
@@ -62,6 +97,46 @@ where
         challenger: &mut Challenger,
     ) -> (OpenedValues<Challenge>, Self::Proof);
 
+    /// Like `open`, with each matrix opened at rotations of `zeta` within its domain: for a
+    /// rotation `r`, at the point `r` steps after `zeta`, as given by `Domain::next_point`.
+    ///
+    /// STARKs open their traces at `zeta` and its next point, and a PCS may share work between
+    /// such points. By default, each point is opened independently.
+    #[allow(clippy::type_complexity)]
+    fn open_rotations(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // rotations of zeta to open.
+                Vec<usize>,
+            )>,
+        )>,
+        zeta: Challenge,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let rounds = rounds
+            .into_iter()
+            .map(|(data, mats)| {
+                let points = mats
+                    .into_iter()
+                    .map(|(domain, rotations)| {
+                        rotations
+                            .into_iter()
+                            .map(|r| (0..r).fold(zeta, |x, _| domain.next_point(x).unwrap()))
+                            .collect()
+                    })
+                    .collect();
+                (data, points)
+            })
+            .collect();
+        self.open(rounds, challenger)
+    }
+
     #[allow(clippy::type_complexity)]
     fn verify(
         &self,
@@ -77,7 +152,7 @@ where
                     // the point,
                     Challenge,
                     // values at the point
-                    Vec<Challenge>,
+                    OpenedValuesForPoint<Challenge>,
                 )>,
             )>,
         )>,
@@ -85,8 +160,3 @@ where
         challenger: &mut Challenger,
     ) -> Result<(), Self::Error>;
 }
-
-pub type OpenedValues<F> = Vec<OpenedValuesForRound<F>>;
-pub type OpenedValuesForRound<F> = Vec<OpenedValuesForMatrix<F>>;
-pub type OpenedValuesForMatrix<F> = Vec<OpenedValuesForPoint<F>>;
-pub type OpenedValuesForPoint<F> = Vec<F>;
@@ -0,0 +1,546 @@
+//! A PCS which commits to each batch with one of two underlying PCSs.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use itertools::Either;
+use p3_challenger::{
+    CanObserve, DuplexChallenger, MultiField32Challenger, MultiField64Challenger,
+    SerializingChallenger32, SerializingChallenger64,
+};
+use p3_field::{ExtensionField, Field, PrimeField, PrimeField32, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_symmetric::CryptographicPermutation;
+use serde::{Deserialize, Serialize};
+
+use crate::{OpenedValues, OpenedValuesForPoint, Pcs, PolynomialSpace, Val};
+
+/// Which of a `RoutedPcs`'s two PCSs a batch is committed with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PcsRoute {
+    First,
+    Second,
+}
+
+/// Decides which PCS each batch is committed with.
+pub trait BatchRouter<Domain: PolynomialSpace> {
+    fn route(&self, evaluations: &[(Domain, RowMajorMatrix<Val<Domain>>)]) -> PcsRoute;
+}
+
+impl<Domain, F> BatchRouter<Domain> for F
+where
+    Domain: PolynomialSpace,
+    F: Fn(&[(Domain, RowMajorMatrix<Val<Domain>>)]) -> PcsRoute,
+{
+    fn route(&self, evaluations: &[(Domain, RowMajorMatrix<Val<Domain>>)]) -> PcsRoute {
+        self(evaluations)
+    }
+}
+
+/// Routes batches with no domain larger than `max_second_size` to the second PCS, and all others
+/// to the first. For example, small preprocessed matrices can go to a PCS with cheap openings,
+/// while tall trace matrices go to FRI.
+#[derive(Copy, Clone, Debug)]
+pub struct RouteBySize {
+    pub max_second_size: usize,
+}
+
+impl<Domain: PolynomialSpace> BatchRouter<Domain> for RouteBySize {
+    fn route(&self, evaluations: &[(Domain, RowMajorMatrix<Val<Domain>>)]) -> PcsRoute {
+        if evaluations
+            .iter()
+            .all(|(domain, _)| domain.size() <= self.max_second_size)
+        {
+            PcsRoute::Second
+        } else {
+            PcsRoute::First
+        }
+    }
+}
+
+/// A PCS over the domains of `First`, committing to each batch with `First` or `Second`, as
+/// chosen by `router`.
+///
+/// When opening, each PCS opens the rounds it committed to, `First` before `Second`, with the
+/// same challenger. Its proof is `None` if it committed to none of the rounds.
+#[derive(Debug)]
+pub struct RoutedPcs<First, Second, Router> {
+    pub first: First,
+    pub second: Second,
+    pub router: Router,
+}
+
+impl<First, Second, Router> RoutedPcs<First, Second, Router> {
+    pub const fn new(first: First, second: Second, router: Router) -> Self {
+        Self {
+            first,
+            second,
+            router,
+        }
+    }
+}
+
+/// A value from whichever of the two PCSs a batch was routed to: its commitment or prover data,
+/// or a matrix of its evaluations.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Routed<A, B> {
+    First(A),
+    Second(B),
+}
+
+impl<A, B> Routed<A, B> {
+    pub const fn as_ref(&self) -> Routed<&A, &B> {
+        match self {
+            Self::First(a) => Routed::First(a),
+            Self::Second(b) => Routed::Second(b),
+        }
+    }
+}
+
+impl<T, A, B> Matrix<T> for Routed<A, B>
+where
+    T: Send + Sync,
+    A: Matrix<T>,
+    B: Matrix<T>,
+{
+    fn width(&self) -> usize {
+        match self {
+            Self::First(a) => a.width(),
+            Self::Second(b) => b.width(),
+        }
+    }
+
+    fn height(&self) -> usize {
+        match self {
+            Self::First(a) => a.height(),
+            Self::Second(b) => b.height(),
+        }
+    }
+
+    type Row<'a> = Either<A::Row<'a>, B::Row<'a>>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        match self {
+            Self::First(a) => Either::Left(a.row(r)),
+            Self::Second(b) => Either::Right(b.row(r)),
+        }
+    }
+
+    fn row_slice(&self, r: usize) -> impl Deref<Target = [T]> {
+        match self {
+            Self::First(a) => Either::Left(a.row_slice(r)),
+            Self::Second(b) => Either::Right(b.row_slice(r)),
+        }
+    }
+}
+
+/// Observe a routed commitment as the index of the PCS it was routed to, followed by the commitment
+/// itself, so that the route is bound into the transcript along with it.
+fn observe_routed<F, C, A, B>(challenger: &mut C, commit: Routed<A, B>)
+where
+    F: Field,
+    C: CanObserve<F> + CanObserve<A> + CanObserve<B>,
+{
+    match commit {
+        Routed::First(a) => {
+            CanObserve::<F>::observe(challenger, F::zero());
+            challenger.observe(a);
+        }
+        Routed::Second(b) => {
+            CanObserve::<F>::observe(challenger, F::one());
+            challenger.observe(b);
+        }
+    }
+}
+
+impl<F, P, A, B, const WIDTH: usize, const RATE: usize> CanObserve<Routed<A, B>>
+    for DuplexChallenger<F, P, WIDTH, RATE>
+where
+    F: PrimeField64,
+    P: CryptographicPermutation<[F; WIDTH]>,
+    Self: CanObserve<A> + CanObserve<B>,
+{
+    fn observe(&mut self, commit: Routed<A, B>) {
+        observe_routed::<F, _, _, _>(self, commit);
+    }
+}
+
+impl<F, PF, P, A, B, const WIDTH: usize> CanObserve<Routed<A, B>>
+    for MultiField32Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField32,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+    Self: CanObserve<A> + CanObserve<B>,
+{
+    fn observe(&mut self, commit: Routed<A, B>) {
+        observe_routed::<F, _, _, _>(self, commit);
+    }
+}
+
+impl<F, PF, P, A, B, const WIDTH: usize> CanObserve<Routed<A, B>>
+    for MultiField64Challenger<F, PF, P, WIDTH>
+where
+    F: PrimeField64,
+    PF: PrimeField,
+    P: CryptographicPermutation<[PF; WIDTH]>,
+    Self: CanObserve<A> + CanObserve<B>,
+{
+    fn observe(&mut self, commit: Routed<A, B>) {
+        observe_routed::<F, _, _, _>(self, commit);
+    }
+}
+
+impl<F, Inner, A, B> CanObserve<Routed<A, B>> for SerializingChallenger32<F, Inner>
+where
+    F: PrimeField32,
+    Inner: CanObserve<u8>,
+    Self: CanObserve<A> + CanObserve<B>,
+{
+    fn observe(&mut self, commit: Routed<A, B>) {
+        observe_routed::<F, _, _, _>(self, commit);
+    }
+}
+
+impl<F, Inner, A, B> CanObserve<Routed<A, B>> for SerializingChallenger64<F, Inner>
+where
+    F: PrimeField64,
+    Inner: CanObserve<u8>,
+    Self: CanObserve<A> + CanObserve<B>,
+{
+    fn observe(&mut self, commit: Routed<A, B>) {
+        observe_routed::<F, _, _, _>(self, commit);
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RoutedProof<FirstProof, SecondProof> {
+    pub first: Option<FirstProof>,
+    pub second: Option<SecondProof>,
+}
+
+#[derive(Debug)]
+pub enum RoutedPcsError<FirstError, SecondError> {
+    /// A PCS's proof is missing although it committed to some of the rounds, or present although
+    /// it committed to none of them.
+    InvalidProofShape,
+    First(FirstError),
+    Second(SecondError),
+}
+
+/// Split rounds by the PCS they were committed with, also returning each round's route.
+#[allow(clippy::type_complexity)]
+fn split_rounds<A, B, T>(
+    rounds: Vec<(Routed<A, B>, T)>,
+) -> (Vec<PcsRoute>, Vec<(A, T)>, Vec<(B, T)>) {
+    let mut routes = vec![];
+    let mut first = vec![];
+    let mut second = vec![];
+    for (routed, round) in rounds {
+        match routed {
+            Routed::First(a) => {
+                routes.push(PcsRoute::First);
+                first.push((a, round));
+            }
+            Routed::Second(b) => {
+                routes.push(PcsRoute::Second);
+                second.push((b, round));
+            }
+        }
+    }
+    (routes, first, second)
+}
+
+/// Interleave the values opened by each PCS back into the order of the rounds.
+fn merge_opened_values<F>(
+    routes: &[PcsRoute],
+    first: OpenedValues<F>,
+    second: OpenedValues<F>,
+) -> OpenedValues<F> {
+    let mut first = first.into_iter();
+    let mut second = second.into_iter();
+    routes
+        .iter()
+        .map(|route| match route {
+            PcsRoute::First => first.next().unwrap(),
+            PcsRoute::Second => second.next().unwrap(),
+        })
+        .collect()
+}
+
+/// Open `rounds` with `open`, unless there are none.
+fn open_if_any<R, F, P>(
+    rounds: Vec<R>,
+    open: impl FnOnce(Vec<R>) -> (OpenedValues<F>, P),
+) -> (OpenedValues<F>, Option<P>) {
+    if rounds.is_empty() {
+        (OpenedValues::new(vec![]), None)
+    } else {
+        let (values, proof) = open(rounds);
+        (values, Some(proof))
+    }
+}
+
+impl<First, Second, Router, Challenge, Challenger> Pcs<Challenge, Challenger>
+    for RoutedPcs<First, Second, Router>
+where
+    First: Pcs<Challenge, Challenger>,
+    Second: Pcs<Challenge, Challenger, Domain = First::Domain>,
+    Router: BatchRouter<First::Domain>,
+    Challenge: ExtensionField<Val<First::Domain>>,
+{
+    type Domain = First::Domain;
+    type Commitment = Routed<First::Commitment, Second::Commitment>;
+    type ProverData = Routed<First::ProverData, Second::ProverData>;
+    type Proof = RoutedProof<First::Proof, Second::Proof>;
+    type Error = RoutedPcsError<First::Error, Second::Error>;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        self.first.natural_domain_for_degree(degree)
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        match self.router.route(&evaluations) {
+            PcsRoute::First => {
+                let (commit, data) = self.first.commit(evaluations);
+                (Routed::First(commit), Routed::First(data))
+            }
+            PcsRoute::Second => {
+                let (commit, data) = self.second.commit(evaluations);
+                (Routed::Second(commit), Routed::Second(data))
+            }
+        }
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> impl Matrix<Val<Self::Domain>> + 'a {
+        match prover_data {
+            Routed::First(data) => {
+                Routed::First(self.first.get_evaluations_on_domain(data, idx, domain))
+            }
+            Routed::Second(data) => {
+                Routed::Second(self.second.get_evaluations_on_domain(data, idx, domain))
+            }
+        }
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let rounds = rounds
+            .into_iter()
+            .map(|(data, points)| (data.as_ref(), points))
+            .collect();
+        let (routes, first_rounds, second_rounds) = split_rounds(rounds);
+        let (first_values, first) =
+            open_if_any(first_rounds, |rounds| self.first.open(rounds, challenger));
+        let (second_values, second) =
+            open_if_any(second_rounds, |rounds| self.second.open(rounds, challenger));
+        (
+            merge_opened_values(&routes, first_values, second_values),
+            RoutedProof { first, second },
+        )
+    }
+
+    fn open_rotations(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // rotations of zeta to open.
+                Vec<usize>,
+            )>,
+        )>,
+        zeta: Challenge,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let rounds = rounds
+            .into_iter()
+            .map(|(data, mats)| (data.as_ref(), mats))
+            .collect();
+        let (routes, first_rounds, second_rounds) = split_rounds(rounds);
+        let (first_values, first) = open_if_any(first_rounds, |rounds| {
+            self.first.open_rotations(rounds, zeta, challenger)
+        });
+        let (second_values, second) = open_if_any(second_rounds, |rounds| {
+            self.second.open_rotations(rounds, zeta, challenger)
+        });
+        (
+            merge_opened_values(&routes, first_values, second_values),
+            RoutedProof { first, second },
+        )
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Challenge,
+                    // values at the point
+                    OpenedValuesForPoint<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        let (_, first_rounds, second_rounds) = split_rounds(rounds);
+        match (first_rounds.is_empty(), &proof.first) {
+            (true, None) => {}
+            (false, Some(first_proof)) => self
+                .first
+                .verify(first_rounds, first_proof, challenger)
+                .map_err(RoutedPcsError::First)?,
+            _ => return Err(RoutedPcsError::InvalidProofShape),
+        }
+        match (second_rounds.is_empty(), &proof.second) {
+            (true, None) => {}
+            (false, Some(second_proof)) => self
+                .second
+                .verify(second_rounds, second_proof, challenger)
+                .map_err(RoutedPcsError::Second)?,
+            _ => return Err(RoutedPcsError::InvalidProofShape),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::marker::PhantomData;
+
+    use p3_baby_bear::BabyBear;
+    use p3_challenger::CanSample;
+    use p3_dft::Radix2Dit;
+    use p3_field::AbstractField;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::testing::TrivialPcs;
+
+    type F = BabyBear;
+    type MyTrivialPcs = TrivialPcs<F, Radix2Dit<F>>;
+    type MyPcs = RoutedPcs<MyTrivialPcs, MyTrivialPcs, RouteBySize>;
+
+    /// Samples consecutive integers, which is enough for the trivial PCS.
+    #[derive(Clone, Default)]
+    struct CountingChallenger(u32);
+
+    impl CanSample<F> for CountingChallenger {
+        fn sample(&mut self) -> F {
+            self.0 += 1;
+            F::from_canonical_u32(self.0)
+        }
+    }
+
+    fn trivial_pcs() -> MyTrivialPcs {
+        TrivialPcs {
+            dft: Radix2Dit::default(),
+            log_n: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    #[test]
+    fn routes_rounds_and_merges_openings() {
+        let pcs: MyPcs = RoutedPcs::new(
+            trivial_pcs(),
+            trivial_pcs(),
+            RouteBySize { max_second_size: 4 },
+        );
+        let mut rng = thread_rng();
+        let batch = |log_n: usize, rng: &mut _| {
+            let domain =
+                <MyPcs as Pcs<F, CountingChallenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+            vec![(domain, RowMajorMatrix::<F>::rand(rng, 1 << log_n, 2))]
+        };
+        let tall = batch(4, &mut rng);
+        let small = batch(2, &mut rng);
+
+        let (tall_commit, tall_data) =
+            <MyPcs as Pcs<F, CountingChallenger>>::commit(&pcs, tall.clone());
+        let (small_commit, small_data) =
+            <MyPcs as Pcs<F, CountingChallenger>>::commit(&pcs, small.clone());
+        assert!(matches!(tall_data, Routed::First(_)));
+        assert!(matches!(small_data, Routed::Second(_)));
+
+        let evals = <MyPcs as Pcs<F, CountingChallenger>>::get_evaluations_on_domain(
+            &pcs,
+            &small_data,
+            0,
+            small[0].0,
+        );
+        assert_eq!(evals.to_row_major_matrix(), small[0].1);
+
+        let zeta = F::from_canonical_u32(1234);
+        let (values, proof) = pcs.open(
+            vec![
+                (&small_data, vec![vec![zeta]]),
+                (&tall_data, vec![vec![zeta]]),
+            ],
+            &mut CountingChallenger::default(),
+        );
+        // Each round opens to the same values as when opened on its own.
+        let (small_values, _) = pcs.open(
+            vec![(&small_data, vec![vec![zeta]])],
+            &mut CountingChallenger::default(),
+        );
+        assert_eq!(values[0], small_values[0]);
+        assert!(proof.first.is_some() && proof.second.is_some());
+
+        let claims = vec![
+            (
+                small_commit,
+                vec![(small[0].0, vec![(zeta, values[0][0][0].clone())])],
+            ),
+            (
+                tall_commit,
+                vec![(tall[0].0, vec![(zeta, values[1][0][0].clone())])],
+            ),
+        ];
+        pcs.verify(claims.clone(), &proof, &mut CountingChallenger::default())
+            .unwrap();
+
+        let missing = RoutedProof {
+            first: None,
+            second: proof.second,
+        };
+        assert!(matches!(
+            pcs.verify(claims, &missing, &mut CountingChallenger::default()),
+            Err(RoutedPcsError::InvalidProofShape)
+        ));
+    }
+}
@@ -10,7 +10,7 @@ use p3_matrix::Matrix;
 use p3_util::log2_strict_usize;
 use serde::{Deserialize, Serialize};
 
-use crate::{OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
+use crate::{OpenedValues, OpenedValuesForPoint, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
 
 /// A trivial PCS: its commitment is simply the coefficients of each poly.
 #[derive(Debug)]
@@ -127,7 +127,7 @@ where
                         .map(|(coeffs_for_mat, points_for_mat)| {
                             points_for_mat
                                 .into_iter()
-                                .map(|pt| eval_coeffs_at_pt(coeffs_for_mat, pt))
+                                .map(|pt| eval_coeffs_at_pt(coeffs_for_mat, pt).into())
                                 .collect()
                         })
                         .collect()
@@ -150,7 +150,7 @@ where
                 Vec<(
                     Challenge,
                     // values at this point
-                    Vec<Challenge>,
+                    OpenedValuesForPoint<Challenge>,
                 )>,
             )>,
         )>,
@@ -163,7 +163,7 @@ where
                 assert_eq!(width * domain.size(), coeff_vec.len());
                 let coeffs = RowMajorMatrix::new(coeff_vec, width);
                 for (pt, values) in points_and_values {
-                    assert_eq!(eval_coeffs_at_pt(&coeffs, pt), values);
+                    assert_eq!(eval_coeffs_at_pt(&coeffs, pt), values.into_vec());
                 }
             }
         }
@@ -0,0 +1,172 @@
+use alloc::vec::Vec;
+
+use p3_field::TwoAdicField;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
+use p3_matrix::Matrix;
+
+use crate::TwoAdicSubgroupDft;
+
+/// Wraps a DFT so that it can transform matrices too large to hold in memory, such as ones backed
+/// by memory-mapped files.
+///
+/// The input can be any `Matrix`, e.g. a `RowMajorMatrixView` over a mapped input file, and the
+/// output is written into a `RowMajorMatrixViewMut`, e.g. over a mapped output file. Columns are
+/// transformed in blocks, each copied into memory, transformed by the inner DFT, then written out,
+/// so that at most about `max_block_len` output values are held in memory at once.
+#[derive(Clone, Debug)]
+pub struct ColumnBlockedDft<Dft> {
+    pub inner: Dft,
+    pub max_block_len: usize,
+}
+
+impl<Dft> ColumnBlockedDft<Dft> {
+    pub const fn new(inner: Dft, max_block_len: usize) -> Self {
+        Self {
+            inner,
+            max_block_len,
+        }
+    }
+
+    /// Compute the DFT of each column in `mat`, writing it to `out`, which must have the same
+    /// dimensions.
+    pub fn dft_batch_into<F, M>(&self, mat: &M, out: &mut RowMajorMatrixViewMut<'_, F>)
+    where
+        F: TwoAdicField,
+        Dft: TwoAdicSubgroupDft<F>,
+        M: Matrix<F>,
+    {
+        self.transform_blocks(mat, out, |block| {
+            self.inner.dft_batch(block).to_row_major_matrix()
+        });
+    }
+
+    /// Compute the low-degree extension of each column in `mat` onto a coset of a larger
+    /// subgroup, writing it to `out`, which must be `mat.height() << added_bits` rows high.
+    pub fn coset_lde_batch_into<F, M>(
+        &self,
+        mat: &M,
+        added_bits: usize,
+        shift: F,
+        out: &mut RowMajorMatrixViewMut<'_, F>,
+    ) where
+        F: TwoAdicField,
+        Dft: TwoAdicSubgroupDft<F>,
+        M: Matrix<F>,
+    {
+        self.transform_blocks(mat, out, |block| {
+            self.inner
+                .coset_lde_batch(block, added_bits, shift)
+                .to_row_major_matrix()
+        });
+    }
+
+    /// Like `coset_lde_batch_into`, but with the output rows in bit-reversed order, as committed
+    /// to by the PCS.
+    pub fn coset_lde_batch_bit_reversed_into<F, M>(
+        &self,
+        mat: &M,
+        added_bits: usize,
+        shift: F,
+        out: &mut RowMajorMatrixViewMut<'_, F>,
+    ) where
+        F: TwoAdicField,
+        Dft: TwoAdicSubgroupDft<F>,
+        M: Matrix<F>,
+    {
+        self.transform_blocks(mat, out, |block| {
+            self.inner
+                .coset_lde_batch_bit_reversed(block, added_bits, shift)
+        });
+    }
+
+    /// Apply `transform` to blocks of `mat`'s columns in turn, writing the results to the same
+    /// columns of `out`.
+    fn transform_blocks<F, M>(
+        &self,
+        mat: &M,
+        out: &mut RowMajorMatrixViewMut<'_, F>,
+        transform: impl Fn(RowMajorMatrix<F>) -> RowMajorMatrix<F>,
+    ) where
+        F: TwoAdicField,
+        M: Matrix<F>,
+    {
+        let width = mat.width();
+        assert_eq!(out.width(), width);
+        let block_width = (self.max_block_len / out.height()).max(1);
+
+        for start in (0..width).step_by(block_width) {
+            let end = (start + block_width).min(width);
+            let values: Vec<F> = (0..mat.height())
+                .flat_map(|r| mat.row(r).skip(start).take(end - start))
+                .collect();
+            let block = transform(RowMajorMatrix::new(values, end - start));
+            assert_eq!(block.height(), out.height());
+
+            for (out_row, block_row) in out.rows_mut().zip(block.values.chunks_exact(block.width)) {
+                out_row[start..end].copy_from_slice(block_row);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+    use p3_matrix::dense::RowMajorMatrixView;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::Radix2DitParallel;
+
+    #[test]
+    fn blocks_match_inner_dft() {
+        type F = BabyBear;
+        let mut rng = thread_rng();
+        let (log_h, width, added_bits) = (4, 7, 2);
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, width);
+        let input = RowMajorMatrixView::new(&mat.values, width);
+        let inner = Radix2DitParallel::default();
+        let shift = F::generator();
+
+        // Room for two LDE columns at a time, so the last block is narrower.
+        let dft = ColumnBlockedDft::new(inner.clone(), 2 << (log_h + added_bits));
+
+        let mut out = vec![F::zero(); mat.values.len()];
+        dft.dft_batch_into(&input, &mut RowMajorMatrixViewMut::new(&mut out, width));
+        assert_eq!(
+            out,
+            inner.dft_batch(mat.clone()).to_row_major_matrix().values
+        );
+
+        let mut out = vec![F::zero(); mat.values.len() << added_bits];
+        dft.coset_lde_batch_into(
+            &input,
+            added_bits,
+            shift,
+            &mut RowMajorMatrixViewMut::new(&mut out, width),
+        );
+        assert_eq!(
+            out,
+            inner
+                .coset_lde_batch(mat.clone(), added_bits, shift)
+                .to_row_major_matrix()
+                .values
+        );
+
+        dft.coset_lde_batch_bit_reversed_into(
+            &input,
+            added_bits,
+            shift,
+            &mut RowMajorMatrixViewMut::new(&mut out, width),
+        );
+        assert_eq!(
+            out,
+            inner
+                .coset_lde_batch_bit_reversed(mat, added_bits, shift)
+                .values
+        );
+    }
+}
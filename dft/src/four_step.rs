@@ -0,0 +1,111 @@
+use alloc::vec;
+
+use p3_field::TwoAdicField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use tracing::instrument;
+
+use crate::TwoAdicSubgroupDft;
+
+/// The four-step FFT algorithm, for transforms too tall for a single butterfly network to run
+/// without thrashing the cache.
+///
+/// A DFT of height `n = n_1 n_2` is computed as a DFT of height `n_1` and width `n_2` times the
+/// original width, a pass which transposes the intermediate result and multiplies it by twiddle
+/// factors, and a DFT of height `n_2` and width `n_1` times the original width. Both inner DFTs,
+/// computed by `Dft`, are short and wide, so their butterflies access nearby memory.
+#[derive(Default, Clone, Debug)]
+pub struct FourStepDft<Dft> {
+    inner: Dft,
+}
+
+impl<Dft> FourStepDft<Dft> {
+    pub const fn new(inner: Dft) -> Self {
+        Self { inner }
+    }
+}
+
+impl<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>> TwoAdicSubgroupDft<F> for FourStepDft<Dft> {
+    type Evaluations = RowMajorMatrix<F>;
+
+    #[instrument(skip_all, fields(dims = %mat.dimensions()))]
+    fn dft_batch(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let width = mat.width();
+        let log_h = log2_strict_usize(mat.height());
+        let log_n1 = log_h / 2;
+        let (n1, n2) = (1 << log_n1, 1 << (log_h - log_n1));
+
+        // With input row j = n_2 j_1 + j_2 and output row k = k_1 + n_1 k_2,
+        //     X_k = \sum_{j_2} w_{n_2}^{j_2 k_2} w_n^{j_2 k_1} \sum_{j_1} w_{n_1}^{j_1 k_1} x_j.
+        // The inner sums are the DFTs of the columns of the input, viewed as a matrix of height
+        // n_1 whose rows each hold n_2 of the original rows.
+        let inner_dfts = self
+            .inner
+            .dft_batch(RowMajorMatrix::new(mat.values, n2 * width))
+            .to_row_major_matrix();
+
+        // Transpose, so that the row for j_2 holds the n_1 inner sums for each k_1, each multiplied
+        // by w_n^{j_2 k_1}.
+        let root = F::two_adic_generator(log_h);
+        let mut transposed = vec![F::zero(); inner_dfts.values.len()];
+        transposed
+            .par_chunks_exact_mut(n1 * width)
+            .enumerate()
+            .for_each(|(j2, out_row)| {
+                let twiddles = root.exp_u64(j2 as u64).powers();
+                for ((k1, out), twiddle) in
+                    out_row.chunks_exact_mut(width).enumerate().zip(twiddles)
+                {
+                    let sum = &inner_dfts.values[(k1 * n2 + j2) * width..][..width];
+                    for (o, &s) in out.iter_mut().zip(sum) {
+                        *o = s * twiddle;
+                    }
+                }
+            });
+
+        // The outer sums are then DFTs of height n_2, whose row for k_2 holds the n_1 original
+        // rows k_1 + n_1 k_2, for each k_1, in order.
+        let outer_dfts = self
+            .inner
+            .dft_batch(RowMajorMatrix::new(transposed, n1 * width))
+            .to_row_major_matrix();
+        RowMajorMatrix::new(outer_dfts.values, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_goldilocks::Goldilocks;
+
+    use crate::testing::*;
+    use crate::{FourStepDft, Radix2Dit, Radix2DitParallel};
+
+    #[test]
+    fn dft_matches_naive() {
+        test_dft_matches_naive::<BabyBear, FourStepDft<Radix2Dit<_>>>();
+        test_dft_matches_naive::<Goldilocks, FourStepDft<Radix2DitParallel<_>>>();
+    }
+
+    #[test]
+    fn coset_dft_matches_naive() {
+        test_coset_dft_matches_naive::<BabyBear, FourStepDft<Radix2Dit<_>>>();
+    }
+
+    #[test]
+    fn idft_matches_naive() {
+        test_idft_matches_naive::<Goldilocks, FourStepDft<Radix2Dit<_>>>();
+    }
+
+    #[test]
+    fn coset_lde_matches_naive() {
+        test_coset_lde_matches_naive::<BabyBear, FourStepDft<Radix2DitParallel<_>>>();
+    }
+
+    #[test]
+    fn dft_idft_consistency() {
+        test_dft_idft_consistency::<BabyBear, FourStepDft<Radix2Dit<_>>>();
+    }
+}
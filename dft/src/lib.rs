@@ -5,6 +5,9 @@
 extern crate alloc;
 
 mod butterflies;
+mod column_blocked;
+mod four_step;
+mod mixed_radix;
 mod naive;
 mod radix_2_bowers;
 mod radix_2_dit;
@@ -14,6 +17,9 @@ mod testing;
 mod traits;
 mod util;
 
+pub use column_blocked::*;
+pub use four_step::*;
+pub use mixed_radix::*;
 pub use naive::*;
 pub use radix_2_bowers::*;
 pub use radix_2_dit::*;
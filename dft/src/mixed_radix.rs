@@ -0,0 +1,264 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use num_bigint::BigUint;
+use p3_field::{Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::util::divide_by_height;
+
+/// A DFT over subgroups of order `2^a 3^b`, so that matrices whose height isn't a power of two
+/// don't have to be padded up to one.
+///
+/// The subgroup of order `n` is generated by `MixedRadixDft::root_of_unity(n)`, which for powers of
+/// two is `F::two_adic_generator`, so that for such heights the results match those of the
+/// `TwoAdicSubgroupDft` implementations.
+#[derive(Default, Clone, Debug)]
+pub struct MixedRadixDft;
+
+impl MixedRadixDft {
+    /// The generator of the subgroup of order `n = 2^a 3^b`: the product of
+    /// `F::two_adic_generator(a)` and `F::generator()^((p - 1) / 3^b)`.
+    ///
+    /// Panics if `n` isn't of that form, or doesn't divide the order of the multiplicative group.
+    pub fn root_of_unity<F: TwoAdicField>(n: usize) -> F {
+        let (log_2, log_3) = factor(n);
+        assert!(
+            log_2 <= F::TWO_ADICITY,
+            "subgroup of order {n} doesn't exist"
+        );
+        let group_order = F::order() - 1u32;
+        let pow_3 = BigUint::from(3u32).pow(log_3 as u32);
+        assert_eq!(
+            &group_order % &pow_3,
+            BigUint::from(0u32),
+            "subgroup of order {n} doesn't exist"
+        );
+        F::two_adic_generator(log_2) * exp_biguint(F::generator(), &(group_order / pow_3))
+    }
+
+    /// Compute the DFT of each column in `mat`, whose height must be of the form `2^a 3^b`.
+    pub fn dft_batch<F: TwoAdicField>(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let root = Self::root_of_unity(mat.height());
+        RowMajorMatrix::new(dft(&mat.values, mat.width(), root), mat.width())
+    }
+
+    /// Compute the inverse DFT of each column in `mat`.
+    pub fn idft_batch<F: TwoAdicField>(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let root = Self::root_of_unity::<F>(mat.height()).inverse();
+        let mut coeffs = RowMajorMatrix::new(dft(&mat.values, mat.width(), root), mat.width());
+        divide_by_height(&mut coeffs);
+        coeffs
+    }
+
+    /// Compute the "coset DFT" of each column in `mat`, i.e. its evaluations over `shift` times
+    /// the subgroup.
+    pub fn coset_dft_batch<F: TwoAdicField>(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        scale_rows_by_powers(&mut mat, shift);
+        self.dft_batch(mat)
+    }
+
+    /// Compute the "coset iDFT" of each column in `mat`, interpolating over `shift` times the
+    /// subgroup.
+    pub fn coset_idft_batch<F: TwoAdicField>(
+        &self,
+        mat: RowMajorMatrix<F>,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let mut coeffs = self.idft_batch(mat);
+        scale_rows_by_powers(&mut coeffs, shift.inverse());
+        coeffs
+    }
+
+    /// Compute the low-degree extension of each column in `mat` onto `shift` times the subgroup
+    /// of order `mat.height() << added_bits`.
+    pub fn coset_lde_batch<F: TwoAdicField>(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let mut coeffs = self.idft_batch(mat);
+        coeffs
+            .values
+            .resize(coeffs.values.len() << added_bits, F::zero());
+        self.coset_dft_batch(coeffs, shift)
+    }
+}
+
+/// Split `n` into `(a, b)` with `n = 2^a 3^b`, panicking if there are any other factors.
+fn factor(mut n: usize) -> (usize, usize) {
+    assert_ne!(n, 0);
+    let log_2 = n.trailing_zeros() as usize;
+    n >>= log_2;
+    let mut log_3 = 0;
+    while n % 3 == 0 {
+        n /= 3;
+        log_3 += 1;
+    }
+    assert_eq!(n, 1, "height must be of the form 2^a 3^b");
+    (log_2, log_3)
+}
+
+fn exp_biguint<F: Field>(base: F, exponent: &BigUint) -> F {
+    (0..exponent.bits()).rev().fold(F::one(), |acc, i| {
+        let acc = acc.square();
+        if exponent.bit(i) {
+            acc * base
+        } else {
+            acc
+        }
+    })
+}
+
+/// Multiply row `i` of `mat` by `base^i`.
+fn scale_rows_by_powers<F: Field>(mat: &mut RowMajorMatrix<F>, base: F) {
+    mat.rows_mut().zip(base.powers()).for_each(|(row, weight)| {
+        row.iter_mut().for_each(|coeff| *coeff *= weight);
+    });
+}
+
+/// The DFT of each column of the row-major matrix of the given width, over the powers of `root`,
+/// which must have order equal to the matrix's height. Each step splits the coefficients by
+/// their degree modulo 2 or 3, transforms each part recursively, then combines them.
+fn dft<F: Field>(values: &[F], width: usize, root: F) -> Vec<F> {
+    let height = values.len() / width;
+    if height <= 1 {
+        return values.to_vec();
+    }
+    let radix = if height % 2 == 0 { 2 } else { 3 };
+    debug_assert_eq!(height % radix, 0);
+    let sub_height = height / radix;
+
+    let sub_root = root.exp_u64(radix as u64);
+    let parts = (0..radix)
+        .map(|residue| {
+            let part = values
+                .chunks_exact(width)
+                .skip(residue)
+                .step_by(radix)
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>();
+            dft(&part, width, sub_root)
+        })
+        .collect::<Vec<_>>();
+
+    // With x = root^(k + sub_height j), the evaluation at x is the sum of x^residue times the
+    // evaluation of each part at x^radix = sub_root^k.
+    let radix_root = root.exp_u64(sub_height as u64);
+    let mut out = vec![F::zero(); values.len()];
+    for (k, root_k) in root.powers().take(sub_height).enumerate() {
+        for (j, radix_root_j) in radix_root.powers().take(radix).enumerate() {
+            let x = root_k * radix_root_j;
+            let out_row = &mut out[(k + sub_height * j) * width..][..width];
+            for part in parts.iter().rev() {
+                for (acc, &value) in out_row.iter_mut().zip(&part[k * width..(k + 1) * width]) {
+                    *acc = *acc * x + value;
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+    use p3_goldilocks::Goldilocks;
+    use rand::distributions::{Distribution, Standard};
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::{Radix2Dit, TwoAdicSubgroupDft};
+
+    /// Evaluate each column of `coeffs` at `shift` times each power of `root`.
+    fn naive_coset_dft<F: Field>(
+        coeffs: &RowMajorMatrix<F>,
+        root: F,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let width = coeffs.width();
+        let mut evals = RowMajorMatrix::new(vec![F::zero(); width * coeffs.height()], width);
+        for (row, point) in evals.rows_mut().zip(root.powers().map(|x| x * shift)) {
+            for (coeff_row, point_power) in coeffs.rows().zip(point.powers()) {
+                for (eval, coeff) in row.iter_mut().zip(coeff_row) {
+                    *eval += point_power * coeff;
+                }
+            }
+        }
+        evals
+    }
+
+    fn matches_naive<F: TwoAdicField>(heights: &[usize])
+    where
+        Standard: Distribution<F>,
+    {
+        let mut rng = thread_rng();
+        for &height in heights {
+            let root = MixedRadixDft::root_of_unity::<F>(height);
+            assert_eq!(root.exp_u64(height as u64), F::one());
+            if height > 1 {
+                assert_ne!(root.exp_u64(height as u64 / 2), F::one());
+            }
+
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, height, 3);
+            let dft = MixedRadixDft.dft_batch(mat.clone());
+            assert_eq!(dft, naive_coset_dft(&mat, root, F::one()));
+            assert_eq!(MixedRadixDft.idft_batch(dft), mat);
+
+            let shift = F::generator();
+            let coset_dft = MixedRadixDft.coset_dft_batch(mat.clone(), shift);
+            assert_eq!(coset_dft, naive_coset_dft(&mat, root, shift));
+            assert_eq!(MixedRadixDft.coset_idft_batch(coset_dft, shift), mat);
+        }
+    }
+
+    #[test]
+    fn dft_matches_naive_baby_bear() {
+        matches_naive::<BabyBear>(&[1, 2, 3, 6, 12, 24, 48]);
+    }
+
+    #[test]
+    fn dft_matches_naive_goldilocks() {
+        matches_naive::<Goldilocks>(&[1, 3, 4, 6, 24]);
+    }
+
+    #[test]
+    fn powers_of_two_match_radix_2() {
+        let mut rng = thread_rng();
+        for log_h in 0..6 {
+            let mat = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << log_h, 3);
+            assert_eq!(
+                MixedRadixDft.dft_batch(mat.clone()),
+                Radix2Dit::default().dft_batch(mat).to_row_major_matrix()
+            );
+        }
+    }
+
+    #[test]
+    fn coset_lde_extends_evaluations() {
+        type F = BabyBear;
+        let mut rng = thread_rng();
+        let height = 12;
+        let evals = RowMajorMatrix::<F>::rand(&mut rng, height, 2);
+        let shift = F::generator();
+        let lde = MixedRadixDft.coset_lde_batch(evals.clone(), 2, shift);
+        assert_eq!(lde.height(), height << 2);
+
+        // The LDE's polynomials agree with the original evaluations over the subgroup...
+        let coeffs = MixedRadixDft.idft_batch(evals.clone());
+        let lde_coeffs = MixedRadixDft.coset_idft_batch(lde, shift);
+        assert_eq!(lde_coeffs.values[..coeffs.values.len()], coeffs.values);
+        // ...and have no higher degree terms.
+        assert!(lde_coeffs.values[coeffs.values.len()..]
+            .iter()
+            .all(|c| c.is_zero()));
+    }
+}
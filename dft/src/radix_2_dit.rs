@@ -1,15 +1,16 @@
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
-use core::cell::RefCell;
+use core::cell::{RefCell, RefMut};
 
-use p3_field::{Field, TwoAdicField};
+use p3_field::{Field, Powers, TwoAdicField};
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixViewMut};
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
-use p3_util::log2_strict_usize;
+use p3_util::{log2_strict_usize, reverse_bits_len};
 
-use crate::butterflies::{Butterfly, DitButterfly, TwiddleFreeButterfly};
+use crate::butterflies::{Butterfly, DifButterfly, DitButterfly, TwiddleFreeButterfly};
+use crate::util::bit_reversed_zero_pad;
 use crate::TwoAdicSubgroupDft;
 
 /// The DIT FFT algorithm.
@@ -17,6 +18,8 @@ use crate::TwoAdicSubgroupDft;
 pub struct Radix2Dit<F: TwoAdicField> {
     /// Memoized twiddle factors for each length log_n.
     twiddles: RefCell<BTreeMap<usize, Vec<F>>>,
+    /// Memoized inverse twiddle factors for each length log_n.
+    inverse_twiddles: RefCell<BTreeMap<usize, Vec<F>>>,
 }
 
 impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Dit<F> {
@@ -27,19 +30,126 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Dit<F> {
         let log_h = log2_strict_usize(h);
 
         // Compute twiddle factors, or take memoized ones if already available.
-        let mut twiddles_ref_mut = self.twiddles.borrow_mut();
-        let twiddles = twiddles_ref_mut.entry(log_h).or_insert_with(|| {
-            let root = F::two_adic_generator(log_h);
-            root.powers().take(1 << log_h).collect()
-        });
+        let twiddles = memoized_powers(&self.twiddles, log_h, false);
 
         // DIT butterfly
         reverse_matrix_index_bits(&mut mat);
         for layer in 0..log_h {
-            dit_layer(&mut mat.as_view_mut(), layer, twiddles);
+            dit_layer(&mut mat.as_view_mut(), layer, &twiddles);
         }
         mat
     }
+
+    /// Unlike the default implementation, this doesn't reorder the coefficients between the two
+    /// transforms: a DIF network computes them in bit-reversed order, which is what the DIT
+    /// network of the extended DFT expects, and the shift and `1 / height` scaling are applied
+    /// together, in the same pass.
+    fn coset_lde_batch(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let log_h = log2_strict_usize(h);
+
+        // Inverse DIF butterfly, leaving the (unscaled) coefficients in bit-reversed order.
+        {
+            let inverse_twiddles = memoized_powers(&self.inverse_twiddles, log_h, true);
+            for layer in 0..log_h {
+                dif_layer(&mut mat.as_view_mut(), layer, &inverse_twiddles);
+            }
+        }
+
+        // Divide by the height, and multiply by powers of the shift (see the default coset DFT
+        // for an explanation).
+        let weights = Powers {
+            base: shift,
+            current: F::from_canonical_usize(h).inverse(),
+        };
+        for (row, weight) in weights.take(h).enumerate() {
+            mat.scale_row(reverse_bits_len(row, log_h), weight);
+        }
+
+        // DIT butterfly, over the bit-reversed zero-padded coefficients.
+        bit_reversed_zero_pad(&mut mat, added_bits);
+        let log_lde = log_h + added_bits;
+        let twiddles = memoized_powers(&self.twiddles, log_lde, false);
+        for layer in 0..log_lde {
+            dit_layer(&mut mat.as_view_mut(), layer, &twiddles);
+        }
+        mat
+    }
+
+    /// A DIF network takes natural-order input to bit-reversed output, so this is the DIF
+    /// counterpart of `dft_batch`, with no reordering.
+    fn dft_batch_bit_reversed(&self, mut mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let log_h = log2_strict_usize(mat.height());
+        let twiddles = memoized_powers(&self.twiddles, log_h, false);
+        for layer in 0..log_h {
+            dif_layer(&mut mat.as_view_mut(), layer, &twiddles);
+        }
+        mat
+    }
+
+    /// Like `coset_lde_batch`, but with the two networks swapped: an inverse DIT network over the
+    /// bit-reversed input leaves the coefficients in natural order, and a DIF network over the
+    /// zero-padded coefficients leaves the LDE in bit-reversed order. Only the input is reordered,
+    /// which is `2^added_bits` times smaller than the LDE.
+    fn coset_lde_batch_bit_reversed(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let h = mat.height();
+        let log_h = log2_strict_usize(h);
+
+        // Inverse DIT butterfly, leaving the (unscaled) coefficients in natural order.
+        reverse_matrix_index_bits(&mut mat);
+        {
+            let inverse_twiddles = memoized_powers(&self.inverse_twiddles, log_h, true);
+            for layer in 0..log_h {
+                dit_layer(&mut mat.as_view_mut(), layer, &inverse_twiddles);
+            }
+        }
+
+        // Divide by the height, and multiply by powers of the shift.
+        let weights = Powers {
+            base: shift,
+            current: F::from_canonical_usize(h).inverse(),
+        };
+        for (row, weight) in weights.take(h).enumerate() {
+            mat.scale_row(row, weight);
+        }
+
+        // DIF butterfly, over the zero-padded coefficients.
+        mat.values.resize(mat.values.len() << added_bits, F::zero());
+        let log_lde = log_h + added_bits;
+        let twiddles = memoized_powers(&self.twiddles, log_lde, false);
+        for layer in 0..log_lde {
+            dif_layer(&mut mat.as_view_mut(), layer, &twiddles);
+        }
+        mat
+    }
+}
+
+/// The powers of the generator of the subgroup of order `2^log_h`, or of its inverse, computed
+/// once and then memoized in `cache`.
+fn memoized_powers<F: TwoAdicField>(
+    cache: &RefCell<BTreeMap<usize, Vec<F>>>,
+    log_h: usize,
+    inverse: bool,
+) -> RefMut<'_, Vec<F>> {
+    RefMut::map(cache.borrow_mut(), |cache| {
+        cache.entry(log_h).or_insert_with(|| {
+            let mut root = F::two_adic_generator(log_h);
+            if inverse {
+                root = root.inverse();
+            }
+            root.powers().take(1 << log_h).collect()
+        })
+    })
 }
 
 /// One layer of a DIT butterfly network.
@@ -70,13 +180,45 @@ fn dit_layer<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, layer: usize, twi
         });
 }
 
+/// One layer of a DIF butterfly network, taking natural-order input to bit-reversed output.
+fn dif_layer<F: Field>(mat: &mut RowMajorMatrixViewMut<'_, F>, layer: usize, twiddles: &[F]) {
+    let h = mat.height();
+    let log_h = log2_strict_usize(h);
+    let layer_rev = log_h - 1 - layer;
+
+    let half_block_size = 1 << layer_rev;
+    let block_size = half_block_size * 2;
+
+    mat.par_row_chunks_exact_mut(block_size)
+        .for_each(|mut block_chunks| {
+            let (mut hi_chunks, mut lo_chunks) = block_chunks.split_rows_mut(half_block_size);
+            hi_chunks
+                .par_rows_mut()
+                .zip(lo_chunks.par_rows_mut())
+                .enumerate()
+                .for_each(|(ind, (hi_chunk, lo_chunk))| {
+                    if ind == 0 {
+                        TwiddleFreeButterfly.apply_to_rows(hi_chunk, lo_chunk)
+                    } else {
+                        DifButterfly(twiddles[ind << layer]).apply_to_rows(hi_chunk, lo_chunk)
+                    }
+                });
+        });
+}
+
 #[cfg(test)]
 mod tests {
     use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::AbstractField;
     use p3_goldilocks::Goldilocks;
+    use p3_matrix::bitrev::BitReversableMatrix;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use rand::thread_rng;
 
     use crate::testing::*;
-    use crate::Radix2Dit;
+    use crate::{NaiveDft, Radix2Dit, TwoAdicSubgroupDft};
 
     #[test]
     fn dft_matches_naive() {
@@ -109,6 +251,42 @@ mod tests {
         test_coset_lde_matches_naive::<BabyBear, Radix2Dit<_>>();
     }
 
+    #[test]
+    fn coset_lde_matches_naive_for_larger_blowups() {
+        type F = BabyBear;
+        let dft = Radix2Dit::default();
+        let mut rng = thread_rng();
+        for log_h in 0..5 {
+            for added_bits in 0..4 {
+                let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, 3);
+                let shift = F::generator();
+                let expected = NaiveDft.coset_lde_batch(mat.clone(), added_bits, shift);
+                assert_eq!(
+                    dft.coset_lde_batch(mat.clone(), added_bits, shift),
+                    expected
+                );
+                assert_eq!(
+                    dft.coset_lde_batch_bit_reversed(mat, added_bits, shift),
+                    expected.bit_reverse_rows().to_row_major_matrix()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn algebra_batches_match_naive() {
+        test_algebra_batches_match_naive::<
+            BabyBear,
+            BinomialExtensionField<BabyBear, 4>,
+            Radix2Dit<_>,
+        >();
+    }
+
+    #[test]
+    fn bit_reversed_outputs_match() {
+        test_bit_reversed_outputs_match::<BabyBear, Radix2Dit<_>>();
+    }
+
     #[test]
     fn dft_idft_consistency() {
         test_dft_idft_consistency::<BabyBear, Radix2Dit<_>>();
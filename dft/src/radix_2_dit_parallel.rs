@@ -1,3 +1,6 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
 use alloc::vec::Vec;
 
 use p3_field::{Field, Powers, TwoAdicField};
@@ -7,6 +10,7 @@ use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_strict_usize, reverse_bits, reverse_slice_index_bits};
+use spin::RwLock;
 use tracing::instrument;
 
 use crate::butterflies::{Butterfly, DitButterfly};
@@ -18,94 +22,239 @@ use crate::TwoAdicSubgroupDft;
 /// i.e. either DIT or Bowers G. Then we bit-reverse, and for the second half, we continue executing
 /// the same network but in bit-reversed order. This way we're always working with small blocks,
 /// so within each half, we can have a certain amount of parallelism with no cross-thread
-/// communication.
-#[derive(Default, Clone, Debug)]
-pub struct Radix2DitParallel;
+/// communication. Wide matrices are also split into strips of columns, which are transformed in
+/// parallel.
+///
+/// Twiddles and coset weights are memoized behind locks, so one instance can be shared between
+/// threads.
+#[derive(Default, Debug)]
+pub struct Radix2DitParallel<F: TwoAdicField> {
+    /// Memoized twiddle factors for each log height.
+    twiddles: RwLock<BTreeMap<usize, Arc<Twiddles<F>>>>,
+    /// Memoized inverse twiddle factors for each log height.
+    inverse_twiddles: RwLock<BTreeMap<usize, Arc<Twiddles<F>>>>,
+    /// Memoized coset LDE weights for each log height, for the last coset shift used with it, so
+    /// that at most one table is kept for each height.
+    coset_weights: RwLock<BTreeMap<usize, (F, Arc<Vec<F>>)>>,
+}
 
-impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2DitParallel {
-    type Evaluations = BitReversedMatrixView<RowMajorMatrix<F>>;
+impl<F: TwoAdicField> Clone for Radix2DitParallel<F> {
+    fn clone(&self) -> Self {
+        Self {
+            twiddles: RwLock::new(self.twiddles.read().clone()),
+            inverse_twiddles: RwLock::new(self.inverse_twiddles.read().clone()),
+            coset_weights: RwLock::new(self.coset_weights.read().clone()),
+        }
+    }
+}
 
-    fn dft_batch(&self, mut mat: RowMajorMatrix<F>) -> Self::Evaluations {
-        let h = mat.height();
-        let log_h = log2_strict_usize(h);
+/// The powers of a root of unity of order `h`, up to `h / 2`, in natural and bit-reversed order.
+#[derive(Debug)]
+struct Twiddles<F> {
+    natural: Vec<F>,
+    bit_reversed: Vec<F>,
+}
 
-        let root = F::two_adic_generator(log_h);
-        let mut twiddles: Vec<F> = root.powers().take(h / 2).collect();
+impl<F: TwoAdicField> Twiddles<F> {
+    fn new(root: F, h: usize) -> Self {
+        let natural: Vec<F> = root.powers().take(h / 2).collect();
+        let mut bit_reversed = natural.clone();
+        reverse_slice_index_bits(&mut bit_reversed);
+        Self {
+            natural,
+            bit_reversed,
+        }
+    }
+}
 
-        let mid = log_h / 2;
+impl<F: TwoAdicField> Radix2DitParallel<F> {
+    fn twiddles(&self, log_h: usize) -> Arc<Twiddles<F>> {
+        memoized(&self.twiddles, log_h, || {
+            Arc::new(Twiddles::new(F::two_adic_generator(log_h), 1 << log_h))
+        })
+    }
 
-        // The first half looks like a normal DIT.
-        reverse_matrix_index_bits(&mut mat);
-        par_dit_layer(&mut mat, mid, &twiddles);
+    fn inverse_twiddles(&self, log_h: usize) -> Arc<Twiddles<F>> {
+        memoized(&self.inverse_twiddles, log_h, || {
+            let root_inv = F::two_adic_generator(log_h).inverse();
+            Arc::new(Twiddles::new(root_inv, 1 << log_h))
+        })
+    }
 
-        // For the second half, we flip the DIT, working in bit-reversed order.
-        reverse_matrix_index_bits(&mut mat);
-        reverse_slice_index_bits(&mut twiddles);
-        par_dit_layer_rev(&mut mat, mid, &twiddles);
+    /// The weights `shift^i / h` which coefficients are rescaled by in a coset LDE: dividing by
+    /// the height inverts the DFT, and multiplying by powers of the shift moves to the coset (see
+    /// the default coset LDE impl for an explanation).
+    fn coset_weights(&self, log_h: usize, shift: F) -> Arc<Vec<F>> {
+        if let Some((s, weights)) = self.coset_weights.read().get(&log_h) {
+            if *s == shift {
+                return weights.clone();
+            }
+        }
+        let h = 1 << log_h;
+        let weights = Arc::new(
+            Powers {
+                base: shift,
+                current: F::from_canonical_usize(h).inverse(),
+            }
+            .take(h)
+            .collect(),
+        );
+        self.coset_weights
+            .write()
+            .insert(log_h, (shift, weights.clone()));
+        weights
+    }
+}
 
-        mat.bit_reverse_rows()
+/// The value memoized in `cache` for `key`, computing it with `f` if there's none yet. The lock
+/// isn't held while computing it, so two threads may both compute it, and the first one kept.
+fn memoized<V: Clone>(cache: &RwLock<BTreeMap<usize, V>>, key: usize, f: impl FnOnce() -> V) -> V {
+    if let Some(value) = cache.read().get(&key) {
+        return value.clone();
+    }
+    let value = f();
+    cache.write().entry(key).or_insert(value).clone()
+}
+
+impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2DitParallel<F> {
+    type Evaluations = BitReversedMatrixView<RowMajorMatrix<F>>;
+
+    fn dft_batch(&self, mat: RowMajorMatrix<F>) -> Self::Evaluations {
+        self.dft_batch_bit_reversed(mat).bit_reverse_rows()
+    }
+
+    fn dft_batch_bit_reversed(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let twiddles = self.twiddles(log2_strict_usize(mat.height()));
+        par_column_strips(mat, |strip| dft_bit_reversed(strip, &twiddles))
     }
 
-    #[instrument(skip_all, fields(dims = %mat.dimensions(), added_bits))]
     fn coset_lde_batch(
         &self,
-        mut mat: RowMajorMatrix<F>,
+        mat: RowMajorMatrix<F>,
         added_bits: usize,
         shift: F,
     ) -> Self::Evaluations {
-        let h = mat.height();
-        let log_h = log2_strict_usize(h);
-        let mid = log_h / 2;
-        let h_inv = F::from_canonical_usize(h).inverse();
-
-        let root = F::two_adic_generator(log_h);
-        let root_inv = root.inverse();
-
-        let mut twiddles_inv: Vec<F> = root_inv.powers().take(h / 2).collect();
-
-        // The first half looks like a normal DIT.
-        reverse_matrix_index_bits(&mut mat);
-        par_dit_layer(&mut mat, mid, &twiddles_inv);
-
-        // For the second half, we flip the DIT, working in bit-reversed order.
-        reverse_matrix_index_bits(&mut mat);
-        reverse_slice_index_bits(&mut twiddles_inv);
-        par_dit_layer_rev(&mut mat, mid, &twiddles_inv);
-        // We skip the final bit-reversal, since the next FFT expects bit-reversed input.
-
-        // Rescale coefficients in two ways:
-        // - divide by height (since we're doing an inverse DFT)
-        // - multiply by powers of the coset shift (see default coset LDE impl for an explanation)
-        let weights = Powers {
-            base: shift,
-            current: h_inv,
-        }
-        .take(h);
-        for (row, weight) in weights.enumerate() {
-            // reverse_bits because mat is encoded in bit-reversed order
-            mat.scale_row(reverse_bits(row, h), weight);
-        }
+        self.coset_lde_batch_bit_reversed(mat, added_bits, shift)
+            .bit_reverse_rows()
+    }
 
-        mat = mat.bit_reversed_zero_pad(added_bits);
+    #[instrument(skip_all, fields(dims = %mat.dimensions(), added_bits))]
+    fn coset_lde_batch_bit_reversed(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        let log_h = log2_strict_usize(mat.height());
+        let inverse_twiddles = self.inverse_twiddles(log_h);
+        let weights = self.coset_weights(log_h, shift);
+        let twiddles = self.twiddles(log_h + added_bits);
+        par_column_strips(mat, |strip| {
+            coset_lde_bit_reversed(strip, added_bits, &inverse_twiddles, &weights, &twiddles)
+        })
+    }
+}
 
-        let h = mat.height();
-        let log_h = log2_strict_usize(h);
-        let mid = log_h / 2;
+/// Matrices wider than this are split into strips of this many columns, which are transformed in
+/// parallel. Otherwise, the only parallelism is between blocks of rows, of which short matrices
+/// have few.
+const COLUMN_STRIP_WIDTH: usize = 256;
+
+/// Apply `f` to strips of `mat`'s columns in parallel, and join the results back together.
+fn par_column_strips<F: Field>(
+    mat: RowMajorMatrix<F>,
+    f: impl Fn(RowMajorMatrix<F>) -> RowMajorMatrix<F> + Sync,
+) -> RowMajorMatrix<F> {
+    let width = mat.width();
+    if width <= COLUMN_STRIP_WIDTH {
+        return f(mat);
+    }
 
-        let root = F::two_adic_generator(log_h);
+    let strips: Vec<RowMajorMatrix<F>> = (0..width)
+        .step_by(COLUMN_STRIP_WIDTH)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|start| {
+            let end = (start + COLUMN_STRIP_WIDTH).min(width);
+            let values = mat
+                .values
+                .chunks_exact(width)
+                .flat_map(|row| &row[start..end])
+                .copied()
+                .collect();
+            f(RowMajorMatrix::new(values, end - start))
+        })
+        .collect();
+
+    let height = strips[0].height();
+    let mut values = vec![F::zero(); height * width];
+    values
+        .par_chunks_exact_mut(width)
+        .enumerate()
+        .for_each(|(r, row)| {
+            let mut start = 0;
+            for strip in &strips {
+                let strip_row = &strip.values[r * strip.width..(r + 1) * strip.width];
+                row[start..start + strip.width].copy_from_slice(strip_row);
+                start += strip.width;
+            }
+        });
+    RowMajorMatrix::new(values, width)
+}
 
-        let mut twiddles: Vec<F> = root.powers().take(h / 2).collect();
+/// The DFT of each column of `mat`, in bit-reversed order.
+fn dft_bit_reversed<F: TwoAdicField>(
+    mut mat: RowMajorMatrix<F>,
+    twiddles: &Twiddles<F>,
+) -> RowMajorMatrix<F> {
+    let mid = log2_strict_usize(mat.height()) / 2;
+
+    // The first half looks like a normal DIT.
+    reverse_matrix_index_bits(&mut mat);
+    par_dit_layer(&mut mat, mid, &twiddles.natural);
+
+    // For the second half, we flip the DIT, working in bit-reversed order.
+    reverse_matrix_index_bits(&mut mat);
+    par_dit_layer_rev(&mut mat, mid, &twiddles.bit_reversed);
+    mat
+}
+
+/// The low-degree extension of each column of `mat` onto a coset of a larger subgroup, in
+/// bit-reversed order.
+fn coset_lde_bit_reversed<F: TwoAdicField>(
+    mut mat: RowMajorMatrix<F>,
+    added_bits: usize,
+    inverse_twiddles: &Twiddles<F>,
+    weights: &[F],
+    twiddles: &Twiddles<F>,
+) -> RowMajorMatrix<F> {
+    let h = mat.height();
+    let mid = log2_strict_usize(h) / 2;
+
+    // The first half looks like a normal DIT.
+    reverse_matrix_index_bits(&mut mat);
+    par_dit_layer(&mut mat, mid, &inverse_twiddles.natural);
+
+    // For the second half, we flip the DIT, working in bit-reversed order.
+    reverse_matrix_index_bits(&mut mat);
+    par_dit_layer_rev(&mut mat, mid, &inverse_twiddles.bit_reversed);
+    // We skip the final bit-reversal, since the next FFT expects bit-reversed input.
+
+    for (row, &weight) in weights.iter().enumerate() {
+        // reverse_bits because mat is encoded in bit-reversed order
+        mat.scale_row(reverse_bits(row, h), weight);
+    }
 
-        // The first half looks like a normal DIT.
-        par_dit_layer(&mut mat, mid, &twiddles);
+    mat = mat.bit_reversed_zero_pad(added_bits);
+    let mid = log2_strict_usize(mat.height()) / 2;
 
-        // For the second half, we flip the DIT, working in bit-reversed order.
-        reverse_matrix_index_bits(&mut mat);
-        reverse_slice_index_bits(&mut twiddles);
-        par_dit_layer_rev(&mut mat, mid, &twiddles);
+    // The first half looks like a normal DIT.
+    par_dit_layer(&mut mat, mid, &twiddles.natural);
 
-        mat.bit_reverse_rows()
-    }
+    // For the second half, we flip the DIT, working in bit-reversed order.
+    reverse_matrix_index_bits(&mut mat);
+    par_dit_layer_rev(&mut mat, mid, &twiddles.bit_reversed);
+    mat
 }
 
 /// This can be used as the first half of a parallelized butterfly network.
@@ -189,44 +338,107 @@ fn dit_layer_rev<F: Field>(
 #[cfg(test)]
 mod tests {
     use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::AbstractField;
     use p3_goldilocks::Goldilocks;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use rand::thread_rng;
 
     use crate::testing::*;
-    use crate::Radix2DitParallel;
+    use crate::{Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
 
     #[test]
     fn dft_matches_naive() {
-        test_dft_matches_naive::<BabyBear, Radix2DitParallel>();
+        test_dft_matches_naive::<BabyBear, Radix2DitParallel<_>>();
     }
 
     #[test]
     fn coset_dft_matches_naive() {
-        test_coset_dft_matches_naive::<BabyBear, Radix2DitParallel>();
+        test_coset_dft_matches_naive::<BabyBear, Radix2DitParallel<_>>();
     }
 
     #[test]
     fn idft_matches_naive() {
-        test_idft_matches_naive::<Goldilocks, Radix2DitParallel>();
+        test_idft_matches_naive::<Goldilocks, Radix2DitParallel<_>>();
     }
 
     #[test]
     fn coset_idft_matches_naive() {
-        test_coset_idft_matches_naive::<BabyBear, Radix2DitParallel>();
-        test_coset_idft_matches_naive::<Goldilocks, Radix2DitParallel>();
+        test_coset_idft_matches_naive::<BabyBear, Radix2DitParallel<_>>();
+        test_coset_idft_matches_naive::<Goldilocks, Radix2DitParallel<_>>();
     }
 
     #[test]
     fn lde_matches_naive() {
-        test_lde_matches_naive::<BabyBear, Radix2DitParallel>();
+        test_lde_matches_naive::<BabyBear, Radix2DitParallel<_>>();
     }
 
     #[test]
     fn coset_lde_matches_naive() {
-        test_coset_lde_matches_naive::<BabyBear, Radix2DitParallel>();
+        test_coset_lde_matches_naive::<BabyBear, Radix2DitParallel<_>>();
+    }
+
+    #[test]
+    fn algebra_batches_match_naive() {
+        test_algebra_batches_match_naive::<
+            BabyBear,
+            BinomialExtensionField<BabyBear, 4>,
+            Radix2DitParallel<_>,
+        >();
+    }
+
+    #[test]
+    fn bit_reversed_outputs_match() {
+        test_bit_reversed_outputs_match::<BabyBear, Radix2DitParallel<_>>();
     }
 
     #[test]
     fn dft_idft_consistency() {
-        test_dft_idft_consistency::<BabyBear, Radix2DitParallel>();
+        test_dft_idft_consistency::<BabyBear, Radix2DitParallel<_>>();
+    }
+
+    #[test]
+    fn wide_matrices_match_radix_2_dit() {
+        type F = BabyBear;
+        let mut rng = thread_rng();
+        let dft = Radix2DitParallel::default();
+        for log_h in [0, 1, 4] {
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, 600);
+            assert_eq!(
+                dft.dft_batch(mat.clone()).to_row_major_matrix(),
+                Radix2Dit::default().dft_batch(mat.clone())
+            );
+            let shift = F::generator();
+            assert_eq!(
+                dft.coset_lde_batch(mat.clone(), 2, shift)
+                    .to_row_major_matrix(),
+                Radix2Dit::default().coset_lde_batch(mat, 2, shift)
+            );
+        }
+    }
+
+    #[test]
+    fn is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&Radix2DitParallel::<BabyBear>::default());
+    }
+
+    #[test]
+    fn memoized_twiddles_are_reused_correctly() {
+        type F = BabyBear;
+        let mut rng = thread_rng();
+        let dft = Radix2DitParallel::default();
+        // Repeat each LDE, and mix heights and shifts, so that later calls use memoized tables.
+        for _ in 0..2 {
+            for (log_h, shift) in [(3, F::generator()), (3, F::two()), (4, F::generator())] {
+                let mat = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_h, 5);
+                assert_eq!(
+                    dft.coset_lde_batch(mat.clone(), 1, shift)
+                        .to_row_major_matrix(),
+                    Radix2Dit::default().coset_lde_batch(mat, 1, shift)
+                );
+            }
+        }
     }
 }
@@ -1,6 +1,8 @@
-use p3_field::TwoAdicField;
+use p3_field::{AbstractField, ExtensionField, TwoAdicField};
+use p3_matrix::bitrev::BitReversableMatrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
 use rand::distributions::{Distribution, Standard};
 use rand::thread_rng;
 
@@ -127,3 +129,87 @@ where
         assert_eq!(original, idft_output);
     }
 }
+
+pub(crate) fn test_bit_reversed_outputs_match<F, Dft>()
+where
+    F: TwoAdicField,
+    Standard: Distribution<F>,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    let dft = Dft::default();
+    let mut rng = thread_rng();
+    for log_h in 0..5 {
+        let h = 1 << log_h;
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
+        let shift = F::generator();
+        assert_eq!(
+            dft.dft_batch_bit_reversed(mat.clone()),
+            NaiveDft
+                .dft_batch(mat.clone())
+                .bit_reverse_rows()
+                .to_row_major_matrix()
+        );
+        assert_eq!(
+            dft.coset_lde_batch_bit_reversed(mat.clone(), 1, shift),
+            NaiveDft
+                .coset_lde_batch(mat, 1, shift)
+                .bit_reverse_rows()
+                .to_row_major_matrix()
+        );
+    }
+}
+
+/// Evaluate each column of `coeffs` at `shift` times each power of `F`'s generator of the
+/// subgroup of order `height`.
+fn naive_coset_evals<F: TwoAdicField, EF: ExtensionField<F>>(
+    coeffs: &RowMajorMatrix<EF>,
+    height: usize,
+    shift: F,
+) -> RowMajorMatrix<EF> {
+    let root = F::two_adic_generator(log2_strict_usize(height));
+    let width = coeffs.width();
+    let values = root
+        .powers()
+        .take(height)
+        .flat_map(|x| {
+            let point = x * shift;
+            (0..width).map(move |c| {
+                coeffs
+                    .rows()
+                    .zip(point.powers())
+                    .map(|(mut row, point_power)| row.nth(c).unwrap() * point_power)
+                    .sum()
+            })
+        })
+        .collect();
+    RowMajorMatrix::new(values, width)
+}
+
+pub(crate) fn test_algebra_batches_match_naive<F, EF, Dft>()
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F>,
+    Standard: Distribution<EF>,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    let dft = Dft::default();
+    let mut rng = thread_rng();
+    for log_h in 0..5 {
+        let h = 1 << log_h;
+        let mat = RowMajorMatrix::<EF>::rand(&mut rng, h, 3);
+        let shift = F::generator();
+
+        let evals = dft.dft_algebra_batch(mat.clone());
+        assert_eq!(evals, naive_coset_evals(&mat, h, F::one()));
+        assert_eq!(dft.idft_algebra_batch(evals), mat);
+
+        let coset_evals = dft.coset_dft_algebra_batch(mat.clone(), shift);
+        assert_eq!(coset_evals, naive_coset_evals(&mat, h, shift));
+        assert_eq!(dft.coset_idft_algebra_batch(coset_evals, shift), mat);
+
+        assert_eq!(
+            dft.coset_lde_algebra_batch(naive_coset_evals(&mat, h, F::one()), 1, shift),
+            naive_coset_evals(&mat, h << 1, shift)
+        );
+    }
+}
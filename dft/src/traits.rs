@@ -1,6 +1,6 @@
 use alloc::vec::Vec;
 
-use p3_field::TwoAdicField;
+use p3_field::{ExtensionField, TwoAdicField};
 use p3_matrix::bitrev::BitReversableMatrix;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::swap_rows;
@@ -25,6 +25,14 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
     /// methods can be derived from this one.
     fn dft_batch(&self, mat: RowMajorMatrix<F>) -> Self::Evaluations;
 
+    /// Compute the DFT of each column in `mat`, with the output rows in bit-reversed order.
+    ///
+    /// Implementations whose natural output is bit-reversed can return it as is, rather than
+    /// permuting it into natural order and back.
+    fn dft_batch_bit_reversed(&self, mat: RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        self.dft_batch(mat).bit_reverse_rows().to_row_major_matrix()
+    }
+
     /// Compute the "coset DFT" of `vec`. This can be viewed as interpolation onto a coset of a
     /// multiplicative subgroup, rather than the subgroup itself.
     fn coset_dft(&self, vec: Vec<F>, shift: F) -> Vec<F> {
@@ -137,4 +145,73 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
         );
         self.coset_dft_batch(coeffs, shift)
     }
+
+    /// Compute the low-degree extension of each column in `mat` onto a coset of a larger subgroup,
+    /// with the output rows in bit-reversed order, as committed to by the PCS.
+    fn coset_lde_batch_bit_reversed(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<F> {
+        self.coset_lde_batch(mat, added_bits, shift)
+            .bit_reverse_rows()
+            .to_row_major_matrix()
+    }
+
+    /// Compute the DFT of each column in `mat`, whose entries lie in an extension of `F`.
+    ///
+    /// The DFT is `F`-linear, so this is the DFT of each base field coefficient column of `mat`,
+    /// which is how it is computed.
+    fn dft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        self.dft_batch(mat.flatten_to_base())
+            .to_row_major_matrix()
+            .reconstitute_from_base()
+    }
+
+    /// Compute the "coset DFT" of each column in `mat`, whose entries lie in an extension of `F`.
+    fn coset_dft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+        shift: F,
+    ) -> RowMajorMatrix<EF> {
+        self.coset_dft_batch(mat.flatten_to_base(), shift)
+            .to_row_major_matrix()
+            .reconstitute_from_base()
+    }
+
+    /// Compute the inverse DFT of each column in `mat`, whose entries lie in an extension of `F`.
+    fn idft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        self.idft_batch(mat.flatten_to_base())
+            .reconstitute_from_base()
+    }
+
+    /// Compute the "coset iDFT" of each column in `mat`, whose entries lie in an extension of `F`.
+    fn coset_idft_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+        shift: F,
+    ) -> RowMajorMatrix<EF> {
+        self.coset_idft_batch(mat.flatten_to_base(), shift)
+            .reconstitute_from_base()
+    }
+
+    /// Compute the low-degree extension of each column in `mat`, whose entries lie in an extension
+    /// of `F`, onto a coset of a larger subgroup.
+    fn coset_lde_algebra_batch<EF: ExtensionField<F>>(
+        &self,
+        mat: RowMajorMatrix<EF>,
+        added_bits: usize,
+        shift: F,
+    ) -> RowMajorMatrix<EF> {
+        self.coset_lde_batch(mat.flatten_to_base(), added_bits, shift)
+            .to_row_major_matrix()
+            .reconstitute_from_base()
+    }
 }
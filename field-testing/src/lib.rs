@@ -7,12 +7,15 @@ extern crate alloc;
 pub mod bench_func;
 pub mod packedfield_testing;
 
+use alloc::vec;
+use alloc::vec::Vec;
+
 pub use bench_func::*;
 use num_bigint::BigUint;
 use num_traits::identities::One;
 use p3_field::{
     cyclic_subgroup_coset_known_order, cyclic_subgroup_known_order, two_adic_coset_zerofier,
-    two_adic_subgroup_zerofier, ExtensionField, Field, TwoAdicField,
+    two_adic_subgroup_zerofier, CanonicalBytes, ExtensionField, Field, PrimeField, TwoAdicField,
 };
 pub use packedfield_testing::*;
 use rand::distributions::{Distribution, Standard};
@@ -79,6 +82,30 @@ where
     }
 }
 
+pub fn test_sqrt<F: Field>()
+where
+    Standard: Distribution<F>,
+{
+    assert_eq!(F::zero().try_sqrt(), Some(F::zero()));
+    assert!(!F::generator().is_square());
+    assert_eq!(F::generator().try_sqrt(), None);
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..10 {
+        let x = rng.gen::<F>();
+        let square = x.square();
+        assert!(square.is_square());
+        let root = square.try_sqrt().unwrap();
+        assert!(root == x || root == -x);
+
+        if !x.is_zero() {
+            let non_square = square * F::generator();
+            assert!(!non_square.is_square());
+            assert_eq!(non_square.try_sqrt(), None);
+        }
+    }
+}
+
 pub fn test_multiplicative_group_factors<F: Field>() {
     let product: BigUint = F::multiplicative_group_factors()
         .into_iter()
@@ -116,6 +143,16 @@ pub fn test_two_adic_generator_consistency<F: TwoAdicField>() {
     }
 }
 
+pub fn test_two_adic_generator_order<F: TwoAdicField>() {
+    for bits in 0..=F::TWO_ADICITY {
+        let g = F::two_adic_generator(bits);
+        assert!(g.exp_power_of_2(bits).is_one());
+        if bits > 0 {
+            assert!(!g.exp_power_of_2(bits - 1).is_one());
+        }
+    }
+}
+
 pub fn test_ef_two_adic_generator_consistency<
     F: TwoAdicField,
     EF: TwoAdicField + ExtensionField<F>,
@@ -126,6 +163,79 @@ pub fn test_ef_two_adic_generator_consistency<
     );
 }
 
+pub fn test_canonical_bytes<F: PrimeField>()
+where
+    Standard: Distribution<F>,
+{
+    let num_bytes = F::num_canonical_bytes();
+    let mut one = vec![0; num_bytes];
+    one[0] = 1;
+    assert_eq!(F::zero().to_canonical_bytes(), vec![0; num_bytes]);
+    assert_eq!(F::one().to_canonical_bytes(), one);
+    assert_eq!(F::from_canonical_bytes(&one), Some(F::one()));
+
+    let neg_one = (F::order() - 1u32).to_bytes_le();
+    assert_eq!(F::neg_one().to_canonical_bytes(), neg_one);
+    assert_eq!(F::from_canonical_bytes(&neg_one), Some(F::neg_one()));
+
+    // Unreduced values and encodings of the wrong length are rejected.
+    let mut order = F::order().to_bytes_le();
+    order.resize(num_bytes, 0);
+    assert_eq!(F::from_canonical_bytes(&order), None);
+    assert_eq!(F::from_canonical_bytes(&vec![0xff; num_bytes]), None);
+    assert_eq!(F::from_canonical_bytes(&one[1..]), None);
+    assert_eq!(
+        F::from_canonical_bytes(&[one.clone(), vec![0]].concat()),
+        None
+    );
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let x = rng.gen::<F>();
+        let bytes = x.to_canonical_bytes();
+        assert_eq!(BigUint::from_bytes_le(&bytes), x.as_canonical_biguint());
+        assert_eq!(F::from_canonical_bytes(&bytes), Some(x));
+    }
+}
+
+pub fn test_ef_canonical_bytes<F, EF>()
+where
+    F: Field + CanonicalBytes,
+    EF: ExtensionField<F> + CanonicalBytes,
+    Standard: Distribution<F>,
+{
+    let num_bytes = F::num_canonical_bytes();
+    assert_eq!(EF::num_canonical_bytes(), num_bytes * EF::D);
+
+    let mut rng = rand::thread_rng();
+    let coeffs: Vec<F> = (0..EF::D).map(|_| rng.gen()).collect();
+    let x = EF::from_base_slice(&coeffs);
+    let bytes = x.to_canonical_bytes();
+    assert_eq!(bytes.len(), EF::num_canonical_bytes());
+    for (chunk, coeff) in bytes.chunks_exact(num_bytes).zip(&coeffs) {
+        assert_eq!(chunk, coeff.to_canonical_bytes());
+    }
+    assert_eq!(EF::from_canonical_bytes(&bytes), Some(x));
+
+    // An unreduced coefficient, or a missing byte, invalidates the whole encoding.
+    let mut unreduced = bytes.clone();
+    unreduced[bytes.len() - num_bytes..].fill(0xff);
+    assert_eq!(EF::from_canonical_bytes(&unreduced), None);
+    assert_eq!(EF::from_canonical_bytes(&bytes[1..]), None);
+}
+
+#[macro_export]
+macro_rules! test_prime_field {
+    ($field:ty) => {
+        mod prime_field_tests {
+            #[test]
+            fn test_canonical_bytes() {
+                $crate::test_canonical_bytes::<$field>();
+            }
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! test_field {
     ($field:ty) => {
@@ -143,6 +253,10 @@ macro_rules! test_field {
                 $crate::test_inverse::<$field>();
             }
             #[test]
+            fn test_sqrt() {
+                $crate::test_sqrt::<$field>();
+            }
+            #[test]
             fn test_multiplicative_group_factors() {
                 $crate::test_multiplicative_group_factors::<$field>();
             }
@@ -166,6 +280,10 @@ macro_rules! test_two_adic_field {
             fn test_two_adic_consisitency() {
                 $crate::test_two_adic_generator_consistency::<$field>();
             }
+            #[test]
+            fn test_two_adic_generator_order() {
+                $crate::test_two_adic_generator_order::<$field>();
+            }
         }
     };
 }
@@ -194,7 +312,10 @@ mod tests {
 
     use p3_baby_bear::BabyBear;
     use p3_field::extension::{BinomialExtensionField, HasFrobenius};
-    use p3_field::{binomial_expand, eval_poly, AbstractExtensionField, AbstractField};
+    use p3_field::{
+        batch_multiplicative_inverse_allowing_zero, binomial_expand, eval_poly,
+        AbstractExtensionField, AbstractField, PackedValue,
+    };
     use rand::random;
 
     use super::*;
@@ -219,4 +340,36 @@ mod tests {
             vec![F::two(), -F::from_canonical_usize(3), F::one()]
         );
     }
+
+    #[test]
+    fn test_batch_inverse_allowing_zero() {
+        type F = BabyBear;
+        for n in 0..10 {
+            let x: Vec<F> = (0..n)
+                .map(|i| if i % 3 == 1 { F::zero() } else { random() })
+                .collect();
+            let (inverses, is_zero) = batch_multiplicative_inverse_allowing_zero(&x);
+            for ((&xi, &inv), &zero) in x.iter().zip(&inverses).zip(&is_zero) {
+                assert_eq!(zero, xi.is_zero());
+                assert_eq!(inv, xi.try_inverse().unwrap_or(F::zero()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_packed_extension() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let width = <F as Field>::Packing::WIDTH;
+        let xs: Vec<EF> = (0..width).map(|_| random()).collect();
+        let ys: Vec<EF> = (0..width).map(|_| random()).collect();
+
+        let packed_xs = EF::pack_ext_slice(&xs);
+        let packed_ys = EF::pack_ext_slice(&ys);
+        assert!(EF::unpack_ext(&packed_xs).eq(xs.iter().copied()));
+
+        let products = packed_xs * packed_ys + packed_xs;
+        let expected = xs.iter().zip(&ys).map(|(&x, &y)| x * y + x);
+        assert!(EF::unpack_ext(&products).eq(expected));
+    }
 }
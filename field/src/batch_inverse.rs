@@ -97,3 +97,25 @@ pub fn batch_multiplicative_inverse<F: Field>(x: &[F]) -> Vec<F> {
 
     buf
 }
+
+/// Like `batch_multiplicative_inverse`, but tolerates zeros in `x`, whose "inverses" are zero.
+///
+/// Also returns a mask, which is `true` exactly where `x` is zero, so that callers can tell these
+/// entries apart from genuine inverses.
+pub fn batch_multiplicative_inverse_allowing_zero<F: Field>(x: &[F]) -> (Vec<F>, Vec<bool>) {
+    let is_zero: Vec<bool> = x.iter().map(|xi| xi.is_zero()).collect();
+    // Substitute ones for zeros, so that the product of all the inputs is invertible.
+    let nonzero: Vec<F> = x
+        .iter()
+        .zip(&is_zero)
+        .map(|(&xi, &zero)| if zero { F::one() } else { xi })
+        .collect();
+
+    let mut inverses = batch_multiplicative_inverse(&nonzero);
+    for (inv, &zero) in inverses.iter_mut().zip(&is_zero) {
+        if zero {
+            *inv = F::zero();
+        }
+    }
+    (inverses, is_zero)
+}
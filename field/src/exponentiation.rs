@@ -1,3 +1,5 @@
+use num_bigint::BigUint;
+
 use crate::AbstractField;
 
 pub fn exp_u64_by_squaring<AF: AbstractField>(val: AF, power: u64) -> AF {
@@ -13,6 +15,19 @@ pub fn exp_u64_by_squaring<AF: AbstractField>(val: AF, power: u64) -> AF {
     product
 }
 
+pub fn exp_biguint_by_squaring<AF: AbstractField>(val: AF, power: &BigUint) -> AF {
+    let mut current = val;
+    let mut product = AF::one();
+
+    for j in 0..power.bits() {
+        if power.bit(j) {
+            product *= current.clone();
+        }
+        current = current.square();
+    }
+    product
+}
+
 const fn bits_u64(n: u64) -> usize {
     (64 - n.leading_zeros()) as usize
 }
@@ -1,5 +1,6 @@
 use alloc::format;
 use alloc::string::ToString;
+use alloc::vec::Vec;
 use core::array;
 use core::fmt::{self, Debug, Display, Formatter};
 use core::iter::{Product, Sum};
@@ -13,7 +14,7 @@ use serde::{Deserialize, Serialize};
 
 use super::{HasFrobenius, HasTwoAdicBionmialExtension};
 use crate::extension::BinomiallyExtendable;
-use crate::field::Field;
+use crate::field::{CanonicalBytes, Embeds, Field};
 use crate::{
     field_to_array, AbstractExtensionField, AbstractField, ExtensionField, Packable, TwoAdicField,
 };
@@ -45,6 +46,37 @@ impl<AF: AbstractField, const D: usize> From<AF> for BinomialExtensionField<AF,
 
 impl<F: BinomiallyExtendable<D>, const D: usize> Packable for BinomialExtensionField<F, D> {}
 
+impl<F: BinomiallyExtendable<D> + CanonicalBytes, const D: usize> CanonicalBytes
+    for BinomialExtensionField<F, D>
+{
+    fn num_canonical_bytes() -> usize {
+        F::num_canonical_bytes() * D
+    }
+
+    /// The canonical encodings of the coefficients over `F`, from the constant coefficient up,
+    /// concatenated.
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.value.iter().flat_map(F::to_canonical_bytes).collect()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::num_canonical_bytes() {
+            return None;
+        }
+        let coeffs: Vec<F> = bytes
+            .chunks_exact(F::num_canonical_bytes())
+            .map(F::from_canonical_bytes)
+            .collect::<Option<_>>()?;
+        Some(Self::from_base_slice(&coeffs))
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> Embeds<F> for BinomialExtensionField<F, D> {
+    fn embed(value: F) -> Self {
+        Self::from_base(value)
+    }
+}
+
 impl<F: BinomiallyExtendable<D>, const D: usize> ExtensionField<F>
     for BinomialExtensionField<F, D>
 {
@@ -77,8 +109,9 @@ impl<F: BinomiallyExtendable<D>, const D: usize> HasFrobenius<F> for BinomialExt
             z0 *= F::dth_root();
         }
 
-        let mut res = [F::zero(); D];
-        for (i, z) in z0.powers().take(D).enumerate() {
+        // The constant coefficient is fixed, so skip multiplying it by z0^0 = 1.
+        let mut res = [arr[0]; D];
+        for (i, z) in z0.powers().take(D).enumerate().skip(1) {
             res[i] = arr[i] * z;
         }
 
@@ -189,8 +222,14 @@ where
         match D {
             2 => {
                 let a = self.value.clone();
+                let w = AF::F::w();
                 let mut res = Self::default();
-                res.value[0] = a[0].square() + a[1].square() * AF::from_f(AF::F::w());
+                // For complex extensions, a0^2 - a1^2 = (a0 + a1)(a0 - a1) saves a multiplication.
+                res.value[0] = if w == AF::F::neg_one() {
+                    (a[0].clone() + a[1].clone()) * (a[0].clone() - a[1].clone())
+                } else {
+                    a[0].square() + a[1].square() * AF::from_f(w)
+                };
                 res.value[1] = a[0].clone() * a[1].double();
                 res
             }
@@ -226,6 +265,14 @@ impl<F: BinomiallyExtendable<D>, const D: usize> Field for BinomialExtensionFiel
         }
     }
 
+    /// The norm `N(x) = x^((q^D - 1) / (q - 1))` down to `F` satisfies
+    /// `N(x)^((q - 1) / 2) = x^((q^D - 1) / 2)`, so `x` is a square if and only if its norm is,
+    /// which is cheap to compute with the Frobenius automorphism.
+    fn is_square(&self) -> bool {
+        let norm = (1..D).fold(*self, |acc, i| acc * self.repeated_frobenius(i));
+        norm.value[0].is_square()
+    }
+
     fn order() -> BigUint {
         F::order().pow(D as u32)
     }
@@ -408,25 +455,40 @@ where
         match D {
             2 => {
                 let mut res = Self::default();
-                res.value[0] = a[0].clone() * b[0].clone() + a[1].clone() * w_af * b[1].clone();
+                let a1_b1 = a[1].clone() * b[1].clone();
+                // For complex extensions, i^2 = -1 turns the multiplication by W into a negation.
+                res.value[0] = if w == AF::F::neg_one() {
+                    a[0].clone() * b[0].clone() - a1_b1
+                } else {
+                    a[0].clone() * b[0].clone() + a1_b1 * w_af
+                };
                 res.value[1] = a[0].clone() * b[1].clone() + a[1].clone() * b[0].clone();
                 res
             }
             3 => Self {
                 value: cubic_mul(&a, &b, w).to_vec().try_into().unwrap(),
             },
+            d if d % 2 == 0 => Self {
+                value: karatsuba_mul(&a, &b, w_af),
+            },
             _ => {
+                // Accumulate the products which wrap around past X^D separately, so that each
+                // coefficient is multiplied by W once rather than once per product.
                 let mut res = Self::default();
+                let mut wrapped = Self::default();
                 #[allow(clippy::needless_range_loop)]
                 for i in 0..D {
                     for j in 0..D {
                         if i + j >= D {
-                            res.value[i + j - D] += a[i].clone() * w_af.clone() * b[j].clone();
+                            wrapped.value[i + j - D] += a[i].clone() * b[j].clone();
                         } else {
                             res.value[i + j] += a[i].clone() * b[j].clone();
                         }
                     }
                 }
+                for (r, w_term) in res.value.iter_mut().zip(wrapped.value).take(D - 1) {
+                    *r += w_term * w_af.clone();
+                }
                 res
             }
         }
@@ -609,6 +671,67 @@ fn cubic_mul<AF: AbstractField>(a: &[AF], b: &[AF], w: AF::F) -> [AF; 3] {
     [c0, c1, c2]
 }
 
+/// Multiplication in `F[X] / (X^D - W)` for even `D`, by one level of Karatsuba: splitting each
+/// operand into halves of `D / 2` coefficients, three schoolbook products of the halves suffice,
+/// rather than four.
+#[inline]
+fn karatsuba_mul<AF: AbstractField, const D: usize>(a: &[AF; D], b: &[AF; D], w: AF) -> [AF; D] {
+    debug_assert_eq!(D % 2, 0);
+    let h = D / 2;
+    let (a_lo, a_hi) = a.split_at(h);
+    let (b_lo, b_hi) = b.split_at(h);
+    let a_sum: [AF; D] = core::array::from_fn(|i| {
+        if i < h {
+            a_lo[i].clone() + a_hi[i].clone()
+        } else {
+            AF::zero()
+        }
+    });
+    let b_sum: [AF; D] = core::array::from_fn(|i| {
+        if i < h {
+            b_lo[i].clone() + b_hi[i].clone()
+        } else {
+            AF::zero()
+        }
+    });
+
+    // Each product of halves has 2h - 1 = D - 1 coefficients.
+    let lo = schoolbook_product::<AF, D>(a_lo, b_lo);
+    let hi = schoolbook_product::<AF, D>(a_hi, b_hi);
+    let mid = schoolbook_product::<AF, D>(&a_sum[..h], &b_sum[..h]);
+
+    // The full product is lo + X^h (mid - lo - hi) + X^D hi, and X^D = W.
+    let mut res: [AF; D] = core::array::from_fn(|_| AF::zero());
+    let mut wrapped: [AF; D] = core::array::from_fn(|_| AF::zero());
+    for k in 0..D - 1 {
+        res[k] += lo[k].clone();
+        wrapped[k] += hi[k].clone();
+        let cross = mid[k].clone() - lo[k].clone() - hi[k].clone();
+        if k + h < D {
+            res[k + h] += cross;
+        } else {
+            wrapped[k + h - D] += cross;
+        }
+    }
+    for (r, w_term) in res.iter_mut().zip(wrapped).take(D - 1) {
+        *r += w_term * w.clone();
+    }
+    res
+}
+
+/// The product of the polynomials with coefficients `a` and `b`, whose lengths must sum to at most
+/// `N + 1`.
+#[inline]
+fn schoolbook_product<AF: AbstractField, const N: usize>(a: &[AF], b: &[AF]) -> [AF; N] {
+    let mut res: [AF; N] = core::array::from_fn(|_| AF::zero());
+    for (i, a_i) in a.iter().enumerate() {
+        for (j, b_j) in b.iter().enumerate() {
+            res[i + j] += a_i.clone() * b_j.clone();
+        }
+    }
+    res
+}
+
 /// Section 11.3.6a in Handbook of Elliptic and Hyperelliptic Curve Cryptography.
 #[inline]
 fn cubic_square<AF: AbstractField>(a: &[AF], w: AF::F) -> [AF; 3] {
@@ -13,7 +13,7 @@ use nums::{Factorizer, FactorizerFromSplitter, MillerRabin, PollardRho};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use crate::exponentiation::exp_u64_by_squaring;
+use crate::exponentiation::{exp_biguint_by_squaring, exp_u64_by_squaring};
 use crate::packed::{PackedField, PackedValue};
 use crate::Packable;
 
@@ -231,6 +231,55 @@ pub trait Field:
         *self * half
     }
 
+    /// Whether this field element is a square, i.e. `0` or a quadratic residue.
+    ///
+    /// The default implementation uses Euler's criterion.
+    fn is_square(&self) -> bool {
+        let order = Self::order();
+        if !order.bit(0) {
+            // In characteristic 2, every element is a square.
+            return true;
+        }
+        self.is_zero() || exp_biguint_by_squaring(*self, &((order - 1u32) >> 1)).is_one()
+    }
+
+    /// A square root of this field element, or `None` if it is not a square.
+    ///
+    /// The default implementation is the Tonelli-Shanks algorithm, with `generator()` as the
+    /// quadratic non-residue.
+    #[must_use]
+    fn try_sqrt(&self) -> Option<Self> {
+        let order = Self::order();
+        if !order.bit(0) {
+            // In characteristic 2, squaring is an automorphism, with inverse x -> x^(q / 2).
+            return Some(exp_biguint_by_squaring(*self, &(order >> 1)));
+        }
+        if self.is_zero() {
+            return Some(*self);
+        }
+
+        // Write q - 1 = 2^s t, with t odd. Then c generates the Sylow 2-subgroup, and
+        // x^2 = self * b throughout, with b in the subgroup of order 2^m.
+        let q_minus_1 = order - 1u32;
+        let s = q_minus_1.trailing_zeros().unwrap() as usize;
+        let t = q_minus_1 >> s;
+        let mut c = exp_biguint_by_squaring(Self::generator(), &t);
+        let mut x = exp_biguint_by_squaring(*self, &((t.clone() + 1u32) >> 1));
+        let mut b = exp_biguint_by_squaring(*self, &t);
+        let mut m = s;
+
+        while !b.is_one() {
+            // The order of b is 2^i. If self is not a square, i = m, and we give up.
+            let i = (1..m).find(|&i| b.exp_power_of_2(i).is_one())?;
+            let d = c.exp_power_of_2(m - i - 1);
+            x *= d;
+            c = d.square();
+            b *= c;
+            m = i;
+        }
+        Some(x)
+    }
+
     fn order() -> BigUint;
 
     /// A list of (factor, exponent) pairs.
@@ -253,6 +302,60 @@ pub trait Field:
 
 pub trait PrimeField: Field + Ord {
     fn as_canonical_biguint(&self) -> BigUint;
+
+    /// The length of the canonical byte encoding of an element, i.e. the number of bytes needed to
+    /// hold `order() - 1`.
+    fn num_canonical_bytes() -> usize {
+        (Self::order() - 1u32).bits().div_ceil(8) as usize
+    }
+
+    /// The canonical byte encoding of `self`: its representative in `0..order()`, in little-endian
+    /// order, padded with zeros to `num_canonical_bytes()` bytes.
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.as_canonical_biguint().to_bytes_le();
+        bytes.resize(Self::num_canonical_bytes(), 0);
+        bytes
+    }
+
+    /// The inverse of `to_canonical_bytes`. Returns `None` if `bytes` is not exactly
+    /// `num_canonical_bytes()` long, or encodes a value which is not reduced.
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::num_canonical_bytes()
+            || BigUint::from_bytes_le(bytes) >= Self::order()
+        {
+            return None;
+        }
+        let radix = Self::from_canonical_u16(1 << 8);
+        Some(bytes.iter().rev().fold(Self::zero(), |acc, &byte| {
+            acc * radix + Self::from_canonical_u8(byte)
+        }))
+    }
+}
+
+/// A canonical byte encoding of field elements, shared by prime fields and their extensions, so
+/// that transcripts, proofs and verifiers in other languages all agree on one encoding.
+pub trait CanonicalBytes: Sized {
+    /// The length of the encoding of any element.
+    fn num_canonical_bytes() -> usize;
+
+    fn to_canonical_bytes(&self) -> Vec<u8>;
+
+    /// The inverse of `to_canonical_bytes`, returning `None` if `bytes` is not a valid encoding.
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl<F: PrimeField> CanonicalBytes for F {
+    fn num_canonical_bytes() -> usize {
+        <F as PrimeField>::num_canonical_bytes()
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        <F as PrimeField>::to_canonical_bytes(self)
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        <F as PrimeField>::from_canonical_bytes(bytes)
+    }
 }
 
 /// A prime field of order less than `2^64`.
@@ -261,6 +364,12 @@ pub trait PrimeField64: PrimeField {
 
     /// Return the representative of `value` that is less than `ORDER_U64`.
     fn as_canonical_u64(&self) -> u64;
+
+    /// The element whose canonical representative is `n`, or `None` if `n` is not less than
+    /// `ORDER_U64`.
+    fn try_from_canonical_u64(n: u64) -> Option<Self> {
+        (n < Self::ORDER_U64).then(|| Self::from_canonical_u64(n))
+    }
 }
 
 /// A prime field of order less than `2^32`.
@@ -355,12 +464,25 @@ pub trait ExtensionField<Base: Field>: Field + AbstractExtensionField<Base> {
         }
     }
 
+    /// Pack `Base::Packing::WIDTH` extension elements into one packed extension element, which
+    /// stores them transposed: its `i`th base coefficient packs the `i`th coefficient of each.
+    fn pack_ext_slice(values: &[Self]) -> Self::ExtensionPacking {
+        assert_eq!(values.len(), Base::Packing::WIDTH);
+        Self::ExtensionPacking::from_base_fn(|i| {
+            Base::Packing::from_fn(|j| values[j].as_base_slice()[i])
+        })
+    }
+
+    /// The inverse of `pack_ext_slice`, yielding the extension element in each lane of `packed`.
+    fn unpack_ext(packed: &Self::ExtensionPacking) -> impl Iterator<Item = Self> + '_ {
+        (0..Base::Packing::WIDTH)
+            .map(move |j| Self::from_base_fn(|i| packed.as_base_slice()[i].as_slice()[j]))
+    }
+
     fn ext_powers_packed(&self) -> impl Iterator<Item = Self::ExtensionPacking> {
         let powers = self.powers().take(Base::Packing::WIDTH + 1).collect_vec();
         // Transpose first WIDTH powers
-        let current = Self::ExtensionPacking::from_base_fn(|i| {
-            Base::Packing::from_fn(|j| powers[j].as_base_slice()[i])
-        });
+        let current = Self::pack_ext_slice(&powers[..Base::Packing::WIDTH]);
         // Broadcast self^WIDTH
         let multiplier = Self::ExtensionPacking::from_base_fn(|i| {
             Base::Packing::from(powers[Base::Packing::WIDTH].as_base_slice()[i])
@@ -374,6 +496,22 @@ impl<F: Field> ExtensionField<F> for F {
     type ExtensionPacking = F::Packing;
 }
 
+/// An injective map from `F` into `Self`, e.g. from a field into an extension of it, or from a
+/// prime field into a larger one, by way of canonical representatives.
+///
+/// This lets generic code move values, such as witnesses, between proof systems with different
+/// fields. It is not a ring homomorphism in general: embeddings between distinct prime fields don't
+/// respect their arithmetic.
+pub trait Embeds<F>: Sized {
+    fn embed(value: F) -> Self;
+}
+
+impl<F: Field> Embeds<F> for F {
+    fn embed(value: F) -> Self {
+        value
+    }
+}
+
 impl<AF: AbstractField> AbstractExtensionField<AF> for AF {
     const D: usize = 1;
 
@@ -7,7 +7,7 @@ use core::ops::Mul;
 use num_bigint::BigUint;
 
 use crate::field::Field;
-use crate::{AbstractField, PrimeField, PrimeField32, TwoAdicField};
+use crate::{AbstractField, PrimeField, PrimeField32, PrimeField64, TwoAdicField};
 
 /// Computes `Z_H(x)`, where `Z_H` is the zerofier of a multiplicative subgroup of order `2^log_n`.
 pub fn two_adic_subgroup_zerofier<F: TwoAdicField>(log_n: usize, x: F) -> F {
@@ -164,6 +164,38 @@ pub fn split_32<SF: PrimeField, TF: PrimeField32>(val: SF, n: usize) -> Vec<TF>
     result
 }
 
+/// Given a slice of SF elements, reduce them to a TF element using a 2^64-base decomposition.
+pub fn reduce_64<SF: PrimeField64, TF: PrimeField>(vals: &[SF]) -> TF {
+    let po2 = TF::from_canonical_u64(1u64 << 32).square();
+    let mut result = TF::zero();
+    for val in vals.iter().rev() {
+        result = result * po2 + TF::from_canonical_u64(val.as_canonical_u64());
+    }
+    result
+}
+
+/// Given an SF element, split it to a vector of TF elements using a 2^64-base decomposition.
+///
+/// For a field of size ~2^64 such as Goldilocks, reducing each digit is biased by at most the
+/// fraction of 64-bit values above the modulus, ~1/2^32 for Goldilocks.
+pub fn split_64<SF: PrimeField, TF: PrimeField64>(val: SF, n: usize) -> Vec<TF> {
+    let po2 = BigUint::from(1u128 << 64);
+    let mut val = val.as_canonical_biguint();
+    let mut result = Vec::new();
+    for _ in 0..n {
+        let mask: BigUint = po2.clone() - BigUint::from(1u128);
+        let digit: BigUint = val.clone() & mask;
+        let digit_u64s = digit.to_u64_digits();
+        if !digit_u64s.is_empty() {
+            result.push(TF::from_wrapped_u64(digit_u64s[0]));
+        } else {
+            result.push(TF::zero())
+        }
+        val /= po2.clone();
+    }
+    result
+}
+
 /// Maximally generic dot product.
 pub fn dot_product<S, LI, RI>(li: LI, ri: RI) -> S
 where
@@ -160,6 +160,39 @@ unsafe impl<T: Packable> PackedValue for T {
     }
 }
 
+/// An array of values, packed with no arithmetic of its own, e.g. for the lanes of a permutation
+/// which operates on several states at once.
+unsafe impl<T: Packable, const WIDTH: usize> PackedValue for [T; WIDTH] {
+    type Value = T;
+
+    const WIDTH: usize = WIDTH;
+
+    fn from_slice(slice: &[Self::Value]) -> &Self {
+        assert_eq!(slice.len(), Self::WIDTH);
+        slice.try_into().unwrap()
+    }
+
+    fn from_slice_mut(slice: &mut [Self::Value]) -> &mut Self {
+        assert_eq!(slice.len(), Self::WIDTH);
+        slice.try_into().unwrap()
+    }
+
+    fn from_fn<Fn>(f: Fn) -> Self
+    where
+        Fn: FnMut(usize) -> Self::Value,
+    {
+        core::array::from_fn(f)
+    }
+
+    fn as_slice(&self) -> &[Self::Value] {
+        &self[..]
+    }
+
+    fn as_slice_mut(&mut self) -> &mut [Self::Value] {
+        &mut self[..]
+    }
+}
+
 unsafe impl<F: Field> PackedField for F {
     type Scalar = Self;
 
@@ -0,0 +1,116 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Pcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::{FriConfig, LowDegreeTest, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::FieldMerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    FieldMerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+const NUM_QUERIES: usize = 100;
+
+fn bench_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("verify_{}_queries", NUM_QUERIES));
+    group.sample_size(10);
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear,
+        &mut rng,
+    );
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        num_queries: NUM_QUERIES,
+        proof_of_work_bits: 8,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings: false,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+    let challenger = Challenger::new(perm);
+
+    for log_height in [12, 16] {
+        // A few rounds with a few matrices each, so every query opens several Merkle paths.
+        let rounds = (0..3)
+            .map(|_| {
+                (0..4)
+                    .map(|_| {
+                        let domain =
+                            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                                &pcs,
+                                1 << log_height,
+                            );
+                        (
+                            domain,
+                            RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_height, 16),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let (commits, data): (Vec<_>, Vec<_>) = rounds
+            .iter()
+            .map(|mats| <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, mats.clone()))
+            .unzip();
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe_slice(&commits);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opened_values, proof) = pcs.open(
+            data.iter().map(|d| (d, vec![vec![zeta]; 4])).collect(),
+            &mut p_challenger,
+        );
+
+        let claims = commits
+            .iter()
+            .zip(&rounds)
+            .zip(&opened_values)
+            .map(|((&commit, mats), openings)| {
+                let claims_for_round = mats
+                    .iter()
+                    .zip(openings)
+                    .map(|((domain, _), mat_openings)| {
+                        (*domain, vec![(zeta, mat_openings[0].clone())])
+                    })
+                    .collect::<Vec<_>>();
+                (commit, claims_for_round)
+            })
+            .collect::<Vec<_>>();
+
+        group.bench_function(BenchmarkId::from_parameter(log_height), |b| {
+            b.iter(|| {
+                let mut v_challenger = challenger.clone();
+                v_challenger.observe_slice(&commits);
+                let _zeta: Challenge = v_challenger.sample_ext_element();
+                pcs.verify(claims.clone(), &proof, &mut v_challenger)
+                    .unwrap();
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_verify);
+criterion_main!(benches);
@@ -0,0 +1,125 @@
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrixView;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+
+use crate::{FriGenericConfig, TwoAdicFriGenericConfig};
+
+/// The compute-heavy kernels of opening a `TwoAdicFriPcs`, abstracted so that they can be run on
+/// an accelerator, such as a GPU, by an implementation living in another crate. `CpuBackend` is
+/// the default.
+///
+/// Hashing leaves and compressing Merkle tree layers are already abstracted by the `Mmcs` trait,
+/// so an accelerated MMCS can be used alongside a backend.
+pub trait TwoAdicFriBackend<Val: Field, Challenge: TwoAdicField + ExtensionField<Val>>:
+    Sync
+{
+    /// Fold a codeword in bit-reversed order, given as a matrix whose width is the folding arity,
+    /// with `beta`. This must agree with `TwoAdicFriGenericConfig::fold_matrix`, which the
+    /// verifier uses.
+    fn fold_matrix<M: Matrix<Challenge>>(&self, beta: Challenge, m: M) -> Vec<Challenge>;
+
+    /// For every row `x` of `mat`, add
+    /// `alpha_pow_offset * (sum_i alpha^i mat[x][i] - reduced_ys) * inv_denoms[x]`
+    /// to `reduced_opening[x]`. `reduced_opening` is as tall as `mat`, while `inv_denoms` may be
+    /// taller, in which case only its first rows are used.
+    fn accumulate_quotient_rows(
+        &self,
+        mat: &RowMajorMatrixView<Val>,
+        alpha: Challenge,
+        alpha_pow_offset: Challenge,
+        reduced_ys: Challenge,
+        inv_denoms: &[Challenge],
+        reduced_opening: &mut [Challenge],
+    );
+}
+
+/// Runs every kernel on the CPU, in parallel if the `parallel` feature is enabled.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CpuBackend;
+
+impl<Val: Field, Challenge: TwoAdicField + ExtensionField<Val>> TwoAdicFriBackend<Val, Challenge>
+    for CpuBackend
+{
+    fn fold_matrix<M: Matrix<Challenge>>(&self, beta: Challenge, m: M) -> Vec<Challenge> {
+        TwoAdicFriGenericConfig::<(), ()>(PhantomData).fold_matrix(beta, m)
+    }
+
+    fn accumulate_quotient_rows(
+        &self,
+        mat: &RowMajorMatrixView<Val>,
+        alpha: Challenge,
+        alpha_pow_offset: Challenge,
+        reduced_ys: Challenge,
+        inv_denoms: &[Challenge],
+        reduced_opening: &mut [Challenge],
+    ) {
+        mat.dot_ext_powers(alpha)
+            .zip(reduced_opening.par_iter_mut())
+            // This might be longer, but zip will truncate to smaller subgroup
+            // (which is ok because it's bitrev)
+            .zip(inv_denoms.par_iter())
+            .for_each(|((reduced_row, ro), &inv_denom)| {
+                *ro += alpha_pow_offset * (reduced_row - reduced_ys) * inv_denom
+            })
+    }
+}
+
+/// The FRI prover's view of a `TwoAdicFriGenericConfig` whose folding is done by a backend.
+pub(crate) struct BackendFriGenericConfig<'a, Val, Backend, InputProof, InputError> {
+    pub(crate) backend: &'a Backend,
+    pub(crate) _phantom: PhantomData<(Val, InputProof, InputError)>,
+}
+
+impl<'a, Val, Challenge, Backend, InputProof, InputError: Debug> FriGenericConfig<Challenge>
+    for BackendFriGenericConfig<'a, Val, Backend, InputProof, InputError>
+where
+    Val: Field,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Backend: TwoAdicFriBackend<Val, Challenge>,
+{
+    type InputProof = InputProof;
+    type InputError = InputError;
+
+    fn extra_query_index_bits(&self) -> usize {
+        FriGenericConfig::<Challenge>::extra_query_index_bits(&self.cpu())
+    }
+
+    fn fold_row(
+        &self,
+        index: usize,
+        log_height: usize,
+        beta: Challenge,
+        evals: impl Iterator<Item = Challenge>,
+    ) -> Challenge {
+        self.cpu().fold_row(index, log_height, beta, evals)
+    }
+
+    fn fold_matrix<M: Matrix<Challenge>>(&self, beta: Challenge, m: M) -> Vec<Challenge> {
+        self.backend.fold_matrix(beta, m)
+    }
+
+    fn final_poly_coeffs(
+        &self,
+        codeword: Vec<Challenge>,
+        log_final_poly_len: usize,
+    ) -> Vec<Challenge> {
+        self.cpu().final_poly_coeffs(codeword, log_final_poly_len)
+    }
+
+    fn eval_final_poly(&self, coeffs: &[Challenge], index: usize, log_height: usize) -> Challenge {
+        self.cpu().eval_final_poly(coeffs, index, log_height)
+    }
+}
+
+impl<'a, Val, Backend, InputProof, InputError>
+    BackendFriGenericConfig<'a, Val, Backend, InputProof, InputError>
+{
+    const fn cpu(&self) -> TwoAdicFriGenericConfig<InputProof, InputError> {
+        TwoAdicFriGenericConfig(PhantomData)
+    }
+}
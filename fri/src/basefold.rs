@@ -0,0 +1,477 @@
+//! A multilinear polynomial commitment scheme in the style of BaseFold
+//! (<https://eprint.iacr.org/2023/1705>), built from FRI's Reed-Solomon codewords and folding.
+//!
+//! A multilinear polynomial `f(x_0, ..., x_{n-1}) = sum_S c_S prod_{j in S} x_j` is encoded as the
+//! univariate polynomial `f'(X) = sum_S c_S X^S`, reading each set of variables `S` as the binary
+//! number with bit `j` set for each `j` in `S`, and committed as a Reed-Solomon codeword of `f'`.
+//! Folding `f'` by two with `r`, as FRI does, gives the encoding of `f(r, x_1, ..., x_{n-1})`, so
+//! after `n` folds with `r_0, ..., r_{n-1}` the codeword is the constant `f(r_0, ..., r_{n-1})`.
+//!
+//! To open `f` at `z`, the prover runs a sumcheck of `f(z) = sum_b f(b) eq(b, z)` over the
+//! hypercube, whose challenges are also the folding challenges. The sumcheck reduces the claim to
+//! `f(r) eq(r, z)`, and FRI's queries check that the codeword does fold down to `f(r)`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use itertools::{izip, Itertools};
+use p3_challenger::{CanObserve, CanSample, FieldChallenger, GrindingChallenger};
+use p3_commit::{Mmcs, MultivariatePcs};
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::{Dimensions, Matrix};
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+
+use crate::prover::{answer_query, prune_commit_phase_openings};
+use crate::verifier::{expand_commit_phase_openings, verify_query, FriError};
+use crate::{
+    BatchOpening, FriConfig, FriGenericConfig, FriProof, QueryProof, TwoAdicFriGenericConfig,
+};
+
+/// A BaseFold PCS, committing to each batch of multilinear polynomials with `mmcs`, and folding
+/// their codewords with the FRI parameters of `fri`.
+///
+/// Every fold is by two, and folding continues down to a constant, so `fri.max_log_arity` and
+/// `fri.log_final_poly_len` are ignored, as is `fri.ldt`.
+#[derive(Debug)]
+pub struct BasefoldPcs<Val, Dft, InputMmcs, FriMmcs> {
+    dft: Dft,
+    mmcs: InputMmcs,
+    fri: FriConfig<FriMmcs>,
+    _phantom: PhantomData<Val>,
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs> BasefoldPcs<Val, Dft, InputMmcs, FriMmcs> {
+    pub const fn new(dft: Dft, mmcs: InputMmcs, fri: FriConfig<FriMmcs>) -> Self {
+        Self {
+            dft,
+            mmcs,
+            fri,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+pub struct BasefoldProverData<Val: Field, InputMmcs: Mmcs<Val>> {
+    /// The committed polynomials, by their evaluations over the hypercube.
+    evaluations: RowMajorMatrix<Val>,
+    data: InputMmcs::ProverData<RowMajorMatrix<Val>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct BasefoldProof<Val, Challenge, InputMmcs, FriMmcs>
+where
+    Val: Field,
+    Challenge: Field,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+{
+    /// For each variable, the evaluations of its sumcheck round polynomial at 0, 1 and 2.
+    pub sumcheck_evals: Vec<[Challenge; 3]>,
+    /// The proof that the batched codeword folds down to the constant `final_poly[0]`. Each query
+    /// opens the committed codewords of the batch as its input.
+    pub fri_proof: FriProof<Challenge, FriMmcs, Val, BatchOpening<Val, InputMmcs>>,
+}
+
+#[derive(Debug)]
+pub enum BasefoldError<FriMmcsError, InputMmcsError> {
+    /// A sumcheck round polynomial doesn't sum to the claim over the hypercube, or the last
+    /// claim doesn't match the constant the codeword folds down to.
+    SumcheckMismatch,
+    Fri(FriError<FriMmcsError, InputMmcsError>),
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger>
+    MultivariatePcs<Val, Challenge, Challenger> for BasefoldPcs<Val, Dft, InputMmcs, FriMmcs>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger: CanObserve<FriMmcs::Commitment>
+        + CanSample<Challenge>
+        + FieldChallenger<Val>
+        + GrindingChallenger<Witness = Val>,
+{
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = BasefoldProverData<Val, InputMmcs>;
+    type Proof = BasefoldProof<Val, Challenge, InputMmcs, FriMmcs>;
+    type Error = BasefoldError<FriMmcs::Error, InputMmcs::Error>;
+
+    fn commit(&self, evaluations: RowMajorMatrix<Val>) -> (Self::Commitment, Self::ProverData) {
+        let log_n = log2_strict_usize(evaluations.height());
+        let mut coeffs = evaluations.clone();
+        multilinear_coeffs(&mut coeffs);
+        coeffs
+            .values
+            .resize(coeffs.width() << (log_n + self.fri.log_blowup), Val::zero());
+        // Commit to the bit-reversed codeword, over the subgroup of its size.
+        let codeword = self.dft.dft_batch_bit_reversed(coeffs);
+        let (commit, data) = self.mmcs.commit_matrix(codeword);
+        (commit, BasefoldProverData { evaluations, data })
+    }
+
+    fn open(
+        &self,
+        prover_data: &Self::ProverData,
+        point: &[Challenge],
+        challenger: &mut Challenger,
+    ) -> (Vec<Challenge>, Self::Proof) {
+        let evaluations = &prover_data.evaluations;
+        let log_n = point.len();
+        assert_eq!(evaluations.height(), 1 << log_n);
+
+        let mut eq = eq_evals(point);
+        let values = evaluations.columnwise_dot_product(&eq);
+        for &value in &values {
+            challenger.observe_ext_element(value);
+        }
+
+        // Batch the polynomials, and their codewords, with powers of alpha.
+        let alpha: Challenge = challenger.sample_ext_element();
+        let mut batched: Vec<Challenge> = evaluations.dot_ext_powers(alpha).collect();
+        let codeword = self.mmcs.get_matrices(&prover_data.data)[0];
+        let mut folded: Vec<Challenge> = codeword.dot_ext_powers(alpha).collect();
+
+        let g: TwoAdicFriGenericConfig<(), ()> = TwoAdicFriGenericConfig(PhantomData);
+        let mut sumcheck_evals = vec![];
+        let mut commits = vec![];
+        let mut data = vec![];
+        for _ in 0..log_n {
+            let (commit, layer_data) = self.fri.mmcs.commit_matrix(RowMajorMatrix::new(folded, 2));
+            challenger.observe(commit.clone());
+
+            let round_evals = sumcheck_round_evals(&batched, &eq);
+            for &eval in &round_evals {
+                challenger.observe_ext_element(eval);
+            }
+
+            let r: Challenge = challenger.sample_ext_element();
+            batched = fold_evals(&batched, r);
+            eq = fold_evals(&eq, r);
+            let leaves = self.fri.mmcs.get_matrices(&layer_data).pop().unwrap();
+            folded = g.fold_matrix(r, leaves.as_view());
+
+            sumcheck_evals.push(round_evals);
+            commits.push(commit);
+            data.push(layer_data);
+        }
+
+        // The codeword is now the constant `batched(r)`.
+        let final_value = folded[0];
+        debug_assert!(folded.iter().all(|&x| x == final_value));
+        debug_assert_eq!(batched, vec![final_value]);
+        challenger.observe_ext_element(final_value);
+
+        let pow_witness = challenger.grind(self.fri.proof_of_work_bits);
+
+        let log_height = log_n + self.fri.log_blowup;
+        let indices = (0..self.fri.num_queries)
+            .map(|_| challenger.sample_bits(log_height))
+            .collect_vec();
+        let mut query_proofs = indices
+            .iter()
+            .map(|&index| {
+                let (opened_values, opening_proof) = self.mmcs.open_batch(index, &prover_data.data);
+                QueryProof {
+                    input_proof: BatchOpening {
+                        opened_values,
+                        opening_proof,
+                    },
+                    commit_phase_openings: answer_query(&self.fri, &data, index),
+                }
+            })
+            .collect_vec();
+
+        if self.fri.prune_openings {
+            let input_proofs = query_proofs
+                .iter()
+                .map(|qp| qp.input_proof.opening_proof.clone())
+                .collect();
+            let pruned = self.mmcs.prune_proofs(&indices, input_proofs);
+            for (qp, proof) in izip!(&mut query_proofs, pruned) {
+                qp.input_proof.opening_proof = proof;
+            }
+            prune_commit_phase_openings(&self.fri, &mut query_proofs, indices);
+        }
+
+        let proof = BasefoldProof {
+            sumcheck_evals,
+            fri_proof: FriProof {
+                commit_phase_commits: commits,
+                query_proofs,
+                final_poly: vec![final_value],
+                pow_witness,
+            },
+        };
+        (values, proof)
+    }
+
+    fn verify(
+        &self,
+        commit: &Self::Commitment,
+        point: &[Challenge],
+        values: &[Challenge],
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        let log_n = point.len();
+        let log_height = log_n + self.fri.log_blowup;
+        let fri_proof = &proof.fri_proof;
+
+        let well_shaped = proof.sumcheck_evals.len() == log_n
+            && fri_proof.commit_phase_commits.len() == log_n
+            && fri_proof.final_poly.len() == 1
+            && fri_proof.query_proofs.len() == self.fri.num_queries
+            && fri_proof.query_proofs.iter().all(|qp| {
+                qp.input_proof.opened_values.len() == 1
+                    && qp.input_proof.opened_values[0].len() == values.len()
+                    && qp.commit_phase_openings.len() == log_n
+                    && qp
+                        .commit_phase_openings
+                        .iter()
+                        .all(|step| step.log_arity == 1 && step.sibling_values.len() == 1)
+            });
+        if !well_shaped {
+            return Err(BasefoldError::Fri(FriError::InvalidProofShape));
+        }
+
+        for &value in values {
+            challenger.observe_ext_element(value);
+        }
+        let alpha: Challenge = challenger.sample_ext_element();
+
+        let mut claim = izip!(alpha.powers(), values)
+            .map(|(alpha_pow, &value)| alpha_pow * value)
+            .sum::<Challenge>();
+        let mut rs = vec![];
+        for (layer_commit, round_evals) in
+            izip!(&fri_proof.commit_phase_commits, &proof.sumcheck_evals)
+        {
+            challenger.observe(layer_commit.clone());
+            if round_evals[0] + round_evals[1] != claim {
+                return Err(BasefoldError::SumcheckMismatch);
+            }
+            for &eval in round_evals {
+                challenger.observe_ext_element(eval);
+            }
+            let r: Challenge = challenger.sample_ext_element();
+            claim = eval_quadratic(round_evals, r);
+            rs.push(r);
+        }
+
+        let final_value = fri_proof.final_poly[0];
+        if claim != final_value * eq_eval(&rs, point) {
+            return Err(BasefoldError::SumcheckMismatch);
+        }
+        challenger.observe_ext_element(final_value);
+
+        if !challenger.check_witness(self.fri.proof_of_work_bits, fri_proof.pow_witness) {
+            return Err(BasefoldError::Fri(FriError::InvalidPowWitness));
+        }
+
+        let indices = (0..self.fri.num_queries)
+            .map(|_| challenger.sample_bits(log_height))
+            .collect_vec();
+
+        let (input_proofs, commit_phase_openings) = if self.fri.prune_openings {
+            let input_proofs = self
+                .mmcs
+                .expand_proofs(
+                    &indices,
+                    fri_proof
+                        .query_proofs
+                        .iter()
+                        .map(|qp| qp.input_proof.opening_proof.clone())
+                        .collect(),
+                )
+                .map_err(|error| BasefoldError::Fri(FriError::InputError { query: None, error }))?;
+            let commit_phase_openings = expand_commit_phase_openings(
+                &self.fri,
+                &fri_proof.query_proofs,
+                indices.clone(),
+                &vec![1; log_n],
+            )
+            .map_err(|(layer, error)| {
+                BasefoldError::Fri(FriError::CommitPhaseMmcsError {
+                    layer,
+                    query: None,
+                    error,
+                })
+            })?;
+            (Some(input_proofs), Some(commit_phase_openings))
+        } else {
+            (None, None)
+        };
+
+        let g: TwoAdicFriGenericConfig<BatchOpening<Val, InputMmcs>, InputMmcs::Error> =
+            TwoAdicFriGenericConfig(PhantomData);
+        let input_dims = [Dimensions {
+            width: values.len(),
+            height: 1 << log_height,
+        }];
+        for (query, (qp, &index)) in izip!(&fri_proof.query_proofs, &indices).enumerate() {
+            let opened_values = &qp.input_proof.opened_values;
+            let opening_proof = input_proofs
+                .as_ref()
+                .map_or(&qp.input_proof.opening_proof, |proofs| &proofs[query]);
+            self.mmcs
+                .verify_batch(commit, &input_dims, index, opened_values, opening_proof)
+                .map_err(|error| {
+                    BasefoldError::Fri(FriError::InputError {
+                        query: Some(query),
+                        error,
+                    })
+                })?;
+
+            let reduced_opening = izip!(alpha.powers(), &opened_values[0])
+                .map(|(alpha_pow, &value)| alpha_pow * value)
+                .sum::<Challenge>();
+            let steps = commit_phase_openings
+                .as_ref()
+                .map_or(&qp.commit_phase_openings, |openings| &openings[query]);
+            let folded_eval = verify_query(
+                &g,
+                &self.fri,
+                query,
+                index,
+                izip!(&rs, &fri_proof.commit_phase_commits, steps),
+                vec![(log_height, reduced_opening)],
+                log_height,
+            )
+            .map_err(BasefoldError::Fri)?;
+            if folded_eval != final_value {
+                return Err(BasefoldError::Fri(FriError::FinalPolyMismatch { query }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert each column, from a multilinear polynomial's evaluations over the hypercube, to its
+/// coefficients in the monomial basis, in place.
+fn multilinear_coeffs<F: Field>(evaluations: &mut RowMajorMatrix<F>) {
+    let log_n = log2_strict_usize(evaluations.height());
+    // The evaluation at `b` is the sum of the coefficients of the monomials of the variables set
+    // in `b`, so for each variable, subtract the evaluations with it unset from those with it set.
+    for j in 0..log_n {
+        evaluations
+            .par_row_chunks_exact_mut(2 << j)
+            .for_each(|mut chunk| {
+                let (lo, hi) = chunk.split_rows_mut(1 << j);
+                izip!(hi.values, lo.values).for_each(|(h, &mut l)| *h -= l);
+            });
+    }
+}
+
+/// The evaluations of `eq(b, point) = prod_j (b_j point_j + (1 - b_j) (1 - point_j))` over the
+/// hypercube, indexed as in `MultivariatePcs`.
+fn eq_evals<F: Field>(point: &[F]) -> Vec<F> {
+    let mut evals = vec![F::one()];
+    for &z in point {
+        let hi = evals.iter().map(|&e| e * z).collect_vec();
+        evals.iter_mut().for_each(|e| *e *= F::one() - z);
+        evals.extend(hi);
+    }
+    evals
+}
+
+/// `eq(x, y)`, for points `x` and `y` of the same dimension.
+fn eq_eval<F: Field>(x: &[F], y: &[F]) -> F {
+    izip!(x, y)
+        .map(|(&x, &y)| x * y + (F::one() - x) * (F::one() - y))
+        .product()
+}
+
+/// Fix the first variable of a multilinear polynomial, given by its evaluations over the
+/// hypercube, to `r`.
+fn fold_evals<F: Field>(evals: &[F], r: F) -> Vec<F> {
+    evals
+        .chunks_exact(2)
+        .map(|pair| pair[0] + r * (pair[1] - pair[0]))
+        .collect()
+}
+
+/// The evaluations at 0, 1 and 2 of `h(X) = sum_b f(X, b) eq(X, b)`, the sum being over the
+/// hypercube of every variable but the first.
+fn sumcheck_round_evals<F: Field>(f: &[F], eq: &[F]) -> [F; 3] {
+    izip!(f.chunks_exact(2), eq.chunks_exact(2))
+        .map(|(f, eq)| {
+            let f_at_2 = f[1].double() - f[0];
+            let eq_at_2 = eq[1].double() - eq[0];
+            [f[0] * eq[0], f[1] * eq[1], f_at_2 * eq_at_2]
+        })
+        .fold([F::zero(); 3], |acc, evals| {
+            [acc[0] + evals[0], acc[1] + evals[1], acc[2] + evals[2]]
+        })
+}
+
+/// Evaluate at `r` the quadratic polynomial whose evaluations at 0, 1 and 2 are `evals`.
+fn eval_quadratic<F: Field>(evals: &[F; 3], r: F) -> F {
+    let [e0, e1, e2] = *evals;
+    let half = F::two().inverse();
+    e0 * (r - F::one()) * (r - F::two()) * half - e1 * r * (r - F::two())
+        + e2 * r * (r - F::one()) * half
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+
+    type F = BabyBear;
+
+    #[test]
+    fn coeffs_and_eq_agree_with_evaluations() {
+        let mut rng = thread_rng();
+        let log_n = 4;
+        let evals = RowMajorMatrix::<F>::rand(&mut rng, 1 << log_n, 2);
+        let mut coeffs = evals.clone();
+        multilinear_coeffs(&mut coeffs);
+
+        let point: Vec<F> = (0..log_n).map(|_| rng.gen()).collect();
+        // Evaluate via the monomial basis.
+        let monomials = (0..1 << log_n)
+            .map(|s: usize| {
+                (0..log_n)
+                    .filter(|&j| s >> j & 1 == 1)
+                    .map(|j| point[j])
+                    .product::<F>()
+            })
+            .collect_vec();
+        let expected = coeffs.columnwise_dot_product(&monomials);
+        assert_eq!(evals.columnwise_dot_product(&eq_evals(&point)), expected);
+
+        // Folding fixes the variables in order.
+        let column = evals.values.iter().step_by(2).copied().collect_vec();
+        let folded = point.iter().fold(column, |evals, &r| fold_evals(&evals, r));
+        assert_eq!(folded, vec![expected[0]]);
+        assert_eq!(
+            eq_eval(&point, &point),
+            eq_evals(&point).iter().map(|&e| e * e).sum::<F>()
+        );
+    }
+
+    #[test]
+    fn quadratic_interpolation() {
+        let [a, b, c] = [
+            F::from_canonical_u32(3),
+            F::from_canonical_u32(5),
+            F::from_canonical_u32(7),
+        ];
+        let h = |x: F| a * x * x + b * x + c;
+        let evals = [h(F::zero()), h(F::one()), h(F::two())];
+        let r = F::from_canonical_u32(12345);
+        assert_eq!(eval_quadratic(&evals, r), h(r));
+    }
+}
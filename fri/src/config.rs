@@ -8,14 +8,274 @@ use p3_matrix::Matrix;
 pub struct FriConfig<M> {
     pub log_blowup: usize,
     pub num_queries: usize,
+    /// The number of leading zero bits the prover must grind for after the commit phase, before
+    /// query indices are sampled. Each bit adds roughly one bit of security, so it can be traded
+    /// against `num_queries`.
     pub proof_of_work_bits: usize,
+    /// The log of the largest folding arity to use in a commit phase round. Each round folds by
+    /// `2^log_arity` for the largest `log_arity <= max_log_arity` which doesn't skip past the
+    /// height of the next input, or the final polynomial. Higher arities mean fewer commit phase
+    /// rounds and Merkle paths per query, at the cost of wider openings.
+    ///
+    /// With STIR, every round but the last folds by exactly `2^max_log_arity`.
+    pub max_log_arity: usize,
+    /// Folding stops once the codeword encodes a polynomial with `2^log_final_poly_len`
+    /// coefficients, which are then sent in the proof. Stopping early saves commit phase rounds
+    /// when the inputs are small.
+    pub log_final_poly_len: usize,
+    /// Which low-degree test to run on the batched inputs.
+    pub ldt: LowDegreeTest,
+    /// Whether to prune the opening proofs of all queries together, leaving out what one query's
+    /// proof repeats from an earlier query's, such as Merkle siblings shared by nearby queries.
+    /// Applies to FRI's commit phase openings, and to the input openings of `TwoAdicFriPcs`.
+    pub prune_openings: bool,
     pub mmcs: M,
 }
 
+/// The low-degree tests a `FriConfig` can select between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LowDegreeTest {
+    /// Vanilla FRI, which keeps the rate fixed and queries every round at each query location.
+    Fri,
+    /// STIR, which halves the evaluation domain each round while folding by `2^max_log_arity`,
+    /// so the rate improves and later rounds need fewer queries. Between rounds, the prover
+    /// answers `num_ood_samples` out-of-domain evaluations of the folded polynomial.
+    ///
+    /// Only supported for two-adic domains.
+    Stir { num_ood_samples: usize },
+}
+
 impl<M> FriConfig<M> {
     pub const fn blowup(&self) -> usize {
         1 << self.log_blowup
     }
+
+    pub const fn final_poly_len(&self) -> usize {
+        1 << self.log_final_poly_len
+    }
+
+    /// The log of the height of the final codeword, at which folding stops.
+    pub const fn log_final_height(&self) -> usize {
+        self.log_blowup + self.log_final_poly_len
+    }
+
+    /// An estimate of the bits of security these parameters achieve, when FRI challenges are
+    /// drawn from `F`.
+    ///
+    /// In the conjectured regime (FRI being sound up to list-decoding capacity, as in the ethSTARK
+    /// conjecture) each query contributes `log_blowup` bits. In the provable regime (up to the
+    /// Johnson bound) each query contributes only `log_blowup / 2` bits. Either way grinding adds
+    /// `proof_of_work_bits`, and the result is capped by the size of `F`, since a random
+    /// challenge can't be harder to guess than that.
+    pub fn security_bits<F: Field>(&self, conjectured: bool) -> f64 {
+        self.security_bits_for_field_bits(F::bits(), conjectured)
+    }
+
+    fn security_bits_for_field_bits(&self, field_bits: usize, conjectured: bool) -> f64 {
+        let bits_per_query = if conjectured {
+            self.log_blowup as f64
+        } else {
+            self.log_blowup as f64 / 2.0
+        };
+        let query_bits = bits_per_query * self.num_queries as f64 + self.proof_of_work_bits as f64;
+        let field_bits = field_bits as f64;
+        if query_bits < field_bits {
+            query_bits
+        } else {
+            field_bits
+        }
+    }
+}
+
+/// Builds a `FriConfig`, checking that its parameters are consistent with each other and with
+/// the inputs they'll be used for, rather than having the prover panic on them.
+///
+/// Starts from `log_blowup = 1`, `num_queries = 100`, `proof_of_work_bits = 16`,
+/// `max_log_arity = 1`, `log_final_poly_len = 0` and FRI, without pruned openings.
+#[derive(Debug)]
+pub struct FriConfigBuilder<M> {
+    config: FriConfig<M>,
+    log_max_degree: Option<usize>,
+    max_log_height: Option<usize>,
+    security_target: Option<SecurityTarget>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct SecurityTarget {
+    bits: usize,
+    conjectured: bool,
+    field_bits: usize,
+}
+
+/// A reason `FriConfigBuilder::build` rejected its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FriConfigError {
+    /// `log_blowup` is zero, so the codewords have rate 1 and queries give no soundness.
+    ZeroBlowup,
+    /// `num_queries` is zero.
+    ZeroQueries,
+    /// `max_log_arity` is zero, so folding would never shrink the codeword.
+    ZeroArity,
+    /// STIR is selected with no out-of-domain samples, which its soundness relies on.
+    ZeroOodSamples,
+    /// The final polynomial has more coefficients than the largest input polynomial.
+    FinalPolyTooLarge {
+        log_final_poly_len: usize,
+        log_max_degree: usize,
+    },
+    /// A single fold by `2^max_log_arity` is more than the whole way from the largest input to
+    /// the final polynomial, so the arity doesn't divide the codewords being folded.
+    ArityTooLarge {
+        max_log_arity: usize,
+        log_max_degree: usize,
+        log_final_poly_len: usize,
+    },
+    /// The largest input, once blown up, is taller than the largest domain available.
+    DomainTooLarge {
+        log_max_degree: usize,
+        log_blowup: usize,
+        max_log_height: usize,
+    },
+    /// The parameters fall short of the targeted security, as estimated by
+    /// `FriConfig::security_bits`.
+    InsufficientSecurity {
+        target_bits: usize,
+        achieved_bits: f64,
+    },
+}
+
+impl<M> FriConfigBuilder<M> {
+    pub const fn new(mmcs: M) -> Self {
+        Self {
+            config: FriConfig {
+                log_blowup: 1,
+                num_queries: 100,
+                proof_of_work_bits: 16,
+                max_log_arity: 1,
+                log_final_poly_len: 0,
+                ldt: LowDegreeTest::Fri,
+                prune_openings: false,
+                mmcs,
+            },
+            log_max_degree: None,
+            max_log_height: None,
+            security_target: None,
+        }
+    }
+
+    pub fn log_blowup(mut self, log_blowup: usize) -> Self {
+        self.config.log_blowup = log_blowup;
+        self
+    }
+
+    pub fn num_queries(mut self, num_queries: usize) -> Self {
+        self.config.num_queries = num_queries;
+        self
+    }
+
+    pub fn proof_of_work_bits(mut self, proof_of_work_bits: usize) -> Self {
+        self.config.proof_of_work_bits = proof_of_work_bits;
+        self
+    }
+
+    pub fn max_log_arity(mut self, max_log_arity: usize) -> Self {
+        self.config.max_log_arity = max_log_arity;
+        self
+    }
+
+    pub fn log_final_poly_len(mut self, log_final_poly_len: usize) -> Self {
+        self.config.log_final_poly_len = log_final_poly_len;
+        self
+    }
+
+    pub fn ldt(mut self, ldt: LowDegreeTest) -> Self {
+        self.config.ldt = ldt;
+        self
+    }
+
+    pub fn prune_openings(mut self, prune_openings: bool) -> Self {
+        self.config.prune_openings = prune_openings;
+        self
+    }
+
+    /// Check the parameters against inputs of up to `2^log_max_degree` coefficients.
+    pub fn log_max_degree(mut self, log_max_degree: usize) -> Self {
+        self.log_max_degree = Some(log_max_degree);
+        self
+    }
+
+    /// Check that blown up inputs fit in a domain of `2^max_log_height` points, e.g. the
+    /// two-adicity of a two-adic field. Only checked if `log_max_degree` is set.
+    pub fn max_log_height(mut self, max_log_height: usize) -> Self {
+        self.max_log_height = Some(max_log_height);
+        self
+    }
+
+    /// Check that the parameters achieve `bits` bits of security when challenges are drawn from
+    /// `F`, in the conjectured or provable regime, as estimated by `FriConfig::security_bits`.
+    pub fn target_security_bits<F: Field>(mut self, bits: usize, conjectured: bool) -> Self {
+        self.security_target = Some(SecurityTarget {
+            bits,
+            conjectured,
+            field_bits: F::bits(),
+        });
+        self
+    }
+
+    pub fn build(self) -> Result<FriConfig<M>, FriConfigError> {
+        let config = self.config;
+        if config.log_blowup == 0 {
+            return Err(FriConfigError::ZeroBlowup);
+        }
+        if config.num_queries == 0 {
+            return Err(FriConfigError::ZeroQueries);
+        }
+        if config.max_log_arity == 0 {
+            return Err(FriConfigError::ZeroArity);
+        }
+        if config.ldt == (LowDegreeTest::Stir { num_ood_samples: 0 }) {
+            return Err(FriConfigError::ZeroOodSamples);
+        }
+
+        if let Some(log_max_degree) = self.log_max_degree {
+            if config.log_final_poly_len > log_max_degree {
+                return Err(FriConfigError::FinalPolyTooLarge {
+                    log_final_poly_len: config.log_final_poly_len,
+                    log_max_degree,
+                });
+            }
+            let log_folded = log_max_degree - config.log_final_poly_len;
+            if log_folded > 0 && config.max_log_arity > log_folded {
+                return Err(FriConfigError::ArityTooLarge {
+                    max_log_arity: config.max_log_arity,
+                    log_max_degree,
+                    log_final_poly_len: config.log_final_poly_len,
+                });
+            }
+            if let Some(max_log_height) = self.max_log_height {
+                if log_max_degree + config.log_blowup > max_log_height {
+                    return Err(FriConfigError::DomainTooLarge {
+                        log_max_degree,
+                        log_blowup: config.log_blowup,
+                        max_log_height,
+                    });
+                }
+            }
+        }
+
+        if let Some(target) = self.security_target {
+            let achieved_bits =
+                config.security_bits_for_field_bits(target.field_bits, target.conjectured);
+            if achieved_bits < target.bits as f64 {
+                return Err(FriConfigError::InsufficientSecurity {
+                    target_bits: target.bits,
+                    achieved_bits,
+                });
+            }
+        }
+
+        Ok(config)
+    }
 }
 
 /// Whereas `FriConfig` encompasses parameters the end user can set, `FriGenericConfig` is
@@ -29,8 +289,8 @@ pub trait FriGenericConfig<F: Field> {
     fn extra_query_index_bits(&self) -> usize;
 
     /// Fold a row, returning a single column.
-    /// Right now the input row will always be 2 columns wide,
-    /// but we may support higher folding arity in the future.
+    /// The input row is `2^log_arity` columns wide, where `log_arity` is the folding arity of
+    /// the current commit phase round, and `index` is the row's index in the folded codeword.
     fn fold_row(
         &self,
         index: usize,
@@ -40,5 +300,146 @@ pub trait FriGenericConfig<F: Field> {
     ) -> F;
 
     /// Same as applying fold_row to every row, possibly faster.
+    /// The folding arity is given by the matrix width.
     fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F>;
+
+    /// Interpolate the final codeword, given in the same order as the folded codewords, into the
+    /// `2^log_final_poly_len` coefficients of the polynomial it encodes.
+    fn final_poly_coeffs(&self, codeword: Vec<F>, log_final_poly_len: usize) -> Vec<F>;
+
+    /// Evaluate the final polynomial at the point of the final codeword at `index`, where the
+    /// final codeword has height `2^log_height`.
+    fn eval_final_poly(&self, coeffs: &[F], index: usize, log_height: usize) -> F;
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_goldilocks::Goldilocks;
+
+    use super::*;
+
+    fn config(log_blowup: usize, num_queries: usize, proof_of_work_bits: usize) -> FriConfig<()> {
+        FriConfig {
+            log_blowup,
+            num_queries,
+            proof_of_work_bits,
+            max_log_arity: 1,
+            log_final_poly_len: 0,
+            ldt: LowDegreeTest::Fri,
+            prune_openings: false,
+            mmcs: (),
+        }
+    }
+
+    #[test]
+    fn security_bits() {
+        type Challenge = BinomialExtensionField<BabyBear, 4>;
+
+        let c = config(1, 100, 16);
+        assert_eq!(c.security_bits::<Challenge>(true), 116.0);
+        assert_eq!(c.security_bits::<Challenge>(false), 66.0);
+
+        let c = config(3, 33, 0);
+        assert_eq!(c.security_bits::<Challenge>(true), 99.0);
+        assert_eq!(c.security_bits::<Challenge>(false), 49.5);
+    }
+
+    #[test]
+    fn builder_accepts_valid_parameters() {
+        type Challenge = BinomialExtensionField<BabyBear, 4>;
+
+        let c = FriConfigBuilder::new(())
+            .log_blowup(2)
+            .num_queries(50)
+            .max_log_arity(3)
+            .log_final_poly_len(2)
+            .ldt(LowDegreeTest::Stir { num_ood_samples: 1 })
+            .log_max_degree(20)
+            .max_log_height(27)
+            .target_security_bits::<Challenge>(100, true)
+            .build()
+            .unwrap();
+        assert_eq!(c.log_blowup, 2);
+        assert_eq!(c.num_queries, 50);
+        assert_eq!(c.proof_of_work_bits, 16);
+        assert_eq!(c.max_log_arity, 3);
+        assert_eq!(c.log_final_poly_len, 2);
+        assert_eq!(c.ldt, LowDegreeTest::Stir { num_ood_samples: 1 });
+    }
+
+    #[test]
+    fn builder_rejects_invalid_parameters() {
+        let builder = || FriConfigBuilder::new(()).log_max_degree(10);
+        assert_eq!(
+            builder().log_blowup(0).build().unwrap_err(),
+            FriConfigError::ZeroBlowup
+        );
+        assert_eq!(
+            builder().num_queries(0).build().unwrap_err(),
+            FriConfigError::ZeroQueries
+        );
+        assert_eq!(
+            builder().max_log_arity(0).build().unwrap_err(),
+            FriConfigError::ZeroArity
+        );
+        assert_eq!(
+            builder()
+                .ldt(LowDegreeTest::Stir { num_ood_samples: 0 })
+                .build()
+                .unwrap_err(),
+            FriConfigError::ZeroOodSamples
+        );
+        assert_eq!(
+            builder().log_final_poly_len(11).build().unwrap_err(),
+            FriConfigError::FinalPolyTooLarge {
+                log_final_poly_len: 11,
+                log_max_degree: 10
+            }
+        );
+        assert_eq!(
+            builder()
+                .log_final_poly_len(8)
+                .max_log_arity(3)
+                .build()
+                .unwrap_err(),
+            FriConfigError::ArityTooLarge {
+                max_log_arity: 3,
+                log_max_degree: 10,
+                log_final_poly_len: 8
+            }
+        );
+        assert_eq!(
+            builder()
+                .log_blowup(3)
+                .max_log_height(12)
+                .build()
+                .unwrap_err(),
+            FriConfigError::DomainTooLarge {
+                log_max_degree: 10,
+                log_blowup: 3,
+                max_log_height: 12
+            }
+        );
+        assert_eq!(
+            builder()
+                .num_queries(20)
+                .proof_of_work_bits(0)
+                .target_security_bits::<Goldilocks>(40, false)
+                .build()
+                .unwrap_err(),
+            FriConfigError::InsufficientSecurity {
+                target_bits: 40,
+                achieved_bits: 10.0
+            }
+        );
+    }
+
+    #[test]
+    fn security_bits_capped_by_field() {
+        let c = config(2, 100, 20);
+        assert_eq!(c.security_bits::<Goldilocks>(true), 64.0);
+        assert_eq!(c.security_bits::<Goldilocks>(false), 64.0);
+    }
 }
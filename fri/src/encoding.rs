@@ -0,0 +1,885 @@
+//! A canonical, versioned byte encoding of proofs, which doesn't depend on the layout of any serde
+//! format crate.
+//!
+//! An encoding starts with the magic bytes `P3FP` and the format version, as a little-endian
+//! `u16`. The proof follows, as serialized through its serde implementation:
+//! - integers are little-endian, at their own width, with `usize` and `isize` taking 64 bits;
+//! - `bool` is a single byte, `0` or `1`, and `char` is its scalar value as a `u32`;
+//! - floats are their IEEE 754 bits, as integers of the same width;
+//! - strings and byte strings are their length, as a `u64`, followed by their UTF-8 or raw bytes;
+//! - sequences and maps are their length, as a `u64`, followed by their elements, with each key
+//!   followed by its value;
+//! - tuples, arrays and structs are their fields in order, with no length;
+//! - an option is a byte, `0` for `None`, or `1` followed by the value;
+//! - an enum is the index of its variant, as a `u32`, followed by the variant's fields;
+//! - units and unit structs are empty.
+//!
+//! Every field element is encoded by its own serde implementation. For the prime fields in this
+//! workspace, and their binomial extensions, that is exactly `CanonicalBytes::to_canonical_bytes`:
+//! each coefficient as its representative in `0..p`, a little-endian integer of the field's width.
+//! Decoding rejects any coefficient which isn't less than `p`, so each proof has one encoding.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+
+/// The magic bytes every encoded proof starts with.
+pub const PROOF_ENCODING_MAGIC: [u8; 4] = *b"P3FP";
+
+/// The version of the encoding written by `to_bytes`, and the only one `from_bytes` accepts.
+pub const PROOF_ENCODING_VERSION: u16 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofEncodingError {
+    /// The bytes don't start with `PROOF_ENCODING_MAGIC`.
+    BadMagic,
+    /// The bytes are of an encoding version other than `PROOF_ENCODING_VERSION`.
+    UnsupportedVersion(u16),
+    /// The bytes end partway through the proof.
+    UnexpectedEnd,
+    /// There are bytes left after the proof.
+    TrailingBytes,
+    /// A value is malformed, or can't be represented in the encoding.
+    Invalid(String),
+}
+
+impl Display for ProofEncodingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "not an encoded proof"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported proof encoding version {version}")
+            }
+            Self::UnexpectedEnd => write!(f, "unexpected end of encoded proof"),
+            Self::TrailingBytes => write!(f, "trailing bytes after encoded proof"),
+            Self::Invalid(msg) => write!(f, "invalid encoded proof: {msg}"),
+        }
+    }
+}
+
+impl ser::StdError for ProofEncodingError {}
+
+impl ser::Error for ProofEncodingError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Invalid(msg.to_string())
+    }
+}
+
+impl de::Error for ProofEncodingError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Invalid(msg.to_string())
+    }
+}
+
+/// Encode `value`, with the version header.
+pub(crate) fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, ProofEncodingError> {
+    let mut encoder = Encoder { bytes: Vec::new() };
+    encoder.bytes.extend_from_slice(&PROOF_ENCODING_MAGIC);
+    encoder
+        .bytes
+        .extend_from_slice(&PROOF_ENCODING_VERSION.to_le_bytes());
+    value.serialize(&mut encoder)?;
+    Ok(encoder.bytes)
+}
+
+/// The length of the encoding of `value`, without the version header.
+pub(crate) fn encoded_len<T: Serialize + ?Sized>(value: &T) -> Result<usize, ProofEncodingError> {
+    let mut encoder = Encoder { bytes: Vec::new() };
+    value.serialize(&mut encoder)?;
+    Ok(encoder.bytes.len())
+}
+
+/// The total length of the encodings of `values`, leaving out the length of the sequence.
+pub(crate) fn sum_encoded_lens<T: Serialize>(values: &[T]) -> Result<usize, ProofEncodingError> {
+    values.iter().map(encoded_len).sum()
+}
+
+/// Decode a value encoded by `to_bytes`, which must take up all of `bytes`.
+pub(crate) fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ProofEncodingError> {
+    let mut decoder = Decoder { bytes };
+    if decoder.take(PROOF_ENCODING_MAGIC.len())? != PROOF_ENCODING_MAGIC {
+        return Err(ProofEncodingError::BadMagic);
+    }
+    let version = u16::from_le_bytes(decoder.take_array()?);
+    if version != PROOF_ENCODING_VERSION {
+        return Err(ProofEncodingError::UnsupportedVersion(version));
+    }
+    let value = T::deserialize(&mut decoder)?;
+    if !decoder.bytes.is_empty() {
+        return Err(ProofEncodingError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn write_len(&mut self, len: Option<usize>) -> Result<(), ProofEncodingError> {
+        let len = len.ok_or_else(|| {
+            ProofEncodingError::Invalid("sequences must have a known length".to_string())
+        })?;
+        self.bytes.extend_from_slice(&(len as u64).to_le_bytes());
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Encoder {
+    type Ok = ();
+    type Error = ProofEncodingError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<(), ProofEncodingError> {
+        self.bytes.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), ProofEncodingError> {
+        self.bytes.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), ProofEncodingError> {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), ProofEncodingError> {
+        self.serialize_u32(v.to_bits())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), ProofEncodingError> {
+        self.serialize_u64(v.to_bits())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), ProofEncodingError> {
+        self.serialize_u32(v as u32)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), ProofEncodingError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), ProofEncodingError> {
+        self.write_len(Some(v.len()))?;
+        self.bytes.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), ProofEncodingError> {
+        self.bytes.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), ProofEncodingError> {
+        self.bytes.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), ProofEncodingError> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        self.serialize_u32(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, ProofEncodingError> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, ProofEncodingError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self, ProofEncodingError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, ProofEncodingError> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, ProofEncodingError> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self, ProofEncodingError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, ProofEncodingError> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+}
+
+impl<'a> ser::SerializeSeq for &'a mut Encoder {
+    type Ok = ();
+    type Error = ProofEncodingError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for &'a mut Encoder {
+    type Ok = ();
+    type Error = ProofEncodingError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for &'a mut Encoder {
+    type Ok = ();
+    type Error = ProofEncodingError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for &'a mut Encoder {
+    type Ok = ();
+    type Error = ProofEncodingError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeMap for &'a mut Encoder {
+    type Ok = ();
+    type Error = ProofEncodingError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ProofEncodingError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for &'a mut Encoder {
+    type Ok = ();
+    type Error = ProofEncodingError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for &'a mut Encoder {
+    type Ok = ();
+    type Error = ProofEncodingError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), ProofEncodingError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+}
+
+struct Decoder<'de> {
+    bytes: &'de [u8],
+}
+
+impl<'de> Decoder<'de> {
+    fn take(&mut self, n: usize) -> Result<&'de [u8], ProofEncodingError> {
+        if self.bytes.len() < n {
+            return Err(ProofEncodingError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], ProofEncodingError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ProofEncodingError> {
+        Ok(u32::from_le_bytes(self.take_array()?))
+    }
+
+    fn read_len(&mut self) -> Result<usize, ProofEncodingError> {
+        let len = u64::from_le_bytes(self.take_array()?);
+        usize::try_from(len)
+            .map_err(|_| ProofEncodingError::Invalid("length too large".to_string()))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'de [u8], ProofEncodingError> {
+        let len = self.read_len()?;
+        self.take(len)
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for &'a mut Decoder<'de> {
+    type Error = ProofEncodingError;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, ProofEncodingError> {
+        Err(ProofEncodingError::Invalid(
+            "the proof encoding is not self-describing".to_string(),
+        ))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        match self.take(1)?[0] {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            b => Err(ProofEncodingError::Invalid(alloc::format!(
+                "invalid bool {b}"
+            ))),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_i8(i8::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_i16(i16::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_i32(i32::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_i64(i64::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_i128(i128::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_u8(self.take(1)?[0])
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_u16(u16::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_u32(self.read_u32()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_u64(u64::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_u128(u128::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_f32(f32::from_bits(self.read_u32()?))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_f64(f64::from_bits(u64::from_le_bytes(self.take_array()?)))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        let c = self.read_u32()?;
+        let c = char::from_u32(c)
+            .ok_or_else(|| ProofEncodingError::Invalid(alloc::format!("invalid char {c}")))?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        let s = core::str::from_utf8(self.read_bytes()?)
+            .map_err(|err| ProofEncodingError::Invalid(err.to_string()))?;
+        visitor.visit_borrowed_str(s)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_borrowed_bytes(self.read_bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        match self.take(1)?[0] {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            b => Err(ProofEncodingError::Invalid(alloc::format!(
+                "invalid option tag {b}"
+            ))),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        let len = self.read_len()?;
+        visitor.visit_seq(Elements { de: self, len })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_seq(Elements { de: self, len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_seq(Elements { de: self, len })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProofEncodingError> {
+        let len = self.read_len()?;
+        visitor.visit_map(Elements { de: self, len })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_seq(Elements {
+            de: self,
+            len: fields.len(),
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        self.deserialize_u32(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        self.deserialize_any(visitor)
+    }
+}
+
+/// The elements of a sequence, tuple, struct or map, of known length.
+struct Elements<'a, 'de> {
+    de: &'a mut Decoder<'de>,
+    len: usize,
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for Elements<'a, 'de> {
+    type Error = ProofEncodingError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, ProofEncodingError> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'a, 'de> de::MapAccess<'de> for Elements<'a, 'de> {
+    type Error = ProofEncodingError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ProofEncodingError> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for &'a mut Decoder<'de> {
+    type Error = ProofEncodingError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), ProofEncodingError> {
+        let variant_index = self.read_u32()?;
+        let value = seed.deserialize(variant_index.into_deserializer())?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for &'a mut Decoder<'de> {
+    type Error = ProofEncodingError;
+
+    fn unit_variant(self) -> Result<(), ProofEncodingError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, ProofEncodingError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ProofEncodingError> {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{AbstractExtensionField, AbstractField, CanonicalBytes, Field, PrimeField};
+    use p3_goldilocks::Goldilocks;
+    use p3_mersenne_31::Mersenne31;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Values(Vec<u32>),
+        Pair { a: u16, b: Option<bool> },
+    }
+
+    #[test]
+    fn layout_is_little_endian_and_length_prefixed() {
+        let bytes = to_bytes(&(0x0102_0304u32, vec![5u8, 6])).unwrap();
+        assert_eq!(
+            bytes,
+            [
+                b'P', b'3', b'F', b'P', 1, 0, // header
+                4, 3, 2, 1, // u32
+                2, 0, 0, 0, 0, 0, 0, 0, 5, 6, // Vec<u8>
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let values = vec![
+            Shape::Empty,
+            Shape::Values(vec![1, u32::MAX]),
+            Shape::Pair {
+                a: 7,
+                b: Some(true),
+            },
+        ];
+        let bytes = to_bytes(&values).unwrap();
+        assert_eq!(from_bytes::<Vec<Shape>>(&bytes).unwrap(), values);
+    }
+
+    /// Check that field elements are encoded as their canonical bytes, and that an encoding of
+    /// `p` is rejected.
+    fn check_field_encoding<F>(order: &[u8])
+    where
+        F: PrimeField + Serialize + DeserializeOwned,
+    {
+        let header = to_bytes(&()).unwrap();
+        for x in [F::zero(), F::one(), F::neg_one(), F::two().inverse()] {
+            let bytes = to_bytes(&x).unwrap();
+            assert_eq!(
+                bytes[header.len()..],
+                CanonicalBytes::to_canonical_bytes(&x)
+            );
+            assert_eq!(from_bytes::<F>(&bytes).unwrap(), x);
+        }
+        let non_canonical = [header.as_slice(), order].concat();
+        assert!(from_bytes::<F>(&non_canonical).is_err());
+    }
+
+    #[test]
+    fn field_elements_are_canonical() {
+        check_field_encoding::<BabyBear>(&0x7800_0001u32.to_le_bytes());
+        check_field_encoding::<Goldilocks>(&0xFFFF_FFFF_0000_0001u64.to_le_bytes());
+        check_field_encoding::<Mersenne31>(&0x7FFF_FFFFu32.to_le_bytes());
+    }
+
+    #[test]
+    fn extension_elements_are_canonical() {
+        type EF = BinomialExtensionField<BabyBear, 4>;
+        let x = EF::from_base_slice(&[1, 2, 3, 4].map(BabyBear::from_canonical_u32));
+        let bytes = to_bytes(&x).unwrap();
+        assert_eq!(bytes[6..], x.to_canonical_bytes());
+        assert_eq!(from_bytes::<EF>(&bytes).unwrap(), x);
+
+        // The last coefficient is `p`.
+        let mut non_canonical = bytes;
+        let len = non_canonical.len();
+        non_canonical[len - 4..].copy_from_slice(&0x7800_0001u32.to_le_bytes());
+        assert!(from_bytes::<EF>(&non_canonical).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_bytes() {
+        let bytes = to_bytes(&vec![1u32, 2]).unwrap();
+        assert_eq!(
+            from_bytes::<Vec<u32>>(&bytes[..bytes.len() - 1]),
+            Err(ProofEncodingError::UnexpectedEnd)
+        );
+        assert_eq!(
+            from_bytes::<u8>(&bytes),
+            Err(ProofEncodingError::TrailingBytes)
+        );
+
+        let mut other_version = bytes.clone();
+        other_version[4] = 2;
+        assert_eq!(
+            from_bytes::<Vec<u32>>(&other_version),
+            Err(ProofEncodingError::UnsupportedVersion(2))
+        );
+
+        let mut other_magic = bytes;
+        other_magic[0] = b'X';
+        assert_eq!(
+            from_bytes::<Vec<u32>>(&other_magic),
+            Err(ProofEncodingError::BadMagic)
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! An alternative layout of `FriProof`, whose shape depends only on the config and on the heights
+//! of the inputs, so that a recursive verifier circuit can consume it without data-dependent
+//! control flow.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter;
+
+use itertools::{izip, Itertools};
+use p3_commit::Mmcs;
+use p3_field::Field;
+use serde::{Deserialize, Serialize};
+
+use crate::prover::log_arity_for_round;
+use crate::{encoding, CommitPhaseProofStep, FriConfig, FriProof, QueryProof};
+
+/// The shape of every FRI proof for a given config and set of input heights.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FriProofShape {
+    pub num_queries: usize,
+    /// The log of the folding arity of each commit phase round.
+    pub log_arities: Vec<usize>,
+    /// The sibling values of each commit phase step are padded to `2^max_log_arity - 1`.
+    pub max_log_arity: usize,
+    pub final_poly_len: usize,
+    /// Whether opening proofs are pruned. The length of a pruned opening proof depends on the
+    /// queries drawn, so proofs made with pruning have no fixed shape.
+    pub prune_openings: bool,
+}
+
+impl FriProofShape {
+    /// The number of sibling values of each commit phase step, once padded.
+    pub const fn num_padded_siblings(&self) -> usize {
+        (1 << self.max_log_arity) - 1
+    }
+
+    /// The number of commit phase rounds.
+    pub fn num_rounds(&self) -> usize {
+        self.log_arities.len()
+    }
+}
+
+impl<M> FriConfig<M> {
+    /// The shape of the proofs `prover::prove` makes for inputs of heights
+    /// `2^input_log_heights`, given in descending order as the prover takes them.
+    pub fn proof_shape(&self, input_log_heights: &[usize]) -> FriProofShape {
+        let mut log_height = input_log_heights[0];
+        let mut next_input_log_heights = input_log_heights[1..].iter().copied().peekable();
+        let mut log_arities = vec![];
+        while log_height > self.log_final_height() {
+            let log_arity =
+                log_arity_for_round(self, log_height, next_input_log_heights.peek().copied());
+            log_arities.push(log_arity);
+            log_height -= log_arity;
+            next_input_log_heights.next_if_eq(&log_height);
+        }
+        FriProofShape {
+            num_queries: self.num_queries,
+            log_arities,
+            max_log_arity: self.max_log_arity,
+            final_poly_len: self.final_poly_len(),
+            prune_openings: self.prune_openings,
+        }
+    }
+}
+
+/// A `FriProof` laid out in flat sequences, whose lengths are all determined by a
+/// `FriProofShape`. The folding arities aren't included, since they're part of the shape.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize, InputProof: Serialize",
+    deserialize = "Witness: Deserialize<'de>, InputProof: Deserialize<'de>"
+))]
+pub struct FixedShapeFriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
+    /// One per commit phase round.
+    pub commit_phase_commits: Vec<M::Commitment>,
+    /// One per query.
+    pub input_proofs: Vec<InputProof>,
+    /// For each query, for each commit phase round, the sibling values opened in that round,
+    /// followed by zeros up to `num_padded_siblings` values.
+    pub sibling_values: Vec<F>,
+    /// For each query, for each commit phase round, the opening proof of that round.
+    pub opening_proofs: Vec<M::Proof>,
+    pub final_poly: Vec<F>,
+    pub pow_witness: Witness,
+}
+
+impl<F: Field, M: Mmcs<F>, Witness, InputProof> FriProof<F, M, Witness, InputProof> {
+    /// Lay the proof out in the fixed shape `shape`, or return `None` if the proof doesn't have
+    /// that shape, which it never does if `shape.prune_openings` is set.
+    pub fn to_fixed_shape(
+        self,
+        shape: &FriProofShape,
+    ) -> Option<FixedShapeFriProof<F, M, Witness, InputProof>> {
+        let num_padded_siblings = shape.num_padded_siblings();
+        let has_shape = !shape.prune_openings
+            && self.commit_phase_commits.len() == shape.num_rounds()
+            && self.query_proofs.len() == shape.num_queries
+            && self.final_poly.len() == shape.final_poly_len
+            && self.query_proofs.iter().all(|qp| {
+                qp.commit_phase_openings.len() == shape.num_rounds()
+                    && izip!(&qp.commit_phase_openings, &shape.log_arities).all(
+                        |(step, &log_arity)| {
+                            step.log_arity as usize == log_arity
+                                && step.sibling_values.len() == (1 << log_arity) - 1
+                                && step.sibling_values.len() <= num_padded_siblings
+                        },
+                    )
+            });
+        if !has_shape {
+            return None;
+        }
+
+        let mut input_proofs = Vec::with_capacity(shape.num_queries);
+        let mut sibling_values =
+            Vec::with_capacity(shape.num_queries * shape.num_rounds() * num_padded_siblings);
+        let mut opening_proofs = Vec::with_capacity(shape.num_queries * shape.num_rounds());
+        for query_proof in self.query_proofs {
+            input_proofs.push(query_proof.input_proof);
+            for step in query_proof.commit_phase_openings {
+                let num_padding = num_padded_siblings - step.sibling_values.len();
+                sibling_values.extend(step.sibling_values);
+                sibling_values.extend(iter::repeat(F::zero()).take(num_padding));
+                opening_proofs.push(step.opening_proof);
+            }
+        }
+        if !has_uniform_opening_proofs::<F, M>(&opening_proofs, shape.num_rounds()) {
+            return None;
+        }
+
+        Some(FixedShapeFriProof {
+            commit_phase_commits: self.commit_phase_commits,
+            input_proofs,
+            sibling_values,
+            opening_proofs,
+            final_poly: self.final_poly,
+            pow_witness: self.pow_witness,
+        })
+    }
+}
+
+impl<F: Field, M: Mmcs<F>, Witness, InputProof> FixedShapeFriProof<F, M, Witness, InputProof> {
+    /// Recover the `FriProof` laid out in the shape `shape`, to be checked by
+    /// `verifier::verify`. Returns `None` if the sequences have the wrong lengths, the padding
+    /// isn't zero, or `shape.prune_openings` is set.
+    pub fn into_proof(self, shape: &FriProofShape) -> Option<FriProof<F, M, Witness, InputProof>> {
+        let num_padded_siblings = shape.num_padded_siblings();
+        if shape.prune_openings
+            || self.commit_phase_commits.len() != shape.num_rounds()
+            || self.input_proofs.len() != shape.num_queries
+            || self.sibling_values.len()
+                != shape.num_queries * shape.num_rounds() * num_padded_siblings
+            || self.opening_proofs.len() != shape.num_queries * shape.num_rounds()
+            || self.final_poly.len() != shape.final_poly_len
+            || shape.log_arities.iter().any(|&a| a > shape.max_log_arity)
+            || !has_uniform_opening_proofs::<F, M>(&self.opening_proofs, shape.num_rounds())
+        {
+            return None;
+        }
+
+        let mut sibling_values = self.sibling_values.into_iter();
+        let mut opening_proofs = self.opening_proofs.into_iter();
+        let query_proofs = self
+            .input_proofs
+            .into_iter()
+            .map(|input_proof| {
+                let commit_phase_openings = shape
+                    .log_arities
+                    .iter()
+                    .map(|&log_arity| {
+                        let mut padded = sibling_values.by_ref().take(num_padded_siblings);
+                        let siblings = padded.by_ref().take((1 << log_arity) - 1).collect_vec();
+                        padded.all(|x| x.is_zero()).then(|| CommitPhaseProofStep {
+                            log_arity: log_arity as u8,
+                            sibling_values: siblings,
+                            opening_proof: opening_proofs.next().unwrap(),
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(QueryProof {
+                    input_proof,
+                    commit_phase_openings,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(FriProof {
+            commit_phase_commits: self.commit_phase_commits,
+            query_proofs,
+            final_poly: self.final_poly,
+            pow_witness: self.pow_witness,
+        })
+    }
+}
+
+/// Whether, in `opening_proofs`, given for each query in turn, one per round, the opening proofs of
+/// each round all have encodings of the same length, as unpruned ones do.
+fn has_uniform_opening_proofs<F: Field, M: Mmcs<F>>(
+    opening_proofs: &[M::Proof],
+    num_rounds: usize,
+) -> bool {
+    (0..num_rounds).all(|round| {
+        opening_proofs
+            .iter()
+            .skip(round)
+            .step_by(num_rounds)
+            .map(|proof| encoding::encoded_len(proof).ok())
+            .collect::<Option<Vec<_>>>()
+            .is_some_and(|lens| lens.iter().all_equal())
+    })
+}
@@ -0,0 +1,114 @@
+//! The randomness `TwoAdicFriPcs` blinds its commitments and openings with when hiding, see
+//! `TwoAdicFriPcs::with_hiding`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use p3_commit::TwoAdicMultiplicativeCoset;
+use p3_field::{Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use rand::distributions::{Distribution, Standard};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+/// Random values are drawn from a `StdRng` seeded, per call, with a secret seed mixed with a
+/// counter, so that the PCS can be shared between threads.
+pub(crate) struct Hiding<Val> {
+    pub(crate) num_random_codewords: usize,
+    seed: <StdRng as SeedableRng>::Seed,
+    num_calls: AtomicU64,
+    sample: fn(&mut StdRng) -> Val,
+}
+
+/// Leaves out the seed, which must stay secret.
+impl<Val> Debug for Hiding<Val> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hiding")
+            .field("num_random_codewords", &self.num_random_codewords)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Val: TwoAdicField> Hiding<Val> {
+    /// Draw the secret seed from `rng`.
+    pub(crate) fn new<R: RngCore>(num_random_codewords: usize, rng: &mut R) -> Self
+    where
+        Standard: Distribution<Val>,
+    {
+        assert!(num_random_codewords > 0);
+        let mut seed = <StdRng as SeedableRng>::Seed::default();
+        rng.fill_bytes(&mut seed);
+        Self {
+            num_random_codewords,
+            seed,
+            num_calls: AtomicU64::new(0),
+            sample: |rng| rng.sample(Standard),
+        }
+    }
+
+    /// A fresh RNG, which no other call draws from.
+    fn next_rng(&self) -> StdRng {
+        let nonce = self.num_calls.fetch_add(1, Ordering::Relaxed);
+        let mut seed = self.seed;
+        for (byte, nonce_byte) in seed.iter_mut().zip(nonce.to_le_bytes()) {
+            *byte ^= nonce_byte;
+        }
+        StdRng::from_seed(seed)
+    }
+
+    /// A uniformly random `height` by `width` matrix.
+    pub(crate) fn random_matrix(&self, height: usize, width: usize) -> RowMajorMatrix<Val> {
+        let mut rng = self.next_rng();
+        let values = (0..height * width)
+            .map(|_| (self.sample)(&mut rng))
+            .collect();
+        RowMajorMatrix::new(values, width)
+    }
+
+    /// Extend evaluations over `H` to evaluations over the blinded domain, whose even-indexed
+    /// points form `H`, by filling the odd-indexed rows with random values. The unique polynomial
+    /// of degree `< 2|H|` through these evaluations is `p + Z_H * r` for a uniformly random `r`.
+    pub(crate) fn blind(
+        &self,
+        domain: TwoAdicMultiplicativeCoset<Val>,
+        evals: RowMajorMatrix<Val>,
+    ) -> (TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>) {
+        assert_eq!(domain.size(), evals.height());
+        let width = evals.width();
+        let random = self.random_matrix(evals.height(), width);
+        let values = evals
+            .values
+            .chunks_exact(width)
+            .zip(random.values.chunks_exact(width))
+            .flat_map(|(row, random_row)| row.iter().chain(random_row).copied())
+            .collect();
+        (blinded_domain(domain), RowMajorMatrix::new(values, width))
+    }
+}
+
+/// A domain twice the size of `domain`, containing it, on which the blinded polynomial is
+/// described.
+pub(crate) fn blinded_domain<Val: TwoAdicField>(
+    domain: TwoAdicMultiplicativeCoset<Val>,
+) -> TwoAdicMultiplicativeCoset<Val> {
+    TwoAdicMultiplicativeCoset {
+        log_n: domain.log_n + 1,
+        shift: domain.shift,
+    }
+}
+
+/// The distinct points opened across all rounds, in order of first appearance.
+pub(crate) fn distinct_points<'a, Challenge: Field + 'a>(
+    points: impl IntoIterator<Item = &'a Challenge>,
+) -> Vec<Challenge> {
+    let mut distinct = vec![];
+    for &point in points {
+        if !distinct.contains(&point) {
+            distinct.push(point);
+        }
+    }
+    distinct
+}
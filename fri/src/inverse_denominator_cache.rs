@@ -0,0 +1,89 @@
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt::{self, Debug, Formatter};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use spin::RwLock;
+
+/// A cached `1 / (x - z)` table: the log height of the coset, the point `z`, and the table.
+///
+/// The point and table are type-erased, as the extension field they lie in is only fixed by each
+/// `Pcs` impl, not by the PCS itself.
+type Entry = (
+    usize,
+    Box<dyn Any + Send + Sync>,
+    Arc<dyn Any + Send + Sync>,
+);
+
+/// The `1 / (x - z)` tables of recent openings, keyed by log height and point, holding at most
+/// `capacity` of them. Once full, the oldest is evicted for each new one.
+pub(crate) struct InverseDenominatorCache {
+    capacity: usize,
+    tables: RwLock<VecDeque<Entry>>,
+    hits: AtomicUsize,
+}
+
+impl InverseDenominatorCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tables: RwLock::new(VecDeque::new()),
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// The table for `z` over the coset of size `2^log_height`, computing it with `f` if it isn't
+    /// cached. The lock isn't held while computing it, so two threads may both compute it.
+    pub(crate) fn get_or_compute<EF: PartialEq + Send + Sync + 'static>(
+        &self,
+        log_height: usize,
+        z: EF,
+        f: impl FnOnce() -> Vec<EF>,
+    ) -> Arc<Vec<EF>> {
+        if let Some(table) = find(&self.tables.read(), log_height, &z) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return table;
+        }
+        let table = Arc::new(f());
+        let mut tables = self.tables.write();
+        if let Some(table) = find(&tables, log_height, &z) {
+            return table;
+        }
+        if self.capacity > 0 {
+            if tables.len() == self.capacity {
+                tables.pop_front();
+            }
+            tables.push_back((log_height, Box::new(z), table.clone()));
+        }
+        table
+    }
+
+    /// The number of tables taken from the cache rather than computed.
+    pub(crate) fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+}
+
+fn find<EF: PartialEq + Send + Sync + 'static>(
+    tables: &VecDeque<Entry>,
+    log_height: usize,
+    z: &EF,
+) -> Option<Arc<Vec<EF>>> {
+    tables
+        .iter()
+        .find(|(h, point, _)| *h == log_height && point.downcast_ref::<EF>() == Some(z))
+        .and_then(|(_, _, table)| table.clone().downcast().ok())
+}
+
+impl Debug for InverseDenominatorCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InverseDenominatorCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.tables.read().len())
+            .field("hits", &self.hits())
+            .finish()
+    }
+}
@@ -4,14 +4,25 @@
 
 extern crate alloc;
 
+mod backend;
+mod basefold;
 mod config;
+mod encoding;
+mod fixed_shape;
 mod fold_even_odd;
+mod hiding;
+mod inverse_denominator_cache;
 mod proof;
 pub mod prover;
+pub mod stir;
 mod two_adic_pcs;
 pub mod verifier;
 
+pub use backend::*;
+pub use basefold::*;
 pub use config::*;
+pub use encoding::*;
+pub use fixed_shape::*;
 pub use fold_even_odd::*;
 pub use proof::*;
 pub use two_adic_pcs::*;
@@ -1,9 +1,13 @@
+use alloc::vec;
 use alloc::vec::Vec;
 
 use p3_commit::Mmcs;
 use p3_field::Field;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::{encoding, ProofEncodingError};
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound(
     serialize = "Witness: Serialize, InputProof: Serialize",
@@ -12,12 +16,79 @@ use serde::{Deserialize, Serialize};
 pub struct FriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     pub commit_phase_commits: Vec<M::Commitment>,
     pub query_proofs: Vec<QueryProof<F, M, InputProof>>,
-    // This could become Vec<FC::Challenge> if this library was generalized to support non-constant
-    // final polynomials.
-    pub final_poly: F,
+    /// The coefficients of the final polynomial, of length `2^log_final_poly_len`.
+    pub final_poly: Vec<F>,
     pub pow_witness: Witness,
 }
 
+impl<F: Field, M: Mmcs<F>, Witness, InputProof> FriProof<F, M, Witness, InputProof> {
+    /// Encode the proof in the canonical byte encoding described in the `encoding` module, which
+    /// is stable across serde format crates and prefixed with its version.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProofEncodingError>
+    where
+        Self: Serialize,
+    {
+        encoding::to_bytes(self)
+    }
+
+    /// Decode a proof encoded by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofEncodingError>
+    where
+        Self: DeserializeOwned,
+    {
+        encoding::from_bytes(bytes)
+    }
+
+    /// The sizes of the components of the proof, in its canonical byte encoding.
+    pub fn stats(&self) -> Result<ProofStats, ProofEncodingError>
+    where
+        Witness: Serialize,
+        InputProof: Serialize,
+    {
+        let num_layers = self.commit_phase_commits.len();
+        let mut query_openings_per_layer = vec![0; num_layers];
+        let mut input_openings = 0;
+        for query_proof in &self.query_proofs {
+            input_openings += encoding::encoded_len(&query_proof.input_proof)?;
+            for (layer, step) in query_proof.commit_phase_openings.iter().enumerate() {
+                query_openings_per_layer[layer] += encoding::encoded_len(step)?;
+            }
+        }
+        Ok(ProofStats {
+            commit_phase_commitments: encoding::sum_encoded_lens(&self.commit_phase_commits)?,
+            query_openings_per_layer,
+            input_openings,
+            final_poly: encoding::sum_encoded_lens(&self.final_poly)?,
+            ood_answers: 0,
+            pow_witnesses: encoding::encoded_len(&self.pow_witness)?,
+            total: self.to_bytes()?.len(),
+        })
+    }
+}
+
+/// A breakdown of the size of a proof, in bytes of its canonical encoding (see `to_bytes`), to
+/// help with choosing parameters.
+///
+/// Each component is measured on its own, so `total` also counts the version header and the
+/// lengths of the proof's sequences, which no other field does.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofStats {
+    /// The commitments to the codewords of each folding round.
+    pub commit_phase_commitments: usize,
+    /// For each folding round, the opened values and opening proofs of all its queries.
+    pub query_openings_per_layer: Vec<usize>,
+    /// The openings of the input matrices, over all queries.
+    pub input_openings: usize,
+    /// The coefficients of the final polynomial.
+    pub final_poly: usize,
+    /// The out-of-domain evaluations of STIR's folded polynomials. Always zero for FRI.
+    pub ood_answers: usize,
+    /// The proof-of-work witnesses.
+    pub pow_witnesses: usize,
+    /// The whole proof.
+    pub total: usize,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound(
     serialize = "InputProof: Serialize",
@@ -33,10 +104,13 @@ pub struct QueryProof<F: Field, M: Mmcs<F>, InputProof> {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CommitPhaseProofStep<F: Field, M: Mmcs<F>> {
-    /// The opening of the commit phase codeword at the sibling location.
-    // This may change to Vec<FC::Challenge> if the library is generalized to support other FRI
-    // folding arities besides 2, meaning that there can be multiple siblings.
-    pub sibling_value: F,
+    /// The log of the folding arity used in this round. The verifier derives the arity of every
+    /// round from its own config, and rejects a proof whose steps disagree with it.
+    pub log_arity: u8,
+
+    /// The openings of the commit phase codeword at the sibling locations, i.e. every entry of
+    /// the opened row except the one at the queried location.
+    pub sibling_values: Vec<F>,
 
     pub opening_proof: M::Proof,
 }
@@ -5,8 +5,9 @@ use core::iter;
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, CanSample, GrindingChallenger};
 use p3_commit::Mmcs;
-use p3_field::Field;
+use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
 use p3_util::log2_strict_usize;
 use tracing::{info_span, instrument};
 
@@ -21,16 +22,21 @@ pub fn prove<G, F, M, Challenger>(
     open_input: impl Fn(usize) -> G::InputProof,
 ) -> FriProof<F, M, Challenger::Witness, G::InputProof>
 where
-    F: Field,
+    F: ExtensionField<Challenger::Witness>,
     M: Mmcs<F>,
     Challenger: GrindingChallenger + CanObserve<M::Commitment> + CanSample<F>,
     G: FriGenericConfig<F>,
 {
+    assert!(config.max_log_arity > 0, "FRI must fold by at least 2");
     // check sorted descending
     assert!(inputs
         .iter()
         .tuple_windows()
         .all(|(l, r)| l.len() >= r.len()));
+    assert!(
+        inputs.last().unwrap().len() >= config.blowup() * config.final_poly_len(),
+        "inputs must be at least as tall as the final codeword"
+    );
 
     let log_max_height = log2_strict_usize(inputs[0].len());
 
@@ -38,10 +44,15 @@ where
 
     let pow_witness = challenger.grind(config.proof_of_work_bits);
 
-    let query_proofs = info_span!("query phase").in_scope(|| {
+    let query_indices =
         iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
             .take(config.num_queries)
-            .map(|index| QueryProof {
+            .collect_vec();
+
+    let mut query_proofs = info_span!("query phase").in_scope(|| {
+        query_indices
+            .iter()
+            .map(|&index| QueryProof {
                 input_proof: open_input(index),
                 commit_phase_openings: answer_query(
                     config,
@@ -49,9 +60,20 @@ where
                     index >> g.extra_query_index_bits(),
                 ),
             })
-            .collect()
+            .collect_vec()
     });
 
+    if config.prune_openings {
+        prune_commit_phase_openings(
+            config,
+            &mut query_proofs,
+            query_indices
+                .iter()
+                .map(|&index| index >> g.extra_query_index_bits())
+                .collect(),
+        );
+    }
+
     FriProof {
         commit_phase_commits: commit_phase_result.commits,
         query_proofs,
@@ -63,7 +85,7 @@ where
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
-    final_poly: F,
+    final_poly: Vec<F>,
 }
 
 #[instrument(name = "commit phase", skip_all)]
@@ -74,9 +96,9 @@ fn commit_phase<G, F, M, Challenger>(
     challenger: &mut Challenger,
 ) -> CommitPhaseResult<F, M>
 where
-    F: Field,
+    F: ExtensionField<Challenger::Witness>,
     M: Mmcs<F>,
-    Challenger: CanObserve<M::Commitment> + CanSample<F>,
+    Challenger: GrindingChallenger + CanObserve<M::Commitment> + CanSample<F>,
     G: FriGenericConfig<F>,
 {
     let mut inputs_iter = inputs.into_iter().peekable();
@@ -84,8 +106,13 @@ where
     let mut commits = vec![];
     let mut data = vec![];
 
-    while folded.len() > config.blowup() {
-        let leaves = RowMajorMatrix::new(folded, 2);
+    while folded.len() > config.blowup() * config.final_poly_len() {
+        let log_arity = log_arity_for_round(
+            config,
+            log2_strict_usize(folded.len()),
+            inputs_iter.peek().map(|v| log2_strict_usize(v.len())),
+        );
+        let leaves = RowMajorMatrix::new(folded, 1 << log_arity);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
 
@@ -102,11 +129,13 @@ where
         }
     }
 
-    // We should be left with `blowup` evaluations of a constant polynomial.
-    assert_eq!(folded.len(), config.blowup());
-    let final_poly = folded[0];
-    for x in folded {
-        assert_eq!(x, final_poly);
+    // We should be left with `blowup` evaluations of each of the final polynomial's cosets.
+    assert_eq!(folded.len(), config.blowup() * config.final_poly_len());
+    let final_poly = g.final_poly_coeffs(folded, config.log_final_poly_len);
+
+    // The final polynomial must be bound to the transcript before any queries are sampled.
+    for &coeff in &final_poly {
+        challenger.observe_slice(coeff.as_base_slice());
     }
 
     CommitPhaseResult {
@@ -116,10 +145,23 @@ where
     }
 }
 
-fn answer_query<F, M>(
+/// The log of the folding arity for a commit phase round starting at `log_height`: as large as
+/// `max_log_arity` allows, without folding past the next input or the final polynomial.
+pub(crate) fn log_arity_for_round<M>(
+    config: &FriConfig<M>,
+    log_height: usize,
+    next_input_log_height: Option<usize>,
+) -> usize {
+    let log_final_height = config.log_final_height();
+    let log_target_height =
+        next_input_log_height.map_or(log_final_height, |h| h.max(log_final_height));
+    (log_height - log_target_height).min(config.max_log_arity)
+}
+
+pub(crate) fn answer_query<F, M>(
     config: &FriConfig<M>,
     commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
-    index: usize,
+    mut index: usize,
 ) -> Vec<CommitPhaseProofStep<F, M>>
 where
     F: Field,
@@ -127,22 +169,54 @@ where
 {
     commit_phase_commits
         .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let index_i = index >> i;
-            let index_i_sibling = index_i ^ 1;
-            let index_pair = index_i >> 1;
+        .map(|commit| {
+            let log_arity = log2_strict_usize(config.mmcs.get_matrices(commit)[0].width());
+            let index_in_row = index & ((1 << log_arity) - 1);
+            let row_index = index >> log_arity;
 
-            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_pair, commit);
+            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(row_index, commit);
             assert_eq!(opened_rows.len(), 1);
-            let opened_row = opened_rows.pop().unwrap();
-            assert_eq!(opened_row.len(), 2, "Committed data should be in pairs");
-            let sibling_value = opened_row[index_i_sibling % 2];
+            let mut sibling_values = opened_rows.pop().unwrap();
+            assert_eq!(
+                sibling_values.len(),
+                1 << log_arity,
+                "Committed data should be in rows of width arity"
+            );
+            sibling_values.remove(index_in_row);
+            index = row_index;
 
             CommitPhaseProofStep {
-                sibling_value,
+                log_arity: log_arity as u8,
+                sibling_values,
                 opening_proof,
             }
         })
         .collect()
 }
+
+/// Prune the opening proofs of each commit phase round across all queries, given the queries'
+/// indices into the first commit phase codeword.
+pub(crate) fn prune_commit_phase_openings<F, M, InputProof>(
+    config: &FriConfig<M>,
+    query_proofs: &mut [QueryProof<F, M, InputProof>],
+    mut indices: Vec<usize>,
+) where
+    F: Field,
+    M: Mmcs<F>,
+{
+    let num_rounds = query_proofs
+        .first()
+        .map_or(0, |qp| qp.commit_phase_openings.len());
+    for round in 0..num_rounds {
+        let log_arity = query_proofs[0].commit_phase_openings[round].log_arity;
+        indices.iter_mut().for_each(|index| *index >>= log_arity);
+        let proofs = query_proofs
+            .iter()
+            .map(|qp| qp.commit_phase_openings[round].opening_proof.clone())
+            .collect();
+        let pruned = config.mmcs.prune_proofs(&indices, proofs);
+        for (qp, proof) in izip!(query_proofs.iter_mut(), pruned) {
+            qp.commit_phase_openings[round].opening_proof = proof;
+        }
+    }
+}
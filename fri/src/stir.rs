@@ -0,0 +1,732 @@
+//! An implementation of the STIR low-degree test, as an alternative to FRI for two-adic domains.
+//!
+//! Like FRI, each round folds the current polynomial `f` by `k = 2^log_arity` with a random
+//! `r_fold`. Unlike FRI, the folded polynomial `g` is committed over a new domain only half the
+//! size of the current one, so the rate improves by `k / 2` every round, and later rounds need
+//! fewer queries for the same security.
+//!
+//! Since the new domain isn't the `k`-th power of the current one, queries to `f` don't directly
+//! check `g`. Instead, the verifier learns `g` at a few out-of-domain points, and computes
+//! `Fold(f, r_fold)` itself at the `k`-th powers of the queried fibers. The next round's polynomial
+//! is the quotient of `g` by these evaluations, degree-corrected back up to the degree of `g`,
+//! which the verifier can evaluate wherever it knows `g`.
+//!
+//! See <https://eprint.iacr.org/2024/390>.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{izip, Itertools};
+use p3_challenger::{CanObserve, CanSample, GrindingChallenger};
+use p3_commit::Mmcs;
+use p3_dft::{Radix2Bowers, TwoAdicSubgroupDft};
+use p3_field::{
+    batch_multiplicative_inverse, binomial_expand, eval_poly, ExtensionField, Field, TwoAdicField,
+};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Dimensions;
+use p3_maybe_rayon::prelude::*;
+use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
+use serde::{Deserialize, Serialize};
+use tracing::{info_span, instrument};
+
+use crate::encoding::{self, ProofEncodingError};
+use crate::verifier::FriError;
+use crate::{FriConfig, LowDegreeTest, ProofStats};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize, InputProof: Serialize",
+    deserialize = "Witness: Deserialize<'de>, InputProof: Deserialize<'de>"
+))]
+pub struct StirProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
+    /// The commitment to the batched input codeword, followed by one commitment per round but the
+    /// last, to the evaluations of that round's folded polynomial over the next domain.
+    pub commits: Vec<M::Commitment>,
+    /// One per commitment, i.e. per folding round.
+    pub rounds: Vec<StirRoundProof<F, M, Witness>>,
+    /// The input openings for each of the first round's queries.
+    pub input_proofs: Vec<InputProof>,
+    /// The coefficients of the last round's folded polynomial.
+    pub final_poly: Vec<F>,
+}
+
+impl<F: Field, M: Mmcs<F>, Witness: Serialize, InputProof: Serialize>
+    StirProof<F, M, Witness, InputProof>
+{
+    /// The sizes of the components of the proof, in its canonical byte encoding.
+    pub fn stats(&self) -> Result<ProofStats, ProofEncodingError> {
+        let mut stats = ProofStats {
+            commit_phase_commitments: encoding::sum_encoded_lens(&self.commits)?,
+            input_openings: encoding::sum_encoded_lens(&self.input_proofs)?,
+            final_poly: encoding::sum_encoded_lens(&self.final_poly)?,
+            total: encoding::to_bytes(self)?.len(),
+            ..Default::default()
+        };
+        for round in &self.rounds {
+            stats
+                .query_openings_per_layer
+                .push(encoding::sum_encoded_lens(&round.query_openings)?);
+            stats.ood_answers += encoding::sum_encoded_lens(&round.ood_answers)?;
+            stats.pow_witnesses += encoding::encoded_len(&round.pow_witness)?;
+        }
+        Ok(stats)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize",
+    deserialize = "Witness: Deserialize<'de>"
+))]
+pub struct StirRoundProof<F: Field, M: Mmcs<F>, Witness> {
+    /// The evaluations of this round's folded polynomial at the out-of-domain points. Empty in
+    /// the last round, whose folded polynomial is sent in the clear instead.
+    pub ood_answers: Vec<F>,
+    pub pow_witness: Witness,
+    pub query_openings: Vec<StirQueryOpening<F, M>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct StirQueryOpening<F: Field, M: Mmcs<F>> {
+    /// The opened row of this round's codeword, i.e. its evaluations over the queried fiber. In
+    /// the first round, the entry at the queried location is left out, since the verifier
+    /// recomputes it from the input openings.
+    pub values: Vec<F>,
+    pub opening_proof: M::Proof,
+}
+
+#[instrument(name = "STIR prover", skip_all)]
+pub fn prove<F, M, Challenger, InputProof>(
+    config: &FriConfig<M>,
+    inputs: Vec<Vec<F>>,
+    challenger: &mut Challenger,
+    open_input: impl Fn(usize) -> InputProof,
+) -> StirProof<F, M, Challenger::Witness, InputProof>
+where
+    F: TwoAdicField + ExtensionField<Challenger::Witness>,
+    M: Mmcs<F>,
+    Challenger: GrindingChallenger + CanObserve<M::Commitment> + CanSample<F>,
+{
+    let LowDegreeTest::Stir { num_ood_samples } = config.ldt else {
+        panic!("config doesn't select STIR");
+    };
+    // check sorted descending, with one input per height
+    assert!(inputs
+        .iter()
+        .tuple_windows()
+        .all(|(l, r)| l.len() > r.len()));
+
+    let log_max_height = log2_strict_usize(inputs[0].len());
+    let log_arities = log_arities(config, log_max_height);
+
+    // Batch the inputs into one codeword, over the largest domain. An input of height `2^h`, read
+    // at `index >> (log_max_height - h)`, is a polynomial in `x^(2^(log_max_height - h))`, so the
+    // batched codeword has the same rate as each input.
+    let gamma: F = challenger.sample();
+    let mut codeword = vec![F::zero(); 1 << log_max_height];
+    for input in &inputs {
+        let bits_reduced = log_max_height - log2_strict_usize(input.len());
+        let weight = gamma.exp_u64(bits_reduced as u64);
+        codeword
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, c)| *c += weight * input[i >> bits_reduced]);
+    }
+    let mut poly = info_span!("interpolate batched inputs").in_scope(|| {
+        let mut evals = codeword.clone();
+        reverse_slice_index_bits(&mut evals);
+        let mut coeffs = Radix2Bowers.idft(evals);
+        let degree = 1 << (log_max_height - config.log_blowup);
+        debug_assert!(
+            coeffs[degree..].iter().all(|c| c.is_zero()),
+            "inputs are of too high degree"
+        );
+        coeffs.truncate(degree);
+        coeffs
+    });
+
+    let (commit, mut data) = config
+        .mmcs
+        .commit_matrix(RowMajorMatrix::new(codeword, 1 << log_arities[0]));
+    challenger.observe(commit.clone());
+    let mut commits = vec![commit];
+
+    // The current domain is the coset `shift * <w>`, of size `2^log_size`.
+    let mut log_size = log_max_height;
+    let mut shift = F::one();
+    let mut rounds = vec![];
+    let mut input_proofs = vec![];
+    let mut final_poly = vec![];
+
+    for (round, &log_arity) in log_arities.iter().enumerate() {
+        let _guard = info_span!("STIR round", round).entered();
+
+        let r_fold: F = challenger.sample();
+        let folded = fold_coeffs(&poly, log_arity, r_fold);
+        let next_domain_shift = next_shift(shift, log_arity);
+
+        let mut next = None;
+        let mut ood_points_and_answers = vec![];
+        if let Some(&next_log_arity) = log_arities.get(round + 1) {
+            // Commit to the folded polynomial over a domain half the size of the current one.
+            let mut evals = folded.clone();
+            evals.resize(1 << (log_size - 1), F::zero());
+            let mut evals = Radix2Bowers.coset_dft(evals, next_domain_shift);
+            reverse_slice_index_bits(&mut evals);
+            let (commit, next_data) = config
+                .mmcs
+                .commit_matrix(RowMajorMatrix::new(evals, 1 << next_log_arity));
+            challenger.observe(commit.clone());
+            commits.push(commit);
+
+            for _ in 0..num_ood_samples {
+                let point: F = challenger.sample();
+                let answer = eval_poly(&folded, point);
+                challenger.observe_slice(answer.as_base_slice());
+                ood_points_and_answers.push((point, answer));
+            }
+            let r_comb: F = challenger.sample();
+            next = Some((next_data, r_comb));
+        } else {
+            for coeff in &folded {
+                challenger.observe_slice(coeff.as_base_slice());
+            }
+        }
+
+        let pow_witness = challenger.grind(config.proof_of_work_bits);
+
+        let log_folded_size = log_size - log_arity;
+        let mut query_openings = vec![];
+        let mut folded_points_and_answers = vec![];
+        for _ in 0..num_queries(config, &log_arities, round) {
+            // The first round's queries also open the inputs, at a single location of the fiber.
+            let (row_index, own_position) = if round == 0 {
+                let index = challenger.sample_bits(log_size);
+                input_proofs.push(open_input(index));
+                (index >> log_arity, Some(index & ((1 << log_arity) - 1)))
+            } else {
+                (challenger.sample_bits(log_folded_size), None)
+            };
+
+            let (mut opened_values, opening_proof) = config.mmcs.open_batch(row_index, &data);
+            let mut values = opened_values.remove(0);
+            if let Some(position) = own_position {
+                values.remove(position);
+            }
+            query_openings.push(StirQueryOpening {
+                values,
+                opening_proof,
+            });
+
+            let point =
+                fiber_start(log_size, shift, log_arity, row_index).exp_power_of_2(log_arity);
+            folded_points_and_answers.push((point, eval_poly(&folded, point)));
+        }
+
+        rounds.push(StirRoundProof {
+            ood_answers: ood_points_and_answers
+                .iter()
+                .map(|&(_, answer)| answer)
+                .collect(),
+            pow_witness,
+            query_openings,
+        });
+
+        if let Some((next_data, r_comb)) = next {
+            poly = Quotient::new(
+                ood_points_and_answers
+                    .into_iter()
+                    .chain(folded_points_and_answers),
+                r_comb,
+            )
+            .apply(&folded);
+            data = next_data;
+            log_size -= 1;
+            shift = next_domain_shift;
+        } else {
+            final_poly = folded;
+        }
+    }
+
+    StirProof {
+        commits,
+        rounds,
+        input_proofs,
+        final_poly,
+    }
+}
+
+/// Verify a STIR proof for inputs whose largest height is `2^log_max_height`.
+///
+/// As with `verifier::verify_with_input_check`, once the first round's query indices have been
+/// sampled, `check_inputs` is handed every `(index, input_proof)` pair before any is opened.
+#[instrument(name = "STIR verifier", skip_all)]
+pub fn verify<F, M, Challenger, InputProof, InputError>(
+    config: &FriConfig<M>,
+    proof: &StirProof<F, M, Challenger::Witness, InputProof>,
+    challenger: &mut Challenger,
+    log_max_height: usize,
+    check_inputs: impl FnOnce(&[(usize, &InputProof)]) -> Result<(), (Option<usize>, InputError)>,
+    open_input: impl Fn(usize, &InputProof) -> Result<Vec<(usize, F)>, InputError>,
+) -> Result<(), FriError<M::Error, InputError>>
+where
+    F: TwoAdicField + ExtensionField<Challenger::Witness>,
+    M: Mmcs<F>,
+    Challenger: GrindingChallenger + CanObserve<M::Commitment> + CanSample<F>,
+{
+    let LowDegreeTest::Stir { num_ood_samples } = config.ldt else {
+        panic!("config doesn't select STIR");
+    };
+    if log_max_height < config.log_blowup {
+        return Err(FriError::InvalidProofShape);
+    }
+    let log_arities = log_arities(config, log_max_height);
+    let last_round = log_arities.len() - 1;
+    let log_final_poly_len = log_max_height - config.log_blowup - log_arities.iter().sum::<usize>();
+
+    let well_shaped = proof.commits.len() == log_arities.len()
+        && proof.rounds.len() == log_arities.len()
+        && proof.input_proofs.len() == num_queries(config, &log_arities, 0)
+        && proof.final_poly.len() == 1 << log_final_poly_len
+        && izip!(0.., &proof.rounds, &log_arities).all(|(round, round_proof, &log_arity)| {
+            let num_ood_answers = if round == last_round {
+                0
+            } else {
+                num_ood_samples
+            };
+            let row_len = if round == 0 {
+                (1 << log_arity) - 1
+            } else {
+                1 << log_arity
+            };
+            round_proof.ood_answers.len() == num_ood_answers
+                && round_proof.query_openings.len() == num_queries(config, &log_arities, round)
+                && round_proof
+                    .query_openings
+                    .iter()
+                    .all(|opening| opening.values.len() == row_len)
+        });
+    if !well_shaped {
+        return Err(FriError::InvalidProofShape);
+    }
+
+    let gamma: F = challenger.sample();
+    challenger.observe(proof.commits[0].clone());
+
+    let mut check_inputs = Some(check_inputs);
+    let mut log_size = log_max_height;
+    let mut shift = F::one();
+    // Maps the committed codeword's evaluations to those of the current round's polynomial. The
+    // first round commits to its polynomial directly.
+    let mut quotient: Option<Quotient<F>> = None;
+
+    for (round, (round_proof, &log_arity)) in izip!(&proof.rounds, &log_arities).enumerate() {
+        let r_fold: F = challenger.sample();
+
+        let mut ood_points_and_answers = vec![];
+        let mut r_comb = None;
+        if round < last_round {
+            challenger.observe(proof.commits[round + 1].clone());
+            for &answer in &round_proof.ood_answers {
+                let point: F = challenger.sample();
+                challenger.observe_slice(answer.as_base_slice());
+                ood_points_and_answers.push((point, answer));
+            }
+            r_comb = Some(challenger.sample());
+        } else {
+            for coeff in &proof.final_poly {
+                challenger.observe_slice(coeff.as_base_slice());
+            }
+        }
+
+        if !challenger.check_witness(config.proof_of_work_bits, round_proof.pow_witness) {
+            return Err(FriError::InvalidPowWitness);
+        }
+
+        let log_folded_size = log_size - log_arity;
+        let index_bits = if round == 0 {
+            log_size
+        } else {
+            log_folded_size
+        };
+        let indices = round_proof
+            .query_openings
+            .iter()
+            .map(|_| challenger.sample_bits(index_bits))
+            .collect_vec();
+        if let Some(check_inputs) = check_inputs.take() {
+            let queries = izip!(indices.iter().copied(), &proof.input_proofs).collect_vec();
+            check_inputs(&queries)
+                .map_err(|(query, error)| FriError::InputError { query, error })?;
+        }
+
+        let mut folded_points_and_answers = vec![];
+        for (query, (&index, opening)) in izip!(&indices, &round_proof.query_openings).enumerate() {
+            let mut values = opening.values.clone();
+            let row_index = if round == 0 {
+                let reduced_openings =
+                    open_input(index, &proof.input_proofs[query]).map_err(|error| {
+                        FriError::InputError {
+                            query: Some(query),
+                            error,
+                        }
+                    })?;
+                let batched = reduced_openings
+                    .into_iter()
+                    .map(|(log_height, ro)| {
+                        gamma.exp_u64((log_max_height - log_height) as u64) * ro
+                    })
+                    .sum();
+                values.insert(index & ((1 << log_arity) - 1), batched);
+                index >> log_arity
+            } else {
+                index
+            };
+
+            let dims = &[Dimensions {
+                width: 1 << log_arity,
+                height: 1 << log_folded_size,
+            }];
+            config
+                .mmcs
+                .verify_batch(
+                    &proof.commits[round],
+                    dims,
+                    row_index,
+                    &[values.clone()],
+                    &opening.opening_proof,
+                )
+                .map_err(|error| FriError::CommitPhaseMmcsError {
+                    layer: round,
+                    query: Some(query),
+                    error,
+                })?;
+
+            let x0 = fiber_start(log_size, shift, log_arity, row_index);
+            if let Some(quotient) = &quotient {
+                values = izip!(fiber(x0, log_arity), values)
+                    .map(|(x, g_at_x)| quotient.eval(x, g_at_x))
+                    .collect();
+            }
+            folded_points_and_answers
+                .push((x0.exp_power_of_2(log_arity), fold_fiber(values, x0, r_fold)));
+        }
+
+        if let Some(r_comb) = r_comb {
+            quotient = Some(Quotient::new(
+                ood_points_and_answers
+                    .into_iter()
+                    .chain(folded_points_and_answers),
+                r_comb,
+            ));
+            log_size -= 1;
+            shift = next_shift(shift, log_arity);
+        } else if let Some(query) = folded_points_and_answers
+            .into_iter()
+            .position(|(point, answer)| eval_poly(&proof.final_poly, point) != answer)
+        {
+            return Err(FriError::FinalPolyMismatch { query });
+        }
+    }
+
+    Ok(())
+}
+
+/// The folding arities of the rounds, for inputs of height `2^log_max_height`. Every round but
+/// the last folds by `2^max_log_arity`, and the last folds whatever is left down to the final
+/// polynomial.
+fn log_arities<M>(config: &FriConfig<M>, log_max_height: usize) -> Vec<usize> {
+    assert!(config.max_log_arity > 0, "STIR must fold by at least 2");
+    let log_degree = log_max_height - config.log_blowup;
+    let mut log_remaining = log_degree.saturating_sub(config.log_final_poly_len);
+    let mut log_arities = vec![];
+    loop {
+        let log_arity = log_remaining.min(config.max_log_arity);
+        log_arities.push(log_arity);
+        log_remaining -= log_arity;
+        if log_remaining == 0 {
+            return log_arities;
+        }
+    }
+}
+
+/// The number of queries made in `round`. Each round halves the domain while shrinking the degree
+/// by its folding arity, so the later rounds are queried at a lower rate, and fewer queries give
+/// the same security as `num_queries` queries did at the initial rate.
+fn num_queries<M>(config: &FriConfig<M>, log_arities: &[usize], round: usize) -> usize {
+    let log_inv_rate = config.log_blowup
+        + log_arities[..round]
+            .iter()
+            .map(|&log_arity| log_arity - 1)
+            .sum::<usize>();
+    if log_inv_rate == 0 {
+        return config.num_queries;
+    }
+    (config.num_queries * config.log_blowup)
+        .div_ceil(log_inv_rate)
+        .max(1)
+}
+
+/// The shift of the domain following the coset `shift * <w>`, when folding by `2^log_arity`. It
+/// is chosen so that the new domain is disjoint from the `2^log_arity`-th power of the current
+/// one, which is where the verifier evaluates the folded polynomial itself.
+fn next_shift<F: TwoAdicField>(shift: F, log_arity: usize) -> F {
+    F::generator() * shift.exp_power_of_2(log_arity)
+}
+
+/// The first point of the fiber at `row_index` of the domain `shift * <w>` of size `2^log_size`,
+/// in bit-reversed order, when folding by `2^log_arity`.
+fn fiber_start<F: TwoAdicField>(
+    log_size: usize,
+    shift: F,
+    log_arity: usize,
+    row_index: usize,
+) -> F {
+    let log_folded_size = log_size - log_arity;
+    shift
+        * F::two_adic_generator(log_size)
+            .exp_u64(reverse_bits_len(row_index, log_folded_size) as u64)
+}
+
+/// All points of the fiber starting at `x0`, in bit-reversed order.
+fn fiber<F: TwoAdicField>(x0: F, log_arity: usize) -> Vec<F> {
+    let mut xs = F::two_adic_generator(log_arity)
+        .shifted_powers(x0)
+        .take(1 << log_arity)
+        .collect_vec();
+    reverse_slice_index_bits(&mut xs);
+    xs
+}
+
+/// Fold the coefficients of `f = sum_t X^t f_t(X^k)` into those of `sum_t r^t f_t`.
+fn fold_coeffs<F: Field>(f: &[F], log_arity: usize, r: F) -> Vec<F> {
+    f.chunks_exact(1 << log_arity)
+        .map(|chunk| eval_poly(chunk, r))
+        .collect()
+}
+
+/// Fold the evaluations of `f` over the fiber starting at `x0`, given in bit-reversed order, into
+/// the evaluation of `fold_coeffs(f, log_arity, beta)` at `x0^k`.
+fn fold_fiber<F: TwoAdicField>(mut evals: Vec<F>, mut x0: F, mut beta: F) -> F {
+    // As in FRI, fold by 2 repeatedly. In bit-reversed order, the pairs are `x` and `-x`, and the
+    // even-indexed points of a fiber starting at `x0` square to a fiber starting at `x0^2`.
+    let one_half = F::two().inverse();
+    while evals.len() > 1 {
+        let log_len = log2_strict_usize(evals.len());
+        let inv_xs =
+            batch_multiplicative_inverse(&fiber(x0, log_len).into_iter().step_by(2).collect_vec());
+        evals = izip!(evals.chunks_exact(2), inv_xs)
+            .map(|(pair, inv_x)| {
+                let (lo, hi) = (pair[0], pair[1]);
+                ((lo + hi) + beta * (lo - hi) * inv_x) * one_half
+            })
+            .collect();
+        x0 = x0.square();
+        beta = beta.square();
+    }
+    evals[0]
+}
+
+/// The constraints a round's folded polynomial `g` is quotiented by, to form the next round's
+/// polynomial `DegCor(Quot(g))`.
+struct Quotient<F> {
+    points: Vec<F>,
+    answers: Vec<F>,
+    /// The barycentric weights `1 / prod_{j != i} (points[i] - points[j])`.
+    weights: Vec<F>,
+    r_comb: F,
+}
+
+impl<F: Field> Quotient<F> {
+    fn new(points_and_answers: impl IntoIterator<Item = (F, F)>, r_comb: F) -> Self {
+        let mut points = vec![];
+        let mut answers = vec![];
+        for (point, answer) in points_and_answers {
+            // Queries can repeat, but each point is only constrained once.
+            if !points.contains(&point) {
+                points.push(point);
+                answers.push(answer);
+            }
+        }
+        let weights = batch_multiplicative_inverse(
+            &points
+                .iter()
+                .enumerate()
+                .map(|(i, &p)| {
+                    points
+                        .iter()
+                        .enumerate()
+                        .filter(|&(j, _)| j != i)
+                        .map(|(_, &q)| p - q)
+                        .product()
+                })
+                .collect_vec(),
+        );
+        Self {
+            points,
+            answers,
+            weights,
+            r_comb,
+        }
+    }
+
+    /// Evaluate `DegCor(Quot(g))` at `x`, given `g(x)`.
+    fn eval(&self, x: F, g_at_x: F) -> F {
+        let inv_diffs =
+            batch_multiplicative_inverse(&self.points.iter().map(|&p| x - p).collect_vec());
+        let inv_vanishing: F = inv_diffs.iter().copied().product();
+        // The interpolant of the answers, divided by the vanishing polynomial of the points.
+        let answers_over_vanishing: F = izip!(&self.weights, &self.answers, &inv_diffs)
+            .map(|(&weight, &answer, &inv_diff)| weight * answer * inv_diff)
+            .sum();
+        (g_at_x * inv_vanishing - answers_over_vanishing) * self.degree_correction(x)
+    }
+
+    /// `sum_{j=0}^{e} (r_comb x)^j`, where `e` is the number of constraints, which raises the
+    /// degree of the quotient back to that of `g`.
+    fn degree_correction(&self, x: F) -> F {
+        let rx = self.r_comb * x;
+        let e = self.points.len();
+        if rx.is_one() {
+            F::from_canonical_usize(e + 1)
+        } else {
+            (F::one() - rx.exp_u64(e as u64 + 1)) / (F::one() - rx)
+        }
+    }
+
+    /// The coefficients of `DegCor(Quot(g))`, given those of `g`.
+    fn apply(&self, g: &[F]) -> Vec<F> {
+        // Subtract the interpolant of the answers, in the Lagrange basis `w_i V(X) / (X - p_i)`.
+        let vanishing = binomial_expand(&self.points);
+        let mut quotient = g.to_vec();
+        quotient.resize(g.len().max(self.points.len()), F::zero());
+        for (&point, &answer, &weight) in izip!(&self.points, &self.answers, &self.weights) {
+            let (basis, _) = divide_by_linear(&vanishing, point);
+            for (q, b) in izip!(quotient.iter_mut(), basis) {
+                *q -= weight * answer * b;
+            }
+        }
+        for &point in &self.points {
+            let (q, remainder) = divide_by_linear(&quotient, point);
+            quotient = q;
+            debug_assert!(remainder.is_zero(), "answers don't match g");
+        }
+
+        // Multiply by the degree correction `sum_{j=0}^{e} r^j X^j`, using that each coefficient
+        // of the product is a sliding window sum of the quotient's.
+        let e = self.points.len();
+        let r_pow = self.r_comb.exp_u64(e as u64 + 1);
+        let mut corrected = Vec::with_capacity(g.len());
+        let mut acc = F::zero();
+        for m in 0..quotient.len() + e {
+            acc *= self.r_comb;
+            if let Some(&q) = quotient.get(m) {
+                acc += q;
+            }
+            if m > e {
+                acc -= r_pow * quotient[m - e - 1];
+            }
+            corrected.push(acc);
+        }
+        debug_assert!(corrected.iter().skip(g.len()).all(|c| c.is_zero()));
+        corrected.resize(g.len(), F::zero());
+        corrected
+    }
+}
+
+/// Divide `p` by `X - a`, returning the quotient and remainder.
+fn divide_by_linear<F: Field>(p: &[F], a: F) -> (Vec<F>, F) {
+    let Some((&constant, rest)) = p.split_first() else {
+        return (vec![], F::zero());
+    };
+    let mut quotient = vec![F::zero(); rest.len()];
+    let mut carry = F::zero();
+    for (q, &c) in izip!(quotient.iter_mut(), rest).rev() {
+        carry = c + carry * a;
+        *q = carry;
+    }
+    (quotient, constant + carry * a)
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+
+    type F = BinomialExtensionField<BabyBear, 4>;
+
+    #[test]
+    fn fold_fiber_matches_fold_coeffs() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let (log_size, log_arity) = (6, 2);
+        let poly: Vec<F> = (0..32).map(|_| rng.gen()).collect();
+        let shift = next_shift(F::one(), 1);
+        let r: F = rng.gen();
+
+        let mut evals = poly.clone();
+        evals.resize(1 << log_size, F::zero());
+        let mut evals = Radix2Bowers.coset_dft(evals, shift);
+        reverse_slice_index_bits(&mut evals);
+
+        let folded = fold_coeffs(&poly, log_arity, r);
+        for (row_index, row) in evals.chunks_exact(1 << log_arity).enumerate() {
+            let x0 = fiber_start(log_size, shift, log_arity, row_index);
+            assert_eq!(
+                fold_fiber(row.to_vec(), x0, r),
+                eval_poly(&folded, x0.exp_power_of_2(log_arity))
+            );
+        }
+    }
+
+    #[test]
+    fn quotient_coeffs_match_evals() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let g: Vec<F> = (0..16).map(|_| rng.gen()).collect();
+        // Include a repeated point, as repeated queries would give.
+        let points: Vec<F> = (0..5).map(|_| rng.gen()).collect();
+        let quotient = Quotient::new(
+            points
+                .iter()
+                .chain(&points[..1])
+                .map(|&p| (p, eval_poly(&g, p))),
+            rng.gen(),
+        );
+
+        let coeffs = quotient.apply(&g);
+        assert_eq!(coeffs.len(), g.len());
+        for _ in 0..10 {
+            let x: F = rng.gen();
+            assert_eq!(eval_poly(&coeffs, x), quotient.eval(x, eval_poly(&g, x)));
+        }
+    }
+
+    #[test]
+    fn later_rounds_need_fewer_queries() {
+        let config = FriConfig {
+            log_blowup: 1,
+            num_queries: 100,
+            proof_of_work_bits: 0,
+            max_log_arity: 3,
+            log_final_poly_len: 0,
+            ldt: LowDegreeTest::Stir { num_ood_samples: 1 },
+            prune_openings: false,
+            mmcs: (),
+        };
+        let log_arities = log_arities(&config, 20);
+        assert_eq!(
+            log_arities,
+            vec![3; 19 / 3].into_iter().chain([1]).collect_vec()
+        );
+        let num_queries = (0..log_arities.len())
+            .map(|round| num_queries(&config, &log_arities, round))
+            .collect_vec();
+        assert_eq!(num_queries[0], 100);
+        assert!(num_queries.iter().tuple_windows().all(|(l, r)| l > r));
+    }
+}
@@ -1,35 +1,63 @@
+use alloc::borrow::Cow;
 use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::cell::RefCell;
 use core::fmt::Debug;
 use core::marker::PhantomData;
 
 use itertools::{izip, Itertools};
-use p3_challenger::{CanObserve, CanSample, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
-use p3_dft::TwoAdicSubgroupDft;
+use p3_challenger::{CanObserve, CanSample, FieldChallenger, GrindingChallenger};
+use p3_commit::{
+    Mmcs, OpenedValues, OpenedValuesForPoint, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset,
+};
+use p3_dft::{Radix2Bowers, TwoAdicSubgroupDft};
 use p3_field::{
-    batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, dot_product, ExtensionField,
-    Field, TwoAdicField,
+    batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, dot_product, eval_poly,
+    ExtensionField, Field, TwoAdicField,
+};
+use p3_interpolation::{
+    interpolate_coset, interpolate_coset_rotations, interpolate_coset_with_precomputation,
 };
-use p3_interpolation::interpolate_coset;
 use p3_matrix::bitrev::{BitReversableMatrix, BitReversalPerm};
-use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
 use p3_util::linear_map::LinearMap;
-use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits, VecExt};
+use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
+use rand::distributions::{Distribution, Standard};
+use rand::RngCore;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tracing::{info_span, instrument};
 
+use crate::backend::BackendFriGenericConfig;
+use crate::encoding::{self, ProofEncodingError};
+use crate::hiding::{blinded_domain, distinct_points, Hiding};
+use crate::inverse_denominator_cache::InverseDenominatorCache;
+use crate::stir::{self, StirProof};
 use crate::verifier::{self, FriError};
-use crate::{prover, FriConfig, FriGenericConfig, FriProof};
+use crate::{
+    prover, CpuBackend, FriConfig, FriGenericConfig, FriProof, LowDegreeTest, ProofStats,
+    TwoAdicFriBackend,
+};
+
+/// The number of rows, in log, reduced at a time by each thread when opening.
+const LOG_REDUCE_CHUNK_HEIGHT: usize = 12;
 
 #[derive(Debug)]
-pub struct TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
+pub struct TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, Backend = CpuBackend> {
     dft: Dft,
-    mmcs: InputMmcs,
-    fri: FriConfig<FriMmcs>,
+    pub(crate) mmcs: InputMmcs,
+    pub(crate) fri: FriConfig<FriMmcs>,
+    /// The coset `Val::generator() * H` of size `2^log_max_height`, in bit-reversed order, if
+    /// precomputed by `with_cache`. Smaller cosets are prefixes of it.
+    cache: Vec<Val>,
+    /// The `1 / (x - z)` tables of recent openings, if kept by `with_inverse_denominator_cache`.
+    inverse_denominator_cache: Option<InverseDenominatorCache>,
+    hiding: Option<Hiding<Val>>,
+    backend: Backend,
     _phantom: PhantomData<Val>,
 }
 
@@ -39,159 +67,289 @@ impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
             dft,
             mmcs,
             fri,
+            cache: Vec::new(),
+            inverse_denominator_cache: None,
+            hiding: None,
+            backend: CpuBackend,
             _phantom: PhantomData,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(bound = "")]
-pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
-    pub opened_values: Vec<Vec<Val>>,
-    pub opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
-}
-
-pub struct TwoAdicFriGenericConfig<InputProof, InputError>(
-    pub PhantomData<(InputProof, InputError)>,
-);
-
-pub type TwoAdicFriGenericConfigForMmcs<F, M> =
-    TwoAdicFriGenericConfig<Vec<BatchOpening<F, M>>, <M as Mmcs<F>>::Error>;
+impl<Val, Dft, InputMmcs, FriMmcs, Backend> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, Backend> {
+    /// Run the folding and quotient accumulation of `open` on `backend` instead.
+    pub fn with_backend<B>(self, backend: B) -> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, B> {
+        TwoAdicFriPcs {
+            dft: self.dft,
+            mmcs: self.mmcs,
+            fri: self.fri,
+            cache: self.cache,
+            inverse_denominator_cache: self.inverse_denominator_cache,
+            hiding: self.hiding,
+            backend,
+            _phantom: PhantomData,
+        }
+    }
 
-impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
-    for TwoAdicFriGenericConfig<InputProof, InputError>
-{
-    type InputProof = InputProof;
-    type InputError = InputError;
+    /// Precompute the coset `Val::generator() * H`, in bit-reversed order, for LDEs of height up
+    /// to `2^log_max_height`, which `open` otherwise recomputes on every call.
+    ///
+    /// The coset is only read once built, so the PCS can still be shared between threads. To also
+    /// keep the `1 / (x - z)` tables, see `with_inverse_denominator_cache`.
+    pub fn with_cache(mut self, log_max_height: usize) -> Self
+    where
+        Val: TwoAdicField,
+    {
+        self.cache = bit_reversed_coset(log_max_height);
+        self
+    }
 
-    fn extra_query_index_bits(&self) -> usize {
-        0
+    /// Keep the `1 / (x - z)` tables computed when opening, keyed by log height and point `z`, so
+    /// that a later opening at the same point, e.g. of another round of a multi-round protocol,
+    /// reuses them rather than redoing the batch inversion. At most `capacity` tables are kept,
+    /// evicting the oldest first.
+    ///
+    /// Each table is as tall as the tallest LDE opened at its point, so this trades an extension
+    /// field column of memory per table for the inversions. With the cache, tables are computed
+    /// for every opening point, rather than only for points opened on more than one matrix.
+    pub fn with_inverse_denominator_cache(mut self, capacity: usize) -> Self {
+        self.inverse_denominator_cache = Some(InverseDenominatorCache::new(capacity));
+        self
     }
 
-    fn fold_row(
-        &self,
-        index: usize,
-        log_height: usize,
-        beta: F,
-        evals: impl Iterator<Item = F>,
-    ) -> F {
-        let arity = 2;
-        let log_arity = 1;
-        let (e0, e1) = evals
-            .collect_tuple()
-            .expect("TwoAdicFriFolder only supports arity=2");
-        // If performance critical, make this API stateful to avoid this
-        // This is a bit more math than is necessary, but leaving it here
-        // in case we want higher arity in the future
-        let subgroup_start = F::two_adic_generator(log_height + log_arity)
-            .exp_u64(reverse_bits_len(index, log_height) as u64);
-        let mut xs = F::two_adic_generator(log_arity)
-            .shifted_powers(subgroup_start)
-            .take(arity)
-            .collect_vec();
-        reverse_slice_index_bits(&mut xs);
-        assert_eq!(log_arity, 1, "can only interpolate two points for now");
-        // interpolate and evaluate at beta
-        e0 + (beta - xs[0]) * (e1 - e0) / (xs[1] - xs[0])
+    /// The number of `1 / (x - z)` tables taken from the cache set by
+    /// `with_inverse_denominator_cache`, rather than computed, over all openings so far.
+    pub fn inverse_denominator_cache_hits(&self) -> usize {
+        self.inverse_denominator_cache
+            .as_ref()
+            .map_or(0, InverseDenominatorCache::hits)
     }
 
-    fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F> {
-        // We use the fact that
-        //     p_e(x^2) = (p(x) + p(-x)) / 2
-        //     p_o(x^2) = (p(x) - p(-x)) / (2 x)
-        // that is,
-        //     p_e(g^(2i)) = (p(g^i) + p(g^(n/2 + i))) / 2
-        //     p_o(g^(2i)) = (p(g^i) - p(g^(n/2 + i))) / (2 g^i)
-        // so
-        //     result(g^(2i)) = p_e(g^(2i)) + beta p_o(g^(2i))
-        //                    = (1/2 + beta/2 g_inv^i) p(g^i)
-        //                    + (1/2 - beta/2 g_inv^i) p(g^(n/2 + i))
-        let g_inv = F::two_adic_generator(log2_strict_usize(m.height()) + 1).inverse();
-        let one_half = F::two().inverse();
-        let half_beta = beta * one_half;
-
-        // TODO: vectorize this (after we have packed extension fields)
-
-        // beta/2 times successive powers of g_inv
-        let mut powers = g_inv
-            .shifted_powers(half_beta)
-            .take(m.height())
-            .collect_vec();
-        reverse_slice_index_bits(&mut powers);
+    /// The coset `Val::generator() * H` of size `2^log_height`, in bit-reversed order.
+    fn coset(&self, log_height: usize) -> Cow<'_, [Val]>
+    where
+        Val: TwoAdicField,
+    {
+        match self.cache.get(..1 << log_height) {
+            Some(coset) => Cow::Borrowed(coset),
+            None => Cow::Owned(bit_reversed_coset(log_height)),
+        }
+    }
 
-        m.par_rows()
-            .zip(powers)
-            .map(|(mut row, power)| {
-                let (lo, hi) = row.next_tuple().unwrap();
-                (one_half + power) * lo + (one_half - power) * hi
-            })
-            .collect()
+    /// Make commitments and openings hiding, revealing nothing about the committed polynomials
+    /// beyond the claimed evaluations. Prover and verifier must agree on this.
+    ///
+    /// Each committed polynomial `p` over a domain `H` is blinded into `p + Z_H * r`, for a random
+    /// `r` of degree less than `|H|`. This agrees with `p` on `H`, but has twice the degree, so its
+    /// LDE is twice as tall. In addition, `num_random_codewords` random codewords are committed
+    /// before the batch combination challenge is sampled and opened alongside everything else,
+    /// which blinds the polynomial tested by the low-degree test.
+    ///
+    /// The randomness is drawn from a secret seed, itself drawn from `rng`; the verifier's is
+    /// never used. For the Merkle openings themselves to be hiding, the input MMCS and the FRI
+    /// MMCS should salt their leaves, e.g. by wrapping them in a `SaltedMmcs`.
+    pub fn with_hiding<R: RngCore>(mut self, num_random_codewords: usize, rng: &mut R) -> Self
+    where
+        Val: TwoAdicField,
+        Standard: Distribution<Val>,
+    {
+        self.hiding = Some(Hiding::new(num_random_codewords, rng));
+        self
     }
 }
 
-impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
-    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+impl<Val, Dft, InputMmcs, FriMmcs, Backend> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, Backend>
 where
     Val: TwoAdicField,
-    Dft: TwoAdicSubgroupDft<Val>,
-    InputMmcs: Mmcs<Val>,
-    FriMmcs: Mmcs<Challenge>,
-    Challenge: TwoAdicField + ExtensionField<Val>,
-    Challenger:
-        CanObserve<FriMmcs::Commitment> + CanSample<Challenge> + GrindingChallenger<Witness = Val>,
+    InputMmcs: Mmcs<Val> + Sync,
+    InputMmcs::Commitment: Sync,
+    InputMmcs::Proof: Sync,
+    InputMmcs::Error: Send,
 {
-    type Domain = TwoAdicMultiplicativeCoset<Val>;
-    type Commitment = InputMmcs::Commitment;
-    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
-    type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
-    type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
+    /// Check the input MMCS openings of every query against the round commitments.
+    ///
+    /// Each round's openings are first checked together, with `verify_batches`, which can share
+    /// the work of overlapping Merkle paths. Only if that fails are the queries checked one by
+    /// one, concurrently with the `parallel` feature enabled, to find the lowest-indexed failing
+    /// query, whose error is reported, so the outcome doesn't depend on the number of threads.
+    ///
+    /// A query repeating an earlier one's index and opened values is skipped, since its values are
+    /// already known to be committed.
+    #[instrument(name = "verify input openings", skip_all)]
+    #[allow(clippy::type_complexity)]
+    fn verify_input_openings<Opening: Sync>(
+        &self,
+        rounds: &[(
+            InputMmcs::Commitment,
+            Vec<(TwoAdicMultiplicativeCoset<Val>, Opening)>,
+        )],
+        log_global_max_height: usize,
+        queries: &[(usize, &Vec<BatchOpening<Val, InputMmcs>>)],
+    ) -> Result<(), (Option<usize>, BatchOpeningError<InputMmcs::Error>)> {
+        let mmcs = &self.mmcs;
+        let log_blowup = self.fri.log_blowup;
+        // A query opening the same values at the same index as the first query at that index
+        // needn't be verified again.
+        let mut first_query_at_index = BTreeMap::new();
+        let is_repeated = queries
+            .iter()
+            .enumerate()
+            .map(|(query, &(index, input_proof))| {
+                let first = *first_query_at_index.entry(index).or_insert(query);
+                first != query
+                    && izip!(queries[first].1, input_proof)
+                        .all(|(first, opening)| first.opened_values == opening.opened_values)
+            })
+            .collect_vec();
+        let batch_log_max_heights = rounds
+            .iter()
+            .map(|(_, mats)| {
+                let batch_max_height = mats
+                    .iter()
+                    .map(|(domain, _)| domain.size() << log_blowup)
+                    .max()
+                    .expect("Empty batch?");
+                log2_strict_usize(batch_max_height)
+            })
+            .collect_vec();
 
-    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
-        let log_n = log2_strict_usize(degree);
-        TwoAdicMultiplicativeCoset {
-            log_n,
-            shift: Val::one(),
+        // For each round, every query's expanded opening proof.
+        let expanded_proofs = if self.fri.prune_openings {
+            let expanded = izip!(0.., &batch_log_max_heights)
+                .map(|(round, &log_batch_max_height)| {
+                    let bits_reduced = log_global_max_height - log_batch_max_height;
+                    let (indices, proofs): (Vec<_>, Vec<_>) = queries
+                        .iter()
+                        .map(|&(index, input_proof)| {
+                            let proof = input_proof[round].opening_proof.clone();
+                            (index >> bits_reduced, proof)
+                        })
+                        .unzip();
+                    mmcs.expand_proofs(&indices, proofs).map_err(|error| {
+                        (
+                            None,
+                            BatchOpeningError {
+                                batch: round,
+                                error,
+                            },
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Some(expanded)
+        } else {
+            None
+        };
+        let batch_dims = |mats: &[(TwoAdicMultiplicativeCoset<Val>, Opening)]| {
+            mats.iter()
+                // TODO: MMCS doesn't really need width; we put 0 for now.
+                .map(|(domain, _)| Dimensions {
+                    width: 0,
+                    height: domain.size() << log_blowup,
+                })
+                .collect_vec()
+        };
+
+        let all_rounds_verify = izip!(0.., rounds, &batch_log_max_heights).all(
+            |(round, (batch_commit, mats), &log_batch_max_height)| {
+                let bits_reduced = log_global_max_height - log_batch_max_height;
+                let openings = (0..queries.len())
+                    .filter(|&query| !is_repeated[query])
+                    .map(|query| {
+                        let (index, input_proof) = queries[query];
+                        let batch_opening = input_proof.get(round)?;
+                        let opening_proof = expanded_proofs
+                            .as_ref()
+                            .map_or(&batch_opening.opening_proof, |proofs| &proofs[round][query]);
+                        Some((
+                            index >> bits_reduced,
+                            batch_opening.opened_values.as_slice(),
+                            opening_proof,
+                        ))
+                    })
+                    .collect::<Option<Vec<_>>>();
+                openings.is_some_and(|openings| {
+                    mmcs.verify_batches(batch_commit, &batch_dims(mats), &openings)
+                        .is_ok()
+                })
+            },
+        );
+        if all_rounds_verify {
+            return Ok(());
         }
-    }
 
-    fn commit(
-        &self,
-        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
-    ) -> (Self::Commitment, Self::ProverData) {
-        let ldes: Vec<_> = evaluations
-            .into_iter()
-            .map(|(domain, evals)| {
-                assert_eq!(domain.size(), evals.height());
-                let shift = Val::generator() / domain.shift;
-                // Commit to the bit-reversed LDE.
-                self.dft
-                    .coset_lde_batch(evals, self.fri.log_blowup, shift)
-                    .bit_reverse_rows()
-                    .to_row_major_matrix()
+        let results: Vec<Result<(), _>> = queries
+            .par_iter()
+            .enumerate()
+            .map(|(query, &(index, input_proof))| {
+                if is_repeated[query] {
+                    return Ok(());
+                }
+                for (round, (batch_opening, (batch_commit, mats), &log_batch_max_height)) in
+                    izip!(input_proof, rounds, &batch_log_max_heights).enumerate()
+                {
+                    let bits_reduced = log_global_max_height - log_batch_max_height;
+                    let reduced_index = index >> bits_reduced;
+
+                    let opening_proof = expanded_proofs
+                        .as_ref()
+                        .map_or(&batch_opening.opening_proof, |proofs| &proofs[round][query]);
+                    mmcs.verify_batch(
+                        batch_commit,
+                        &batch_dims(mats),
+                        reduced_index,
+                        &batch_opening.opened_values,
+                        opening_proof,
+                    )
+                    .map_err(|error| {
+                        (
+                            Some(query),
+                            BatchOpeningError {
+                                batch: round,
+                                error,
+                            },
+                        )
+                    })?;
+                }
+                Ok(())
             })
             .collect();
-
-        self.mmcs.commit(ldes)
+        results.into_iter().collect()
     }
 
-    fn get_evaluations_on_domain<'a>(
+    /// Prune the input opening proofs of all queries, given the queries' indices, round by round.
+    fn prune_input_openings(
         &self,
-        prover_data: &'a Self::ProverData,
-        idx: usize,
-        domain: Self::Domain,
-    ) -> impl Matrix<Val> + 'a {
-        // todo: handle extrapolation for LDEs we don't have
-        assert_eq!(domain.shift, Val::generator());
-        let lde = self.mmcs.get_matrices(prover_data)[idx];
-        assert!(lde.height() >= domain.size());
-        lde.split_rows(domain.size()).0.bit_reverse_rows()
+        batch_log_max_heights: &[usize],
+        log_global_max_height: usize,
+        indices: &[usize],
+        mut input_proofs: Vec<&mut Vec<BatchOpening<Val, InputMmcs>>>,
+    ) {
+        for (round, &log_batch_max_height) in batch_log_max_heights.iter().enumerate() {
+            let bits_reduced = log_global_max_height - log_batch_max_height;
+            let reduced_indices = indices
+                .iter()
+                .map(|&index| index >> bits_reduced)
+                .collect_vec();
+            let proofs = input_proofs
+                .iter()
+                .map(|input_proof| input_proof[round].opening_proof.clone())
+                .collect();
+            let pruned = self.mmcs.prune_proofs(&reduced_indices, proofs);
+            for (input_proof, proof) in izip!(&mut input_proofs, pruned) {
+                input_proof[round].opening_proof = proof;
+            }
+        }
     }
 
-    fn open(
+    /// The opening argument of `open` and `open_rotations`, given how to evaluate each matrix,
+    /// from `(round, matrix index, the LDE over its original coset, its points)`, at its points.
+    #[allow(clippy::type_complexity)]
+    fn open_with<Challenge, Challenger>(
         &self,
         // For each round,
         rounds: Vec<(
-            &Self::ProverData,
+            &InputMmcs::ProverData<RowMajorMatrix<Val>>,
             // for each matrix,
             Vec<
                 // points to open
@@ -199,7 +357,33 @@ where
             >,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        interpolate: impl Fn(
+            usize,
+            usize,
+            RowMajorMatrixView<'_, Val>,
+            &[Challenge],
+        ) -> Vec<Vec<Challenge>>,
+    ) -> (
+        OpenedValues<Challenge>,
+        TwoAdicFriPcsProof<
+            Challenge,
+            FriMmcs,
+            Val,
+            Vec<BatchOpening<Val, InputMmcs>>,
+            InputMmcs::Commitment,
+        >,
+    )
+    where
+        Dft: TwoAdicSubgroupDft<Val>,
+        FriMmcs: Mmcs<Challenge>,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: CanObserve<FriMmcs::Commitment>
+            + CanObserve<InputMmcs::Commitment>
+            + CanSample<Challenge>
+            + FieldChallenger<Val>
+            + GrindingChallenger<Witness = Val>,
+        Backend: TwoAdicFriBackend<Val, Challenge>,
+    {
         /*
 
         A quick rundown of the optimizations in this function:
@@ -215,8 +399,9 @@ where
             = a^0 ( a^0 x0 + a^1 x1 ) + a^2 ( a^0 x2 + a^1 x3 ) + ...
             (see `alpha_pows`, `alpha_pow_offset`, `num_reduced`)
 
-        - For each unique point z, we precompute 1/(X-z) for the largest subgroup opened at this point.
-        Since we compute it in bit-reversed order, smaller subgroups can simply truncate the vector.
+        - For each unique point z opened on several matrices (or on any, if the tables are
+        cached), we precompute 1/(X-z) for the largest subgroup opened at this point. Since we compute it in bit-reversed order, smaller subgroups
+        can simply truncate the vector.
             (see `inv_denoms`)
 
         - Then, for each matrix (with columns p_i) and opening point z, we want:
@@ -237,8 +422,30 @@ where
 
         */
 
-        // Batch combination challenge
-        let alpha: Challenge = challenger.sample();
+        // With hiding, random codewords as tall as the tallest committed LDE are committed, and
+        // bound to the transcript, before anything is opened. They are opened as one more round,
+        // at every distinct point.
+        let random_codewords = self.hiding.as_ref().map(|hiding| {
+            let max_height = rounds
+                .iter()
+                .map(|(data, _)| self.mmcs.get_max_height(data))
+                .max()
+                .unwrap();
+            let domain = TwoAdicMultiplicativeCoset {
+                log_n: log2_strict_usize(max_height) - self.fri.log_blowup,
+                shift: Val::one(),
+            };
+            let evals = hiding.random_matrix(domain.size(), hiding.num_random_codewords);
+            let (commit, data) = self.commit_ldes(self.bit_reversed_ldes(vec![(domain, evals)]));
+            challenger.observe(commit.clone());
+            let points = distinct_points(rounds.iter().flat_map(|(_, points)| points).flatten());
+            (commit, data, points)
+        });
+        let num_rounds = rounds.len();
+        let mut rounds = rounds;
+        if let Some((_, data, points)) = &random_codewords {
+            rounds.push((data, vec![points.clone()]));
+        }
 
         let mats_and_points = rounds
             .iter()
@@ -261,66 +468,125 @@ where
         let global_max_height = mats.iter().map(|m| m.height()).max().unwrap();
         let log_global_max_height = log2_strict_usize(global_max_height);
 
-        // For each unique opening point z, we will find the largest degree bound
-        // for that point, and precompute 1/(X - z) for the largest subgroup (in bitrev order).
-        let inv_denoms = compute_inverse_denominators(&mats_and_points, Val::generator());
+        // For each point z opened on more than one matrix, we will find the largest degree bound
+        // for that point, and precompute 1/(X - z) for the largest subgroup (in bitrev order),
+        // which the matrices opened there share. Precomputing them for a point opened on a single
+        // matrix would save no inversions, unless they're cached for later openings, so those are
+        // otherwise computed one chunk at a time, as the rows are reduced.
+        let cache = self.inverse_denominator_cache.as_ref();
+        let shared_points = max_log_height_for_shared_point(&mats_and_points, cache.is_some());
+        let coset = shared_points
+            .values()
+            .max()
+            .map(|&log_height| self.coset(log_height));
+        let inv_denoms = coset
+            .as_ref()
+            .map(|coset| compute_inverse_denominators(shared_points, coset, cache));
 
-        let mut all_opened_values: OpenedValues<Challenge> = vec![];
+        // Matrices opened only at such points reuse prefixes of the inverses for interpolation,
+        // along with prefixes of the coset, rather than inverting their own. A prefix of the
+        // bit-reversed coset `g * H` is the bit-reversed coset `g * K` of a subgroup `K` of `H`.
+        let precomputed = |points_for_mat: &[Challenge]| {
+            let inv_denoms = inv_denoms.as_ref()?;
+            let xs = coset.as_deref()?;
+            points_for_mat
+                .iter()
+                .map(|z| inv_denoms.get(z).map(|inv_denoms| (*z, inv_denoms)))
+                .collect::<Option<Vec<_>>>()
+                .map(|inv_denoms| (xs, inv_denoms))
+        };
 
-        let mut reduced_openings: [_; 32] = core::array::from_fn(|_| None);
-        let mut num_reduced = [0; 32];
+        // Evaluate each matrix at each of its points, from the LDE over its original coset.
+        let all_opened_values: OpenedValues<Challenge> =
+            info_span!("compute opened values with Lagrange interpolation").in_scope(|| {
+                mats_and_points
+                    .iter()
+                    .enumerate()
+                    .map(|(round, (mats, points))| {
+                        izip!(mats, *points)
+                            .enumerate()
+                            .map(|(mat_index, (mat, points_for_mat))| {
+                                let low_height = mat.height() >> self.fri.log_blowup;
+                                let (low_coset, _) = mat.split_rows(low_height);
+                                let values = match precomputed(points_for_mat) {
+                                    Some((xs, inv_denoms)) => inv_denoms
+                                        .into_iter()
+                                        .map(|(z, inv_denoms)| {
+                                            interpolate_coset_with_precomputation(
+                                                &low_coset,
+                                                Val::generator(),
+                                                z,
+                                                &xs[..low_height],
+                                                &inv_denoms[..low_height],
+                                            )
+                                        })
+                                        .collect(),
+                                    _ if round < num_rounds => {
+                                        interpolate(round, mat_index, low_coset, points_for_mat)
+                                    }
+                                    _ => interpolate_bit_reversed(low_coset, points_for_mat),
+                                };
+                                values.into_iter().map(OpenedValuesForPoint::from).collect()
+                            })
+                            .collect()
+                    })
+                    .collect()
+            });
 
-        for (mats, points) in mats_and_points {
-            let opened_values_for_round = all_opened_values.pushed_mut(vec![]);
-            for (mat, points_for_mat) in izip!(mats, points) {
+        // Bind the claimed evaluations into the transcript before they are batched.
+        observe_opened_values(challenger, all_opened_values.iter().flatten().flatten());
+
+        // Batch combination challenge
+        let alpha: Challenge = challenger.sample();
+
+        // For each log height, the quotients to accumulate into its reduced opening.
+        let mut quotients_by_log_height: BTreeMap<usize, Vec<_>> = BTreeMap::new();
+        let mut num_reduced = BTreeMap::<usize, usize>::new();
+
+        for ((mats, points), opened_values_for_round) in izip!(mats_and_points, &all_opened_values)
+        {
+            for (mat, points_for_mat, opened_values_for_mat) in
+                izip!(mats, points, opened_values_for_round)
+            {
                 let log_height = log2_strict_usize(mat.height());
-                let reduced_opening_for_log_height = reduced_openings[log_height]
-                    .get_or_insert_with(|| vec![Challenge::zero(); mat.height()]);
-                debug_assert_eq!(reduced_opening_for_log_height.len(), mat.height());
-
-                let opened_values_for_mat = opened_values_for_round.pushed_mut(vec![]);
-                for &point in points_for_mat {
-                    let _guard =
-                        info_span!("reduce matrix quotient", dims = %mat.dimensions()).entered();
-
-                    // Use Barycentric interpolation to evaluate the matrix at the given point.
-                    let ys = info_span!("compute opened values with Lagrange interpolation")
-                        .in_scope(|| {
-                            let (low_coset, _) =
-                                mat.split_rows(mat.height() >> self.fri.log_blowup);
-                            interpolate_coset(
-                                &BitReversalPerm::new_view(low_coset),
-                                Val::generator(),
-                                point,
-                            )
-                        });
-
-                    let alpha_pow_offset = alpha.exp_u64(num_reduced[log_height] as u64);
-                    let reduced_ys: Challenge = dot_product(alpha.powers(), ys.iter().copied());
-
-                    info_span!("reduce rows").in_scope(|| {
-                        mat.dot_ext_powers(alpha)
-                            .zip(reduced_opening_for_log_height.par_iter_mut())
-                            // This might be longer, but zip will truncate to smaller subgroup
-                            // (which is ok because it's bitrev)
-                            .zip(inv_denoms.get(&point).unwrap().par_iter())
-                            .for_each(|((reduced_row, ro), &inv_denom)| {
-                                *ro += alpha_pow_offset * (reduced_row - reduced_ys) * inv_denom
-                            })
-                    });
+                // Shared by all the points the matrix is opened at.
+                let alpha_pows = alpha.powers().take(mat.width()).collect_vec();
 
-                    num_reduced[log_height] += mat.width();
-                    opened_values_for_mat.push(ys);
+                for (&point, ys) in izip!(points_for_mat, opened_values_for_mat) {
+                    let num_reduced_for_log_height = num_reduced.entry(log_height).or_default();
+                    let alpha_pow_offset = alpha.exp_u64(*num_reduced_for_log_height as u64);
+                    let reduced_ys: Challenge =
+                        dot_product(alpha_pows.iter().copied(), ys.iter().copied());
+                    quotients_by_log_height
+                        .entry(log_height)
+                        .or_default()
+                        .push((mat, point, alpha_pow_offset, reduced_ys));
+                    *num_reduced_for_log_height += mat.width();
                 }
             }
         }
 
-        let fri_input = reduced_openings.into_iter().rev().flatten().collect_vec();
-
-        let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
-            TwoAdicFriGenericConfig(PhantomData);
+        // Reduced openings, descending by log height.
+        let fri_input = quotients_by_log_height
+            .iter()
+            .rev()
+            .map(|(&log_height, quotients)| {
+                info_span!("reduce rows", log_height).in_scope(|| {
+                    reduce_rows(
+                        &self.backend,
+                        log_height,
+                        quotients,
+                        alpha,
+                        inv_denoms.as_ref(),
+                        LOG_REDUCE_CHUNK_HEIGHT,
+                    )
+                })
+            })
+            .collect_vec();
 
-        let fri_proof = prover::prove(&g, &self.fri, fri_input, challenger, |index| {
+        let query_indices = RefCell::new(vec![]);
+        let open_input = |index: usize| -> Vec<BatchOpening<Val, InputMmcs>> {
+            query_indices.borrow_mut().push(index);
             rounds
                 .iter()
                 .map(|(data, _)| {
@@ -334,9 +600,570 @@ where
                     }
                 })
                 .collect()
+        };
+
+        let mut ldt_proof = match self.fri.ldt {
+            LowDegreeTest::Fri => {
+                let g: BackendFriGenericConfig<
+                    '_,
+                    Val,
+                    Backend,
+                    Vec<BatchOpening<Val, InputMmcs>>,
+                    InputMmcs::Error,
+                > = BackendFriGenericConfig {
+                    backend: &self.backend,
+                    _phantom: PhantomData,
+                };
+                LdtProof::Fri(prover::prove(
+                    &g, &self.fri, fri_input, challenger, open_input,
+                ))
+            }
+            LowDegreeTest::Stir { .. } => {
+                LdtProof::Stir(stir::prove(&self.fri, fri_input, challenger, open_input))
+            }
+        };
+
+        if self.fri.prune_openings {
+            let batch_log_max_heights = rounds
+                .iter()
+                .map(|(data, _)| log2_strict_usize(self.mmcs.get_max_height(data)))
+                .collect_vec();
+            let input_proofs = match &mut ldt_proof {
+                LdtProof::Fri(proof) => proof
+                    .query_proofs
+                    .iter_mut()
+                    .map(|qp| &mut qp.input_proof)
+                    .collect_vec(),
+                LdtProof::Stir(proof) => proof.input_proofs.iter_mut().collect_vec(),
+            };
+            self.prune_input_openings(
+                &batch_log_max_heights,
+                log_global_max_height,
+                &query_indices.into_inner(),
+                input_proofs,
+            );
+        }
+
+        let mut all_opened_values = all_opened_values.into_vec();
+        let random_codewords = random_codewords.map(|(commit, _, _)| {
+            let values = all_opened_values.pop().unwrap().into_vec().pop().unwrap();
+            let values = values
+                .into_iter()
+                .map(OpenedValuesForPoint::into_vec)
+                .collect();
+            (commit, values)
         });
+        (
+            all_opened_values.into(),
+            TwoAdicFriPcsProof {
+                random_codewords,
+                ldt: ldt_proof,
+            },
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
+    pub opened_values: Vec<Vec<Val>>,
+    pub opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
+}
+
+/// An opening proof of a `TwoAdicFriPcs`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize, InputProof: Serialize, Commitment: Serialize",
+    deserialize = "Witness: Deserialize<'de>, InputProof: Deserialize<'de>, Commitment: Deserialize<'de>"
+))]
+pub struct TwoAdicFriPcsProof<F: Field, M: Mmcs<F>, Witness, InputProof, Commitment> {
+    /// With hiding, the commitment to the random codewords, and for each distinct opening point,
+    /// the values of the random codewords there.
+    pub random_codewords: Option<(Commitment, Vec<Vec<F>>)>,
+    pub ldt: LdtProof<F, M, Witness, InputProof>,
+}
+
+/// A proof of the low-degree test selected by a `FriConfig`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize, InputProof: Serialize",
+    deserialize = "Witness: Deserialize<'de>, InputProof: Deserialize<'de>"
+))]
+pub enum LdtProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
+    Fri(FriProof<F, M, Witness, InputProof>),
+    Stir(StirProof<F, M, Witness, InputProof>),
+}
+
+impl<F: Field, M: Mmcs<F>, Witness, InputProof, Commitment>
+    TwoAdicFriPcsProof<F, M, Witness, InputProof, Commitment>
+{
+    /// Encode the proof in the canonical byte encoding described in the `encoding` module.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProofEncodingError>
+    where
+        Self: Serialize,
+    {
+        encoding::to_bytes(self)
+    }
+
+    /// Decode a proof encoded by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofEncodingError>
+    where
+        Self: DeserializeOwned,
+    {
+        encoding::from_bytes(bytes)
+    }
+
+    /// The sizes of the components of the proof, in its canonical byte encoding. The random
+    /// codewords of a hiding proof are only counted in the total.
+    pub fn stats(&self) -> Result<ProofStats, ProofEncodingError>
+    where
+        Witness: Serialize,
+        InputProof: Serialize,
+        Commitment: Serialize,
+    {
+        let mut stats = match &self.ldt {
+            LdtProof::Fri(proof) => proof.stats()?,
+            LdtProof::Stir(proof) => proof.stats()?,
+        };
+        stats.total = self.to_bytes()?.len();
+        Ok(stats)
+    }
+}
+
+/// An input opening which doesn't verify against its batch's commitment.
+#[derive(Debug)]
+pub struct BatchOpeningError<E> {
+    /// The index of the batch, i.e. of the round of commitments.
+    pub batch: usize,
+    pub error: E,
+}
+
+pub struct TwoAdicFriGenericConfig<InputProof, InputError>(
+    pub PhantomData<(InputProof, InputError)>,
+);
+
+pub type TwoAdicFriGenericConfigForMmcs<F, M> =
+    TwoAdicFriGenericConfig<Vec<BatchOpening<F, M>>, BatchOpeningError<<M as Mmcs<F>>::Error>>;
+
+impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
+    for TwoAdicFriGenericConfig<InputProof, InputError>
+{
+    type InputProof = InputProof;
+    type InputError = InputError;
+
+    fn extra_query_index_bits(&self) -> usize {
+        0
+    }
+
+    fn fold_row(
+        &self,
+        index: usize,
+        log_height: usize,
+        beta: F,
+        evals: impl Iterator<Item = F>,
+    ) -> F {
+        // Folding by 2^k with beta is the same as folding by 2 k times, with beta, beta^2, beta^4,
+        // etc. Each binary fold halves the row, until a single value is left.
+        let mut evals = evals.collect_vec();
+        let log_arity = log2_strict_usize(evals.len());
+        let mut beta = beta;
+        for i in (0..log_arity).rev() {
+            evals = evals
+                .chunks_exact(2)
+                .enumerate()
+                .map(|(j, pair)| {
+                    fold_pair((index << i) + j, log_height + i, beta, pair[0], pair[1])
+                })
+                .collect();
+            beta = beta.square();
+        }
+        evals[0]
+    }
+
+    fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F> {
+        let log_arity = log2_strict_usize(m.width());
+        if log_arity == 1 {
+            return fold_matrix_by_2(beta, m);
+        }
+        // As in `fold_row`, fold by 2 repeatedly. Since the codeword is in bit-reversed order,
+        // the pairs folded at each step are always adjacent.
+        let mut folded = m.to_row_major_matrix().values;
+        let mut beta = beta;
+        for _ in 0..log_arity {
+            folded = fold_matrix_by_2(beta, RowMajorMatrix::new(folded, 2));
+            beta = beta.square();
+        }
+        folded
+    }
+
+    fn final_poly_coeffs(&self, mut codeword: Vec<F>, log_final_poly_len: usize) -> Vec<F> {
+        // The codeword is in bit-reversed order, over the subgroup of its size.
+        reverse_slice_index_bits(&mut codeword);
+        let mut coeffs = Radix2Bowers.idft(codeword);
+        debug_assert!(
+            coeffs[1 << log_final_poly_len..]
+                .iter()
+                .all(|c| c.is_zero()),
+            "final polynomial is of too high degree"
+        );
+        coeffs.truncate(1 << log_final_poly_len);
+        coeffs
+    }
+
+    fn eval_final_poly(&self, coeffs: &[F], index: usize, log_height: usize) -> F {
+        let x =
+            F::two_adic_generator(log_height).exp_u64(reverse_bits_len(index, log_height) as u64);
+        eval_poly(coeffs, x)
+    }
+}
+
+/// Fold a single pair of sibling evaluations, where `index` is the index of the pair in the
+/// folded codeword of height `2^log_height`.
+fn fold_pair<F: TwoAdicField>(index: usize, log_height: usize, beta: F, e0: F, e1: F) -> F {
+    let arity = 2;
+    let log_arity = 1;
+    // If performance critical, make this API stateful to avoid this
+    // This is a bit more math than is necessary, but leaving it here
+    // in case we want higher arity in the future
+    let subgroup_start = F::two_adic_generator(log_height + log_arity)
+        .exp_u64(reverse_bits_len(index, log_height) as u64);
+    let mut xs = F::two_adic_generator(log_arity)
+        .shifted_powers(subgroup_start)
+        .take(arity)
+        .collect_vec();
+    reverse_slice_index_bits(&mut xs);
+    // interpolate and evaluate at beta
+    e0 + (beta - xs[0]) * (e1 - e0) / (xs[1] - xs[0])
+}
+
+fn fold_matrix_by_2<F: TwoAdicField, M: Matrix<F>>(beta: F, m: M) -> Vec<F> {
+    // We use the fact that
+    //     p_e(x^2) = (p(x) + p(-x)) / 2
+    //     p_o(x^2) = (p(x) - p(-x)) / (2 x)
+    // that is,
+    //     p_e(g^(2i)) = (p(g^i) + p(g^(n/2 + i))) / 2
+    //     p_o(g^(2i)) = (p(g^i) - p(g^(n/2 + i))) / (2 g^i)
+    // so
+    //     result(g^(2i)) = p_e(g^(2i)) + beta p_o(g^(2i))
+    //                    = (1/2 + beta/2 g_inv^i) p(g^i)
+    //                    + (1/2 - beta/2 g_inv^i) p(g^(n/2 + i))
+    let g_inv = F::two_adic_generator(log2_strict_usize(m.height()) + 1).inverse();
+    let one_half = F::two().inverse();
+    let half_beta = beta * one_half;
+
+    // TODO: vectorize this (after we have packed extension fields)
+
+    // beta/2 times successive powers of g_inv
+    let mut powers = g_inv
+        .shifted_powers(half_beta)
+        .take(m.height())
+        .collect_vec();
+    reverse_slice_index_bits(&mut powers);
+
+    m.par_rows()
+        .zip(powers)
+        .map(|(mut row, power)| {
+            let (lo, hi) = row.next_tuple().unwrap();
+            (one_half + power) * lo + (one_half - power) * hi
+        })
+        .collect()
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Backend> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, Backend>
+where
+    Val: TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+{
+    /// Commit to LDEs the caller has already computed, e.g. for evaluating constraints, skipping
+    /// the DFTs `Pcs::commit` would otherwise run.
+    ///
+    /// For each matrix, the LDE must hold the evaluations of its polynomials, whose values over
+    /// `domain` are what would be passed to `Pcs::commit`, over `Val::generator()` times the
+    /// subgroup of order `domain.size() << log_blowup`, in bit-reversed order.
+    ///
+    /// Not supported with hiding, which must blind the evaluations before their LDEs are taken.
+    pub fn commit_bit_reversed_ldes(
+        &self,
+        ldes: Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)>,
+    ) -> (
+        InputMmcs::Commitment,
+        InputMmcs::ProverData<RowMajorMatrix<Val>>,
+    ) {
+        assert!(
+            self.hiding.is_none(),
+            "precomputed LDEs can't be committed with hiding"
+        );
+        self.commit_ldes(ldes)
+    }
+
+    /// Commit to bit-reversed LDEs, as they are.
+    fn commit_ldes(
+        &self,
+        ldes: Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)>,
+    ) -> (
+        InputMmcs::Commitment,
+        InputMmcs::ProverData<RowMajorMatrix<Val>>,
+    ) {
+        let ldes = ldes
+            .into_iter()
+            .map(|(domain, lde)| {
+                assert_eq!(domain.size() << self.fri.log_blowup, lde.height());
+                lde
+            })
+            .collect();
+        self.mmcs.commit(ldes)
+    }
+
+    /// The row at `row` of the LDE of the `matrix_index`th matrix committed in `prover_data`, in
+    /// natural order, i.e. its evaluations at `g * w^row`, where `g` is `Val::generator()` and `w`
+    /// generates the LDE's subgroup. The LDE is committed in bit-reversed order; this undoes that.
+    pub fn get_lde_row(
+        &self,
+        prover_data: &InputMmcs::ProverData<RowMajorMatrix<Val>>,
+        matrix_index: usize,
+        row: usize,
+    ) -> Vec<Val> {
+        let lde = self.mmcs.get_matrices(prover_data)[matrix_index];
+        let log_height = log2_strict_usize(lde.height());
+        lde.row(reverse_bits_len(row, log_height)).collect()
+    }
+
+    /// Open the row `get_lde_row` returns, with a proof that it was committed. As with any MMCS
+    /// opening, the other matrices of the batch are opened too, at the rows paired with it.
+    pub fn open_lde_row(
+        &self,
+        prover_data: &InputMmcs::ProverData<RowMajorMatrix<Val>>,
+        matrix_index: usize,
+        row: usize,
+    ) -> BatchOpening<Val, InputMmcs> {
+        let heights = self.mmcs.get_matrix_heights(prover_data);
+        let log_max_height = log2_strict_usize(self.mmcs.get_max_height(prover_data));
+        let index = lde_row_index(log_max_height, heights[matrix_index], row);
+        let (opened_values, opening_proof) = self.mmcs.open_batch(index, prover_data);
+        BatchOpening {
+            opened_values,
+            opening_proof,
+        }
+    }
+
+    /// Check an opening made by `open_lde_row` against `commit`, whose matrices were committed
+    /// over `domains`, returning the opened row, or `None` if the opening is invalid.
+    pub fn verify_lde_row<'a>(
+        &self,
+        commit: &InputMmcs::Commitment,
+        domains: &[TwoAdicMultiplicativeCoset<Val>],
+        matrix_index: usize,
+        row: usize,
+        opening: &'a BatchOpening<Val, InputMmcs>,
+    ) -> Option<&'a [Val]> {
+        if matrix_index >= domains.len() || opening.opened_values.len() != domains.len() {
+            return None;
+        }
+        let dims = izip!(domains, &opening.opened_values)
+            .map(|(domain, values)| Dimensions {
+                width: values.len(),
+                height: domain.size() << self.fri.log_blowup,
+            })
+            .collect_vec();
+        let log_max_height = log2_strict_usize(dims.iter().map(|d| d.height).max().unwrap());
+        let index = lde_row_index(log_max_height, dims[matrix_index].height, row);
+        self.mmcs
+            .verify_batch(
+                commit,
+                &dims,
+                index,
+                &opening.opened_values,
+                &opening.opening_proof,
+            )
+            .ok()?;
+        Some(&opening.opened_values[matrix_index])
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Backend> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, Backend>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+{
+    /// Recover the values of an extension field matrix at a point from those opened from its
+    /// coefficient columns, `EF::D` for each of its columns.
+    ///
+    /// Extension field matrices, such as the auxiliary columns of permutation and lookup
+    /// arguments, are committed with `Pcs::commit` like any other, as the `Val` matrix of their
+    /// coefficients given by `RowMajorMatrix::flatten_to_base`, and opened as such.
+    pub fn reconstitute_extension_values<EF: ExtensionField<Val>>(&self, values: &[EF]) -> Vec<EF> {
+        assert_eq!(values.len() % EF::D, 0);
+        values
+            .chunks_exact(EF::D)
+            .map(|coeffs| {
+                coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &c)| EF::monomial(i) * c)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// With hiding, blind each matrix's polynomials, doubling its domain, and otherwise leave them
+    /// as they are.
+    fn blind(
+        &self,
+        evaluations: Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)>,
+    ) -> Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)> {
+        match &self.hiding {
+            Some(hiding) => evaluations
+                .into_iter()
+                .map(|(domain, evals)| hiding.blind(domain, evals))
+                .collect(),
+            None => evaluations,
+        }
+    }
+
+    /// The bit-reversed LDE of each matrix, as committed by `Pcs::commit`.
+    fn bit_reversed_ldes(
+        &self,
+        evaluations: Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)>,
+    ) -> Vec<(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)> {
+        evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                assert_eq!(domain.size(), evals.height());
+                let shift = Val::generator() / domain.shift;
+                // Commit to the bit-reversed LDE.
+                let lde = self
+                    .dft
+                    .coset_lde_batch_bit_reversed(evals, self.fri.log_blowup, shift);
+                (domain, lde)
+            })
+            .collect()
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Backend, Challenge, Challenger> Pcs<Challenge, Challenger>
+    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, Backend>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val> + Sync,
+    InputMmcs::Commitment: Sync,
+    InputMmcs::Proof: Sync,
+    InputMmcs::Error: Send,
+    FriMmcs: Mmcs<Challenge>,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger: CanObserve<FriMmcs::Commitment>
+        + CanObserve<InputMmcs::Commitment>
+        + CanSample<Challenge>
+        + FieldChallenger<Val>
+        + GrindingChallenger<Witness = Val>,
+    Backend: TwoAdicFriBackend<Val, Challenge>,
+{
+    type Domain = TwoAdicMultiplicativeCoset<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type Proof = TwoAdicFriPcsProof<
+        Challenge,
+        FriMmcs,
+        Val,
+        Vec<BatchOpening<Val, InputMmcs>>,
+        InputMmcs::Commitment,
+    >;
+    type Error = FriError<FriMmcs::Error, BatchOpeningError<InputMmcs::Error>>;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        let log_n = log2_strict_usize(degree);
+        TwoAdicMultiplicativeCoset {
+            log_n,
+            shift: Val::one(),
+        }
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        self.commit_ldes(self.bit_reversed_ldes(self.blind(evaluations)))
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> impl Matrix<Val> + 'a {
+        // todo: handle extrapolation for LDEs we don't have
+        assert_eq!(domain.shift, Val::generator());
+        let lde = self.mmcs.get_matrices(prover_data)[idx];
+        assert!(lde.height() >= domain.size());
+        lde.split_rows(domain.size()).0.bit_reverse_rows()
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        // Use Barycentric interpolation to evaluate each matrix at each of its points.
+        self.open_with(rounds, challenger, |_, _, low_coset, points| {
+            interpolate_bit_reversed(low_coset, points)
+        })
+    }
 
-        (all_opened_values, fri_proof)
+    fn open_rotations(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // rotations of zeta to open.
+                Vec<usize>,
+            )>,
+        )>,
+        zeta: Challenge,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let (rounds, domains_and_rotations): (Vec<_>, Vec<_>) = rounds
+            .into_iter()
+            .map(|(data, mats)| {
+                let points = mats
+                    .iter()
+                    .map(|(domain, rotations)| {
+                        rotations
+                            .iter()
+                            .map(|&r| zeta * domain.gen().exp_u64(r as u64))
+                            .collect_vec()
+                    })
+                    .collect_vec();
+                ((data, points), mats)
+            })
+            .unzip();
+        self.open_with(rounds, challenger, |round, mat_index, low_coset, points| {
+            let (domain, rotations) = &domains_and_rotations[round][mat_index];
+            if domain.size() == low_coset.height() {
+                // Share the barycentric weights of zeta between the rotations, which are all
+                // within the subgroup of the original coset.
+                interpolate_coset_rotations(
+                    &BitReversalPerm::new_view(low_coset),
+                    Val::generator(),
+                    zeta,
+                    rotations,
+                )
+            } else {
+                interpolate_bit_reversed(low_coset, points)
+            }
+        })
     }
 
     fn verify(
@@ -353,53 +1180,127 @@ where
                     // the point,
                     Challenge,
                     // values at the point
-                    Vec<Challenge>,
+                    OpenedValuesForPoint<Challenge>,
                 )>,
             )>,
         )>,
         proof: &Self::Proof,
         challenger: &mut Challenger,
     ) -> Result<(), Self::Error> {
+        // With hiding, every committed polynomial was blinded to twice its degree, and the random
+        // codewords are opened as one more round, at every distinct point.
+        let rounds = match (&self.hiding, &proof.random_codewords) {
+            (None, None) => rounds,
+            (Some(_), Some((random_commit, random_values))) => {
+                challenger.observe(random_commit.clone());
+                let random_points = distinct_points(
+                    rounds
+                        .iter()
+                        .flat_map(|(_, mats)| mats)
+                        .flat_map(|(_, points_and_values)| points_and_values)
+                        .map(|(point, _)| point),
+                );
+                if random_points.len() != random_values.len() {
+                    return Err(FriError::InvalidProofShape);
+                }
+                let mut rounds = rounds
+                    .into_iter()
+                    .map(|(commit, mats)| {
+                        let mats = mats
+                            .into_iter()
+                            .map(|(domain, points_and_values)| {
+                                (blinded_domain(domain), points_and_values)
+                            })
+                            .collect_vec();
+                        (commit, mats)
+                    })
+                    .collect_vec();
+                let random_domain = TwoAdicMultiplicativeCoset {
+                    log_n: rounds
+                        .iter()
+                        .flat_map(|(_, mats)| mats)
+                        .map(|(domain, _)| domain.log_n)
+                        .max()
+                        .ok_or(FriError::InvalidProofShape)?,
+                    shift: Val::one(),
+                };
+                let random_points_and_values = izip!(
+                    random_points,
+                    random_values
+                        .iter()
+                        .cloned()
+                        .map(OpenedValuesForPoint::from)
+                )
+                .collect();
+                rounds.push((
+                    random_commit.clone(),
+                    vec![(random_domain, random_points_and_values)],
+                ));
+                rounds
+            }
+            _ => return Err(FriError::InvalidProofShape),
+        };
+        let proof = &proof.ldt;
+
+        observe_opened_values(
+            challenger,
+            rounds
+                .iter()
+                .flat_map(|(_, mats)| mats)
+                .flat_map(|(_, points_and_values)| points_and_values)
+                .map(|(_, values)| values),
+        );
+
         // Batch combination challenge
         let alpha: Challenge = challenger.sample();
 
-        let log_global_max_height = proof.commit_phase_commits.len() + self.fri.log_blowup;
-
-        let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
-            TwoAdicFriGenericConfig(PhantomData);
+        // Every query must open each round's batch with one row per matrix, of the claimed width.
+        let log_global_max_height = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(domain, _)| domain.log_n + self.fri.log_blowup)
+            .max()
+            .ok_or(FriError::InvalidProofShape)?;
+        let input_proofs = match proof {
+            LdtProof::Fri(proof) => proof
+                .query_proofs
+                .iter()
+                .map(|qp| &qp.input_proof)
+                .collect_vec(),
+            LdtProof::Stir(proof) => proof.input_proofs.iter().collect_vec(),
+        };
+        let well_shaped = input_proofs.iter().all(|input_proof| {
+            input_proof.len() == rounds.len()
+                && izip!(*input_proof, &rounds).all(|(batch_opening, (_, mats))| {
+                    !mats.is_empty()
+                        && batch_opening.opened_values.len() == mats.len()
+                        && izip!(&batch_opening.opened_values, mats).all(
+                            |(mat_opening, (_, mat_points_and_values))| {
+                                mat_points_and_values
+                                    .iter()
+                                    .all(|(_, ps_at_z)| ps_at_z.len() == mat_opening.len())
+                            },
+                        )
+                })
+        });
+        if !well_shaped {
+            return Err(FriError::InvalidProofShape);
+        }
 
-        verifier::verify(&g, &self.fri, proof, challenger, |index, input_proof| {
+        let check_inputs = |queries: &[(usize, &Vec<BatchOpening<Val, InputMmcs>>)]| {
+            self.verify_input_openings(&rounds, log_global_max_height, queries)
+        };
+        let open_input = |index: usize,
+                          input_proof: &Vec<BatchOpening<Val, InputMmcs>>|
+         -> Result<Vec<(usize, Challenge)>, _> {
             // TODO: separate this out into functions
 
-            // log_height -> (alpha_pow, reduced_opening)
-            let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
-
-            for (batch_opening, (batch_commit, mats)) in izip!(input_proof, &rounds) {
-                let batch_heights = mats
-                    .iter()
-                    .map(|(domain, _)| domain.size() << self.fri.log_blowup)
-                    .collect_vec();
-                let batch_dims = batch_heights
-                    .iter()
-                    // TODO: MMCS doesn't really need width; we put 0 for now.
-                    .map(|&height| Dimensions { width: 0, height })
-                    .collect_vec();
+            let mats = rounds.iter().flat_map(|(_, mats)| mats);
 
-                let batch_max_height = batch_heights.iter().max().expect("Empty batch?");
-                let log_batch_max_height = log2_strict_usize(*batch_max_height);
-                let bits_reduced = log_global_max_height - log_batch_max_height;
-                let reduced_index = index >> bits_reduced;
-
-                self.mmcs.verify_batch(
-                    batch_commit,
-                    &batch_dims,
-                    reduced_index,
-                    &batch_opening.opened_values,
-                    &batch_opening.opening_proof,
-                )?;
-                for (mat_opening, (mat_domain, mat_points_and_values)) in
-                    izip!(&batch_opening.opened_values, mats)
-                {
+            // For each matrix, its log height and the queried point x in its domain.
+            let log_heights_and_xs = mats
+                .clone()
+                .map(|(mat_domain, _)| {
                     let log_height = log2_strict_usize(mat_domain.size()) + self.fri.log_blowup;
 
                     let bits_reduced = log_global_max_height - log_height;
@@ -409,14 +1310,36 @@ where
 
                     let x = Val::generator()
                         * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
+                    (log_height, x)
+                })
+                .collect_vec();
+
+            // 1 / (x - z) for each matrix and each of its points, inverted in one batch.
+            let denoms = izip!(mats, &log_heights_and_xs)
+                .flat_map(|((_, mat_points_and_values), &(_, x))| {
+                    mat_points_and_values.iter().map(move |(z, _)| -*z + x)
+                })
+                .collect_vec();
+            let mut inv_denoms = batch_multiplicative_inverse(&denoms).into_iter();
+            let mut log_heights_and_xs = log_heights_and_xs.into_iter();
+
+            // log_height -> (alpha_pow, reduced_opening)
+            let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
+
+            for (batch_opening, (_, mats)) in izip!(input_proof, &rounds) {
+                for (mat_opening, (_, mat_points_and_values)) in
+                    izip!(&batch_opening.opened_values, mats)
+                {
+                    let (log_height, _) = log_heights_and_xs.next().unwrap();
 
                     let (alpha_pow, ro) = reduced_openings
                         .entry(log_height)
                         .or_insert((Challenge::one(), Challenge::zero()));
 
-                    for (z, ps_at_z) in mat_points_and_values {
+                    for (_, ps_at_z) in mat_points_and_values {
+                        let inv_denom = inv_denoms.next().unwrap();
                         for (&p_at_x, &p_at_z) in izip!(mat_opening, ps_at_z) {
-                            let quotient = (-p_at_z + p_at_x) / (-*z + x);
+                            let quotient = (-p_at_z + p_at_x) * inv_denom;
                             *ro += *alpha_pow * quotient;
                             *alpha_pow *= alpha;
                         }
@@ -430,54 +1353,216 @@ where
                 .rev()
                 .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
                 .collect())
-        })
-        .expect("fri err");
+        };
 
-        Ok(())
+        match (self.fri.ldt, proof) {
+            (LowDegreeTest::Fri, LdtProof::Fri(proof)) => {
+                // One reduced opening per distinct matrix height, tallest first.
+                let input_log_heights = rounds
+                    .iter()
+                    .flat_map(|(_, mats)| mats)
+                    .map(|(domain, _)| domain.log_n + self.fri.log_blowup)
+                    .sorted()
+                    .dedup()
+                    .rev()
+                    .collect_vec();
+                let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
+                    TwoAdicFriGenericConfig(PhantomData);
+                verifier::verify_with_input_check(
+                    &g,
+                    &self.fri,
+                    &input_log_heights,
+                    proof,
+                    challenger,
+                    check_inputs,
+                    open_input,
+                )
+            }
+            (LowDegreeTest::Stir { .. }, LdtProof::Stir(proof)) => stir::verify(
+                &self.fri,
+                proof,
+                challenger,
+                log_global_max_height,
+                check_inputs,
+                open_input,
+            ),
+            _ => Err(FriError::InvalidProofShape),
+        }
     }
 }
 
-#[instrument(skip_all)]
-fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matrix<F>>(
-    mats_and_points: &[(Vec<M>, &Vec<Vec<EF>>)],
-    coset_shift: F,
-) -> LinearMap<EF, Vec<EF>> {
-    let mut max_log_height_for_point: LinearMap<EF, usize> = LinearMap::new();
-    for (mats, points) in mats_and_points {
-        for (mat, points_for_mat) in izip!(mats, *points) {
-            let log_height = log2_strict_usize(mat.height());
-            for &z in points_for_mat {
-                if let Some(lh) = max_log_height_for_point.get_mut(&z) {
-                    *lh = core::cmp::max(*lh, log_height);
-                } else {
-                    max_log_height_for_point.insert(z, log_height);
-                }
-            }
+/// The MMCS index opening `row`, in natural order, of a bit-reversed LDE of height `height`, in
+/// a batch whose tallest matrix has height `2^log_max_height`.
+fn lde_row_index(log_max_height: usize, height: usize, row: usize) -> usize {
+    let log_height = log2_strict_usize(height);
+    reverse_bits_len(row, log_height) << (log_max_height - log_height)
+}
+
+/// Evaluate the polynomials of an LDE at each of `points`, by barycentric interpolation over its
+/// original coset `F::generator() * H`, given in bit-reversed order.
+fn interpolate_bit_reversed<F: TwoAdicField, EF: ExtensionField<F> + TwoAdicField>(
+    low_coset: RowMajorMatrixView<'_, F>,
+    points: &[EF],
+) -> Vec<Vec<EF>> {
+    points
+        .iter()
+        .map(|&point| {
+            interpolate_coset(&BitReversalPerm::new_view(low_coset), F::generator(), point)
+        })
+        .collect()
+}
+
+/// Observe every opened value, in the order they are given.
+fn observe_opened_values<F: Field, EF: ExtensionField<F>, V: AsRef<[EF]>>(
+    challenger: &mut impl FieldChallenger<F>,
+    opened_values: impl IntoIterator<Item = V>,
+) {
+    for values in opened_values {
+        for &value in values.as_ref() {
+            challenger.observe_ext_element(value);
         }
     }
+}
+
+/// Accumulate `alpha_pow_offset * (sum_i alpha^i p_i(x) - reduced_ys) / (x - z)` for every
+/// `(mat, z, alpha_pow_offset, reduced_ys)` in `quotients`, all of height `2^log_height`, into a
+/// single reduced opening.
+///
+/// The rows are split into chunks of `2^log_chunk_height`, which are reduced in parallel, each
+/// accumulating every quotient in turn. For a point `z` missing from `inv_denoms`, `1 / (x - z)` is
+/// computed for one chunk at a time instead.
+fn reduce_rows<F: TwoAdicField, EF: TwoAdicField + ExtensionField<F>>(
+    backend: &impl TwoAdicFriBackend<F, EF>,
+    log_height: usize,
+    quotients: &[(RowMajorMatrixView<'_, F>, EF, EF, EF)],
+    alpha: EF,
+    inv_denoms: Option<&LinearMap<EF, Arc<Vec<EF>>>>,
+    log_chunk_height: usize,
+) -> Vec<EF> {
+    let log_chunk_height = log_chunk_height.min(log_height);
+    let chunk_height = 1 << log_chunk_height;
+    let mut reduced_opening = vec![EF::zero(); 1 << log_height];
 
-    // Compute the largest subgroup we will use, in bitrev order.
-    let max_log_height = *max_log_height_for_point.values().max().unwrap();
-    let mut subgroup = cyclic_subgroup_coset_known_order(
-        F::two_adic_generator(max_log_height),
-        coset_shift,
-        1 << max_log_height,
+    reduced_opening
+        .par_chunks_mut(chunk_height)
+        .enumerate()
+        .for_each(|(chunk_index, ro_chunk)| {
+            let start = chunk_index * chunk_height;
+            for &(mat, z, alpha_pow_offset, reduced_ys) in quotients {
+                let width = mat.width();
+                let chunk = RowMajorMatrixView::new(
+                    &mat.values[start * width..(start + chunk_height) * width],
+                    width,
+                );
+                let chunk_inv_denoms;
+                let chunk_inv_denoms = match inv_denoms.and_then(|inv_denoms| inv_denoms.get(&z)) {
+                    // These might be longer, but are in bitrev order, so a prefix of them is
+                    // over the smaller subgroup.
+                    Some(inv_denoms) => &inv_denoms[start..start + chunk_height],
+                    None => {
+                        chunk_inv_denoms =
+                            chunk_inverse_denominators(log_height, start, log_chunk_height, z);
+                        &chunk_inv_denoms
+                    }
+                };
+                backend.accumulate_quotient_rows(
+                    &chunk,
+                    alpha,
+                    alpha_pow_offset,
+                    reduced_ys,
+                    chunk_inv_denoms,
+                    ro_chunk,
+                );
+            }
+        });
+
+    reduced_opening
+}
+
+/// Compute `1 / (x - z)` for the `2^log_chunk_height` rows starting at `start` of the coset
+/// `F::generator() * H` of size `2^log_height`, in bit-reversed order.
+fn chunk_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>>(
+    log_height: usize,
+    start: usize,
+    log_chunk_height: usize,
+    z: EF,
+) -> Vec<EF> {
+    // In bit-reversed order, an aligned chunk of the coset is itself a coset of the chunk-sized
+    // subgroup, in bit-reversed order.
+    let chunk_shift = F::generator()
+        * F::two_adic_generator(log_height).exp_u64(reverse_bits_len(start, log_height) as u64);
+    let mut xs = cyclic_subgroup_coset_known_order(
+        F::two_adic_generator(log_chunk_height),
+        chunk_shift,
+        1 << log_chunk_height,
     )
     .collect_vec();
-    reverse_slice_index_bits(&mut subgroup);
+    reverse_slice_index_bits(&mut xs);
+    batch_multiplicative_inverse(&xs.into_iter().map(|x| EF::from_base(x) - z).collect_vec())
+}
 
+/// For each point `z` in `max_log_height_for_point`, `1 / (x - z)` over the coset of that log
+/// height, in bitrev order, given as a prefix of `coset`. Tables in `cache` are reused, and those
+/// computed are added to it.
+#[instrument(skip_all)]
+fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>>(
+    max_log_height_for_point: LinearMap<EF, usize>,
+    coset: &[F],
+    cache: Option<&InverseDenominatorCache>,
+) -> LinearMap<EF, Arc<Vec<EF>>> {
     max_log_height_for_point
         .into_iter()
         .map(|(z, log_height)| {
-            (
-                z,
+            let compute = || {
                 batch_multiplicative_inverse(
-                    &subgroup[..(1 << log_height)]
+                    &coset[..(1 << log_height)]
                         .iter()
                         .map(|&x| EF::from_base(x) - z)
                         .collect_vec(),
-                ),
-            )
+                )
+            };
+            let inv_denoms = match cache {
+                Some(cache) => cache.get_or_compute(log_height, z, compute),
+                None => Arc::new(compute()),
+            };
+            (z, inv_denoms)
         })
         .collect()
 }
+
+/// For each point opened on more than one matrix, or on any matrix if `include_unshared`, the log
+/// height of the tallest of them.
+fn max_log_height_for_shared_point<F: Field, EF: ExtensionField<F>, M: Matrix<F>>(
+    mats_and_points: &[(Vec<M>, &Vec<Vec<EF>>)],
+    include_unshared: bool,
+) -> LinearMap<EF, usize> {
+    let mut openings_of_point: LinearMap<EF, (usize, usize)> = LinearMap::new();
+    for (mats, points) in mats_and_points {
+        for (mat, points_for_mat) in izip!(mats, *points) {
+            let log_height = log2_strict_usize(mat.height());
+            for &z in points_for_mat {
+                let (num_mats, max_log_height) =
+                    openings_of_point.get_or_insert_with(z, || (0, log_height));
+                *num_mats += 1;
+                *max_log_height = core::cmp::max(*max_log_height, log_height);
+            }
+        }
+    }
+    openings_of_point
+        .into_iter()
+        .filter(|&(_, (num_mats, _))| include_unshared || num_mats > 1)
+        .map(|(z, (_, max_log_height))| (z, max_log_height))
+        .collect()
+}
+
+/// The coset `F::generator() * H` of size `2^log_height`, in bit-reversed order.
+fn bit_reversed_coset<F: TwoAdicField>(log_height: usize) -> Vec<F> {
+    let mut coset = cyclic_subgroup_coset_known_order(
+        F::two_adic_generator(log_height),
+        F::generator(),
+        1 << log_height,
+    )
+    .collect_vec();
+    reverse_slice_index_bits(&mut coset);
+    coset
+}
@@ -1,32 +1,83 @@
-use alloc::vec;
 use alloc::vec::Vec;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, CanSample, GrindingChallenger};
 use p3_commit::Mmcs;
-use p3_field::Field;
+use p3_field::{ExtensionField, Field};
 use p3_matrix::Dimensions;
 
-use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof};
+use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof, QueryProof};
 
 #[derive(Debug)]
 pub enum FriError<CommitMmcsErr, InputError> {
     InvalidProofShape,
-    CommitPhaseMmcsError(CommitMmcsErr),
-    InputError(InputError),
-    FinalPolyMismatch,
+    /// A query's opening of a commit phase layer doesn't verify against the layer's commitment.
+    /// The opening includes the value folded from the previous layer, so a fold inconsistent with
+    /// that layer is caught here too. `query` is `None` if the layer's pruned openings couldn't be
+    /// expanded.
+    CommitPhaseMmcsError {
+        layer: usize,
+        query: Option<usize>,
+        error: CommitMmcsErr,
+    },
+    /// A query's input openings are invalid. `query` is `None` if the failure isn't specific to
+    /// one query.
+    InputError {
+        query: Option<usize>,
+        error: InputError,
+    },
+    /// The value a query folds down to doesn't match the final polynomial.
+    FinalPolyMismatch {
+        query: usize,
+    },
     InvalidPowWitness,
 }
 
+/// Verify a FRI proof of inputs of heights `2^input_log_heights`, given in descending order
+/// without repeats, as the prover takes them.
+///
+/// The folding arity of each round is fixed by `config` and the input heights, as in
+/// `FriConfig::proof_shape`, so a proof which folds any other way is rejected.
 pub fn verify<G, F, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    input_log_heights: &[usize],
     proof: &FriProof<F, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, F)>, G::InputError>,
 ) -> Result<(), FriError<M::Error, G::InputError>>
 where
-    F: Field,
+    F: ExtensionField<Challenger::Witness>,
+    M: Mmcs<F>,
+    Challenger: GrindingChallenger + CanObserve<M::Commitment> + CanSample<F>,
+    G: FriGenericConfig<F>,
+{
+    verify_with_input_check(
+        g,
+        config,
+        input_log_heights,
+        proof,
+        challenger,
+        |_| Ok(()),
+        open_input,
+    )
+}
+
+/// Like `verify`, but once all query indices have been sampled, `check_inputs` is handed every
+/// `(index, input_proof)` pair before any query is folded. This lets the caller batch work that
+/// is independent across queries, such as checking input MMCS openings in parallel. On failure,
+/// it returns the index of the failing query, if the failure is specific to one, with its error.
+pub fn verify_with_input_check<G, F, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    input_log_heights: &[usize],
+    proof: &FriProof<F, M, Challenger::Witness, G::InputProof>,
+    challenger: &mut Challenger,
+    check_inputs: impl FnOnce(&[(usize, &G::InputProof)]) -> Result<(), (Option<usize>, G::InputError)>,
+    open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, F)>, G::InputError>,
+) -> Result<(), FriError<M::Error, G::InputError>>
+where
+    F: ExtensionField<Challenger::Witness>,
     M: Mmcs<F>,
     Challenger: GrindingChallenger + CanObserve<M::Commitment> + CanSample<F>,
     G: FriGenericConfig<F>,
@@ -40,56 +91,150 @@ where
         })
         .collect();
 
-    if proof.query_proofs.len() != config.num_queries {
+    // The folding schedule is derived from the config and the input heights, never from the
+    // proof, and every query must follow it.
+    let well_formed_inputs = input_log_heights.first().is_some()
+        && input_log_heights.iter().tuple_windows().all(|(l, r)| l > r)
+        && input_log_heights
+            .iter()
+            .all(|&log_height| log_height >= config.log_final_height());
+    if !well_formed_inputs {
+        return Err(FriError::InvalidProofShape);
+    }
+    let log_arities = config.proof_shape(input_log_heights).log_arities;
+    if proof.query_proofs.len() != config.num_queries
+        || proof.commit_phase_commits.len() != log_arities.len()
+        || proof.final_poly.len() != config.final_poly_len()
+        || proof.query_proofs.iter().any(|qp| {
+            qp.commit_phase_openings.len() != log_arities.len()
+                || izip!(&qp.commit_phase_openings, &log_arities).any(|(step, &log_arity)| {
+                    step.log_arity as usize != log_arity
+                        || step.sibling_values.len() != (1 << log_arity) - 1
+                })
+        })
+    {
         return Err(FriError::InvalidProofShape);
     }
 
+    for &coeff in &proof.final_poly {
+        challenger.observe_slice(coeff.as_base_slice());
+    }
+
     // Check PoW.
     if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
         return Err(FriError::InvalidPowWitness);
     }
 
-    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
+    let log_max_height = input_log_heights[0];
+
+    let queries = proof
+        .query_proofs
+        .iter()
+        .map(|qp| {
+            let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
+            (index, &qp.input_proof)
+        })
+        .collect_vec();
+
+    check_inputs(&queries).map_err(|(query, error)| FriError::InputError { query, error })?;
 
-    for qp in &proof.query_proofs {
-        let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
-        let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
+    let expanded_openings = if config.prune_openings {
+        let indices = queries
+            .iter()
+            .map(|&(index, _)| index >> g.extra_query_index_bits())
+            .collect();
+        let openings =
+            expand_commit_phase_openings(config, &proof.query_proofs, indices, &log_arities)
+                .map_err(|(layer, error)| FriError::CommitPhaseMmcsError {
+                    layer,
+                    query: None,
+                    error,
+                })?;
+        Some(openings)
+    } else {
+        None
+    };
+
+    for (query, (qp, &(index, input_proof))) in izip!(&proof.query_proofs, &queries).enumerate() {
+        let commit_phase_openings = expanded_openings
+            .as_ref()
+            .map_or(&qp.commit_phase_openings, |openings| &openings[query]);
+        let ro = open_input(index, input_proof).map_err(|error| FriError::InputError {
+            query: Some(query),
+            error,
+        })?;
 
         debug_assert!(
             ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
             "reduced openings sorted by height descending"
         );
 
+        let final_index =
+            index >> g.extra_query_index_bits() >> (log_max_height - config.log_final_height());
         let folded_eval = verify_query(
             g,
             config,
+            query,
             index >> g.extra_query_index_bits(),
-            izip!(
-                &betas,
-                &proof.commit_phase_commits,
-                &qp.commit_phase_openings
-            ),
+            izip!(&betas, &proof.commit_phase_commits, commit_phase_openings),
             ro,
             log_max_height,
         )?;
 
-        if folded_eval != proof.final_poly {
-            return Err(FriError::FinalPolyMismatch);
+        let final_eval =
+            g.eval_final_poly(&proof.final_poly, final_index, config.log_final_height());
+        if folded_eval != final_eval {
+            return Err(FriError::FinalPolyMismatch { query });
         }
     }
 
     Ok(())
 }
 
+/// Recover every query's commit phase openings from their pruned form, given the queries' indices
+/// into the first commit phase codeword. On failure, the layer whose openings couldn't be expanded
+/// is returned with the error.
+pub(crate) fn expand_commit_phase_openings<F, M, InputProof>(
+    config: &FriConfig<M>,
+    query_proofs: &[QueryProof<F, M, InputProof>],
+    mut indices: Vec<usize>,
+    log_arities: &[usize],
+) -> Result<Vec<Vec<CommitPhaseProofStep<F, M>>>, (usize, M::Error)>
+where
+    F: Field,
+    M: Mmcs<F>,
+{
+    let mut openings = query_proofs
+        .iter()
+        .map(|qp| qp.commit_phase_openings.clone())
+        .collect_vec();
+    for (round, &log_arity) in log_arities.iter().enumerate() {
+        indices.iter_mut().for_each(|index| *index >>= log_arity);
+        let pruned = openings
+            .iter()
+            .map(|steps| steps[round].opening_proof.clone())
+            .collect();
+        let expanded = config
+            .mmcs
+            .expand_proofs(&indices, pruned)
+            .map_err(|error| (round, error))?;
+        for (steps, proof) in izip!(&mut openings, expanded) {
+            steps[round].opening_proof = proof;
+        }
+    }
+    Ok(openings)
+}
+
 type CommitStep<'a, F, M> = (
     &'a F,
     &'a <M as Mmcs<F>>::Commitment,
     &'a CommitPhaseProofStep<F, M>,
 );
 
-fn verify_query<'a, G, F, M>(
+pub(crate) fn verify_query<'a, G, F, M>(
     g: &G,
     config: &FriConfig<M>,
+    query: usize,
     mut index: usize,
     steps: impl Iterator<Item = CommitStep<'a, F, M>>,
     reduced_openings: Vec<(usize, F)>,
@@ -102,20 +247,23 @@ where
 {
     let mut folded_eval = F::zero();
     let mut ro_iter = reduced_openings.into_iter().peekable();
+    let mut log_height = log_max_height;
 
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+    for (layer, (&beta, comm, opening)) in steps.enumerate() {
+        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
             folded_eval += ro;
         }
 
-        let index_sibling = index ^ 1;
-        let index_pair = index >> 1;
+        let log_arity = opening.log_arity as usize;
+        let log_folded_height = log_height - log_arity;
+        let index_in_row = index & ((1 << log_arity) - 1);
+        let row_index = index >> log_arity;
 
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
+        let mut evals = opening.sibling_values.clone();
+        evals.insert(index_in_row, folded_eval);
 
         let dims = &[Dimensions {
-            width: 2,
+            width: 1 << log_arity,
             height: 1 << log_folded_height,
         }];
         config
@@ -123,22 +271,37 @@ where
             .verify_batch(
                 comm,
                 dims,
-                index_pair,
+                row_index,
                 &[evals.clone()],
                 &opening.opening_proof,
             )
-            .map_err(FriError::CommitPhaseMmcsError)?;
+            .map_err(|error| FriError::CommitPhaseMmcsError {
+                layer,
+                query: Some(query),
+                error,
+            })?;
 
-        index = index_pair;
+        index = row_index;
+        log_height = log_folded_height;
 
         folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
     }
 
-    debug_assert!(index < config.blowup(), "index was {}", index);
+    // Inputs as small as the final codeword are added to it directly.
+    if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
+        folded_eval += ro;
+    }
+
     debug_assert!(
-        ro_iter.next().is_none(),
-        "verifier reduced_openings were not in descending order?"
+        index < config.blowup() * config.final_poly_len(),
+        "index was {}",
+        index
     );
 
+    // Any reduced opening we didn't consume had a height that doesn't fall on a round boundary.
+    if ro_iter.next().is_some() {
+        return Err(FriError::InvalidProofShape);
+    }
+
     Ok(folded_eval)
 }
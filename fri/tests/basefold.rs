@@ -0,0 +1,129 @@
+use itertools::Itertools;
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::{ExtensionMmcs, MultivariatePcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{BasefoldError, BasefoldPcs, FriConfig, LowDegreeTest};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::FieldMerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    FieldMerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = BasefoldPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+fn seeded_rng() -> impl Rng {
+    ChaCha20Rng::seed_from_u64(0)
+}
+
+fn get_pcs(log_blowup: usize, prune_openings: bool) -> (MyPcs, Challenger) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear,
+        &mut seeded_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+    let fri_config = FriConfig {
+        log_blowup,
+        num_queries: 10,
+        proof_of_work_bits: 8,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings,
+        mmcs: challenge_mmcs,
+    };
+
+    let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+    (pcs, Challenger::new(perm))
+}
+
+/// Evaluate each column's multilinear extension at `point`, by fixing one variable at a time.
+fn eval_multilinear(evaluations: &RowMajorMatrix<Val>, point: &[Challenge]) -> Vec<Challenge> {
+    (0..evaluations.width())
+        .map(|col| {
+            let column = evaluations
+                .values
+                .iter()
+                .skip(col)
+                .step_by(evaluations.width())
+                .map(|&v| Challenge::from_base(v))
+                .collect_vec();
+            point
+                .iter()
+                .fold(column, |evals, &z| {
+                    evals
+                        .chunks_exact(2)
+                        .map(|pair| pair[0] * (Challenge::one() - z) + pair[1] * z)
+                        .collect()
+                })
+                .pop()
+                .unwrap()
+        })
+        .collect()
+}
+
+fn do_test_basefold(log_blowup: usize, prune_openings: bool, log_n: usize) {
+    let (pcs, challenger) = get_pcs(log_blowup, prune_openings);
+    let mut rng = seeded_rng();
+
+    let evaluations = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 5);
+    let point: Vec<Challenge> = (0..log_n).map(|_| rng.gen()).collect();
+
+    let (commit, data) =
+        <MyPcs as MultivariatePcs<_, _, Challenger>>::commit(&pcs, evaluations.clone());
+
+    let mut p_challenger = challenger.clone();
+    let (values, proof) = pcs.open(&data, &point, &mut p_challenger);
+    assert_eq!(values, eval_multilinear(&evaluations, &point));
+
+    let mut v_challenger = challenger.clone();
+    pcs.verify(&commit, &point, &values, &proof, &mut v_challenger)
+        .expect("verification failed");
+
+    let mut bad_values = values.clone();
+    bad_values[0] += Challenge::one();
+    let mut v_challenger = challenger.clone();
+    assert!(matches!(
+        pcs.verify(&commit, &point, &bad_values, &proof, &mut v_challenger),
+        Err(BasefoldError::SumcheckMismatch)
+    ));
+}
+
+#[test]
+fn basefold_blowup_1() {
+    for log_n in 1..=6 {
+        do_test_basefold(1, false, log_n);
+    }
+}
+
+#[test]
+fn basefold_blowup_2() {
+    do_test_basefold(2, false, 5);
+}
+
+#[test]
+fn basefold_pruned() {
+    do_test_basefold(1, true, 6);
+}
@@ -1,13 +1,14 @@
 use core::cmp::Reverse;
 use std::marker::PhantomData;
 
+use itertools::Itertools;
 use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
 use p3_challenger::{CanSampleBits, DuplexChallenger, FieldChallenger};
-use p3_commit::ExtensionMmcs;
+use p3_commit::{ExtensionMmcs, Mmcs};
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field};
-use p3_fri::{prover, verifier, FriConfig, TwoAdicFriGenericConfig};
+use p3_fri::{prover, verifier, FriConfig, LowDegreeTest, TwoAdicFriGenericConfig};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
@@ -39,13 +40,30 @@ fn get_ldt_for_testing<R: Rng>(rng: &mut R) -> (Perm, MyFriConfig) {
         log_blowup: 1,
         num_queries: 10,
         proof_of_work_bits: 8,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings: false,
         mmcs,
     };
     (perm, fri_config)
 }
 
-fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
-    let (perm, fc) = get_ldt_for_testing(rng);
+fn do_test_fri_ldt<R: Rng>(rng: &mut R, max_log_arity: usize) {
+    prove_and_verify(rng, max_log_arity, max_log_arity, false).unwrap();
+}
+
+/// Prove with folding arities of up to `2^max_log_arity`, and verify with a config allowing up to
+/// `2^verifier_max_log_arity` instead.
+fn prove_and_verify<R: Rng>(
+    rng: &mut R,
+    max_log_arity: usize,
+    verifier_max_log_arity: usize,
+    prune_openings: bool,
+) -> Result<(), verifier::FriError<<ChallengeMmcs as Mmcs<Challenge>>::Error, ()>> {
+    let (perm, mut fc) = get_ldt_for_testing(rng);
+    fc.max_log_arity = max_log_arity;
+    fc.prune_openings = prune_openings;
     let dft = Radix2Dit::default();
 
     let shift = Val::generator();
@@ -59,7 +77,7 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
         })
         .collect();
 
-    let (proof, p_sample) = {
+    let (proof, input_log_heights, p_sample) = {
         // Prover world
         let mut chal = Challenger::new(perm.clone());
         let alpha: Challenge = chal.sample_ext_element();
@@ -106,25 +124,48 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
             },
         );
 
-        (proof, chal.sample_bits(8))
+        let input_log_heights = input
+            .iter()
+            .map(|v| log2_strict_usize(v.len()))
+            .collect_vec();
+        (proof, input_log_heights, chal.sample_bits(8))
+    };
+    let shape = fc.proof_shape(&input_log_heights);
+
+    // Round trip through the fixed shape layout before verifying, unless the opening proofs are
+    // pruned, leaving the proof without a fixed shape.
+    let proof = if prune_openings {
+        proof
+    } else {
+        let fixed_shape_proof = proof.to_fixed_shape(&shape).unwrap();
+        assert_eq!(
+            fixed_shape_proof.sibling_values.len(),
+            fc.num_queries * shape.num_rounds() * shape.num_padded_siblings()
+        );
+        fixed_shape_proof.into_proof(&shape).unwrap()
     };
 
+    fc.max_log_arity = verifier_max_log_arity;
     let mut v_challenger = Challenger::new(perm);
     let _alpha: Challenge = v_challenger.sample_ext_element();
     verifier::verify(
         &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
         &fc,
+        &input_log_heights,
         &proof,
         &mut v_challenger,
         |_index, proof| Ok(proof.clone()),
-    )
-    .unwrap();
+    )?;
 
     assert_eq!(
         p_sample,
         v_challenger.sample_bits(8),
         "prover and verifier transcript have same state after FRI"
     );
+    if prune_openings {
+        assert!(proof.to_fixed_shape(&shape).is_none());
+    }
+    Ok(())
 }
 
 #[test]
@@ -132,6 +173,32 @@ fn test_fri_ldt() {
     // FRI is kind of flaky depending on indexing luck
     for i in 0..4 {
         let mut rng = ChaCha20Rng::seed_from_u64(i);
-        do_test_fri_ldt(&mut rng);
+        do_test_fri_ldt(&mut rng, 1);
+    }
+}
+
+#[test]
+fn test_fri_ldt_arity_8() {
+    for i in 0..4 {
+        let mut rng = ChaCha20Rng::seed_from_u64(i);
+        do_test_fri_ldt(&mut rng, 3);
+    }
+}
+
+#[test]
+fn test_fri_ldt_pruned() {
+    for i in 0..4 {
+        let mut rng = ChaCha20Rng::seed_from_u64(i);
+        prove_and_verify(&mut rng, 2, 2, true).unwrap();
     }
 }
+
+#[test]
+fn test_fri_rejects_other_folding_schedule() {
+    // Each round's arity is fixed by the verifier's config, not taken from the proof.
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    assert!(matches!(
+        prove_and_verify(&mut rng, 1, 3, false),
+        Err(verifier::FriError::InvalidProofShape)
+    ));
+}
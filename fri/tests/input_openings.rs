@@ -0,0 +1,412 @@
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Mmcs, OpenedValuesForPoint, Pcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::verifier::FriError;
+use p3_fri::{
+    BatchOpening, BatchOpeningError, FriConfig, FriProof, LdtProof, LowDegreeTest, TwoAdicFriPcs,
+};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::{Dimensions, Matrix};
+use p3_merkle_tree::FieldMerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type InnerMmcs =
+    FieldMerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+
+/// Wraps an MMCS so that a failed `verify_batch` reports which row index it was checking, letting
+/// tests tell apart which query's opening was rejected.
+#[derive(Clone)]
+struct IndexReportingMmcs(InnerMmcs);
+
+impl Mmcs<Val> for IndexReportingMmcs {
+    type ProverData<M> = <InnerMmcs as Mmcs<Val>>::ProverData<M>;
+    type Commitment = <InnerMmcs as Mmcs<Val>>::Commitment;
+    type Proof = <InnerMmcs as Mmcs<Val>>::Proof;
+    type Error = usize;
+
+    fn commit<M: Matrix<Val>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
+        self.0.commit(inputs)
+    }
+
+    fn open_batch<M: Matrix<Val>>(
+        &self,
+        index: usize,
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Val>>, Self::Proof) {
+        self.0.open_batch(index, prover_data)
+    }
+
+    fn get_matrices<'a, M: Matrix<Val>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
+        self.0.get_matrices(prover_data)
+    }
+
+    fn verify_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        index: usize,
+        opened_values: &[Vec<Val>],
+        proof: &Self::Proof,
+    ) -> Result<(), Self::Error> {
+        self.0
+            .verify_batch(commit, dimensions, index, opened_values, proof)
+            .map_err(|_| index)
+    }
+}
+
+type ValMmcs = IndexReportingMmcs;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, InnerMmcs>;
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+const LOG_HEIGHT: usize = 8;
+
+fn inner_mmcs(perm: Perm) -> InnerMmcs {
+    InnerMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm))
+}
+
+/// The same permutation as `prove_and_get_claims` commits with.
+fn seeded_perm() -> Perm {
+    Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear,
+        &mut ChaCha20Rng::seed_from_u64(0),
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn prove_and_get_claims() -> (
+    MyPcs,
+    Challenger,
+    Vec<(
+        <MyPcs as Pcs<Challenge, Challenger>>::Commitment,
+        Vec<(
+            <MyPcs as Pcs<Challenge, Challenger>>::Domain,
+            Vec<(Challenge, OpenedValuesForPoint<Challenge>)>,
+        )>,
+    )>,
+    <MyPcs as Pcs<Challenge, Challenger>>::Proof,
+) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear,
+        &mut rng,
+    );
+    let inner_mmcs = inner_mmcs(perm.clone());
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        num_queries: 20,
+        proof_of_work_bits: 8,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings: false,
+        mmcs: ChallengeMmcs::new(inner_mmcs.clone()),
+    };
+    let pcs = MyPcs::new(Dft::default(), IndexReportingMmcs(inner_mmcs), fri_config);
+    let challenger = Challenger::new(perm);
+
+    let domain =
+        <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << LOG_HEIGHT);
+    let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << LOG_HEIGHT, 8);
+    let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+    let mut p_challenger = challenger.clone();
+    p_challenger.observe(commit);
+    let zeta: Challenge = p_challenger.sample_ext_element();
+    let (opened_values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+
+    let claims = vec![(
+        commit,
+        vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])],
+    )];
+    (pcs, challenger, claims, proof)
+}
+
+fn verify(
+    pcs: &MyPcs,
+    challenger: &Challenger,
+    claims: &[(
+        <MyPcs as Pcs<Challenge, Challenger>>::Commitment,
+        Vec<(
+            <MyPcs as Pcs<Challenge, Challenger>>::Domain,
+            Vec<(Challenge, OpenedValuesForPoint<Challenge>)>,
+        )>,
+    )],
+    proof: &<MyPcs as Pcs<Challenge, Challenger>>::Proof,
+) -> Result<(), <MyPcs as Pcs<Challenge, Challenger>>::Error> {
+    let mut v_challenger = challenger.clone();
+    v_challenger.observe(claims[0].0);
+    let _zeta: Challenge = v_challenger.sample_ext_element();
+    pcs.verify(claims.to_vec(), proof, &mut v_challenger)
+}
+
+fn fri_proof(
+    proof: &mut <MyPcs as Pcs<Challenge, Challenger>>::Proof,
+) -> &mut FriProof<Challenge, ChallengeMmcs, Val, Vec<BatchOpening<Val, ValMmcs>>> {
+    match &mut proof.ldt {
+        LdtProof::Fri(proof) => proof,
+        LdtProof::Stir(_) => panic!("expected a FRI proof"),
+    }
+}
+
+fn corrupt_input_path(proof: &mut <MyPcs as Pcs<Challenge, Challenger>>::Proof, query: usize) {
+    let opening_proof = &mut fri_proof(proof).query_proofs[query].input_proof[0].opening_proof;
+    opening_proof[0][0] += Val::one();
+}
+
+/// The failing query and the MMCS index it opened.
+fn rejected_index(
+    result: Result<(), <MyPcs as Pcs<Challenge, Challenger>>::Error>,
+) -> (usize, usize) {
+    match result {
+        Err(FriError::InputError {
+            query: Some(query),
+            error: BatchOpeningError { batch: 0, error },
+        }) => (query, error),
+        other => panic!("expected an input opening error, got {:?}", other),
+    }
+}
+
+/// The outcome of verifying the input openings, as the failing query, batch and MMCS index.
+fn input_outcome(
+    result: Result<(), <MyPcs as Pcs<Challenge, Challenger>>::Error>,
+) -> Result<(), (Option<usize>, usize, usize)> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(FriError::InputError {
+            query,
+            error: BatchOpeningError { batch, error },
+        }) => Err((query, batch, error)),
+        Err(other) => panic!("expected an input opening error, got {:?}", other),
+    }
+}
+
+/// The LDE row index each query of a valid proof opened, found by trying every row.
+fn query_indices(
+    claims: &[(
+        <MyPcs as Pcs<Challenge, Challenger>>::Commitment,
+        Vec<(
+            <MyPcs as Pcs<Challenge, Challenger>>::Domain,
+            Vec<(Challenge, OpenedValuesForPoint<Challenge>)>,
+        )>,
+    )],
+    proof: &mut <MyPcs as Pcs<Challenge, Challenger>>::Proof,
+) -> Vec<usize> {
+    let mmcs = inner_mmcs(seeded_perm());
+    let dims = [Dimensions {
+        width: 0,
+        height: 2 << LOG_HEIGHT,
+    }];
+    fri_proof(proof)
+        .query_proofs
+        .iter()
+        .map(|query_proof| {
+            let opening = &query_proof.input_proof[0];
+            (0..2 << LOG_HEIGHT)
+                .find(|&index| {
+                    mmcs.verify_batch(
+                        &claims[0].0,
+                        &dims,
+                        index,
+                        &opening.opened_values,
+                        &opening.opening_proof,
+                    )
+                    .is_ok()
+                })
+                .unwrap()
+        })
+        .collect()
+}
+
+/// Verify the input openings one query at a time, in order, skipping a query that opens the same
+/// values at the same index as an earlier one, as a reference for the parallel verifier.
+fn verify_input_openings_serially(
+    claims: &[(
+        <MyPcs as Pcs<Challenge, Challenger>>::Commitment,
+        Vec<(
+            <MyPcs as Pcs<Challenge, Challenger>>::Domain,
+            Vec<(Challenge, OpenedValuesForPoint<Challenge>)>,
+        )>,
+    )],
+    indices: &[usize],
+    proof: &mut <MyPcs as Pcs<Challenge, Challenger>>::Proof,
+) -> Result<(), (Option<usize>, usize, usize)> {
+    let mmcs = IndexReportingMmcs(inner_mmcs(seeded_perm()));
+    let dims = [Dimensions {
+        width: 0,
+        height: 2 << LOG_HEIGHT,
+    }];
+    let query_proofs = &fri_proof(proof).query_proofs;
+    for (query, (&index, query_proof)) in indices.iter().zip(query_proofs).enumerate() {
+        let opening = &query_proof.input_proof[0];
+        let is_repeated = (0..query).any(|earlier| {
+            indices[earlier] == index
+                && query_proofs[earlier].input_proof[0].opened_values == opening.opened_values
+        });
+        if is_repeated {
+            continue;
+        }
+        mmcs.verify_batch(
+            &claims[0].0,
+            &dims,
+            index,
+            &opening.opened_values,
+            &opening.opening_proof,
+        )
+        .map_err(|error| (Some(query), 0, error))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn valid_proof_is_accepted() {
+    let (pcs, challenger, claims, proof) = prove_and_get_claims();
+    verify(&pcs, &challenger, &claims, &proof).unwrap();
+}
+
+#[test]
+fn lowest_failing_query_is_reported() {
+    let (pcs, challenger, claims, proof) = prove_and_get_claims();
+
+    // Reference outcomes, each with a single corrupted query.
+    let single = |query| {
+        let mut proof = proof.clone();
+        corrupt_input_path(&mut proof, query);
+        rejected_index(verify(&pcs, &challenger, &claims, &proof))
+    };
+    let low = single(3);
+    assert_eq!(low.0, 3);
+
+    // With several corrupted queries, whichever of them is checked first, the reported error
+    // must be that of the lowest-indexed one.
+    let mut proof_many = proof.clone();
+    for query in [17, 3, 11] {
+        corrupt_input_path(&mut proof_many, query);
+    }
+    for _ in 0..4 {
+        assert_eq!(
+            rejected_index(verify(&pcs, &challenger, &claims, &proof_many)),
+            low
+        );
+    }
+}
+
+#[test]
+fn parallel_input_verification_matches_serial_reference() {
+    let (pcs, challenger, claims, mut proof) = prove_and_get_claims();
+    let indices = query_indices(&claims, &mut proof);
+
+    for corrupted in [
+        vec![],
+        vec![0],
+        vec![19],
+        vec![4, 9],
+        vec![17, 3, 11],
+        (0..20).step_by(3).collect(),
+        (0..20).collect(),
+    ] {
+        let mut corrupted_proof = proof.clone();
+        for &query in &corrupted {
+            corrupt_input_path(&mut corrupted_proof, query);
+        }
+        let expected = verify_input_openings_serially(&claims, &indices, &mut corrupted_proof);
+        if corrupted.is_empty() {
+            assert_eq!(expected, Ok(()));
+        }
+        assert_eq!(
+            input_outcome(verify(&pcs, &challenger, &claims, &corrupted_proof)),
+            expected,
+            "corrupted queries {:?}",
+            corrupted
+        );
+    }
+}
+
+#[test]
+fn commit_phase_error_reports_query_and_layer() {
+    let (pcs, challenger, claims, proof) = prove_and_get_claims();
+
+    let mut bad_sibling = proof;
+    fri_proof(&mut bad_sibling).query_proofs[5].commit_phase_openings[1].sibling_values[0] +=
+        Challenge::one();
+    assert!(matches!(
+        verify(&pcs, &challenger, &claims, &bad_sibling),
+        Err(FriError::CommitPhaseMmcsError {
+            layer: 1,
+            query: Some(5),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn malformed_proofs_are_rejected() {
+    let (pcs, challenger, claims, proof) = prove_and_get_claims();
+    let is_shape_error = |proof| {
+        matches!(
+            verify(&pcs, &challenger, &claims, proof),
+            Err(FriError::InvalidProofShape)
+        )
+    };
+
+    let mut missing_round = proof.clone();
+    fri_proof(&mut missing_round).query_proofs[5]
+        .input_proof
+        .clear();
+    assert!(is_shape_error(&missing_round));
+
+    let mut missing_matrix = proof.clone();
+    fri_proof(&mut missing_matrix).query_proofs[5].input_proof[0]
+        .opened_values
+        .clear();
+    assert!(is_shape_error(&missing_matrix));
+
+    let mut short_row = proof.clone();
+    fri_proof(&mut short_row).query_proofs[5].input_proof[0].opened_values[0].pop();
+    assert!(is_shape_error(&short_row));
+
+    let mut missing_step = proof.clone();
+    fri_proof(&mut missing_step).query_proofs[5]
+        .commit_phase_openings
+        .pop();
+    assert!(is_shape_error(&missing_step));
+
+    let mut missing_layer = proof.clone();
+    fri_proof(&mut missing_layer).commit_phase_commits.pop();
+    assert!(is_shape_error(&missing_layer));
+}
+
+#[test]
+fn bad_pow_witness_is_rejected() {
+    let (pcs, challenger, claims, proof) = prove_and_get_claims();
+
+    let results = (1..=4)
+        .map(|offset| {
+            let mut proof = proof.clone();
+            fri_proof(&mut proof).pow_witness += Val::from_canonical_u32(offset);
+            verify(&pcs, &challenger, &claims, &proof)
+        })
+        .collect::<Vec<_>>();
+
+    // A tampered witness may by chance still satisfy the grinding condition, but then the query
+    // indices change and the openings no longer match, so every proof must be rejected.
+    assert!(results.iter().all(|r| r.is_err()));
+    assert!(results
+        .iter()
+        .any(|r| matches!(r, Err(FriError::InvalidPowWitness))));
+}
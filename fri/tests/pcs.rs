@@ -1,15 +1,24 @@
+use core::marker::PhantomData;
+
 use itertools::{izip, Itertools};
 use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
 use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
-use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace};
-use p3_dft::Radix2DitParallel;
+use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
+use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
-use p3_field::{ExtensionField, Field};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
-use p3_matrix::dense::RowMajorMatrix;
+use p3_field::{AbstractExtensionField, AbstractField, ExtensionField, Field, TwoAdicField};
+use p3_fri::{
+    FriConfig, FriGenericConfig, LowDegreeTest, TwoAdicFriBackend, TwoAdicFriGenericConfig,
+    TwoAdicFriPcs,
+};
+use p3_interpolation::interpolate_subgroup;
+use p3_matrix::bitrev::BitReversableMatrix;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::Matrix;
 use p3_merkle_tree::FieldMerkleTreeMmcs;
 use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_util::log2_strict_usize;
 use rand::distributions::{Distribution, Standard};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
@@ -169,11 +178,26 @@ mod babybear_fri_pcs {
     >;
     type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 
-    type Dft = Radix2DitParallel;
+    type Dft = Radix2DitParallel<Val>;
     type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
     type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 
-    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+    fn get_pcs(
+        log_blowup: usize,
+        max_log_arity: usize,
+        log_final_poly_len: usize,
+        ldt: LowDegreeTest,
+    ) -> (MyPcs, Challenger) {
+        get_pcs_with_pruning(log_blowup, max_log_arity, log_final_poly_len, ldt, false)
+    }
+
+    fn get_pcs_with_pruning(
+        log_blowup: usize,
+        max_log_arity: usize,
+        log_final_poly_len: usize,
+        ldt: LowDegreeTest,
+        prune_openings: bool,
+    ) -> (MyPcs, Challenger) {
         let perm = Perm::new_from_rng_128(
             Poseidon2ExternalMatrixGeneral,
             DiffusionMatrixBabyBear,
@@ -189,24 +213,700 @@ mod babybear_fri_pcs {
             log_blowup,
             num_queries: 10,
             proof_of_work_bits: 8,
+            max_log_arity,
+            log_final_poly_len,
+            ldt,
+            prune_openings,
             mmcs: challenge_mmcs,
         };
 
-        let pcs = MyPcs::new(Dft {}, val_mmcs, fri_config);
+        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
         (pcs, Challenger::new(perm.clone()))
     }
 
+    const FRI: LowDegreeTest = LowDegreeTest::Fri;
+
     mod blowup_1 {
-        make_tests_for_pcs!(super::get_pcs(1));
+        make_tests_for_pcs!(super::get_pcs(1, 1, 0, super::FRI));
     }
     mod blowup_2 {
-        make_tests_for_pcs!(super::get_pcs(2));
+        make_tests_for_pcs!(super::get_pcs(2, 1, 0, super::FRI));
+    }
+    mod blowup_1_arity_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 2, 0, super::FRI));
+    }
+    mod blowup_2_arity_8 {
+        make_tests_for_pcs!(super::get_pcs(2, 3, 0, super::FRI));
+    }
+    mod blowup_1_final_poly_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 1, 2, super::FRI));
+    }
+    mod blowup_1_arity_4_final_poly_2 {
+        make_tests_for_pcs!(super::get_pcs(1, 2, 1, super::FRI));
+    }
+
+    #[test]
+    fn arity_4_mmcs_mixed_heights() {
+        type MyHash4 = PaddingFreeSponge<Perm, 16, 8, 4>;
+        type MyCompress4 = TruncatedPermutation<Perm, 4, 4, 16>;
+        type ValMmcs4 = FieldMerkleTreeMmcs<
+            <Val as Field>::Packing,
+            <Val as Field>::Packing,
+            MyHash4,
+            MyCompress4,
+            4,
+            4,
+        >;
+        type Pcs4 = TwoAdicFriPcs<Val, Dft, ValMmcs4, ChallengeMmcs>;
+
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut seeded_rng(),
+        );
+        let val_mmcs = ValMmcs4::new(MyHash4::new(perm.clone()), MyCompress4::new(perm.clone()));
+        // FRI's own commitments are to one matrix each, so they stay on a binary tree.
+        let challenge_mmcs = ChallengeMmcs::new(ValMmcs::new(
+            MyHash::new(perm.clone()),
+            MyCompress::new(perm.clone()),
+        ));
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            max_log_arity: 1,
+            log_final_poly_len: 0,
+            ldt: FRI,
+            prune_openings: false,
+            mmcs: challenge_mmcs,
+        };
+        let pcs = Pcs4::new(Dft::default(), val_mmcs, fri_config);
+        let challenger = Challenger::new(perm);
+
+        // Within a round, heights differ by powers of 4, as the arity-4 tree requires. Across
+        // rounds, they needn't.
+        do_test_fri_pcs(&(pcs, challenger), &[&[3, 5, 7, 5], &[4, 2], &[6]]);
+    }
+
+    #[test]
+    fn commit_precomputed_ldes() {
+        let (pcs, challenger) = get_pcs(1, 1, 0, FRI);
+        let dft = Dft::default();
+        let mut rng = seeded_rng();
+        let evaluations = [(4, Val::one()), (3, Val::generator().square())]
+            .map(|(log_n, shift)| {
+                let domain = TwoAdicMultiplicativeCoset { log_n, shift };
+                (domain, RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3))
+            })
+            .to_vec();
+        let ldes = evaluations
+            .iter()
+            .map(|(domain, evals)| {
+                let lde = dft
+                    .coset_lde_batch(evals.clone(), 1, Val::generator() / domain.shift)
+                    .bit_reverse_rows()
+                    .to_row_major_matrix();
+                (*domain, lde)
+            })
+            .collect_vec();
+
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.clone());
+        let (precomputed_commit, precomputed_data) = pcs.commit_bit_reversed_ldes(ldes);
+        assert_eq!(precomputed_commit, commit);
+
+        // Openings of the precomputed LDEs verify against the usual claims.
+        let zeta: Challenge = challenger.clone().sample_ext_element();
+        let points = vec![vec![zeta]; evaluations.len()];
+        let (values, _) = pcs.open(vec![(&data, points.clone())], &mut challenger.clone());
+        let (precomputed_values, proof) =
+            pcs.open(vec![(&precomputed_data, points)], &mut challenger.clone());
+        assert_eq!(precomputed_values, values);
+
+        let claims = izip!(&evaluations, &values[0])
+            .map(|((domain, _), mat_values)| (*domain, vec![(zeta, mat_values[0].clone())]))
+            .collect_vec();
+        pcs.verify(vec![(commit, claims)], &proof, &mut challenger.clone())
+            .unwrap();
+    }
+
+    #[test]
+    fn commit_extension_matrices() {
+        let (pcs, challenger) = get_pcs(1, 1, 0, FRI);
+        let mut rng = seeded_rng();
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 4);
+        let base = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 4, 3);
+        let ext = RowMajorMatrix::<Challenge>::rand(&mut rng, 1 << 3, 2);
+        let ext_domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 3);
+
+        let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(
+            &pcs,
+            vec![(domain, base), (ext_domain, ext.flatten_to_base())],
+        );
+
+        let zeta: Challenge = challenger.clone().sample_ext_element();
+        let (values, proof) = pcs.open(
+            vec![(&data, vec![vec![zeta], vec![zeta]])],
+            &mut challenger.clone(),
+        );
+        assert_eq!(
+            values[0][1][0].len(),
+            2 * <Challenge as AbstractExtensionField<Val>>::D
+        );
+
+        let ext_values = pcs.reconstitute_extension_values(values[0][1][0].values());
+        let expected = interpolate_subgroup::<Challenge, Challenge, _>(&ext, zeta);
+        assert_eq!(ext_values, expected);
+
+        let claims = vec![
+            (domain, vec![(zeta, values[0][0][0].clone())]),
+            (ext_domain, vec![(zeta, values[0][1][0].clone())]),
+        ];
+        pcs.verify(vec![(commit, claims)], &proof, &mut challenger.clone())
+            .unwrap();
+    }
+
+    #[test]
+    fn open_lde_rows() {
+        let (pcs, _) = get_pcs(1, 1, 0, FRI);
+        let dft = Dft::default();
+        let mut rng = seeded_rng();
+        let evaluations = [4, 3]
+            .map(|log_n| {
+                let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    1 << log_n,
+                );
+                (domain, RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3))
+            })
+            .to_vec();
+        let domains = evaluations.iter().map(|(domain, _)| *domain).collect_vec();
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations.clone());
+
+        for (matrix_index, (_, evals)) in evaluations.into_iter().enumerate() {
+            let lde = dft.coset_lde_batch(evals, 1, Val::generator());
+            for row in [0, 1, 5, lde.height() - 1] {
+                let expected = lde.row(row).collect_vec();
+                assert_eq!(pcs.get_lde_row(&data, matrix_index, row), expected);
+
+                let mut opening = pcs.open_lde_row(&data, matrix_index, row);
+                assert_eq!(
+                    pcs.verify_lde_row(&commit, &domains, matrix_index, row, &opening),
+                    Some(expected.as_slice())
+                );
+
+                opening.opened_values[matrix_index][0] += Val::one();
+                assert_eq!(
+                    pcs.verify_lde_row(&commit, &domains, matrix_index, row, &opening),
+                    None
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn commit_matrices_on_different_cosets() {
+        let (pcs, challenger) = get_pcs(1, 1, 0, FRI);
+        let dft = Dft::default();
+        let mut rng = seeded_rng();
+        let log_n = 3;
+        let shifts = [Val::one(), Val::generator(), Val::generator().square()];
+        let coeffs = shifts
+            .iter()
+            .map(|_| RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 4))
+            .collect_vec();
+
+        // The same polynomials, evaluated over the subgroup, and over a different coset each.
+        let natural = coeffs
+            .iter()
+            .map(|c| {
+                let domain = TwoAdicMultiplicativeCoset {
+                    log_n,
+                    shift: Val::one(),
+                };
+                (domain, dft.dft_batch(c.clone()).to_row_major_matrix())
+            })
+            .collect_vec();
+        let shifted = izip!(&coeffs, shifts)
+            .map(|(c, shift)| {
+                let domain = TwoAdicMultiplicativeCoset { log_n, shift };
+                (
+                    domain,
+                    dft.coset_dft_batch(c.clone(), shift).to_row_major_matrix(),
+                )
+            })
+            .collect_vec();
+
+        let (_, natural_data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, natural);
+        let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, shifted.clone());
+
+        let zeta: Challenge = challenger.clone().sample_ext_element();
+        let points = vec![vec![zeta]; shifts.len()];
+        let (natural_values, _) = pcs.open(
+            vec![(&natural_data, points.clone())],
+            &mut challenger.clone(),
+        );
+        let (values, proof) = pcs.open(vec![(&data, points)], &mut challenger.clone());
+        assert_eq!(values, natural_values);
+
+        let claims = izip!(&shifted, &values[0])
+            .map(|((domain, _), mat_values)| (*domain, vec![(zeta, mat_values[0].clone())]))
+            .collect_vec();
+        pcs.verify(vec![(commit, claims)], &proof, &mut challenger.clone())
+            .unwrap();
+    }
+
+    #[test]
+    fn open_rotations_matches_open() {
+        let (pcs, challenger) = get_pcs(1, 1, 0, FRI);
+        let mut rng = seeded_rng();
+        // Each matrix is opened at a point no other matrix is (`zeta * g^5` of the taller one, and
+        // `zeta * g` of the shorter one, which is `zeta * g^2` of the taller one), so neither is
+        // interpolated from precomputed inverse denominators, but by sharing the weights of `zeta`
+        // between its rotations.
+        let domains_and_rotations =
+            [(3, vec![0, 1]), (4, vec![0, 2, 5])].map(|(log_n, rotations)| {
+                let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    1 << log_n,
+                );
+                (domain, rotations)
+            });
+        let evaluations = domains_and_rotations
+            .iter()
+            .map(|(domain, _)| {
+                (
+                    *domain,
+                    RowMajorMatrix::<Val>::rand(&mut rng, domain.size(), 4),
+                )
+            })
+            .collect_vec();
+        let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations);
+
+        let zeta: Challenge = challenger.clone().sample_ext_element();
+        let claimed_points = domains_and_rotations
+            .iter()
+            .map(|(domain, rotations)| {
+                rotations
+                    .iter()
+                    .map(|&r| (0..r).fold(zeta, |x, _| domain.next_point(x).unwrap()))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let (values, _) = pcs.open(
+            vec![(&data, claimed_points.clone())],
+            &mut challenger.clone(),
+        );
+        let (rotated_values, proof) = pcs.open_rotations(
+            vec![(&data, domains_and_rotations.to_vec())],
+            zeta,
+            &mut challenger.clone(),
+        );
+        assert_eq!(rotated_values, values);
+
+        let claims = izip!(&domains_and_rotations, claimed_points, &rotated_values[0])
+            .map(|((domain, _), points, mat_values)| {
+                (*domain, izip!(points, mat_values.clone()).collect_vec())
+            })
+            .collect_vec();
+        pcs.verify(vec![(commit, claims)], &proof, &mut challenger.clone())
+            .unwrap();
+    }
+
+    type MyProof = <MyPcs as Pcs<Challenge, Challenger>>::Proof;
+
+    #[test]
+    fn proof_bytes_round_trip() {
+        for ldt in [FRI, STIR] {
+            let (pcs, challenger) = get_pcs(1, 2, 0, ldt);
+            let mut rng = seeded_rng();
+            let domain =
+                <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+            let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 5, 3);
+            let (commit, data) =
+                <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+            let zeta: Challenge = challenger.clone().sample_ext_element();
+            let (values, proof) =
+                pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger.clone());
+
+            let bytes = proof.to_bytes().unwrap();
+            let decoded = MyProof::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded.to_bytes().unwrap(), bytes);
+
+            let claims = vec![(
+                commit,
+                vec![(domain, vec![(zeta, values[0][0][0].clone())])],
+            )];
+            pcs.verify(claims, &decoded, &mut challenger.clone())
+                .unwrap();
+
+            assert!(MyProof::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        }
+    }
+
+    #[test]
+    fn proof_stats() {
+        for ldt in [FRI, STIR] {
+            let (pcs, challenger) = get_pcs(1, 1, 0, ldt);
+            let mut rng = seeded_rng();
+            let log_n = 6;
+            let domain =
+                <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+            let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3);
+            let (_, data) =
+                <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+            let zeta: Challenge = challenger.clone().sample_ext_element();
+            let (_, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger.clone());
+
+            let stats = proof.stats().unwrap();
+            assert_eq!(stats.total, proof.to_bytes().unwrap().len());
+            assert!(!stats.query_openings_per_layer.is_empty());
+            assert!(stats.query_openings_per_layer.iter().all(|&n| n > 0));
+            assert!(stats.input_openings > 0);
+            assert_eq!(stats.ood_answers > 0, ldt != FRI);
+            let components = stats.commit_phase_commitments
+                + stats.query_openings_per_layer.iter().sum::<usize>()
+                + stats.input_openings
+                + stats.final_poly
+                + stats.ood_answers
+                + stats.pow_witnesses;
+            assert!(components < stats.total);
+        }
+    }
+
+    // The tallest LDEs here are of height 2^6 with a blowup of 1, so opening them misses the
+    // cache, while the others are prefixes of it.
+    mod cached_blowup_1 {
+        make_tests_for_pcs!({
+            let (pcs, challenger) = super::get_pcs(1, 1, 0, super::FRI);
+            (pcs.with_cache(5), challenger)
+        });
+    }
+    mod cached_blowup_2_arity_4 {
+        make_tests_for_pcs!({
+            let (pcs, challenger) = super::get_pcs(2, 2, 0, super::FRI);
+            (pcs.with_cache(7), challenger)
+        });
+    }
+
+    #[test]
+    fn cached_pcs_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&get_pcs(1, 1, 0, FRI).0.with_cache(5));
+        assert_send_sync(&get_pcs(1, 1, 0, FRI).0.with_inverse_denominator_cache(4));
+    }
+
+    #[test]
+    fn stages_observed_out_of_order_change_the_transcript() {
+        let (pcs, challenger) = get_pcs(1, 1, 0, FRI);
+        let mut rng = seeded_rng();
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 4);
+        let mut stage_evals = || vec![(domain, RowMajorMatrix::<Val>::rand(&mut rng, 1 << 4, 2))];
+
+        let mut p_challenger = challenger.clone();
+        let (commit_0, _) = <MyPcs as Pcs<Challenge, Challenger>>::commit_stage(
+            &pcs,
+            0,
+            stage_evals(),
+            &mut p_challenger,
+        );
+        let (commit_1, _) = <MyPcs as Pcs<Challenge, Challenger>>::commit_stage(
+            &pcs,
+            1,
+            stage_evals(),
+            &mut p_challenger,
+        );
+        let prover_challenge: Challenge = p_challenger.sample_ext_element();
+
+        let verifier_challenge = |stages: [(usize, _); 2]| -> Challenge {
+            let mut v_challenger = challenger.clone();
+            for (stage, commit) in stages {
+                <MyPcs as Pcs<Challenge, Challenger>>::observe_stage(
+                    &pcs,
+                    stage,
+                    commit,
+                    &mut v_challenger,
+                );
+            }
+            v_challenger.sample_ext_element()
+        };
+        assert_eq!(
+            verifier_challenge([(0, &commit_0), (1, &commit_1)]),
+            prover_challenge
+        );
+        // The same commitments, in the same order, but labelled as other stages.
+        assert_ne!(
+            verifier_challenge([(1, &commit_0), (0, &commit_1)]),
+            prover_challenge
+        );
+        assert_ne!(
+            verifier_challenge([(0, &commit_0), (2, &commit_1)]),
+            prover_challenge
+        );
+        // The commitments in the wrong order.
+        assert_ne!(
+            verifier_challenge([(0, &commit_1), (1, &commit_0)]),
+            prover_challenge
+        );
+    }
+
+    #[test]
+    fn reopening_at_a_point_hits_the_inverse_denominator_cache() {
+        let (pcs, challenger) = get_pcs(1, 1, 0, FRI);
+        let pcs = pcs.with_inverse_denominator_cache(4);
+        let mut rng = seeded_rng();
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+        let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << 5, 3);
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+        let zeta: Challenge = challenger.clone().sample_ext_element();
+
+        let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger.clone());
+        assert_eq!(pcs.inverse_denominator_cache_hits(), 0);
+        let (reopened_values, reopened_proof) =
+            pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger.clone());
+        assert_eq!(pcs.inverse_denominator_cache_hits(), 1);
+        assert_eq!(reopened_values, values);
+
+        let claims = vec![(
+            commit,
+            vec![(domain, vec![(zeta, values[0][0][0].clone())])],
+        )];
+        pcs.verify(claims.clone(), &proof, &mut challenger.clone())
+            .unwrap();
+        pcs.verify(claims, &reopened_proof, &mut challenger.clone())
+            .unwrap();
+
+        // A point not yet opened at misses the cache.
+        let (_, _) = pcs.open(
+            vec![(&data, vec![vec![zeta.double()]])],
+            &mut challenger.clone(),
+        );
+        assert_eq!(pcs.inverse_denominator_cache_hits(), 1);
+    }
+
+    /// A backend folding and accumulating one row at a time, standing in for an external one.
+    struct RowByRowBackend;
+
+    impl<Val: Field, Challenge: TwoAdicField + ExtensionField<Val>>
+        TwoAdicFriBackend<Val, Challenge> for RowByRowBackend
+    {
+        fn fold_matrix<M: Matrix<Challenge>>(&self, beta: Challenge, m: M) -> Vec<Challenge> {
+            let g = TwoAdicFriGenericConfig::<(), ()>(PhantomData);
+            let log_height = log2_strict_usize(m.height());
+            (0..m.height())
+                .map(|i| g.fold_row(i, log_height, beta, m.row(i)))
+                .collect()
+        }
+
+        fn accumulate_quotient_rows(
+            &self,
+            mat: &RowMajorMatrixView<Val>,
+            alpha: Challenge,
+            alpha_pow_offset: Challenge,
+            reduced_ys: Challenge,
+            inv_denoms: &[Challenge],
+            reduced_opening: &mut [Challenge],
+        ) {
+            for (i, ro) in reduced_opening.iter_mut().enumerate() {
+                let reduced_row: Challenge = alpha
+                    .powers()
+                    .zip(mat.row(i))
+                    .map(|(alpha_pow, v)| alpha_pow * v)
+                    .sum();
+                *ro += alpha_pow_offset * (reduced_row - reduced_ys) * inv_denoms[i];
+            }
+        }
+    }
+
+    mod row_by_row_backend_blowup_1_arity_4 {
+        make_tests_for_pcs!({
+            let (pcs, challenger) = super::get_pcs(1, 2, 0, super::FRI);
+            (pcs.with_backend(super::RowByRowBackend), challenger)
+        });
+    }
+    mod row_by_row_backend_blowup_2 {
+        make_tests_for_pcs!({
+            let (pcs, challenger) = super::get_pcs(2, 1, 0, super::FRI);
+            (pcs.with_backend(super::RowByRowBackend), challenger)
+        });
+    }
+
+    const STIR: LowDegreeTest = LowDegreeTest::Stir { num_ood_samples: 1 };
+    const STIR_TWO_OOD_SAMPLES: LowDegreeTest = LowDegreeTest::Stir { num_ood_samples: 2 };
+
+    mod pruned_blowup_1 {
+        make_tests_for_pcs!(super::get_pcs_with_pruning(1, 1, 0, super::FRI, true));
+    }
+    mod pruned_blowup_2_arity_4 {
+        make_tests_for_pcs!(super::get_pcs_with_pruning(2, 2, 0, super::FRI, true));
+    }
+    mod pruned_stir_blowup_1_arity_4 {
+        make_tests_for_pcs!(super::get_pcs_with_pruning(1, 2, 0, super::STIR, true));
+    }
+
+    #[test]
+    fn pruned_openings_are_smaller() {
+        let mut rng = seeded_rng();
+        let log_n = 8;
+        let evals = RowMajorMatrix::<Val>::rand(&mut rng, 1 << log_n, 3);
+        let sizes = [false, true].map(|prune_openings| {
+            let (pcs, challenger) = get_pcs_with_pruning(1, 1, 0, FRI, prune_openings);
+            let domain =
+                <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_n);
+            let (_, data) =
+                <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals.clone())]);
+            let zeta: Challenge = challenger.clone().sample_ext_element();
+            let (_, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger.clone());
+            proof.stats().unwrap()
+        });
+        let [full, pruned] = sizes;
+        assert_eq!(full.final_poly, pruned.final_poly);
+        assert!(pruned.input_openings < full.input_openings);
+        assert!(
+            pruned.query_openings_per_layer.iter().sum::<usize>()
+                < full.query_openings_per_layer.iter().sum::<usize>()
+        );
+    }
+
+    mod stir_blowup_1_arity_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 2, 0, super::STIR));
+    }
+    mod stir_blowup_2_arity_8 {
+        make_tests_for_pcs!(super::get_pcs(2, 3, 0, super::STIR));
+    }
+    mod stir_blowup_1_arity_2_final_poly_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 1, 2, super::STIR));
+    }
+    mod stir_blowup_1_arity_4_two_ood_samples {
+        make_tests_for_pcs!(super::get_pcs(1, 2, 0, super::STIR_TWO_OOD_SAMPLES));
+    }
+
+    fn get_hiding_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+        let (pcs, challenger) = get_pcs(log_blowup, 1, 0, FRI);
+        (
+            pcs.with_hiding(4, &mut ChaCha20Rng::seed_from_u64(1)),
+            challenger,
+        )
+    }
+
+    mod hiding_blowup_1 {
+        make_tests_for_pcs!(super::get_hiding_pcs(1));
+    }
+    mod hiding_blowup_2 {
+        make_tests_for_pcs!(super::get_hiding_pcs(2));
+    }
+    mod hiding_stir_blowup_1_arity_4 {
+        make_tests_for_pcs!({
+            let (pcs, challenger) = super::get_pcs(1, 2, 0, super::STIR);
+            (
+                pcs.with_hiding(2, &mut super::ChaCha20Rng::seed_from_u64(1)),
+                challenger,
+            )
+        });
+    }
+
+    #[test]
+    fn hiding_commitments_are_randomized() {
+        let (pcs, _) = get_hiding_pcs(1);
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 8);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 8, 5);
+        let (commit_1, _) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals.clone())]);
+        let (commit_2, _) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+        assert_ne!(commit_1, commit_2);
+    }
+
+    #[test]
+    fn hiding_proofs_dont_verify_without_hiding() {
+        let (hiding_pcs, challenger) = get_hiding_pcs(1);
+        let (pcs, _) = get_pcs(1, 1, 0, FRI);
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 8);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 8, 5);
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&hiding_pcs, vec![(domain, evals)]);
+        let zeta: Challenge = challenger.clone().sample_ext_element();
+        let (values, proof) =
+            hiding_pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger.clone());
+        assert!(proof.random_codewords.is_some());
+
+        let claims = vec![(
+            commit,
+            vec![(domain, vec![(zeta, values[0][0][0].clone())])],
+        )];
+        hiding_pcs
+            .verify(claims.clone(), &proof, &mut challenger.clone())
+            .unwrap();
+        assert!(pcs.verify(claims, &proof, &mut challenger.clone()).is_err());
+    }
+
+    #[test]
+    fn hiding_pcs_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&get_hiding_pcs(1).0);
+    }
+}
+
+/// BabyBear with a quintic extension as the challenge field, for more than 128 bits of conjectured
+/// FRI security.
+mod babybear_quintic_fri_pcs {
+    use super::*;
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 5>;
+
+    type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+
+    type ValMmcs = FieldMerkleTreeMmcs<
+        <Val as Field>::Packing,
+        <Val as Field>::Packing,
+        MyHash,
+        MyCompress,
+        8,
+    >;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+    fn get_pcs(log_blowup: usize, max_log_arity: usize) -> (MyPcs, Challenger) {
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut seeded_rng(),
+        );
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+        let fri_config = FriConfig {
+            log_blowup,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            max_log_arity,
+            log_final_poly_len: 0,
+            ldt: LowDegreeTest::Fri,
+            prune_openings: false,
+            mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+        };
+        let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+        (pcs, Challenger::new(perm))
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1, 1));
+    }
+    mod blowup_2_arity_4 {
+        make_tests_for_pcs!(super::get_pcs(2, 2));
     }
 }
 
 mod m31_fri_pcs {
     use p3_challenger::{HashChallenger, SerializingChallenger32};
-    use p3_circle::{Cfft, CirclePcs};
+    use p3_circle::CirclePcs;
     use p3_keccak::Keccak256Hash;
     use p3_mersenne_31::Mersenne31;
     use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
@@ -239,13 +939,13 @@ mod m31_fri_pcs {
             log_blowup,
             num_queries: 10,
             proof_of_work_bits: 8,
+            max_log_arity: 1,
+            log_final_poly_len: 0,
+            ldt: LowDegreeTest::Fri,
+            prune_openings: false,
             mmcs: challenge_mmcs,
         };
-        let pcs = Pcs {
-            cfft: Cfft::default(),
-            mmcs: val_mmcs,
-            fri_config,
-        };
+        let pcs = Pcs::new(val_mmcs, fri_config);
         (pcs, Challenger::from_hasher(vec![], byte_hash))
     }
 
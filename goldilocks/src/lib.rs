@@ -8,29 +8,36 @@ mod extension;
 mod mds;
 mod poseidon2;
 
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod aarch64_neon;
+
+use alloc::vec::Vec;
 use core::fmt;
 use core::fmt::{Debug, Display, Formatter};
 use core::hash::{Hash, Hasher};
 use core::iter::{Product, Sum};
 use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub use aarch64_neon::*;
 pub use mds::*;
 use num_bigint::BigUint;
 use p3_field::{
-    exp_10540996611094048183, exp_u64_by_squaring, halve_u64, AbstractField, Field, Packable,
-    PrimeField, PrimeField64, TwoAdicField,
+    exp_10540996611094048183, exp_u64_by_squaring, halve_u64, AbstractField, Embeds, Field,
+    Packable, PrimeField, PrimeField32, PrimeField64, TwoAdicField,
 };
 use p3_util::{assume, branch_hint};
 pub use poseidon2::*;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// The Goldilocks prime
 const P: u64 = 0xFFFF_FFFF_0000_0001;
 
 /// The prime field known as Goldilocks, defined as `F_p` where `p = 2^64 - 2^32 + 1`.
-#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default)]
+#[repr(transparent)] // Packed field implementations rely on this!
 pub struct Goldilocks {
     /// Not necessarily canonical.
     value: u64,
@@ -45,6 +52,26 @@ impl Goldilocks {
     const NEG_ORDER: u64 = Self::ORDER_U64.wrapping_neg();
 }
 
+impl Serialize for Goldilocks {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.as_canonical_u64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Goldilocks {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let val = u64::deserialize(d)?;
+        // Reject unreduced values, so that each element has a single encoding.
+        if val < P {
+            Ok(Self::new(val))
+        } else {
+            Err(serde::de::Error::custom(
+                "value is not less than the field order",
+            ))
+        }
+    }
+}
+
 impl PartialEq for Goldilocks {
     fn eq(&self, other: &Self) -> bool {
         self.as_canonical_u64() == other.as_canonical_u64()
@@ -161,7 +188,10 @@ impl AbstractField for Goldilocks {
 }
 
 impl Field for Goldilocks {
-    // TODO: Add cfg-guarded Packing for AVX2, NEON, etc.
+    // TODO: Add cfg-guarded Packing for AVX2.
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    type Packing = crate::PackedGoldilocksNeon;
+    #[cfg(not(all(target_arch = "aarch64", target_feature = "neon")))]
     type Packing = Self;
 
     fn is_zero(&self) -> bool {
@@ -177,7 +207,9 @@ impl Field for Goldilocks {
     }
 
     fn try_inverse(&self) -> Option<Self> {
-        if self.is_zero() {
+        // With the `ct` feature, zero is only rejected after the exponentiation, so that the
+        // running time doesn't reveal whether `self` is zero.
+        if !cfg!(feature = "ct") && self.is_zero() {
             return None;
         }
 
@@ -218,7 +250,7 @@ impl Field for Goldilocks {
         let t63 = exp_acc::<32>(t31, t31);
 
         // compute base^1111111111111111111111111111111011111111111111111111111111111111
-        Some(t63.square() * *self)
+        (!self.is_zero()).then(|| t63.square() * *self)
     }
 
     #[inline]
@@ -236,6 +268,26 @@ impl PrimeField for Goldilocks {
     fn as_canonical_biguint(&self) -> BigUint {
         <Self as PrimeField64>::as_canonical_u64(self).into()
     }
+
+    fn num_canonical_bytes() -> usize {
+        core::mem::size_of::<u64>()
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.as_canonical_u64().to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u64::from_le_bytes(bytes.try_into().ok()?);
+        (value < Self::ORDER_U64).then(|| Self::from_canonical_u64(value))
+    }
+}
+
+/// Every prime field of order less than `2^32` embeds in Goldilocks.
+impl<F: PrimeField32> Embeds<F> for Goldilocks {
+    fn embed(value: F) -> Self {
+        Self::new(value.as_canonical_u64())
+    }
 }
 
 impl PrimeField64 for Goldilocks {
@@ -245,7 +297,9 @@ impl PrimeField64 for Goldilocks {
     fn as_canonical_u64(&self) -> u64 {
         let mut c = self.value;
         // We only need one condition subtraction, since 2 * ORDER would not fit in a u64.
-        if c >= Self::ORDER_U64 {
+        if cfg!(feature = "ct") {
+            c -= u64::from(c >= Self::ORDER_U64) * Self::ORDER_U64;
+        } else if c >= Self::ORDER_U64 {
             c -= Self::ORDER_U64;
         }
         c
@@ -269,7 +323,9 @@ impl Add for Goldilocks {
     fn add(self, rhs: Self) -> Self {
         let (sum, over) = self.value.overflowing_add(rhs.value);
         let (mut sum, over) = sum.overflowing_add(u64::from(over) * Self::NEG_ORDER);
-        if over {
+        if cfg!(feature = "ct") {
+            sum += u64::from(over) * Self::NEG_ORDER; // Cannot overflow.
+        } else if over {
             // NB: self.value > Self::ORDER && rhs.value > Self::ORDER is necessary but not
             // sufficient for double-overflow.
             // This assume does two things:
@@ -307,7 +363,9 @@ impl Sub for Goldilocks {
     fn sub(self, rhs: Self) -> Self {
         let (diff, under) = self.value.overflowing_sub(rhs.value);
         let (mut diff, under) = diff.overflowing_sub(u64::from(under) * Self::NEG_ORDER);
-        if under {
+        if cfg!(feature = "ct") {
+            diff -= u64::from(under) * Self::NEG_ORDER; // Cannot underflow.
+        } else if under {
             // NB: self.value < NEG_ORDER - 1 && rhs.value > ORDER is necessary but not
             // sufficient for double-underflow.
             // This assume does two things:
@@ -381,7 +439,9 @@ pub(crate) fn reduce128(x: u128) -> Goldilocks {
     let x_hi_lo = x_hi & Goldilocks::NEG_ORDER;
 
     let (mut t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
-    if borrow {
+    if cfg!(feature = "ct") {
+        t0 -= u64::from(borrow) * Goldilocks::NEG_ORDER; // Cannot underflow.
+    } else if borrow {
         branch_hint(); // A borrow is exceedingly rare. It is faster to branch.
         t0 -= Goldilocks::NEG_ORDER; // Cannot underflow.
     }
@@ -454,7 +514,8 @@ pub(crate) const fn to_goldilocks_array<const N: usize>(input: [u64; N]) -> [Gol
 
 #[cfg(test)]
 mod tests {
-    use p3_field_testing::{test_field, test_two_adic_field};
+    use p3_baby_bear::BabyBear;
+    use p3_field_testing::{test_field, test_prime_field, test_two_adic_field};
 
     use super::*;
 
@@ -542,6 +603,43 @@ mod tests {
         assert_eq!(f_2.exp_u64(10540996611094048183).exp_const_u64::<7>(), f_2);
     }
 
+    #[test]
+    fn test_non_canonical_add_sub() {
+        // Non-canonical inputs exercise the double overflow and underflow corrections, which
+        // take a different code path with the `ct` feature.
+        let values = [
+            0,
+            1,
+            F::ORDER_U64 - 1,
+            F::ORDER_U64,
+            F::ORDER_U64 + 1,
+            u64::MAX,
+        ];
+        for &x in &values {
+            for &y in &values {
+                let (a, b) = (F::new(x), F::new(y));
+                let (a_canon, b_canon) = (F::new(x % F::ORDER_U64), F::new(y % F::ORDER_U64));
+                assert_eq!(a + b, a_canon + b_canon);
+                assert_eq!(a - b, a_canon - b_canon);
+            }
+        }
+        assert_eq!(F::zero().try_inverse(), None);
+        assert_eq!(F::new(F::ORDER_U64).try_inverse(), None);
+    }
+
+    #[test]
+    fn test_embeddings() {
+        assert_eq!(F::embed(BabyBear::zero()), F::zero());
+        assert_eq!(
+            F::embed(BabyBear::neg_one()),
+            F::from_canonical_u32(BabyBear::ORDER_U32 - 1)
+        );
+
+        assert_eq!(F::try_from_canonical_u64(P - 1), Some(F::neg_one()));
+        assert_eq!(F::try_from_canonical_u64(P), None);
+    }
+
     test_field!(crate::Goldilocks);
+    test_prime_field!(crate::Goldilocks);
     test_two_adic_field!(crate::Goldilocks);
 }
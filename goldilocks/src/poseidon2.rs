@@ -237,7 +237,10 @@ pub const HL_GOLDILOCKS_8_INTERNAL_ROUND_CONSTANTS: [u64; 22] = [
 mod tests {
     use core::array;
 
-    use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixHL};
+    use p3_field::{Field, PackedValue};
+    use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral, Poseidon2ExternalMatrixHL};
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoroshiro128Plus;
 
     use super::*;
 
@@ -360,4 +363,31 @@ mod tests {
         hl_poseidon2_goldilocks_width_8(&mut input);
         assert_eq!(input, expected);
     }
+
+    /// The permutation of a packed state must permute each lane as the scalar permutation does.
+    #[test]
+    fn test_poseidon2_width_16_packed_matches_scalar() {
+        type P = <F as Field>::Packing;
+        let mut rng = Xoroshiro128Plus::seed_from_u64(1);
+        let poseidon2: Poseidon2<
+            F,
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixGoldilocks,
+            16,
+            7,
+        > = Poseidon2::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixGoldilocks,
+            &mut rng,
+        );
+
+        let inputs: [[F; 16]; P::WIDTH] = rng.gen();
+        let mut packed: [P; 16] = array::from_fn(|i| P::from_fn(|lane| inputs[lane][i]));
+        poseidon2.permute_mut(&mut packed);
+
+        for (lane, input) in inputs.into_iter().enumerate() {
+            let expected = poseidon2.permute(input);
+            assert_eq!(packed.map(|p| p.as_slice()[lane]), expected);
+        }
+    }
 }
@@ -4,6 +4,7 @@
 
 extern crate alloc;
 
+use alloc::vec;
 use alloc::vec::Vec;
 
 use p3_field::{
@@ -32,9 +33,134 @@ where
     EF: ExtensionField<F> + TwoAdicField,
     Mat: Matrix<F>,
 {
-    // Slight variation of this approach: https://hackmd.io/@vbuterin/barycentric_evaluation
+    let (col_scale, scale) = barycentric_weights(coset_evals.height(), shift, point);
+    let sum = coset_evals.columnwise_dot_product(&col_scale);
+    scale_vec(scale, sum)
+}
+
+/// Like `interpolate_coset`, but with the coset points, and `1 / (x - point)` for each coset point
+/// `x`, precomputed by the caller, e.g. a PCS which needs the same inverses for its quotients.
+///
+/// `coset` may list the coset `shift * H` in any order, such as bit-reversed, so long as
+/// `coset_evals` and `inv_denoms` list their rows in the same order.
+pub fn interpolate_coset_with_precomputation<F, EF, Mat>(
+    coset_evals: &Mat,
+    shift: F,
+    point: EF,
+    coset: &[F],
+    inv_denoms: &[EF],
+) -> Vec<EF>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    Mat: Matrix<F>,
+{
+    let height = coset_evals.height();
+    debug_assert_eq!(coset.len(), height);
+    debug_assert_eq!(inv_denoms.len(), height);
+    let log_height = log2_strict_usize(height);
+
+    // With x = shift g^i, the weight g^i / (point - x) of `barycentric_weights` is
+    // -x / (shift (x - point)), and the 1 / shift moves into the common scale factor.
+    let col_scale: Vec<EF> = coset
+        .iter()
+        .zip(inv_denoms)
+        .map(|(&x, &inv_denom)| inv_denom * x)
+        .collect();
+    let sum = coset_evals.columnwise_dot_product(&col_scale);
+
+    let zerofier = two_adic_coset_zerofier::<EF>(log_height, EF::from_base(shift), point);
+    let denominator = F::from_canonical_usize(height) * shift.exp_u64(height as u64);
+    scale_vec(-zerofier * denominator.inverse(), sum)
+}
+
+/// Given evaluations of a batch of polynomials over the given coset of the canonical power-of-two
+/// subgroup, evaluate the polynomials at each of `points`.
+///
+/// This is equivalent to calling `interpolate_coset` once per point, but the coset is enumerated
+/// once, and the inversions for all points are batched together.
+pub fn interpolate_coset_multi_point<F, EF, Mat>(
+    coset_evals: &Mat,
+    shift: F,
+    points: &[EF],
+) -> Vec<Vec<EF>>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    Mat: Matrix<F>,
+{
+    let height = coset_evals.height();
+    let log_height = log2_strict_usize(height);
+    let g = F::two_adic_generator(log_height);
+
+    let coset: Vec<F> = cyclic_subgroup_coset_known_order(g, shift, height).collect();
+    let diffs: Vec<EF> = points
+        .iter()
+        .flat_map(|&point| coset.iter().map(move |&x| point - x))
+        .collect();
+    let diff_invs = batch_multiplicative_inverse(&diffs);
 
+    let subgroup: Vec<F> = g.powers().take(height).collect();
+    let denominator = F::from_canonical_usize(height) * shift.exp_u64(height as u64 - 1);
+    let denominator_inv = denominator.inverse();
+
+    points
+        .iter()
+        .zip(diff_invs.chunks_exact(height))
+        .map(|(&point, diff_invs)| {
+            let col_scale: Vec<EF> = diff_invs
+                .iter()
+                .zip(&subgroup)
+                .map(|(&diff_inv, &sg)| diff_inv * sg)
+                .collect();
+            let sum = coset_evals.columnwise_dot_product(&col_scale);
+            let zerofier = two_adic_coset_zerofier::<EF>(log_height, EF::from_base(shift), point);
+            scale_vec(zerofier * denominator_inv, sum)
+        })
+        .collect()
+}
+
+/// Given evaluations of a batch of polynomials over the given coset of the canonical power-of-two
+/// subgroup, evaluate the polynomials at `point * g^r` for each rotation `r`, where `g` generates
+/// the subgroup.
+///
+/// The barycentric weights of `point` are computed once and shared between all rotations, since
+/// evaluating `p` at `point * g^r` is evaluating `p(g^r X)` at `point`, and the evaluations of
+/// `p(g^r X)` over the coset are those of `p`, rotated by `r` rows.
+pub fn interpolate_coset_rotations<F, EF, Mat>(
+    coset_evals: &Mat,
+    shift: F,
+    point: EF,
+    rotations: &[usize],
+) -> Vec<Vec<EF>>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    Mat: Matrix<F>,
+{
     let height = coset_evals.height();
+    let (col_scale, scale) = barycentric_weights(height, shift, point);
+    rotations
+        .iter()
+        .map(|&rotation| {
+            let mut rotated_col_scale = col_scale.clone();
+            rotated_col_scale.rotate_right(rotation % height);
+            let sum = coset_evals.columnwise_dot_product(&rotated_col_scale);
+            scale_vec(scale, sum)
+        })
+        .collect()
+}
+
+/// For a coset of the canonical subgroup of size `height`, the per-row weights, and the common
+/// scale factor, such that the evaluation at `point` of a polynomial is the dot product of the
+/// weights with its evaluations over the coset, times the scale factor.
+fn barycentric_weights<F, EF>(height: usize, shift: F, point: EF) -> (Vec<EF>, EF)
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+{
+    // Slight variation of this approach: https://hackmd.io/@vbuterin/barycentric_evaluation
+
     let log_height = log2_strict_usize(height);
     let g = F::two_adic_generator(log_height);
 
@@ -51,11 +177,83 @@ where
         .map(|(sg, diff_inv)| diff_inv * sg)
         .collect();
 
-    let sum = coset_evals.columnwise_dot_product(&col_scale);
-
     let zerofier = two_adic_coset_zerofier::<EF>(log_height, EF::from_base(shift), point);
     let denominator = F::from_canonical_usize(height) * shift.exp_u64(height as u64 - 1);
-    scale_vec(zerofier * denominator.inverse(), sum)
+    (col_scale, zerofier * denominator.inverse())
+}
+
+/// The coefficients, lowest degree first, of the polynomial of degree less than `points.len()`
+/// taking each of `values` at the corresponding one of `points`, which must be distinct.
+///
+/// Unlike the coset routines above, this works over any set of points, in quadratic time.
+pub fn interpolate_lagrange_coeffs<F: Field>(points: &[F], values: &[F]) -> Vec<F> {
+    assert_eq!(points.len(), values.len());
+    let n = points.len();
+
+    // The coefficients of prod_i (X - x_i).
+    let mut vanishing = vec![F::one()];
+    for &x in points {
+        vanishing.insert(0, F::zero());
+        for j in 0..vanishing.len() - 1 {
+            let next = vanishing[j + 1];
+            vanishing[j] -= next * x;
+        }
+    }
+
+    let weights = lagrange_weights(points);
+    let mut coeffs = vec![F::zero(); n];
+    let mut quotient = vec![F::zero(); n];
+    for ((&x, &y), weight) in points.iter().zip(values).zip(weights) {
+        // Divide the vanishing polynomial by X - x, from the top down.
+        let mut carry = F::zero();
+        for j in (0..n).rev() {
+            carry = vanishing[j + 1] + carry * x;
+            quotient[j] = carry;
+        }
+        let scale = y * weight;
+        coeffs
+            .iter_mut()
+            .zip(&quotient)
+            .for_each(|(c, &q)| *c += q * scale);
+    }
+    coeffs
+}
+
+/// Evaluate at `point` the polynomial of degree less than `points.len()` taking each of `values`
+/// at the corresponding one of `points`, which must be distinct, without finding its coefficients.
+pub fn interpolate_lagrange_at<F: Field>(points: &[F], values: &[F], point: F) -> F {
+    assert_eq!(points.len(), values.len());
+    if let Some(i) = points.iter().position(|&x| x == point) {
+        return values[i];
+    }
+
+    let diffs: Vec<F> = points.iter().map(|&x| point - x).collect();
+    let vanishing: F = diffs.iter().copied().product();
+    let diff_invs = batch_multiplicative_inverse(&diffs);
+    let sum: F = lagrange_weights(points)
+        .into_iter()
+        .zip(values)
+        .zip(diff_invs)
+        .map(|((weight, &y), diff_inv)| weight * y * diff_inv)
+        .sum();
+    vanishing * sum
+}
+
+/// The barycentric weights `1 / prod_{j != i} (x_i - x_j)` of distinct `points`.
+fn lagrange_weights<F: Field>(points: &[F]) -> Vec<F> {
+    let denominators: Vec<F> = points
+        .iter()
+        .enumerate()
+        .map(|(i, &x_i)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &x_j)| x_i - x_j)
+                .product()
+        })
+        .collect();
+    batch_multiplicative_inverse(&denominators)
 }
 
 /// `x += y * s`, where `s` is a scalar.
@@ -72,12 +270,23 @@ where
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use alloc::vec::Vec;
 
     use p3_baby_bear::BabyBear;
-    use p3_field::AbstractField;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{
+        cyclic_subgroup_coset_known_order, eval_poly, AbstractExtensionField, AbstractField, Field,
+        TwoAdicField,
+    };
     use p3_matrix::dense::RowMajorMatrix;
+    use p3_util::reverse_slice_index_bits;
+    use rand::{thread_rng, Rng};
 
-    use crate::{interpolate_coset, interpolate_subgroup};
+    use crate::{
+        interpolate_coset, interpolate_coset_multi_point, interpolate_coset_rotations,
+        interpolate_coset_with_precomputation, interpolate_lagrange_at,
+        interpolate_lagrange_coeffs, interpolate_subgroup,
+    };
 
     #[test]
     fn test_interpolate_subgroup() {
@@ -107,4 +316,88 @@ mod tests {
         let result = interpolate_coset(&evals_mat, shift, point);
         assert_eq!(result, vec![F::from_canonical_u32(10203)]);
     }
+
+    #[test]
+    fn test_interpolate_coset_rotations() {
+        // x^2 + 2 x + 3
+        type F = BabyBear;
+        let shift = F::generator();
+        let evals = [
+            1026, 129027310, 457985035, 994890337, 902, 1988942953, 1555278970, 913671254,
+        ]
+        .map(F::from_canonical_u32);
+        let evals_mat = RowMajorMatrix::new(evals.to_vec(), 1);
+        let point = F::from_canonical_u32(100);
+        let g = F::two_adic_generator(3);
+        let result = interpolate_coset_rotations(&evals_mat, shift, point, &[0, 1, 9]);
+        let expected = [0, 1, 9]
+            .map(|rotation| {
+                let x = point * g.exp_u64(rotation);
+                vec![x * x + x.double() + F::from_canonical_u32(3)]
+            })
+            .to_vec();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_interpolate_coset_multi_point() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let shift = F::generator();
+        let mut rng = thread_rng();
+        let evals_mat = RowMajorMatrix::<F>::rand(&mut rng, 16, 3);
+        let points: Vec<EF> = (0..4).map(|_| rng.gen()).collect();
+        let result = interpolate_coset_multi_point(&evals_mat, shift, &points);
+        let expected = points
+            .iter()
+            .map(|&point| interpolate_coset(&evals_mat, shift, point))
+            .collect::<Vec<_>>();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_interpolate_coset_with_precomputation() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let shift = F::generator();
+        let mut rng = thread_rng();
+        let evals_mat = RowMajorMatrix::<F>::rand(&mut rng, 16, 3);
+        let point: EF = rng.gen();
+
+        // Bit-reverse the rows and the coset together, as a PCS would store them.
+        let mut coset = cyclic_subgroup_coset_known_order(F::two_adic_generator(4), shift, 16)
+            .collect::<Vec<_>>();
+        reverse_slice_index_bits(&mut coset);
+        let inv_denoms = coset
+            .iter()
+            .map(|&x| (EF::from_base(x) - point).inverse())
+            .collect::<Vec<_>>();
+        let mut bit_reversed = evals_mat.clone();
+        bit_reversed.bit_reverse_row_index_bits_in_place();
+
+        let result =
+            interpolate_coset_with_precomputation(&bit_reversed, shift, point, &coset, &inv_denoms);
+        assert_eq!(result, interpolate_coset(&evals_mat, shift, point));
+    }
+
+    #[test]
+    fn test_interpolate_lagrange() {
+        type F = BabyBear;
+        let mut rng = thread_rng();
+        let coeffs: Vec<F> = (0..5).map(|_| rng.gen()).collect();
+        let points = [3, 8, 1, 100, 12345].map(F::from_canonical_u32);
+        let values = points.map(|x| eval_poly(&coeffs, x));
+
+        assert_eq!(interpolate_lagrange_coeffs(&points, &values), coeffs);
+
+        let point: F = rng.gen();
+        assert_eq!(
+            interpolate_lagrange_at(&points, &values, point),
+            eval_poly(&coeffs, point)
+        );
+        assert_eq!(
+            interpolate_lagrange_at(&points, &values, points[3]),
+            values[3]
+        );
+    }
 }
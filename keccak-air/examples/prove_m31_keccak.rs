@@ -1,8 +1,8 @@
 use p3_challenger::{HashChallenger, SerializingChallenger32};
-use p3_circle::{Cfft, CirclePcs};
+use p3_circle::CirclePcs;
 use p3_commit::ExtensionMmcs;
 use p3_field::extension::BinomialExtensionField;
-use p3_fri::FriConfig;
+use p3_fri::{FriConfig, LowDegreeTest};
 use p3_keccak::Keccak256Hash;
 use p3_keccak_air::{generate_trace_rows, KeccakAir};
 use p3_matrix::Matrix;
@@ -53,15 +53,15 @@ fn main() -> Result<(), VerificationError> {
         log_blowup: 1,
         num_queries: 100,
         proof_of_work_bits: 16,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings: false,
         mmcs: challenge_mmcs,
     };
 
     type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
-    let pcs = Pcs {
-        cfft: Cfft::default(),
-        mmcs: val_mmcs,
-        fri_config,
-    };
+    let pcs = Pcs::new(val_mmcs, fri_config);
 
     type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
     let config = MyConfig::new(pcs);
@@ -0,0 +1,18 @@
+use p3_symmetric::{CryptographicPermutation, Permutation};
+use tiny_keccak::keccakf;
+
+use crate::KeccakF;
+
+/// The number of Keccak-f states permuted at once. Without a vectorized Keccak-f for this target,
+/// there is just one.
+pub const VECTOR_LEN: usize = 1;
+
+impl Permutation<[[u64; VECTOR_LEN]; 25]> for KeccakF {
+    fn permute_mut(&self, input: &mut [[u64; VECTOR_LEN]; 25]) {
+        let mut state = input.map(|[lane]| lane);
+        keccakf(&mut state);
+        *input = state.map(|lane| [lane]);
+    }
+}
+
+impl CryptographicPermutation<[[u64; VECTOR_LEN]; 25]> for KeccakF {}
@@ -0,0 +1,90 @@
+//! Keccak-f over several states at once, one in each lane of a SIMD vector.
+
+/// A vector of Keccak lanes, one from each of the states being permuted.
+pub(crate) trait Lanes: Copy {
+    fn xor(self, other: Self) -> Self;
+
+    /// `!self & other`.
+    fn andnot(self, other: Self) -> Self;
+
+    /// `self ^ rc`, in every lane.
+    fn xor_constant(self, rc: u64) -> Self;
+
+    fn rotate_left(self, n: u32) -> Self;
+}
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rotation of each lane visited by the combined rho and pi steps, in order.
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// The order in which the combined rho and pi steps visit the lanes, starting from lane 1.
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+#[inline(always)]
+pub(crate) fn keccak_f<L: Lanes>(state: &mut [L; 25]) {
+    for rc in ROUND_CONSTANTS {
+        // Theta.
+        let c: [L; 5] = core::array::from_fn(|x| {
+            state[x]
+                .xor(state[x + 5])
+                .xor(state[x + 10])
+                .xor(state[x + 15])
+                .xor(state[x + 20])
+        });
+        for x in 0..5 {
+            let d = c[(x + 4) % 5].xor(c[(x + 1) % 5].rotate_left(1));
+            for y in 0..5 {
+                state[x + 5 * y] = state[x + 5 * y].xor(d);
+            }
+        }
+
+        // Rho and pi.
+        let mut last = state[1];
+        for (&pi, &rho) in PI.iter().zip(&RHO) {
+            let next = state[pi];
+            state[pi] = last.rotate_left(rho);
+            last = next;
+        }
+
+        // Chi.
+        for y in 0..5 {
+            let row: [L; 5] = core::array::from_fn(|x| state[x + 5 * y]);
+            for x in 0..5 {
+                state[x + 5 * y] = row[x].xor(row[(x + 1) % 5].andnot(row[(x + 2) % 5]));
+            }
+        }
+
+        // Iota.
+        state[0] = state[0].xor_constant(rc);
+    }
+}
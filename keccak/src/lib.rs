@@ -1,6 +1,14 @@
 //! The Keccak-f permutation, and hash functions built from it.
 
 #![no_std]
+#![cfg_attr(
+    all(
+        feature = "nightly-features",
+        target_arch = "x86_64",
+        target_feature = "avx512f"
+    ),
+    feature(stdarch_x86_avx512)
+)]
 
 extern crate alloc;
 
@@ -9,7 +17,48 @@ use alloc::vec::Vec;
 use p3_symmetric::{CryptographicHasher, CryptographicPermutation, Permutation};
 use tiny_keccak::{keccakf, Hasher, Keccak};
 
+#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+mod lanes;
+
+#[cfg(all(
+    feature = "nightly-features",
+    target_arch = "x86_64",
+    target_feature = "avx512f"
+))]
+mod x86_64_avx512;
+#[cfg(all(
+    feature = "nightly-features",
+    target_arch = "x86_64",
+    target_feature = "avx512f"
+))]
+pub use x86_64_avx512::*;
+
+#[cfg(all(
+    target_arch = "x86_64",
+    target_feature = "avx2",
+    not(all(feature = "nightly-features", target_feature = "avx512f"))
+))]
+mod x86_64_avx2;
+#[cfg(all(
+    target_arch = "x86_64",
+    target_feature = "avx2",
+    not(all(feature = "nightly-features", target_feature = "avx512f"))
+))]
+pub use x86_64_avx2::*;
+
+#[cfg(not(all(target_arch = "x86_64", target_feature = "avx2")))]
+mod fallback;
+#[cfg(not(all(target_arch = "x86_64", target_feature = "avx2")))]
+pub use fallback::*;
+
 /// The Keccak-f permutation.
+///
+/// Besides single states, this permutes `VECTOR_LEN` states at once, given as
+/// `[[u64; VECTOR_LEN]; 25]`, using SIMD instructions where the target has them. A sponge or
+/// compression function over `[u64; VECTOR_LEN]` built from it, such as
+/// `PaddingFreeSponge<KeccakF, 25, 17, 4>`, then hashes `VECTOR_LEN` inputs per permutation, which
+/// lets a Merkle tree whose digests are packed as `[u64; VECTOR_LEN]` hash that many leaves at a
+/// time.
 #[derive(Copy, Clone, Debug)]
 pub struct KeccakF;
 
@@ -70,3 +119,22 @@ impl CryptographicHasher<u8, [u8; 32]> for Keccak256Hash {
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vectorized_keccak_f_matches_scalar() {
+        let input: [[u64; VECTOR_LEN]; 25] = core::array::from_fn(|i| {
+            core::array::from_fn(|lane| {
+                (i as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ lane as u64
+            })
+        });
+        let output = KeccakF.permute(input);
+        for lane in 0..VECTOR_LEN {
+            let expected = KeccakF.permute(input.map(|lanes| lanes[lane]));
+            assert_eq!(output.map(|lanes| lanes[lane]), expected);
+        }
+    }
+}
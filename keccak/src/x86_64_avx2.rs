@@ -0,0 +1,48 @@
+use core::arch::x86_64::*;
+use core::mem::transmute;
+
+use p3_symmetric::{CryptographicPermutation, Permutation};
+
+use crate::lanes::{keccak_f, Lanes};
+use crate::KeccakF;
+
+/// The number of Keccak-f states permuted at once, one per 64-bit lane of an AVX2 vector.
+pub const VECTOR_LEN: usize = 4;
+
+impl Lanes for __m256i {
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        unsafe { _mm256_xor_si256(self, other) }
+    }
+
+    #[inline(always)]
+    fn andnot(self, other: Self) -> Self {
+        unsafe { _mm256_andnot_si256(self, other) }
+    }
+
+    #[inline(always)]
+    fn xor_constant(self, rc: u64) -> Self {
+        unsafe { _mm256_xor_si256(self, _mm256_set1_epi64x(rc as i64)) }
+    }
+
+    #[inline(always)]
+    fn rotate_left(self, n: u32) -> Self {
+        unsafe {
+            let left = _mm256_sllv_epi64(self, _mm256_set1_epi64x(n as i64));
+            let right = _mm256_srlv_epi64(self, _mm256_set1_epi64x(64 - n as i64));
+            _mm256_or_si256(left, right)
+        }
+    }
+}
+
+impl Permutation<[[u64; VECTOR_LEN]; 25]> for KeccakF {
+    fn permute_mut(&self, input: &mut [[u64; VECTOR_LEN]; 25]) {
+        // SAFETY: `[u64; 4]` and `__m256i` have the same size, and every bit pattern is valid for
+        // both.
+        let mut state: [__m256i; 25] = input.map(|lanes| unsafe { transmute(lanes) });
+        keccak_f(&mut state);
+        *input = state.map(|vector| unsafe { transmute(vector) });
+    }
+}
+
+impl CryptographicPermutation<[[u64; VECTOR_LEN]; 25]> for KeccakF {}
@@ -0,0 +1,44 @@
+use core::arch::x86_64::*;
+use core::mem::transmute;
+
+use p3_symmetric::{CryptographicPermutation, Permutation};
+
+use crate::lanes::{keccak_f, Lanes};
+use crate::KeccakF;
+
+/// The number of Keccak-f states permuted at once, one per 64-bit lane of an AVX-512 vector.
+pub const VECTOR_LEN: usize = 8;
+
+impl Lanes for __m512i {
+    #[inline(always)]
+    fn xor(self, other: Self) -> Self {
+        unsafe { _mm512_xor_si512(self, other) }
+    }
+
+    #[inline(always)]
+    fn andnot(self, other: Self) -> Self {
+        unsafe { _mm512_andnot_si512(self, other) }
+    }
+
+    #[inline(always)]
+    fn xor_constant(self, rc: u64) -> Self {
+        unsafe { _mm512_xor_si512(self, _mm512_set1_epi64(rc as i64)) }
+    }
+
+    #[inline(always)]
+    fn rotate_left(self, n: u32) -> Self {
+        unsafe { _mm512_rolv_epi64(self, _mm512_set1_epi64(n as i64)) }
+    }
+}
+
+impl Permutation<[[u64; VECTOR_LEN]; 25]> for KeccakF {
+    fn permute_mut(&self, input: &mut [[u64; VECTOR_LEN]; 25]) {
+        // SAFETY: `[u64; 8]` and `__m512i` have the same size, and every bit pattern is valid for
+        // both.
+        let mut state: [__m512i; 25] = input.map(|lanes| unsafe { transmute(lanes) });
+        keccak_f(&mut state);
+        *input = state.map(|vector| unsafe { transmute(vector) });
+    }
+}
+
+impl CryptographicPermutation<[[u64; VECTOR_LEN]; 25]> for KeccakF {}
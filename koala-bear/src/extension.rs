@@ -99,6 +99,11 @@ mod test_quadratic_extension {
     test_field!(super::EF);
     test_two_adic_extension_field!(super::F, super::EF);
 
+    #[test]
+    fn canonical_bytes() {
+        p3_field_testing::test_ef_canonical_bytes::<F, EF>();
+    }
+
     #[test]
     fn display() {
         assert_eq!(format!("{}", EF::zero()), "0");
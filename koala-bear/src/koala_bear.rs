@@ -16,7 +16,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 /// the cube map (x -> x^3) is an automorphism of the multiplicative group.
 /// Its not unique, as there is one other option with equal 2 adicity: 2^30 + 2^27 + 2^24 + 1.
 /// There is also one 29-bit prime with higher two adicity which might be appropriate for some applications: 2^29 - 2^26 + 1.
-const P: u32 = 0x7f000001;
+pub(crate) const P: u32 = 0x7f000001;
 
 const MONTY_BITS: u32 = 32;
 
@@ -92,7 +92,14 @@ impl Serialize for KoalaBear {
 impl<'de> Deserialize<'de> for KoalaBear {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
         let val = u32::deserialize(d)?;
-        Ok(KoalaBear::from_canonical_u32(val))
+        // Reject unreduced values, so that each element has a single encoding.
+        if val < P {
+            Ok(KoalaBear::from_canonical_u32(val))
+        } else {
+            Err(serde::de::Error::custom(
+                "value is not less than the field order",
+            ))
+        }
     }
 }
 
@@ -471,7 +478,7 @@ pub(crate) const fn monty_reduce(x: u64) -> u32 {
 
 #[cfg(test)]
 mod tests {
-    use p3_field_testing::{test_field, test_two_adic_field};
+    use p3_field_testing::{test_field, test_prime_field, test_two_adic_field};
 
     use super::*;
 
@@ -571,8 +578,13 @@ mod tests {
         let m2_serialized = serde_json::to_string(&m2).unwrap();
         let m2_deserialized: F = serde_json::from_str(&m2_serialized).unwrap();
         assert_eq!(m2, m2_deserialized);
+
+        // Unreduced values are rejected, rather than reduced.
+        assert!(serde_json::from_str::<F>(&P.to_string()).is_err());
+        assert!(serde_json::from_str::<F>(&u32::MAX.to_string()).is_err());
     }
 
     test_field!(crate::KoalaBear);
+    test_prime_field!(crate::KoalaBear);
     test_two_adic_field!(crate::KoalaBear);
 }
@@ -0,0 +1,106 @@
+//! MDS matrices over the KoalaBear field, and permutations defined by them.
+//!
+//! Supported sizes: 24, 32.
+//! These are the Cauchy matrices `1 / (i + j + 1)`, which are MDS for any field with more than
+//! twice as many elements as their width. KoalaBear has no multiplicative subgroup of order 24, so
+//! there is no circulant Cauchy matrix of that size to multiply by with a convolution; instead,
+//! both sizes sum the unreduced products and reduce once per output.
+
+use p3_mds::util::{cauchy_matrix, matmul_delayed_reduction};
+use p3_mds::MdsPermutation;
+use p3_symmetric::Permutation;
+
+use crate::{KoalaBear, P};
+
+#[derive(Clone, Debug, Default)]
+pub struct MdsMatrixKoalaBear;
+
+/// Multiply the Montgomery representatives of `input` by `matrix`. As the matrix entries are plain
+/// integers, the outputs are again Montgomery representatives.
+#[inline]
+fn apply_cauchy<const N: usize>(matrix: &[[u64; N]; N], input: [KoalaBear; N]) -> [KoalaBear; N] {
+    matmul_delayed_reduction(matrix, input.map(|x| x.value), P as u64)
+        .map(|value| KoalaBear { value })
+}
+
+const MATRIX_CAUCHY_MDS_24: [[u64; 24]; 24] = cauchy_matrix(P as u64);
+
+impl Permutation<[KoalaBear; 24]> for MdsMatrixKoalaBear {
+    fn permute(&self, input: [KoalaBear; 24]) -> [KoalaBear; 24] {
+        apply_cauchy(&MATRIX_CAUCHY_MDS_24, input)
+    }
+
+    fn permute_mut(&self, input: &mut [KoalaBear; 24]) {
+        *input = self.permute(*input);
+    }
+}
+impl MdsPermutation<KoalaBear, 24> for MdsMatrixKoalaBear {}
+
+const MATRIX_CAUCHY_MDS_32: [[u64; 32]; 32] = cauchy_matrix(P as u64);
+
+impl Permutation<[KoalaBear; 32]> for MdsMatrixKoalaBear {
+    fn permute(&self, input: [KoalaBear; 32]) -> [KoalaBear; 32] {
+        apply_cauchy(&MATRIX_CAUCHY_MDS_32, input)
+    }
+
+    fn permute_mut(&self, input: &mut [KoalaBear; 32]) {
+        *input = self.permute(*input);
+    }
+}
+impl MdsPermutation<KoalaBear, 32> for MdsMatrixKoalaBear {}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::AbstractField;
+    use p3_symmetric::Permutation;
+
+    use super::{KoalaBear, MdsMatrixKoalaBear};
+
+    #[test]
+    fn koalabear24() {
+        let input: [KoalaBear; 24] = [
+            288545018, 1222356005, 1819850095, 1722851096, 1640193506, 135520872, 547756574,
+            253228484, 1063938749, 1634154402, 965274705, 1014138928, 1399285261, 815217483,
+            1693770507, 450874518, 201561926, 1047664193, 60875732, 1918383731, 1794791897,
+            837108038, 929360195, 1304463163,
+        ]
+        .map(KoalaBear::from_canonical_u64);
+
+        let output = MdsMatrixKoalaBear.permute(input);
+
+        let expected: [KoalaBear; 24] = [
+            1353799745, 1994194003, 889168052, 155662162, 2119541308, 2058433714, 1023441159,
+            607619993, 1626414667, 143958644, 1091386428, 2070927016, 325604348, 163126567,
+            226410437, 388279908, 100470555, 692483430, 459586410, 1677542298, 1015544242,
+            1367670855, 677105087, 1866538350,
+        ]
+        .map(KoalaBear::from_canonical_u64);
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn koalabear32() {
+        let input: [KoalaBear; 32] = [
+            2053067961, 1853513164, 2035444029, 1823332324, 121443151, 196676841, 182261230,
+            775365019, 1794220178, 363104290, 1580483516, 1737232767, 1438235079, 1833595380,
+            661718012, 540271020, 1301255191, 455736430, 1303096808, 76739130, 1248126120,
+            1462965050, 340124608, 924856009, 1371106759, 845103140, 1725866985, 1552656621,
+            1847360140, 1093214019, 2038811261, 798993397,
+        ]
+        .map(KoalaBear::from_canonical_u64);
+
+        let output = MdsMatrixKoalaBear.permute(input);
+
+        let expected: [KoalaBear; 32] = [
+            1604726004, 659511544, 121494663, 516793055, 89705257, 408722575, 1567513782,
+            873327809, 723762519, 63779925, 1407157247, 918673068, 654822194, 196027673, 466836063,
+            281544062, 834552936, 523971405, 1355207488, 709806029, 193804192, 986257754, 45515794,
+            1450294382, 1254064710, 83918805, 183164912, 1480563705, 548092364, 1705949627,
+            489632256, 1245614570,
+        ]
+        .map(KoalaBear::from_canonical_u64);
+
+        assert_eq!(output, expected);
+    }
+}
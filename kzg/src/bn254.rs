@@ -0,0 +1,112 @@
+//! The BN254 curve, via `halo2curves`, with scalar field `Bn254Fr`.
+
+use alloc::vec::Vec;
+
+use ff::PrimeField as FFPrimeField;
+use group::prime::PrimeCurveAffine;
+use group::{Curve, Group, GroupEncoding};
+use halo2curves::bn256::{Bn256, Fr, G1Affine, G2Affine, G2Prepared, G1, G2};
+use itertools::{izip, Itertools};
+use p3_bn254_fr::Bn254Fr;
+use pairing::{MillerLoopResult, MultiMillerLoop};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::PairingEngine;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Bn254;
+
+/// A point of BN254's `G1`, serialized in compressed form.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bn254G1(pub G1Affine);
+
+/// A point of BN254's `G2`, serialized in compressed form.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bn254G2(pub G2Affine);
+
+impl PairingEngine for Bn254 {
+    type Fr = Bn254Fr;
+    type G1 = Bn254G1;
+    type G2 = Bn254G2;
+
+    fn g1_generator() -> Bn254G1 {
+        Bn254G1(G1Affine::generator())
+    }
+
+    fn g2_generator() -> Bn254G2 {
+        Bn254G2(G2Affine::generator())
+    }
+
+    fn g1_msm(bases: &[Bn254G1], scalars: &[Bn254Fr]) -> Bn254G1 {
+        assert_eq!(bases.len(), scalars.len());
+        let sum: G1 = izip!(bases, scalars)
+            .map(|(base, &scalar)| G1::from(base.0) * to_halo2_fr(scalar))
+            .sum();
+        Bn254G1(sum.to_affine())
+    }
+
+    fn g2_mul(base: Bn254G2, scalar: Bn254Fr) -> Bn254G2 {
+        Bn254G2((G2::from(base.0) * to_halo2_fr(scalar)).to_affine())
+    }
+
+    fn pairing_product_is_identity(pairs: &[(Bn254G1, Bn254G2)]) -> bool {
+        let prepared = pairs
+            .iter()
+            .map(|(a, b)| (a.0, G2Prepared::from(b.0)))
+            .collect_vec();
+        let terms = prepared.iter().map(|(a, b)| (a, b)).collect_vec();
+        Bn256::multi_miller_loop(&terms)
+            .final_exponentiation()
+            .is_identity()
+            .into()
+    }
+}
+
+fn to_halo2_fr(x: Bn254Fr) -> Fr {
+    let mut repr = <Fr as FFPrimeField>::Repr::default();
+    // Both representations are little-endian.
+    repr.as_mut().copy_from_slice(x.value.to_repr().as_ref());
+    Fr::from_repr(repr).unwrap()
+}
+
+impl Serialize for Bn254G1 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_point(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bn254G1 {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize_point(d).map(Self)
+    }
+}
+
+impl Serialize for Bn254G2 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_point(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bn254G2 {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize_point(d).map(Self)
+    }
+}
+
+fn serialize_point<P: GroupEncoding, S: Serializer>(
+    point: &P,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    point.to_bytes().as_ref().serialize(serializer)
+}
+
+fn deserialize_point<'de, P: GroupEncoding, D: Deserializer<'de>>(d: D) -> Result<P, D::Error> {
+    let bytes: Vec<u8> = Deserialize::deserialize(d)?;
+    let mut repr = P::Repr::default();
+    if bytes.len() != repr.as_ref().len() {
+        return Err(D::Error::custom("Invalid point encoding"));
+    }
+    repr.as_mut().copy_from_slice(&bytes);
+    Option::from(P::from_bytes(&repr)).ok_or_else(|| D::Error::custom("Invalid point"))
+}
@@ -0,0 +1,25 @@
+use core::fmt::Debug;
+
+use p3_field::TwoAdicField;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A pairing-friendly curve, with groups `G1` and `G2` of prime order `|Fr|`, and a pairing
+/// `e: G1 x G2 -> GT`.
+pub trait PairingEngine: Clone + Debug + Send + Sync {
+    type Fr: TwoAdicField;
+    type G1: Copy + Debug + Eq + Send + Sync + Serialize + DeserializeOwned;
+    type G2: Copy + Debug + Eq + Send + Sync + Serialize + DeserializeOwned;
+
+    fn g1_generator() -> Self::G1;
+
+    fn g2_generator() -> Self::G2;
+
+    /// Compute `sum_i scalars[i] * bases[i]`.
+    fn g1_msm(bases: &[Self::G1], scalars: &[Self::Fr]) -> Self::G1;
+
+    fn g2_mul(base: Self::G2, scalar: Self::Fr) -> Self::G2;
+
+    /// Whether `prod_i e(a_i, b_i)` is the identity of `GT`, for `pairs` of `(a_i, b_i)`.
+    fn pairing_product_is_identity(pairs: &[(Self::G1, Self::G2)]) -> bool;
+}
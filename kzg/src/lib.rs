@@ -0,0 +1,15 @@
+//! A KZG polynomial commitment scheme, over any pairing-friendly curve with a two-adic scalar field.
+
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(feature = "bn254")]
+mod bn254;
+mod engine;
+mod pcs;
+
+#[cfg(feature = "bn254")]
+pub use bn254::*;
+pub use engine::*;
+pub use pcs::*;
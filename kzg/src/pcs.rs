@@ -0,0 +1,296 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{izip, Itertools};
+use p3_challenger::FieldChallenger;
+use p3_commit::{
+    OpenedValues, OpenedValuesForPoint, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset,
+};
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::{AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+
+use crate::PairingEngine;
+
+/// A structured reference string: `[tau^i] G1` for each `i` below the maximum supported degree,
+/// along with `G2` and `[tau] G2`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct KzgSrs<E: PairingEngine> {
+    pub g1_powers: Vec<E::G1>,
+    pub g2: E::G2,
+    pub g2_tau: E::G2,
+}
+
+impl<E: PairingEngine> KzgSrs<E> {
+    /// Generate an SRS for polynomials of degree less than `max_degree` from a known `tau`.
+    ///
+    /// Anyone who knows `tau` can open commitments to any value, so this is only suitable for
+    /// testing. Otherwise, use the SRS of a trusted setup ceremony.
+    pub fn insecure_from_secret(max_degree: usize, tau: E::Fr) -> Self {
+        let g1 = E::g1_generator();
+        let g2 = E::g2_generator();
+        Self {
+            g1_powers: tau
+                .powers()
+                .take(max_degree)
+                .map(|tau_pow| E::g1_msm(&[g1], &[tau_pow]))
+                .collect(),
+            g2,
+            g2_tau: E::g2_mul(g2, tau),
+        }
+    }
+
+    fn commit_coeffs(&self, coeffs: &[E::Fr]) -> E::G1 {
+        assert!(
+            coeffs.len() <= self.g1_powers.len(),
+            "polynomial degree exceeds the SRS size"
+        );
+        E::g1_msm(&self.g1_powers[..coeffs.len()], coeffs)
+    }
+}
+
+/// A KZG PCS, committing to each column of each matrix separately.
+///
+/// Openings are batched: the polynomials of a matrix are combined with powers of a random
+/// challenge, so each opened point of each matrix costs one group element in the proof.
+#[derive(Debug)]
+pub struct KzgPcs<E: PairingEngine, Dft> {
+    srs: KzgSrs<E>,
+    dft: Dft,
+}
+
+impl<E: PairingEngine, Dft> KzgPcs<E, Dft> {
+    pub const fn new(srs: KzgSrs<E>, dft: Dft) -> Self {
+        Self { srs, dft }
+    }
+}
+
+/// For each committed matrix, the commitments to its columns.
+pub type KzgCommitment<E> = Vec<Vec<<E as PairingEngine>::G1>>;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct KzgProof<E: PairingEngine> {
+    /// For each round, matrix and opened point `z` in turn, the commitment to
+    /// `(p(X) - p(z)) / (X - z)`, where `p` is the batched polynomial of the matrix.
+    pub witnesses: Vec<E::G1>,
+}
+
+#[derive(Debug)]
+pub enum KzgError {
+    InvalidProofShape,
+    InvalidOpening,
+}
+
+impl<E, Dft, Challenger> Pcs<E::Fr, Challenger> for KzgPcs<E, Dft>
+where
+    E: PairingEngine,
+    Dft: TwoAdicSubgroupDft<E::Fr>,
+    Challenger: FieldChallenger<E::Fr>,
+{
+    type Domain = TwoAdicMultiplicativeCoset<E::Fr>;
+    type Commitment = KzgCommitment<E>;
+    /// The coefficients of each committed matrix.
+    type ProverData = Vec<RowMajorMatrix<E::Fr>>;
+    type Proof = KzgProof<E>;
+    type Error = KzgError;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        TwoAdicMultiplicativeCoset {
+            log_n: log2_strict_usize(degree),
+            shift: E::Fr::one(),
+        }
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<E::Fr>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let coeffs = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                assert_eq!(domain.size(), evals.height());
+                self.dft.coset_idft_batch(evals, domain.shift)
+            })
+            .collect_vec();
+        let srs = &self.srs;
+        let commit = coeffs
+            .iter()
+            .map(|coeffs| {
+                let height = coeffs.height();
+                coeffs
+                    .clone()
+                    .transpose()
+                    .values
+                    .par_chunks_exact(height)
+                    .map(|column| srs.commit_coeffs(column))
+                    .collect()
+            })
+            .collect();
+        (commit, coeffs)
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> impl Matrix<E::Fr> + 'a {
+        let mut coeffs = prover_data[idx].clone();
+        assert!(domain.size() >= coeffs.height());
+        coeffs
+            .values
+            .resize(domain.size() * coeffs.width(), E::Fr::zero());
+        self.dft.coset_dft_batch(coeffs, domain.shift)
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<E::Fr>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<E::Fr>, Self::Proof) {
+        let values: OpenedValues<E::Fr> = rounds
+            .iter()
+            .map(|(coeffs_for_round, points_for_round)| {
+                assert_eq!(coeffs_for_round.len(), points_for_round.len());
+                izip!(coeffs_for_round.iter(), points_for_round)
+                    .map(|(coeffs, points)| {
+                        points
+                            .iter()
+                            .map(|&point| eval_coeffs(coeffs, point).into())
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        for values_at_point in values.iter().flatten().flatten() {
+            challenger.observe_slice(values_at_point.values());
+        }
+
+        let gamma: E::Fr = challenger.sample();
+        let mut witnesses = vec![];
+        for (coeffs_for_round, points_for_round) in &rounds {
+            for (coeffs, points) in izip!(coeffs_for_round.iter(), points_for_round) {
+                let batched: Vec<E::Fr> = coeffs.dot_ext_powers(gamma).collect();
+                for &point in points {
+                    let quotient = divide_by_linear(&batched, point);
+                    witnesses.push(self.srs.commit_coeffs(&quotient));
+                }
+            }
+        }
+
+        (values, KzgProof { witnesses })
+    }
+
+    fn verify(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            Self::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                Self::Domain,
+                // for each point:
+                Vec<(
+                    // the point,
+                    E::Fr,
+                    // values at the point
+                    OpenedValuesForPoint<E::Fr>,
+                )>,
+            )>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        for (_, mats) in &rounds {
+            for (_, points) in mats {
+                for (_, values) in points {
+                    challenger.observe_slice(values.values());
+                }
+            }
+        }
+        let gamma: E::Fr = challenger.sample();
+
+        let num_openings: usize = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(_, points)| points.len())
+            .sum();
+        if proof.witnesses.len() != num_openings {
+            return Err(KzgError::InvalidProofShape);
+        }
+
+        let mut witnesses = proof.witnesses.iter();
+        for (commit, mats) in rounds {
+            if commit.len() != mats.len() {
+                return Err(KzgError::InvalidProofShape);
+            }
+            for (column_commits, (_, points)) in izip!(commit, mats) {
+                let gamma_powers = gamma.powers().take(column_commits.len()).collect_vec();
+                for (point, values) in points {
+                    if values.len() != column_commits.len() {
+                        return Err(KzgError::InvalidProofShape);
+                    }
+                    let witness = *witnesses.next().unwrap();
+                    let value = izip!(&gamma_powers, &values)
+                        .map(|(&gamma_pow, &value)| gamma_pow * value)
+                        .sum::<E::Fr>();
+
+                    // With C the batched commitment and W the witness, check that
+                    // e(C - [value] G1 + [point] W, G2) = e(W, [tau] G2).
+                    let bases = column_commits
+                        .iter()
+                        .copied()
+                        .chain([E::g1_generator(), witness])
+                        .collect_vec();
+                    let scalars = gamma_powers
+                        .iter()
+                        .copied()
+                        .chain([-value, point])
+                        .collect_vec();
+                    let lhs = E::g1_msm(&bases, &scalars);
+                    let neg_witness = E::g1_msm(&[witness], &[-E::Fr::one()]);
+                    if !E::pairing_product_is_identity(&[
+                        (lhs, self.srs.g2),
+                        (neg_witness, self.srs.g2_tau),
+                    ]) {
+                        return Err(KzgError::InvalidOpening);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn eval_coeffs<F: Field>(coeffs: &RowMajorMatrix<F>, point: F) -> Vec<F> {
+    let point_powers = point.powers().take(coeffs.height()).collect_vec();
+    coeffs.columnwise_dot_product(&point_powers)
+}
+
+/// The quotient of the polynomial with the given coefficients by `X - point`, discarding the
+/// remainder.
+fn divide_by_linear<F: Field>(coeffs: &[F], point: F) -> Vec<F> {
+    let mut quotient = vec![F::zero(); coeffs.len().saturating_sub(1)];
+    let mut acc = F::zero();
+    for (q, &c) in izip!(quotient.iter_mut().rev(), coeffs.iter().skip(1).rev()) {
+        acc = acc * point + c;
+        *q = acc;
+    }
+    quotient
+}
@@ -0,0 +1,161 @@
+use itertools::Itertools;
+use p3_bn254_fr::Bn254Fr;
+use p3_challenger::{CanObserve, CanSample, CanSampleBits, FieldChallenger, HashChallenger};
+use p3_commit::{OpenedValuesForRound, Pcs, PolynomialSpace};
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::{AbstractField, PrimeField};
+use p3_keccak::Keccak256Hash;
+use p3_kzg::{Bn254, Bn254G1, KzgCommitment, KzgError, KzgPcs, KzgSrs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+type F = Bn254Fr;
+type MyPcs = KzgPcs<Bn254, Radix2Dit<F>>;
+
+/// A challenger for BN254's scalar field, absorbing field elements and points by their bytes.
+#[derive(Clone)]
+struct Challenger(HashChallenger<u8, Keccak256Hash, 32>);
+
+impl Challenger {
+    fn new() -> Self {
+        Self(HashChallenger::new(vec![], Keccak256Hash))
+    }
+}
+
+impl CanObserve<F> for Challenger {
+    fn observe(&mut self, value: F) {
+        let mut bytes = value.as_canonical_biguint().to_bytes_le();
+        bytes.resize(32, 0);
+        self.0.observe_slice(&bytes);
+    }
+}
+
+impl CanObserve<KzgCommitment<Bn254>> for Challenger {
+    fn observe(&mut self, commit: KzgCommitment<Bn254>) {
+        for Bn254G1(point) in commit.into_iter().flatten() {
+            self.0
+                .observe_slice(group::GroupEncoding::to_bytes(&point).as_ref());
+        }
+    }
+}
+
+impl CanSample<F> for Challenger {
+    fn sample(&mut self) -> F {
+        // Slightly biased, which doesn't matter here.
+        (0..32).fold(F::zero(), |acc, _| {
+            acc * F::from_canonical_u32(256) + F::from_canonical_u8(self.0.sample())
+        })
+    }
+}
+
+impl CanSampleBits<usize> for Challenger {
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        let bytes: [u8; 8] = self.0.sample_array();
+        (u64::from_le_bytes(bytes) as usize) & ((1 << bits) - 1)
+    }
+}
+
+impl FieldChallenger<F> for Challenger {}
+
+fn seeded_rng() -> impl Rng {
+    ChaCha20Rng::seed_from_u64(0)
+}
+
+fn get_pcs(max_degree: usize) -> MyPcs {
+    let tau: F = seeded_rng().gen();
+    KzgPcs::new(
+        KzgSrs::insecure_from_secret(max_degree, tau),
+        Radix2Dit::default(),
+    )
+}
+
+#[test]
+fn open_and_verify() {
+    let pcs = get_pcs(1 << 5);
+    let mut rng = seeded_rng();
+
+    let natural = <MyPcs as Pcs<F, Challenger>>::natural_domain_for_degree(&pcs, 1 << 4);
+    let coset = natural.create_disjoint_domain(1 << 5);
+    let evals_a = RowMajorMatrix::<F>::rand(&mut rng, natural.size(), 3);
+    let evals_b = RowMajorMatrix::<F>::rand(&mut rng, coset.size(), 2);
+
+    let (commit, data) = <MyPcs as Pcs<F, Challenger>>::commit(
+        &pcs,
+        vec![(natural, evals_a.clone()), (coset, evals_b)],
+    );
+    let mut p_challenger = Challenger::new();
+    p_challenger.observe(commit.clone());
+    let zeta: F = p_challenger.sample();
+    let zeta_next = natural.next_point(zeta).unwrap();
+
+    // Committed polynomials can be evaluated over other domains.
+    let lde = <MyPcs as Pcs<F, Challenger>>::get_evaluations_on_domain(
+        &pcs,
+        &data,
+        0,
+        natural.create_disjoint_domain(1 << 5),
+    );
+    assert_eq!(lde.height(), 1 << 5);
+    let on_natural =
+        <MyPcs as Pcs<F, Challenger>>::get_evaluations_on_domain(&pcs, &data, 0, natural);
+    assert_eq!(on_natural.to_row_major_matrix(), evals_a);
+
+    let (opened_values, proof) = pcs.open(
+        vec![(&data, vec![vec![zeta, zeta_next], vec![zeta]])],
+        &mut p_challenger,
+    );
+
+    let claims = |opened_values: &OpenedValuesForRound<F>| {
+        vec![(
+            commit.clone(),
+            vec![
+                (
+                    natural,
+                    vec![
+                        (zeta, opened_values[0][0].clone()),
+                        (zeta_next, opened_values[0][1].clone()),
+                    ],
+                ),
+                (coset, vec![(zeta, opened_values[1][0].clone())]),
+            ],
+        )]
+    };
+
+    let mut v_challenger = Challenger::new();
+    v_challenger.observe(commit.clone());
+    let v_zeta: F = v_challenger.sample();
+    assert_eq!(v_zeta, zeta);
+    pcs.verify(claims(&opened_values[0]), &proof, &mut v_challenger)
+        .expect("verification failed");
+
+    let mut bad_values = opened_values[0].clone();
+    bad_values[1][0][1] += F::one();
+    let mut v_challenger = Challenger::new();
+    v_challenger.observe(commit.clone());
+    let _: F = v_challenger.sample();
+    assert!(matches!(
+        pcs.verify(claims(&bad_values), &proof, &mut v_challenger),
+        Err(KzgError::InvalidOpening)
+    ));
+}
+
+#[test]
+fn opened_values_match_interpolation() {
+    let pcs = get_pcs(1 << 3);
+    let mut rng = seeded_rng();
+
+    let domain = <MyPcs as Pcs<F, Challenger>>::natural_domain_for_degree(&pcs, 1 << 3);
+    let evals = RowMajorMatrix::<F>::rand(&mut rng, domain.size(), 4);
+    let coeffs = Radix2Dit::default().idft_batch(evals.clone());
+    let (_, data) = <MyPcs as Pcs<F, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+    let point: F = rng.gen();
+    let (opened_values, _) = pcs.open(vec![(&data, vec![vec![point]])], &mut Challenger::new());
+    let point_powers = point.powers().take(coeffs.height()).collect_vec();
+    assert_eq!(
+        opened_values[0][0][0].values(),
+        coeffs.columnwise_dot_product(&point_powers)
+    );
+}
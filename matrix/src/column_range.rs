@@ -0,0 +1,97 @@
+use core::iter;
+use core::ops::{Deref, Range};
+
+use crate::Matrix;
+
+/// A view of a contiguous range of the columns of `inner`, e.g. just the CPU columns of a wide
+/// shared trace, without copying them.
+#[derive(Clone, Debug)]
+pub struct ColumnRangeView<Inner> {
+    inner: Inner,
+    cols: Range<usize>,
+}
+
+impl<Inner> ColumnRangeView<Inner> {
+    pub fn new<T>(inner: Inner, cols: Range<usize>) -> Self
+    where
+        T: Send + Sync,
+        Inner: Matrix<T>,
+    {
+        assert!(cols.start <= cols.end && cols.end <= inner.width());
+        Self { inner, cols }
+    }
+
+    pub fn into_inner(self) -> Inner {
+        self.inner
+    }
+}
+
+impl<T: Send + Sync, Inner: Matrix<T>> Matrix<T> for ColumnRangeView<Inner> {
+    fn width(&self) -> usize {
+        self.cols.len()
+    }
+
+    fn height(&self) -> usize {
+        self.inner.height()
+    }
+
+    type Row<'a> = iter::Take<iter::Skip<Inner::Row<'a>>> where Self: 'a;
+
+    fn get(&self, r: usize, c: usize) -> T {
+        debug_assert!(c < self.width());
+        self.inner.get(r, self.cols.start + c)
+    }
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        self.inner
+            .row(r)
+            .skip(self.cols.start)
+            .take(self.cols.len())
+    }
+
+    fn row_slice(&self, r: usize) -> impl Deref<Target = [T]> {
+        SubSlice {
+            slice: self.inner.row_slice(r),
+            range: self.cols.clone(),
+        }
+    }
+}
+
+/// A range of a row slice of the inner matrix, so that dense inner matrices aren't copied.
+struct SubSlice<S> {
+    slice: S,
+    range: Range<usize>,
+}
+
+impl<T, S: Deref<Target = [T]>> Deref for SubSlice<S> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.slice[self.range.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::ops::Deref;
+
+    use crate::dense::RowMajorMatrix;
+    use crate::Matrix;
+
+    #[test]
+    fn column_ranges_select_columns() {
+        let mat = RowMajorMatrix::new((0..12).collect::<Vec<u32>>(), 4);
+
+        let view = mat.as_view().col_range(1..3);
+        assert_eq!((view.width(), view.height()), (2, 3));
+        assert_eq!(view.get(2, 1), 10);
+        assert_eq!(view.row_slice(1).deref(), &[5, 6]);
+        assert_eq!(view.to_row_major_matrix().values, vec![1, 2, 5, 6, 9, 10]);
+
+        let (left, right) = mat.as_view().split_cols(1);
+        assert_eq!(left.to_row_major_matrix().values, vec![0, 4, 8]);
+        assert_eq!(right.row(2).collect::<Vec<_>>(), vec![9, 10, 11]);
+    }
+}
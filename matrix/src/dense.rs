@@ -11,6 +11,7 @@ use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use crate::util::reverse_matrix_index_bits;
 use crate::Matrix;
 
 /// A default constant for block size matrix transposition. The value was chosen with 32-byte type, in mind.
@@ -87,6 +88,22 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
         RowMajorMatrix::new(values, width)
     }
 
+    /// The inverse of `flatten_to_base`: combine each `EF::D` consecutive entries of a row into an
+    /// extension field element.
+    pub fn reconstitute_from_base<EF: ExtensionField<T>>(&self) -> RowMajorMatrix<EF>
+    where
+        T: Field,
+    {
+        assert_eq!(self.width % EF::D, 0);
+        let values = self
+            .values
+            .borrow()
+            .chunks_exact(EF::D)
+            .map(EF::from_base_slice)
+            .collect();
+        RowMajorMatrix::new(values, self.width / EF::D)
+    }
+
     pub fn par_row_slices(&self) -> impl IndexedParallelIterator<Item = &[T]>
     where
         T: Sync,
@@ -168,6 +185,36 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
         )
     }
 
+    /// Split the matrix into disjoint blocks of `chunk_rows` rows, to be processed in parallel.
+    /// The last block may be shorter.
+    pub fn par_row_chunks(
+        &self,
+        chunk_rows: usize,
+    ) -> impl IndexedParallelIterator<Item = RowMajorMatrixView<T>>
+    where
+        T: Sync,
+    {
+        self.values
+            .borrow()
+            .par_chunks(self.width * chunk_rows)
+            .map(|slice| RowMajorMatrixView::new(slice, self.width))
+    }
+
+    /// Split the matrix into disjoint blocks of exactly `chunk_rows` rows, to be processed in
+    /// parallel, leaving out any remaining rows.
+    pub fn par_row_chunks_exact(
+        &self,
+        chunk_rows: usize,
+    ) -> impl IndexedParallelIterator<Item = RowMajorMatrixView<T>>
+    where
+        T: Sync,
+    {
+        self.values
+            .borrow()
+            .par_chunks_exact(self.width * chunk_rows)
+            .map(|slice| RowMajorMatrixView::new(slice, self.width))
+    }
+
     pub fn par_row_chunks_mut(
         &mut self,
         chunk_rows: usize,
@@ -340,6 +387,28 @@ impl<T: Clone + Default + Send + Sync> DenseMatrix<T, Vec<T>> {
         Self::new(values, cols)
     }
 
+    /// Build a matrix of the given dimensions by calling `f(r, row)` to fill in each row `r`, in
+    /// parallel. Each row starts out filled with default values.
+    pub fn par_from_rows_fn<F>(height: usize, width: usize, f: F) -> Self
+    where
+        F: Fn(usize, &mut [T]) + Sync,
+    {
+        let mut mat = Self::default(width, height);
+        if width != 0 {
+            mat.par_rows_mut()
+                .enumerate()
+                .for_each(|(r, row)| f(r, row));
+        }
+        mat
+    }
+
+    /// Permute the rows in place, moving row `i` to the bit reversal of `i`. This is what
+    /// `bit_reverse_rows().to_row_major_matrix()` does for an owned matrix, without the detour
+    /// through a view.
+    pub fn bit_reverse_row_index_bits_in_place(&mut self) {
+        reverse_matrix_index_bits(self);
+    }
+
     pub fn transpose(self) -> Self {
         let block_size = TRANSPOSE_BLOCK_SIZE;
         let height = self.height();
@@ -374,8 +443,77 @@ impl<T: Clone + Default + Send + Sync> DenseMatrix<T, Vec<T>> {
 
 #[cfg(test)]
 mod tests {
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{dot_product, AbstractExtensionField, AbstractField};
+    use p3_mersenne_31::Mersenne31;
+
     use super::*;
 
+    #[test]
+    fn test_par_from_rows_fn() {
+        let matrix = RowMajorMatrix::par_from_rows_fn(4, 3, |r, row: &mut [u32]| {
+            row.iter_mut()
+                .enumerate()
+                .for_each(|(c, x)| *x = (r * 3 + c) as u32)
+        });
+        assert_eq!(matrix, RowMajorMatrix::new((0..12).collect(), 3));
+        assert_eq!(
+            RowMajorMatrix::<u32>::par_from_rows_fn(4, 0, |_, _| ()).height(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_par_row_chunks() {
+        let mut matrix = RowMajorMatrix::new((0..10u32).collect(), 2);
+        let chunks = matrix.par_row_chunks(2).collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].values, &[8, 9]);
+        assert_eq!(chunks[1].row_slice(1).deref(), &[6, 7]);
+        assert_eq!(matrix.par_row_chunks_exact(2).count(), 2);
+
+        matrix
+            .par_row_chunks_exact_mut(2)
+            .enumerate()
+            .for_each(|(i, mut chunk)| chunk.row_mut(0)[0] = 100 + i as u32);
+        assert_eq!(matrix.get(0, 0), 100);
+        assert_eq!(matrix.get(2, 0), 101);
+        assert_eq!(matrix.get(4, 0), 8);
+    }
+
+    #[test]
+    fn test_dot_ext_powers() {
+        type F = Mersenne31;
+        let base = F::from_canonical_u32(7);
+        // Cover widths with and without a partial packed lane.
+        for width in 1..=2 * <F as Field>::Packing::WIDTH + 3 {
+            let matrix = RowMajorMatrix::new(
+                (0..3 * width as u32).map(F::from_canonical_u32).collect(),
+                width,
+            );
+            let expected = matrix
+                .rows()
+                .map(|row| dot_product::<F, _, _>(base.powers(), row))
+                .collect::<Vec<_>>();
+            let actual = matrix.dot_ext_powers(base).collect::<Vec<_>>();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_reconstitute_from_base() {
+        type EF = BinomialExtensionField<Mersenne31, 3>;
+        let matrix = RowMajorMatrix::new(
+            (0..12u32)
+                .map(|i| EF::from_base_fn(|j| Mersenne31::from_canonical_u32(3 * i + j as u32)))
+                .collect(),
+            4,
+        );
+        let flat = matrix.flatten_to_base::<Mersenne31>();
+        assert_eq!(flat.width, 12);
+        assert_eq!(flat.reconstitute_from_base::<EF>(), matrix);
+    }
+
     #[test]
     fn test_transpose_square_matrix() {
         const START_INDEX: usize = 1;
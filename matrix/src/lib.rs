@@ -3,27 +3,35 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "mmap")]
+extern crate std;
 
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::{Debug, Display, Formatter};
-use core::ops::Deref;
+use core::ops::{Deref, Range};
 
 use itertools::{izip, Itertools};
 use p3_field::{dot_product, AbstractExtensionField, ExtensionField, Field, PackedValue};
 use p3_maybe_rayon::prelude::*;
 use strided::{VerticallyStridedMatrixView, VerticallyStridedRowIndexMap};
 
+use crate::column_range::ColumnRangeView;
 use crate::dense::RowMajorMatrix;
 
 pub mod bitrev;
+pub mod column_range;
 pub mod dense;
 pub mod extension;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 pub mod mul;
+pub mod row_fn;
 pub mod row_index_mapped;
 pub mod sparse;
 pub mod stack;
 pub mod strided;
+pub mod transposed;
 pub mod util;
 
 #[derive(Clone, Copy)]
@@ -166,6 +174,26 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
         (0..self.width()).map(move |c| P::from_fn(|i| self.get((r + i) % self.height(), c)))
     }
 
+    /// A view of the columns in `cols`.
+    fn col_range(self, cols: Range<usize>) -> ColumnRangeView<Self>
+    where
+        Self: Sized,
+    {
+        ColumnRangeView::new(self, cols)
+    }
+
+    /// Views of the columns before `c`, and of the rest.
+    fn split_cols(self, c: usize) -> (ColumnRangeView<Self>, ColumnRangeView<Self>)
+    where
+        Self: Sized + Clone,
+    {
+        let width = self.width();
+        (
+            ColumnRangeView::new(self.clone(), 0..c),
+            ColumnRangeView::new(self, c..width),
+        )
+    }
+
     fn vertically_strided(self, stride: usize, offset: usize) -> VerticallyStridedMatrixView<Self>
     where
         Self: Sized,
@@ -195,17 +223,22 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
     }
 
     /// Multiply this matrix by the vector of powers of `base`, which is an extension element.
+    ///
+    /// Each row is processed a full packed lane of columns at a time, with any remaining columns
+    /// handled one by one.
     fn dot_ext_powers<EF>(&self, base: EF) -> impl IndexedParallelIterator<Item = EF>
     where
         T: Field,
         EF: ExtensionField<T>,
     {
-        let powers_packed = base
-            .ext_powers_packed()
-            .take(self.width().next_multiple_of(T::Packing::WIDTH))
+        let num_packed = self.width() / T::Packing::WIDTH;
+        let powers_packed = base.ext_powers_packed().take(num_packed).collect_vec();
+        let powers_sfx = base
+            .shifted_powers(base.exp_u64((num_packed * T::Packing::WIDTH) as u64))
+            .take(self.width() % T::Packing::WIDTH)
             .collect_vec();
-        self.par_padded_horizontally_packed_rows::<T::Packing>()
-            .map(move |row_packed| {
+        self.par_horizontally_packed_rows::<T::Packing>()
+            .map(move |(row_packed, row_sfx)| {
                 let packed_sum_of_packed: EF::ExtensionPacking =
                     dot_product(powers_packed.iter().copied(), row_packed);
                 let sum_of_packed: EF = EF::from_base_fn(|i| {
@@ -215,7 +248,8 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
                         .copied()
                         .sum()
                 });
-                sum_of_packed
+                let sum_of_sfx: EF = dot_product(powers_sfx.iter().copied(), row_sfx);
+                sum_of_packed + sum_of_sfx
             })
     }
 }
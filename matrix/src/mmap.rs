@@ -0,0 +1,218 @@
+//! Dense matrices whose values live in a memory-mapped file, rather than on the heap.
+
+use alloc::vec::Vec;
+use core::borrow::{Borrow, BorrowMut};
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of, size_of_val};
+use core::slice;
+use std::fs::File;
+use std::io::{self, Write};
+
+use memmap2::{Mmap, MmapMut, MmapOptions};
+
+use crate::dense::{DenseMatrix, DenseStorage};
+
+/// A row-major matrix backed by a read-only memory map of a file.
+pub type MmapMatrix<T> = DenseMatrix<T, MmapStorage<T>>;
+
+/// A row-major matrix backed by a copy-on-write memory map of a file. It can be written to, but
+/// the writes are private to the map, and never reach the file.
+pub type MmapMatrixMut<T> = DenseMatrix<T, MmapStorageMut<T>>;
+
+/// The values of a matrix, held in a read-only memory map.
+#[derive(Debug)]
+pub struct MmapStorage<T> {
+    mmap: Mmap,
+    _phantom: PhantomData<T>,
+}
+
+/// The values of a matrix, held in a copy-on-write memory map.
+#[derive(Debug)]
+pub struct MmapStorageMut<T> {
+    mmap: MmapMut,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Clone + Send + Sync> MmapMatrix<T> {
+    /// Map `file`, which holds the values of a row-major matrix of the given width, as written by
+    /// `write_mmap_matrix`.
+    ///
+    /// # Safety
+    /// The contents of `file` must be valid values of `T`, and the file must not be modified while
+    /// it is mapped.
+    pub unsafe fn open(file: &File, width: usize) -> io::Result<Self> {
+        let mmap = Mmap::map(file)?;
+        check_layout::<T>(&mmap, width)?;
+        Ok(Self::new(
+            MmapStorage {
+                mmap,
+                _phantom: PhantomData,
+            },
+            width,
+        ))
+    }
+}
+
+impl<T: Clone + Send + Sync> MmapMatrixMut<T> {
+    /// Map `file` copy-on-write, so that the matrix can be modified without modifying the file.
+    /// `file` holds the values of a row-major matrix of the given width, as written by
+    /// `write_mmap_matrix`.
+    ///
+    /// # Safety
+    /// The contents of `file` must be valid values of `T`, and the file must not be modified while
+    /// it is mapped.
+    pub unsafe fn open_copy_on_write(file: &File, width: usize) -> io::Result<Self> {
+        let mmap = MmapOptions::new().map_copy(file)?;
+        check_layout::<T>(&mmap, width)?;
+        Ok(Self::new(
+            MmapStorageMut {
+                mmap,
+                _phantom: PhantomData,
+            },
+            width,
+        ))
+    }
+}
+
+/// Write the values of `mat` to `writer` in their in-memory representation, which is what
+/// `MmapMatrix::open` and `MmapMatrixMut::open_copy_on_write` expect to map.
+///
+/// The file is only meaningful on a machine with the same endianness, and to a build with the same
+/// representation of `T`.
+///
+/// # Safety
+/// Every byte of every value of `T` must be initialized, so `T` must not contain padding bytes
+/// (including within enums or unions).
+pub unsafe fn write_mmap_matrix<T, S, W>(mat: &DenseMatrix<T, S>, writer: &mut W) -> io::Result<()>
+where
+    T: Copy,
+    S: DenseStorage<T>,
+    W: Write,
+{
+    let values = mat.values.borrow();
+    // SAFETY: the caller promised that each byte of each value is initialized.
+    let bytes = unsafe { slice::from_raw_parts(values.as_ptr().cast::<u8>(), size_of_val(values)) };
+    writer.write_all(bytes)
+}
+
+/// Check that `bytes` can be reinterpreted as the values of a matrix of `T`s with the given width.
+fn check_layout<T>(bytes: &[u8], width: usize) -> io::Result<()> {
+    assert_ne!(
+        size_of::<T>(),
+        0,
+        "cannot map a matrix of zero-sized values"
+    );
+    let invalid = |msg| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+    if bytes.as_ptr() as usize % align_of::<T>() != 0 {
+        return invalid("mapped file is misaligned for the value type");
+    }
+    if bytes.len() % size_of::<T>() != 0 {
+        return invalid("file length is not a multiple of the value size");
+    }
+    let len = bytes.len() / size_of::<T>();
+    if (width == 0 && len != 0) || (width != 0 && len % width != 0) {
+        return invalid("file length is not a multiple of the matrix width");
+    }
+    Ok(())
+}
+
+impl<T> Borrow<[T]> for MmapStorage<T> {
+    fn borrow(&self) -> &[T] {
+        // SAFETY: `check_layout` checked the alignment and length of the map, and the caller of
+        // `open` promised that it holds valid `T`s.
+        unsafe {
+            slice::from_raw_parts(self.mmap.as_ptr().cast(), self.mmap.len() / size_of::<T>())
+        }
+    }
+}
+
+impl<T> Borrow<[T]> for MmapStorageMut<T> {
+    fn borrow(&self) -> &[T] {
+        // SAFETY: as for `MmapStorage`.
+        unsafe {
+            slice::from_raw_parts(self.mmap.as_ptr().cast(), self.mmap.len() / size_of::<T>())
+        }
+    }
+}
+
+impl<T> BorrowMut<[T]> for MmapStorageMut<T> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        // SAFETY: as for `MmapStorage`. The map is private, so no one else can observe the writes.
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.mmap.as_mut_ptr().cast(),
+                self.mmap.len() / size_of::<T>(),
+            )
+        }
+    }
+}
+
+impl<T: Clone> From<MmapStorage<T>> for Vec<T> {
+    fn from(storage: MmapStorage<T>) -> Self {
+        Borrow::<[T]>::borrow(&storage).to_vec()
+    }
+}
+
+impl<T: Clone> From<MmapStorageMut<T>> for Vec<T> {
+    fn from(storage: MmapStorageMut<T>) -> Self {
+        Borrow::<[T]>::borrow(&storage).to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, OpenOptions};
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::dense::RowMajorMatrix;
+    use crate::Matrix;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(alloc::format!("p3-matrix-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn mmap_round_trip() {
+        let path = temp_path("round-trip");
+        let mat = RowMajorMatrix::new((0..12).collect::<Vec<u32>>(), 3);
+        unsafe { write_mmap_matrix(&mat, &mut File::create(&path).unwrap()) }.unwrap();
+
+        let file = File::open(&path).unwrap();
+        let mapped = unsafe { MmapMatrix::<u32>::open(&file, 3) }.unwrap();
+        assert_eq!((mapped.width(), mapped.height()), (3, 4));
+        assert_eq!(mapped.get(2, 1), 7);
+        assert_eq!(mapped.to_row_major_matrix(), mat);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn copy_on_write_leaves_file_unchanged() {
+        let path = temp_path("copy-on-write");
+        let mat = RowMajorMatrix::new((0..8).collect::<Vec<u64>>(), 2);
+        unsafe { write_mmap_matrix(&mat, &mut File::create(&path).unwrap()) }.unwrap();
+
+        let file = OpenOptions::new().read(true).open(&path).unwrap();
+        let mut mapped = unsafe { MmapMatrixMut::<u64>::open_copy_on_write(&file, 2) }.unwrap();
+        mapped.row_mut(1)[0] = 100;
+        assert_eq!(mapped.get(1, 0), 100);
+
+        let reread = unsafe { MmapMatrix::<u64>::open(&file, 2) }.unwrap();
+        assert_eq!(reread.to_row_major_matrix(), mat);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_ragged_file() {
+        let path = temp_path("ragged");
+        let mat = RowMajorMatrix::new((0..5).collect::<Vec<u32>>(), 5);
+        unsafe { write_mmap_matrix(&mat, &mut File::create(&path).unwrap()) }.unwrap();
+
+        let file = File::open(&path).unwrap();
+        assert!(unsafe { MmapMatrix::<u32>::open(&file, 2) }.is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+use p3_field::PackedValue;
+
+use crate::Matrix;
+
+/// A matrix whose rows are computed on demand by a closure, rather than stored.
+///
+/// Committing to one hashes each row as it is produced, so e.g. a low-degree extension can be
+/// committed without ever holding all of its rows in memory. Each access recomputes the row it
+/// touches, so the closure should be cheap relative to the matrix, or the matrix only read a row at
+/// a time, as an MMCS does when it commits and opens.
+#[derive(Clone, Debug)]
+pub struct RowFnMatrix<T, RowFn> {
+    height: usize,
+    width: usize,
+    row_fn: RowFn,
+    _phantom: PhantomData<T>,
+}
+
+impl<T, RowFn: Fn(usize) -> Vec<T>> RowFnMatrix<T, RowFn> {
+    /// A `height` by `width` matrix whose row `r` is `row_fn(r)`, which must have length `width`.
+    pub const fn new(height: usize, width: usize, row_fn: RowFn) -> Self {
+        Self {
+            height,
+            width,
+            row_fn,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn compute_row(&self, r: usize) -> Vec<T> {
+        let row = (self.row_fn)(r);
+        debug_assert_eq!(row.len(), self.width);
+        row
+    }
+}
+
+impl<T, RowFn> Matrix<T> for RowFnMatrix<T, RowFn>
+where
+    T: Send + Sync,
+    RowFn: Fn(usize) -> Vec<T> + Send + Sync,
+{
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    type Row<'a> = alloc::vec::IntoIter<T>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        self.compute_row(r).into_iter()
+    }
+
+    fn row_slice(&self, r: usize) -> impl Deref<Target = [T]> {
+        self.compute_row(r)
+    }
+
+    /// Unlike the default implementation, which reads each element separately, this computes each
+    /// of the `P::WIDTH` rows once.
+    fn vertically_packed_row<P>(&self, r: usize) -> impl Iterator<Item = P>
+    where
+        P: PackedValue<Value = T>,
+    {
+        let rows: Vec<Vec<T>> = (0..P::WIDTH)
+            .map(|i| self.compute_row((r + i) % self.height))
+            .collect();
+        (0..self.width).map(move |c| P::from_fn(|i| rows[i][c]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use p3_field::{AbstractField, Field};
+    use p3_mersenne_31::Mersenne31;
+
+    use super::*;
+    use crate::dense::RowMajorMatrix;
+
+    type F = Mersenne31;
+    type P = <F as Field>::Packing;
+
+    #[test]
+    fn row_fn_matrix_matches_dense() {
+        let dense = RowMajorMatrix::new((0..15).map(F::from_canonical_u32).collect_vec(), 3);
+        let lazy = RowFnMatrix::new(5, 3, |r| dense.row(r).collect());
+        assert_eq!(lazy.dimensions().height, 5);
+        assert_eq!(lazy.get(3, 1), F::from_canonical_u32(10));
+        assert_eq!(lazy.row_slice(4).to_vec(), dense.row(4).collect_vec());
+        for r in 0..5 {
+            let unpack = |packed: P| packed.as_slice().to_vec();
+            assert_eq!(
+                lazy.vertically_packed_row::<P>(r).map(unpack).collect_vec(),
+                dense
+                    .vertically_packed_row::<P>(r)
+                    .map(unpack)
+                    .collect_vec()
+            );
+        }
+        assert_eq!(lazy.to_row_major_matrix(), dense);
+    }
+}
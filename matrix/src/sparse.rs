@@ -6,6 +6,7 @@ use core::ops::Range;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 
+use crate::dense::RowMajorMatrix;
 use crate::Matrix;
 
 /// A sparse matrix stored in the compressed sparse row format.
@@ -22,6 +23,46 @@ pub struct CsrMatrix<T> {
 }
 
 impl<T: Clone + Default + Send + Sync> CsrMatrix<T> {
+    /// Build a sparse matrix from the nonzero entries of a dense one, i.e. those which differ from
+    /// `T::default()`.
+    pub fn from_row_major(mat: &RowMajorMatrix<T>) -> Self
+    where
+        T: PartialEq,
+    {
+        let zero = T::default();
+        let mut nonzero_values = Vec::new();
+        let mut row_indices = vec![0];
+        for row in mat.values.chunks_exact(mat.width) {
+            nonzero_values.extend(
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, v)| **v != zero)
+                    .map(|(c, v)| (c, v.clone())),
+            );
+            row_indices.push(nonzero_values.len());
+        }
+        Self {
+            width: mat.width,
+            nonzero_values,
+            row_indices,
+        }
+    }
+
+    /// The number of stored entries.
+    pub fn num_nonzero(&self) -> usize {
+        self.nonzero_values.len()
+    }
+
+    /// The indices of the rows with at least one stored entry. Every other row is entirely zero,
+    /// so callers can skip it, or handle all such rows at once.
+    pub fn nonzero_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.row_indices
+            .windows(2)
+            .enumerate()
+            .filter(|(_, bounds)| bounds[0] < bounds[1])
+            .map(|(r, _)| r)
+    }
+
     fn row_index_range(&self, r: usize) -> Range<usize> {
         debug_assert!(r < self.height());
         self.row_indices[r]..self.row_indices[r + 1]
@@ -85,4 +126,37 @@ impl<T: Clone + Default + Send + Sync> Matrix<T> for CsrMatrix<T> {
         }
         row.into_iter()
     }
+
+    /// Scatter the stored entries into one zeroed buffer, rather than building each row in turn.
+    fn to_row_major_matrix(self) -> RowMajorMatrix<T>
+    where
+        Self: Sized,
+        T: Clone,
+    {
+        let mut values = vec![T::default(); self.width * self.height()];
+        for r in 0..self.height() {
+            let row = &mut values[r * self.width..(r + 1) * self.width];
+            for (c, v) in self.sparse_row(r) {
+                row[*c] = v.clone();
+            }
+        }
+        RowMajorMatrix::new(values, self.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_round_trip() {
+        let mat = RowMajorMatrix::new(vec![0u32, 3, 0, 0, 0, 0, 7, 0, 9], 3);
+        let sparse = CsrMatrix::from_row_major(&mat);
+        assert_eq!(sparse.num_nonzero(), 3);
+        assert_eq!(sparse.sparse_row(2), &[(0, 7), (2, 9)]);
+        assert_eq!(sparse.nonzero_rows().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(sparse.get(1, 1), 0);
+        assert_eq!(sparse.row(0).collect::<Vec<_>>(), vec![0, 3, 0]);
+        assert_eq!(sparse.to_row_major_matrix(), mat);
+    }
 }
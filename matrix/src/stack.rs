@@ -1,5 +1,10 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter;
 use core::ops::Deref;
 
+use itertools::Itertools;
+
 use crate::Matrix;
 
 /// A combination of two matrices, stacked together vertically.
@@ -59,6 +64,110 @@ impl<T: Send + Sync, First: Matrix<T>, Second: Matrix<T>> Matrix<T>
     }
 }
 
+/// A combination of two matrices of equal height, stacked together horizontally, e.g. the main and
+/// auxiliary columns of a trace.
+#[derive(Copy, Clone, Debug)]
+pub struct HorizontalPair<First, Second> {
+    first: First,
+    second: Second,
+}
+
+impl<First, Second> HorizontalPair<First, Second> {
+    pub fn new<T>(first: First, second: Second) -> Self
+    where
+        T: Send + Sync,
+        First: Matrix<T>,
+        Second: Matrix<T>,
+    {
+        assert_eq!(first.height(), second.height());
+        Self { first, second }
+    }
+
+    /// The left-hand matrix.
+    pub const fn first(&self) -> &First {
+        &self.first
+    }
+
+    /// The right-hand matrix.
+    pub const fn second(&self) -> &Second {
+        &self.second
+    }
+}
+
+impl<T: Send + Sync, First: Matrix<T>, Second: Matrix<T>> Matrix<T>
+    for HorizontalPair<First, Second>
+{
+    fn width(&self) -> usize {
+        self.first.width() + self.second.width()
+    }
+
+    fn height(&self) -> usize {
+        self.first.height()
+    }
+
+    type Row<'a> = iter::Chain<First::Row<'a>, Second::Row<'a>> where Self: 'a;
+
+    fn get(&self, r: usize, c: usize) -> T {
+        if c < self.first.width() {
+            self.first.get(r, c)
+        } else {
+            self.second.get(r, c - self.first.width())
+        }
+    }
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        self.first.row(r).chain(self.second.row(r))
+    }
+}
+
+/// Any number of matrices of equal height, stacked together horizontally.
+#[derive(Clone, Debug)]
+pub struct HorizontalStack<Inner> {
+    mats: Vec<Inner>,
+}
+
+impl<Inner> HorizontalStack<Inner> {
+    pub fn new<T>(mats: Vec<Inner>) -> Self
+    where
+        T: Send + Sync,
+        Inner: Matrix<T>,
+    {
+        assert!(mats.iter().map(|mat| mat.height()).all_equal());
+        Self { mats }
+    }
+}
+
+impl<T: Send + Sync, Inner: Matrix<T>> Matrix<T> for HorizontalStack<Inner> {
+    fn width(&self) -> usize {
+        self.mats.iter().map(|mat| mat.width()).sum()
+    }
+
+    fn height(&self) -> usize {
+        self.mats.first().map_or(0, |mat| mat.height())
+    }
+
+    type Row<'a> = iter::Flatten<vec::IntoIter<Inner::Row<'a>>> where Self: 'a;
+
+    fn get(&self, r: usize, mut c: usize) -> T {
+        for mat in &self.mats {
+            if c < mat.width() {
+                return mat.get(r, c);
+            }
+            c -= mat.width();
+        }
+        panic!("column index out of bounds");
+    }
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        self.mats
+            .iter()
+            .map(|mat| mat.row(r))
+            .collect_vec()
+            .into_iter()
+            .flatten()
+    }
+}
+
 /// We use this to wrap both the row iterator and the row slice.
 #[derive(Debug)]
 pub enum EitherRow<L, R> {
@@ -94,3 +203,25 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dense::RowMajorMatrix;
+
+    #[test]
+    fn horizontal_stacks_concatenate_rows() {
+        let a = RowMajorMatrix::new(vec![1u32, 2, 3, 4], 2);
+        let b = RowMajorMatrix::new(vec![5u32, 6], 1);
+        let expected = RowMajorMatrix::new(vec![1u32, 2, 5, 3, 4, 6], 3);
+
+        let pair = HorizontalPair::new(a.as_view(), b.as_view());
+        assert_eq!(pair.get(1, 2), 6);
+        assert_eq!(pair.to_row_major_matrix(), expected);
+
+        let stack = HorizontalStack::new(vec![a.as_view(), b.as_view(), a.as_view()]);
+        assert_eq!(stack.width(), 5);
+        assert_eq!(stack.get(0, 3), 1);
+        assert_eq!(stack.row(1).collect_vec(), vec![3, 4, 6, 3, 4]);
+    }
+}
@@ -42,3 +42,28 @@ impl RowIndexMap for VerticallyStridedRowIndexMap {
         r * self.stride + self.offset
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::dense::RowMajorMatrix;
+    use crate::Matrix;
+
+    #[test]
+    fn strided_views_select_every_kth_row() {
+        // Rows 0..7, each holding its own index twice.
+        let mat = RowMajorMatrix::new((0..7).flat_map(|r| [r, r]).collect::<Vec<u32>>(), 2);
+
+        let view = mat.as_view().vertically_strided(3, 1);
+        assert_eq!(view.height(), 2);
+        assert_eq!(view.get(1, 0), 4);
+        assert_eq!(view.to_row_major_matrix().values, vec![1, 1, 4, 4]);
+
+        // 7 = 2 * 3 + 1, so only offset 0 gets a third row.
+        assert_eq!(mat.as_view().vertically_strided(3, 0).height(), 3);
+        assert_eq!(mat.as_view().vertically_strided(3, 2).height(), 2);
+        assert_eq!(mat.as_view().vertically_strided(1, 0).height(), 7);
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::Range;
+
+use crate::dense::RowMajorMatrix;
+use crate::Matrix;
+
+/// A view of the transpose of `inner`, whose rows are the columns of `inner`, without copying it.
+///
+/// This suits algorithms which naturally work a column at a time. Note that reading a row of the
+/// view reads a column of `inner`, so is strided if `inner` is row-major.
+#[derive(Copy, Clone, Debug)]
+pub struct TransposedView<Inner> {
+    pub inner: Inner,
+}
+
+/// A matrix stored in column-major order, i.e. the transpose of a row-major matrix, so that each
+/// of its columns is a contiguous slice.
+pub type ColMajorMatrix<T> = TransposedView<RowMajorMatrix<T>>;
+
+impl<Inner> TransposedView<Inner> {
+    pub const fn new(inner: Inner) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Clone + Send + Sync> ColMajorMatrix<T> {
+    /// A `height`-row matrix, given its values in column-major order.
+    pub fn new_col_major(values: Vec<T>, height: usize) -> Self {
+        Self::new(RowMajorMatrix::new(values, height))
+    }
+
+    /// The `c`th column.
+    pub fn column(&self, c: usize) -> &[T] {
+        let height = self.inner.width;
+        &self.inner.values[c * height..(c + 1) * height]
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = &[T]> {
+        self.inner.values.chunks_exact(self.inner.width)
+    }
+}
+
+impl<T: Clone + Default + Send + Sync> ColMajorMatrix<T> {
+    /// Convert a row-major matrix to column-major order, copying its values.
+    pub fn from_row_major(mat: RowMajorMatrix<T>) -> Self {
+        Self::new(mat.transpose())
+    }
+}
+
+impl<T: Send + Sync, Inner: Matrix<T>> Matrix<T> for TransposedView<Inner> {
+    fn width(&self) -> usize {
+        self.inner.height()
+    }
+
+    fn height(&self) -> usize {
+        self.inner.width()
+    }
+
+    type Row<'a> = TransposedRow<'a, T, Inner>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        TransposedRow {
+            inner: &self.inner,
+            col: r,
+            rows: 0..self.inner.height(),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn get(&self, r: usize, c: usize) -> T {
+        self.inner.get(c, r)
+    }
+}
+
+/// A row of a `TransposedView`, i.e. a column of the matrix it transposes.
+pub struct TransposedRow<'a, T, Inner> {
+    inner: &'a Inner,
+    col: usize,
+    rows: Range<usize>,
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T: Send + Sync, Inner: Matrix<T>> Iterator for TransposedRow<'a, T, Inner> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rows.next().map(|r| self.inner.get(r, self.col))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<T> {
+        self.rows.nth(n).map(|r| self.inner.get(r, self.col))
+    }
+}
+
+impl<'a, T: Send + Sync, Inner: Matrix<T>> ExactSizeIterator for TransposedRow<'a, T, Inner> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn transposed_view_matches_transpose() {
+        let mat = RowMajorMatrix::new((0..12).collect::<Vec<u32>>(), 4);
+        let view = TransposedView::new(mat.as_view());
+        assert_eq!((view.width(), view.height()), (3, 4));
+        assert_eq!(view.get(1, 2), 9);
+        assert_eq!(view.row(3).nth(1), Some(7));
+        assert_eq!(view.to_row_major_matrix(), mat.clone().transpose());
+    }
+
+    #[test]
+    fn col_major_columns_are_contiguous() {
+        let mat = RowMajorMatrix::new((0..6).collect::<Vec<u32>>(), 2);
+        let col_major = ColMajorMatrix::from_row_major(mat.clone());
+        assert_eq!((col_major.width(), col_major.height()), (2, 3));
+        assert_eq!(col_major.column(0), &[0, 2, 4]);
+        assert_eq!(col_major.column(1), &[1, 3, 5]);
+        assert_eq!(col_major.columns().count(), 2);
+        assert_eq!(col_major.row(1).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(col_major.to_row_major_matrix(), mat);
+    }
+}
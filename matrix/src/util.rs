@@ -4,17 +4,42 @@ use p3_util::{log2_strict_usize, reverse_bits_len};
 use crate::dense::RowMajorMatrix;
 use crate::Matrix;
 
+/// The log of the side of the tiles that `reverse_matrix_index_bits` works through one at a time.
+const LOG_TILE_HEIGHT: usize = 4;
+
+/// Permute the rows of `mat` in place, moving row `i` to the bit reversal of `i`.
+///
+/// Writing a row index as `(x, y, z)`, with `x` and `z` each `LOG_TILE_HEIGHT` bits wide, the
+/// rows with middle bits `y` and those with middle bits `reverse(y)` form a pair of tiles which
+/// the permutation swaps with each other, and nothing else. Each thread handles one pair of tiles
+/// at a time, so its memory accesses stay within a few short runs of rows rather than striding
+/// over the whole matrix.
 pub fn reverse_matrix_index_bits<F: Clone + Send + Sync>(mat: &mut RowMajorMatrix<F>) {
     let w = mat.width();
     let h = mat.height();
     let log_h = log2_strict_usize(h);
+    let log_tile = LOG_TILE_HEIGHT.min(log_h / 2);
+    let log_mid = log_h - 2 * log_tile;
     let values = mat.values.as_mut_ptr() as usize;
 
-    (0..h).into_par_iter().for_each(|i| {
+    (0..1 << log_mid).into_par_iter().for_each(|y| {
         let values = values as *mut F;
-        let j = reverse_bits_len(i, log_h);
-        if i < j {
-            unsafe { swap_rows_raw(values, w, i, j) };
+        let y_rev = reverse_bits_len(y, log_mid);
+        if y > y_rev {
+            // This pair of tiles is handled by `y_rev`.
+            return;
+        }
+        for x in 0..1 << log_tile {
+            let x_rev = reverse_bits_len(x, log_tile);
+            for z in 0..1 << log_tile {
+                let z_rev = reverse_bits_len(z, log_tile);
+                let i = (x << (log_h - log_tile)) | (y << log_tile) | z;
+                let j = (z_rev << (log_h - log_tile)) | (y_rev << log_tile) | x_rev;
+                // Within a single tile, each pair of rows comes up twice.
+                if y < y_rev || i < j {
+                    unsafe { swap_rows_raw(values, w, i.min(j), i.max(j)) };
+                }
+            }
         }
     });
 }
@@ -36,3 +61,24 @@ pub(crate) unsafe fn swap_rows_raw<F>(mat: *mut F, w: usize, i: usize, j: usize)
     let row_j = core::slice::from_raw_parts_mut(mat.add(j * w), w);
     row_i.swap_with_slice(row_j);
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::ops::Deref;
+
+    use super::*;
+
+    #[test]
+    fn reverse_matrix_index_bits_matches_naive() {
+        for log_h in 0..12 {
+            let h = 1 << log_h;
+            let mut mat = RowMajorMatrix::new((0..2 * h as u32).collect::<Vec<_>>(), 2);
+            reverse_matrix_index_bits(&mut mat);
+            for r in 0..h {
+                let expected = 2 * reverse_bits_len(r, log_h) as u32;
+                assert_eq!(mat.row_slice(r).deref(), &[expected, expected + 1]);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use alloc::vec::Vec;
+
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::TwoAdicField;
+use p3_symmetric::Permutation;
+
+use crate::util::first_row_to_first_col;
+use crate::MdsPermutation;
+
+/// Multiplication by a circulant matrix via the convolution theorem, in `O(N log N)` rather than
+/// the `O(N^2)` of the schoolbook product, for power-of-two `N`.
+///
+/// Unlike `util::apply_circulant_fft`, which transforms the matrix on every call, this transforms
+/// its first column once, on construction, so each permutation costs one DFT, one inverse DFT and
+/// `N` multiplications. It is MDS iff the circulant matrix it is built from is.
+#[derive(Clone, Debug)]
+pub struct CirculantFftMds<F, Dft, const N: usize> {
+    dft: Dft,
+    column_dft: Vec<F>,
+}
+
+impl<F, Dft, const N: usize> CirculantFftMds<F, Dft, N>
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    /// Construct from the first row of the circulant matrix, as canonical integers.
+    pub fn new(dft: Dft, first_row: &[u64; N]) -> Self {
+        let column = first_row_to_first_col(first_row).map(F::from_canonical_u64);
+        let column_dft = dft.dft(column.to_vec());
+        Self { dft, column_dft }
+    }
+}
+
+impl<F, Dft, const N: usize> Permutation<[F; N]> for CirculantFftMds<F, Dft, N>
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F> + Sync,
+{
+    fn permute(&self, input: [F; N]) -> [F; N] {
+        let input_dft = self.dft.dft(input.to_vec());
+        let product = self
+            .column_dft
+            .iter()
+            .zip(input_dft)
+            .map(|(&x, y)| x * y)
+            .collect();
+        self.dft.idft(product).try_into().unwrap()
+    }
+
+    fn permute_mut(&self, input: &mut [F; N]) {
+        *input = self.permute(*input);
+    }
+}
+
+impl<F, Dft, const N: usize> MdsPermutation<F, N> for CirculantFftMds<F, Dft, N>
+where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F> + Sync,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_dft::Radix2Dit;
+    use p3_symmetric::Permutation;
+    use rand::{thread_rng, Rng};
+
+    use super::CirculantFftMds;
+    use crate::util::apply_circulant;
+
+    #[test]
+    fn matches_schoolbook() {
+        let mut rng = thread_rng();
+        let first_row: [u64; 32] = core::array::from_fn(|_| rng.gen_range(0..(1 << 30)));
+        let mds = CirculantFftMds::<BabyBear, _, 32>::new(Radix2Dit::default(), &first_row);
+
+        let input: [BabyBear; 32] = rng.gen();
+        assert_eq!(mds.permute(input), apply_circulant(&first_row, input));
+    }
+}
@@ -7,9 +7,11 @@ extern crate alloc;
 use p3_symmetric::Permutation;
 
 mod butterflies;
+pub mod circulant_fft;
 pub mod coset_mds;
 pub mod integrated_coset_mds;
 pub mod karatsuba_convolution;
+pub mod search;
 pub mod util;
 
 pub trait MdsPermutation<T: Clone, const WIDTH: usize>: Permutation<[T; WIDTH]> {}
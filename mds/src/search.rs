@@ -0,0 +1,170 @@
+//! Finding and checking MDS matrices, for use when adding a field or a width.
+//!
+//! A matrix is MDS iff all of its square submatrices are nonsingular. There are about
+//! `binomial(2N, N)` of these, so checking them all is only practical for small widths; larger
+//! ones should use a construction which is MDS by design, such as a Cauchy matrix.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use p3_field::Field;
+use rand::Rng;
+
+/// The square matrix whose first row is `first_row`, and whose other rows are each the previous
+/// row rotated one step to the right.
+pub fn circulant_matrix<F: Field>(first_row: &[F]) -> Vec<Vec<F>> {
+    let mut row = first_row.to_vec();
+    (0..first_row.len())
+        .map(|_| {
+            let current = row.clone();
+            row.rotate_right(1);
+            current
+        })
+        .collect()
+}
+
+/// The Cauchy matrix with entries `1 / (x_i + y_j)`, which is MDS if the `x_i` are distinct, the
+/// `y_j` are distinct, and no `x_i + y_j` is zero. Returns `None` if any of these fails.
+pub fn cauchy_matrix<F: Field>(xs: &[F], ys: &[F]) -> Option<Vec<Vec<F>>> {
+    if !xs.iter().all_unique() || !ys.iter().all_unique() {
+        return None;
+    }
+    xs.iter()
+        .map(|&x| {
+            ys.iter()
+                .map(|&y| (x + y).try_inverse())
+                .collect::<Option<Vec<_>>>()
+        })
+        .collect()
+}
+
+/// Whether every square submatrix of `matrix` is nonsingular.
+pub fn is_mds<F: Field>(matrix: &[Vec<F>]) -> bool {
+    let n = matrix.len();
+    (1..=n).all(|k| {
+        (0..n).combinations(k).all(|rows| {
+            (0..n)
+                .combinations(k)
+                .all(|cols| is_nonsingular(matrix, &rows, &cols))
+        })
+    })
+}
+
+/// Whether the circulant matrix with the given first row is MDS.
+///
+/// Shifting both the rows and the columns of a submatrix of a circulant matrix by the same amount
+/// gives the same submatrix, so only submatrices including the first row need to be checked. This
+/// is about `N / k` times faster than `is_mds` for `k x k` submatrices.
+pub fn is_circulant_mds<F: Field>(first_row: &[F]) -> bool {
+    let matrix = circulant_matrix(first_row);
+    let n = matrix.len();
+    (1..=n).all(|k| {
+        (1..n).combinations(k - 1).all(|other_rows| {
+            let rows = [0].into_iter().chain(other_rows).collect_vec();
+            (0..n)
+                .combinations(k)
+                .all(|cols| is_nonsingular(&matrix, &rows, &cols))
+        })
+    })
+}
+
+/// Search for an MDS circulant matrix of the given width whose entries are at most `max_entry`, so
+/// that it can be applied with small-integer convolutions. Returns its first row, or `None` if
+/// none is found within `max_attempts` random candidates.
+pub fn search_circulant_mds<F: Field, R: Rng>(
+    width: usize,
+    max_entry: u64,
+    max_attempts: usize,
+    rng: &mut R,
+) -> Option<Vec<u64>> {
+    (0..max_attempts)
+        .map(|_| {
+            (0..width)
+                .map(|_| rng.gen_range(1..=max_entry))
+                .collect_vec()
+        })
+        .find(|first_row| {
+            is_circulant_mds(
+                &first_row
+                    .iter()
+                    .map(|&x| F::from_canonical_u64(x))
+                    .collect_vec(),
+            )
+        })
+}
+
+/// Whether the submatrix of `matrix` with the given rows and columns is nonsingular, by Gaussian
+/// elimination.
+fn is_nonsingular<F: Field>(matrix: &[Vec<F>], rows: &[usize], cols: &[usize]) -> bool {
+    let k = rows.len();
+    let mut sub = vec![vec![F::zero(); k]; k];
+    for (sub_row, &r) in sub.iter_mut().zip(rows) {
+        for (entry, &c) in sub_row.iter_mut().zip(cols) {
+            *entry = matrix[r][c];
+        }
+    }
+
+    for col in 0..k {
+        let Some(pivot) = (col..k).find(|&r| !sub[r][col].is_zero()) else {
+            return false;
+        };
+        sub.swap(col, pivot);
+        let pivot_inv = sub[col][col].inverse();
+        for r in col + 1..k {
+            let factor = sub[r][col] * pivot_inv;
+            if factor.is_zero() {
+                continue;
+            }
+            for c in col..k {
+                let delta = factor * sub[col][c];
+                sub[r][c] -= delta;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::AbstractField;
+    use rand::thread_rng;
+
+    use super::*;
+
+    type F = BabyBear;
+
+    fn row(entries: &[u64]) -> Vec<F> {
+        entries.iter().map(|&x| F::from_canonical_u64(x)).collect()
+    }
+
+    #[test]
+    fn known_circulant_mds() {
+        let first_row = row(&[7, 1, 3, 8, 8, 3, 4, 9]);
+        assert!(is_circulant_mds(&first_row));
+        assert!(is_mds(&circulant_matrix(&first_row)));
+    }
+
+    #[test]
+    fn non_mds() {
+        // A zero entry is a singular 1x1 submatrix.
+        assert!(!is_circulant_mds(&row(&[7, 0, 3, 8])));
+        // Equal rows in columns 0 and 1 make a singular 2x2 submatrix.
+        assert!(!is_circulant_mds(&row(&[1, 1, 1, 2])));
+    }
+
+    #[test]
+    fn cauchy_is_mds() {
+        let xs = row(&[1, 2, 3, 4, 5, 6]);
+        let ys = row(&[7, 8, 9, 10, 11, 12]);
+        assert!(is_mds(&cauchy_matrix(&xs, &ys).unwrap()));
+        assert!(cauchy_matrix(&xs, &row(&[7, 7, 9, 10, 11, 12])).is_none());
+    }
+
+    #[test]
+    fn search_finds_mds() {
+        let first_row = search_circulant_mds::<F, _>(6, 16, 1000, &mut thread_rng()).unwrap();
+        assert!(is_mds(&circulant_matrix(&row(&first_row))));
+    }
+}
@@ -107,9 +107,66 @@ pub fn apply_circulant_fft<F: TwoAdicField, const N: usize, FFT: TwoAdicSubgroup
     output.try_into().unwrap()
 }
 
+/// The `N x N` Cauchy matrix with entries `1 / (i + j + 1)` over the prime field of order `P`, as
+/// canonical integers.
+///
+/// Every square submatrix of a Cauchy matrix `1 / (x_i + y_j)` has a nonzero determinant when the
+/// `x_i` are distinct, the `y_j` are distinct and no `x_i + y_j` vanishes. Here `x_i = i` and
+/// `y_j = j + 1`, so the matrix is MDS whenever `P > 2N`.
+pub const fn cauchy_matrix<const N: usize>(p: u64) -> [[u64; N]; N] {
+    assert!((2 * N as u64) < p);
+    let mut output = [[0; N]; N];
+    let mut i = 0;
+    while i < N {
+        let mut j = 0;
+        while j < N {
+            output[i][j] = const_inverse(i as u64 + j as u64 + 1, p);
+            j += 1;
+        }
+        i += 1;
+    }
+    output
+}
+
+/// `x^(p - 2) mod p`, for `p < 2^32`.
+const fn const_inverse(x: u64, p: u64) -> u64 {
+    let mut result = 1;
+    let mut base = x % p;
+    let mut exp = p - 2;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % p;
+        }
+        base = base * base % p;
+        exp >>= 1;
+    }
+    result
+}
+
+/// Multiply `input` by `matrix`, where the entries of both are below `p < 2^32`, reducing each
+/// output modulo `p` once, after summing the unreduced products in a `u128`.
+///
+/// For fields in Montgomery form, the input may be the Montgomery representatives, in which case
+/// so is the output.
+#[inline]
+pub fn matmul_delayed_reduction<const N: usize>(
+    matrix: &[[u64; N]; N],
+    input: [u32; N],
+    p: u64,
+) -> [u32; N] {
+    array::from_fn(|i| {
+        let sum = matrix[i]
+            .iter()
+            .zip(input)
+            .map(|(&m, x)| (m * x as u64) as u128)
+            .sum::<u128>();
+        (sum % p as u128) as u32
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::first_row_to_first_col;
+    use super::{cauchy_matrix, first_row_to_first_col};
 
     #[test]
     fn rotation() {
@@ -118,4 +175,15 @@ mod tests {
 
         assert_eq!(first_row_to_first_col(&input), output);
     }
+
+    #[test]
+    fn cauchy_entries() {
+        const P: u64 = 101;
+        let matrix = cauchy_matrix::<8>(P);
+        for (i, row) in matrix.iter().enumerate() {
+            for (j, &entry) in row.iter().enumerate() {
+                assert_eq!(entry * (i + j + 1) as u64 % P, 1);
+            }
+        }
+    }
 }
@@ -4,8 +4,8 @@ use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
 use p3_blake3::Blake3;
 use p3_commit::Mmcs;
-use p3_field::{Field, PackedField, PackedValue};
-use p3_keccak::Keccak256Hash;
+use p3_field::{Field, PackedValue};
+use p3_keccak::{Keccak256Hash, KeccakF, VECTOR_LEN};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_mds::integrated_coset_mds::IntegratedCosetMds;
@@ -14,7 +14,7 @@ use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 use p3_rescue::{BasicSboxLayer, Rescue};
 use p3_symmetric::{
     CompressionFunctionFromHasher, CryptographicHasher, PaddingFreeSponge,
-    PseudoCompressionFunction, SerializingHasher32, TruncatedPermutation,
+    PseudoCompressionFunction, SerializingHasher32, SerializingHasher64, TruncatedPermutation,
 };
 use rand::distributions::{Distribution, Standard};
 use rand::thread_rng;
@@ -25,7 +25,9 @@ fn bench_merkle_trees(criterion: &mut Criterion) {
     bench_bb_poseidon2(criterion);
     bench_bb_rescue(criterion);
     bench_bb_blake3(criterion);
+    bench_bb_blake3_batched(criterion);
     bench_bb_keccak(criterion);
+    bench_bb_keccak_vectorized(criterion);
 }
 
 fn bench_bb_poseidon2(criterion: &mut Criterion) {
@@ -79,6 +81,18 @@ fn bench_bb_blake3(criterion: &mut Criterion) {
     bench_merkle_tree::<F, u8, H, C, 32>(criterion, h, c);
 }
 
+fn bench_bb_blake3_batched(criterion: &mut Criterion) {
+    type F = BabyBear;
+
+    type H = SerializingHasher32<Blake3>;
+    let h = H::new(Blake3 {});
+
+    type C = CompressionFunctionFromHasher<u32, Blake3, 2, 8>;
+    let c = C::new(Blake3 {});
+
+    bench_merkle_tree::<[F; 8], [u32; 8], H, C, 8>(criterion, h, c);
+}
+
 fn bench_bb_keccak(criterion: &mut Criterion) {
     type F = BabyBear;
 
@@ -92,23 +106,36 @@ fn bench_bb_keccak(criterion: &mut Criterion) {
     bench_merkle_tree::<F, u8, H, C, 32>(criterion, h, c);
 }
 
+fn bench_bb_keccak_vectorized(criterion: &mut Criterion) {
+    type F = BabyBear;
+
+    type U64Hash = PaddingFreeSponge<KeccakF, 25, 17, 4>;
+    type H = SerializingHasher64<U64Hash>;
+    let h = H::new(U64Hash::new(KeccakF));
+
+    type C = TruncatedPermutation<KeccakF, 2, 4, 25>;
+    let c = C::new(KeccakF);
+
+    bench_merkle_tree::<[F; VECTOR_LEN], [u64; VECTOR_LEN], H, C, 4>(criterion, h, c);
+}
+
 fn bench_merkle_tree<P, PW, H, C, const DIGEST_ELEMS: usize>(criterion: &mut Criterion, h: H, c: C)
 where
-    P: PackedField,
+    P: PackedValue,
     PW: PackedValue,
-    H: CryptographicHasher<P::Scalar, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
     H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
     H: Sync,
     C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>,
     C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
     C: Sync,
     [PW::Value; DIGEST_ELEMS]: Serialize + DeserializeOwned,
-    Standard: Distribution<P::Scalar>,
+    Standard: Distribution<P::Value>,
 {
     const ROWS: usize = 1 << 15;
     const COLS: usize = 135;
 
-    let matrix = RowMajorMatrix::<P::Scalar>::rand(&mut thread_rng(), ROWS, COLS);
+    let matrix = RowMajorMatrix::<P::Value>::rand(&mut thread_rng(), ROWS, COLS);
     let dims = matrix.dimensions();
     let leaves = vec![matrix];
 
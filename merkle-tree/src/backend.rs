@@ -0,0 +1,83 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use p3_field::PackedValue;
+use p3_matrix::Matrix;
+use p3_symmetric::{CryptographicHasher, PseudoCompressionFunction};
+
+use crate::merkle_tree::{compress_and_inject, first_digest_layer};
+
+/// Computes the digest layers of a `FieldMerkleTree`, one layer at a time.
+///
+/// `FieldMerkleTree::new` builds its layers with `PackedCpuBackend`. Another implementation, e.g.
+/// one hashing on a GPU, can be passed to `FieldMerkleTree::new_with_backend` instead; building a
+/// tree that way on one thread leaves the others free for other work, such as the next DFT. A
+/// backend must compute the same digests as the hasher and compression function its trees are
+/// later verified with.
+pub trait MerkleTreeBackend<F, W, const DIGEST_ELEMS: usize, const ARITY: usize> {
+    /// The digests of the rows of `tallest_matrices`, which all have the same height, padded with
+    /// default digests up to the smallest power of `ARITY` at least that height.
+    fn hash_leaves<M: Matrix<F>>(&self, tallest_matrices: Vec<&M>) -> Vec<[W; DIGEST_ELEMS]>;
+
+    /// The layer above `prev_layer`, each node compressing its `ARITY` children. If
+    /// `matrices_to_inject` is nonempty, each node's digest is then compressed with the digest of
+    /// its rows in those matrices (or the default digest, past their height), padded with default
+    /// digests up to `ARITY` inputs.
+    fn compress_and_inject<M: Matrix<F>>(
+        &self,
+        prev_layer: &[[W; DIGEST_ELEMS]],
+        matrices_to_inject: Vec<&M>,
+    ) -> Vec<[W; DIGEST_ELEMS]>;
+}
+
+/// Builds trees on the CPU, hashing and compressing a packing width of rows or nodes at a time.
+#[derive(Debug)]
+pub struct PackedCpuBackend<'a, P, PW, H, C> {
+    hash: &'a H,
+    compress: &'a C,
+    _phantom: PhantomData<(P, PW)>,
+}
+
+impl<'a, P, PW, H, C> PackedCpuBackend<'a, P, PW, H, C> {
+    pub const fn new(hash: &'a H, compress: &'a C) -> Self {
+        Self {
+            hash,
+            compress,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    MerkleTreeBackend<P::Value, PW::Value, DIGEST_ELEMS, ARITY>
+    for PackedCpuBackend<'a, P, PW, H, C>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
+    H: Sync,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
+    C: Sync,
+{
+    fn hash_leaves<M: Matrix<P::Value>>(
+        &self,
+        tallest_matrices: Vec<&M>,
+    ) -> Vec<[PW::Value; DIGEST_ELEMS]> {
+        first_digest_layer::<P, PW, H, M, DIGEST_ELEMS, ARITY>(self.hash, tallest_matrices)
+    }
+
+    fn compress_and_inject<M: Matrix<P::Value>>(
+        &self,
+        prev_layer: &[[PW::Value; DIGEST_ELEMS]],
+        matrices_to_inject: Vec<&M>,
+    ) -> Vec<[PW::Value; DIGEST_ELEMS]> {
+        compress_and_inject::<P, PW, H, C, M, DIGEST_ELEMS, ARITY>(
+            prev_layer,
+            matrices_to_inject,
+            self.hash,
+            self.compress,
+        )
+    }
+}
@@ -2,8 +2,10 @@
 
 extern crate alloc;
 
+mod backend;
 mod merkle_tree;
 mod mmcs;
 
+pub use backend::*;
 pub use merkle_tree::*;
 pub use mmcs::*;
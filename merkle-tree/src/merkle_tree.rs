@@ -1,3 +1,4 @@
+use alloc::collections::BTreeSet;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::array;
@@ -5,20 +6,29 @@ use core::cmp::Reverse;
 use core::marker::PhantomData;
 
 use itertools::Itertools;
-use p3_field::{PackedField, PackedValue};
+use p3_field::PackedValue;
+use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
-use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
+use p3_symmetric::{CryptographicHasher, Hash, MerkleCap, PseudoCompressionFunction};
+use p3_util::{log2_ceil_usize, log2_strict_usize};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
-/// A binary Merkle tree for field data. It has leaves of type `F` and digests of type
-/// `[W; DIGEST_ELEMS]`.
+use crate::{MerkleTreeBackend, PackedCpuBackend};
+
+/// A Merkle tree for field data. It has leaves of type `F` and digests of type
+/// `[W; DIGEST_ELEMS]`, and each internal node compresses `ARITY` children, where `ARITY` is a
+/// power of two. The default, binary, tree is the usual choice; higher arities give shorter paths
+/// at the cost of `ARITY - 1` siblings per layer in an opening.
 ///
 /// This generally shouldn't be used directly. If you're using a Merkle tree as an MMCS,
 /// see `FieldMerkleTreeMmcs`.
+///
+/// The tree, including its leaf matrices, can be serialized, so that a prover can commit once,
+/// persist the tree, and answer openings later, possibly from another process.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct FieldMerkleTree<F, W, M, const DIGEST_ELEMS: usize> {
+pub struct FieldMerkleTree<F, W, M, const DIGEST_ELEMS: usize, const ARITY: usize = 2> {
     pub(crate) leaves: Vec<M>,
     // Enable serialization for this field whenever the underlying array type supports it (len 1-32).
     #[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
@@ -28,27 +38,47 @@ pub struct FieldMerkleTree<F, W, M, const DIGEST_ELEMS: usize> {
     _phantom: PhantomData<F>,
 }
 
-impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
-    FieldMerkleTree<F, W, M, DIGEST_ELEMS>
+impl<
+        F: Clone + Send + Sync,
+        W: Clone,
+        M: Matrix<F>,
+        const DIGEST_ELEMS: usize,
+        const ARITY: usize,
+    > FieldMerkleTree<F, W, M, DIGEST_ELEMS, ARITY>
 {
-    /// Matrix heights need not be powers of two. However, if the heights of two given matrices
-    /// round up to the same power of two, they must be equal.
-    #[instrument(name = "build merkle tree", level = "debug", skip_all,
-                 fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
+    /// Matrix heights need not be powers of `ARITY`. However, if the heights of two given matrices
+    /// round up to the same power of two, they must be equal, and each height must round up to the
+    /// tallest height, rounded up to a power of two, divided by a power of `ARITY`. See
+    /// `heights_fit_arity`.
     pub fn new<P, PW, H, C>(h: &H, c: &C, leaves: Vec<M>) -> Self
     where
-        P: PackedField<Scalar = F>,
+        P: PackedValue<Value = F>,
         PW: PackedValue<Value = W>,
         H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
         H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
         H: Sync,
-        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], 2>,
-        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+        C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
         C: Sync,
+    {
+        assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
+        Self::new_with_backend(&PackedCpuBackend::<P, PW, H, C>::new(h, c), leaves)
+    }
+
+    /// Build the tree with `backend` computing its digest layers, rather than the packed CPU
+    /// hashing `new` uses.
+    #[instrument(name = "build merkle tree", level = "debug", skip_all,
+                 fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
+    pub fn new_with_backend<B>(backend: &B, leaves: Vec<M>) -> Self
+    where
+        B: MerkleTreeBackend<F, W, DIGEST_ELEMS, ARITY>,
     {
         assert!(!leaves.is_empty(), "No matrices given?");
 
-        assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
+        assert!(
+            ARITY >= 2 && ARITY.is_power_of_two(),
+            "arity must be a power of two"
+        );
 
         // check height property
         assert!(
@@ -58,8 +88,12 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
                 .sorted()
                 .tuple_windows()
                 .all(|(curr, next)| curr == next
-                    || curr.next_power_of_two() != next.next_power_of_two()),
-            "matrix heights that round up to the same power of two must be equal"
+                    || padded_height::<ARITY>(curr) != padded_height::<ARITY>(next)),
+            "matrix heights that round up to the same power of the arity must be equal"
+        );
+        assert!(
+            heights_fit_arity::<ARITY>(leaves.iter().map(|m| m.height())),
+            "matrix heights must differ from the tallest by a power of the arity"
         );
 
         let mut leaves_largest_first = leaves
@@ -72,28 +106,20 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
             .peeking_take_while(|m| m.height() == max_height)
             .collect_vec();
 
-        let mut digest_layers = vec![first_digest_layer::<P, PW, H, M, DIGEST_ELEMS>(
-            h,
-            tallest_matrices,
-        )];
+        let mut digest_layers = vec![backend.hash_leaves(tallest_matrices)];
         loop {
             let prev_layer = digest_layers.last().unwrap().as_slice();
             if prev_layer.len() == 1 {
                 break;
             }
-            let next_layer_len = prev_layer.len() / 2;
+            let next_layer_len = prev_layer.len() / ARITY;
 
             // The matrices that get injected at this layer.
             let matrices_to_inject = leaves_largest_first
-                .peeking_take_while(|m| m.height().next_power_of_two() == next_layer_len)
+                .peeking_take_while(|m| padded_height::<ARITY>(m.height()) == next_layer_len)
                 .collect_vec();
 
-            let next_digests = compress_and_inject::<P, PW, H, C, M, DIGEST_ELEMS>(
-                prev_layer,
-                matrices_to_inject,
-                h,
-                c,
-            );
+            let next_digests = backend.compress_and_inject(prev_layer, matrices_to_inject);
             digest_layers.push(next_digests);
         }
 
@@ -111,23 +137,147 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
     {
         self.digest_layers.last().unwrap()[0].into()
     }
+
+    /// The cap of height (at most) `cap_height`, i.e. the digests of the layer `cap_height` layers
+    /// below the root. See `cap_layer` for when the cap is lower.
+    #[must_use]
+    pub fn cap(&self, cap_height: usize) -> MerkleCap<F, W, DIGEST_ELEMS> {
+        let layer = cap_layer::<ARITY>(self.leaves.iter().map(|m| m.height()), cap_height);
+        self.digest_layers[layer].clone().into()
+    }
+}
+
+impl<F: Clone + Send + Sync, W: Copy + Default, const DIGEST_ELEMS: usize, const ARITY: usize>
+    FieldMerkleTree<F, W, RowMajorMatrix<F>, DIGEST_ELEMS, ARITY>
+{
+    /// Overwrite rows of the leaf matrices, each update given as `(matrix, row, values)`, and
+    /// recompute only the digests on the paths from those rows up, so that `k` updates take
+    /// `O(k log n)` hashes. `h` and `c` must be those the tree was built with.
+    pub fn update_rows<H, C>(
+        &mut self,
+        h: &H,
+        c: &C,
+        updates: impl IntoIterator<Item = (usize, usize, Vec<F>)>,
+    ) where
+        H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
+        C: PseudoCompressionFunction<[W; DIGEST_ELEMS], ARITY>,
+    {
+        let num_layers = self.digest_layers.len();
+        let log_arity = log2_strict_usize(ARITY);
+        let max_height_padded = self.digest_layers[0].len();
+        let layer_of = |height: usize| {
+            log2_strict_usize(max_height_padded / padded_height::<ARITY>(height)) / log_arity
+        };
+
+        // The nodes, in each layer, whose digests change.
+        let mut dirty = vec![BTreeSet::new(); num_layers];
+        for (matrix, r, values) in updates {
+            let leaf = &mut self.leaves[matrix];
+            assert!(r < leaf.height(), "row {r} is out of bounds");
+            leaf.row_mut(r).clone_from_slice(&values);
+            dirty[layer_of(leaf.height())].insert(r);
+        }
+
+        // The matrices hashed into each layer, in the same order as when the tree was built.
+        let mut layer_matrices = vec![vec![]; num_layers];
+        for matrix in (0..self.leaves.len()).sorted_by_key(|&i| Reverse(self.leaves[i].height())) {
+            layer_matrices[layer_of(self.leaves[matrix].height())].push(matrix);
+        }
+        let rows_digest = |matrices: &[usize], r: usize| -> [W; DIGEST_ELEMS] {
+            h.hash_iter(matrices.iter().flat_map(|&m| self.leaves[m].row(r)))
+        };
+
+        let default_digest = [W::default(); DIGEST_ELEMS];
+        for layer in 0..num_layers {
+            if layer > 0 {
+                let parents = dirty[layer - 1]
+                    .iter()
+                    .map(|&node| node >> log_arity)
+                    .collect_vec();
+                dirty[layer].extend(parents);
+            }
+            let matrices = &layer_matrices[layer];
+            for &node in &dirty[layer] {
+                let digest = if layer == 0 {
+                    rows_digest(matrices, node)
+                } else {
+                    let digest = c.compress(children(&self.digest_layers[layer - 1], node));
+                    match matrices.first() {
+                        None => digest,
+                        Some(&first) => {
+                            let injected = if node < self.leaves[first].height() {
+                                rows_digest(matrices, node)
+                            } else {
+                                default_digest
+                            };
+                            c.compress(pad_injection([digest, injected], default_digest))
+                        }
+                    }
+                };
+                self.digest_layers[layer][node] = digest;
+            }
+        }
+    }
+}
+
+/// The layer, counting up from the leaves, whose digests form the cap of height (at most)
+/// `cap_height` of a tree over matrices of the given heights.
+///
+/// The cap is lowered, if necessary, to the layer at which the shortest matrices are injected, as
+/// anything injected above the cap wouldn't be committed to.
+pub(crate) fn cap_layer<const ARITY: usize>(
+    heights: impl Iterator<Item = usize> + Clone,
+    cap_height: usize,
+) -> usize {
+    let log_arity = log2_strict_usize(ARITY);
+    let log_padded = |height| log2_strict_usize(padded_height::<ARITY>(height)) / log_arity;
+    let num_layers = log_padded(heights.clone().max().unwrap());
+    let shortest_layer = num_layers - log_padded(heights.min().unwrap());
+    num_layers.saturating_sub(cap_height).max(shortest_layer)
+}
+
+/// Whether matrices of the given heights can share a tree of this arity while keeping the `Mmcs`
+/// row contract, under which leaf `index` holds row `index >> (log2_ceil(max_height) -
+/// log2_ceil(height))` of each matrix. A matrix is injected a whole number of layers, each of
+/// `log2(ARITY)` bits, above the leaves, so the bits it drops from the index must be a multiple of
+/// `log2(ARITY)`. A binary tree takes any heights.
+pub(crate) fn heights_fit_arity<const ARITY: usize>(
+    heights: impl Iterator<Item = usize> + Clone,
+) -> bool {
+    let log_arity = log2_strict_usize(ARITY);
+    let Some(log_max_height) = heights.clone().max().map(log2_ceil_usize) else {
+        return true;
+    };
+    heights
+        .map(log2_ceil_usize)
+        .all(|log_height| (log_max_height - log_height) % log_arity == 0)
+}
+
+/// The smallest power of `ARITY` which is at least `height`, i.e. the number of leaves a tree of
+/// this arity needs to hold `height` rows.
+pub(crate) fn padded_height<const ARITY: usize>(height: usize) -> usize {
+    let mut padded = 1;
+    while padded < height {
+        padded *= ARITY;
+    }
+    padded
 }
 
-fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize>(
+pub(crate) fn first_digest_layer<P, PW, H, M, const DIGEST_ELEMS: usize, const ARITY: usize>(
     h: &H,
     tallest_matrices: Vec<&M>,
 ) -> Vec<[PW::Value; DIGEST_ELEMS]>
 where
-    P: PackedField,
+    P: PackedValue,
     PW: PackedValue,
-    H: CryptographicHasher<P::Scalar, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
     H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
     H: Sync,
-    M: Matrix<P::Scalar>,
+    M: Matrix<P::Value>,
 {
     let width = PW::WIDTH;
     let max_height = tallest_matrices[0].height();
-    let max_height_padded = max_height.next_power_of_two();
+    let max_height_padded = padded_height::<ARITY>(max_height);
 
     let default_digest: [PW::Value; DIGEST_ELEMS] = [PW::Value::default(); DIGEST_ELEMS];
     let mut digests = vec![default_digest; max_height_padded];
@@ -158,9 +308,12 @@ where
     digests
 }
 
-/// Compress `n` digests from the previous layer into `n/2` digests, while potentially mixing in
-/// some leaf data, if there are input matrices with (padded) height `n/2`.
-fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize>(
+/// Compress `n` digests from the previous layer into `n/ARITY` digests, while potentially mixing
+/// in some leaf data, if there are input matrices with (padded) height `n/ARITY`.
+///
+/// Leaf data is mixed in by compressing a node's digest with the digest of its rows, padded with
+/// default digests up to `ARITY` inputs.
+pub(crate) fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize, const ARITY: usize>(
     prev_layer: &[[PW::Value; DIGEST_ELEMS]],
     matrices_to_inject: Vec<&M>,
     h: &H,
@@ -168,22 +321,22 @@ fn compress_and_inject<P, PW, H, C, M, const DIGEST_ELEMS: usize>(
 ) -> Vec<[PW::Value; DIGEST_ELEMS]>
 where
     PW: PackedValue,
-    P: PackedField,
-    H: CryptographicHasher<P::Scalar, [PW::Value; DIGEST_ELEMS]>,
+    P: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
     H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
     H: Sync,
-    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
     C: Sync,
-    M: Matrix<P::Scalar>,
+    M: Matrix<P::Value>,
 {
     if matrices_to_inject.is_empty() {
-        return compress::<PW, C, DIGEST_ELEMS>(prev_layer, c);
+        return compress::<PW, C, DIGEST_ELEMS, ARITY>(prev_layer, c);
     }
 
     let width = PW::WIDTH;
     let next_len = matrices_to_inject[0].height();
-    let next_len_padded = prev_layer.len() / 2;
+    let next_len_padded = prev_layer.len() / ARITY;
 
     let default_digest: [PW::Value; DIGEST_ELEMS] = [PW::Value::default(); DIGEST_ELEMS];
     let mut next_digests = vec![default_digest; next_len_padded];
@@ -193,15 +346,16 @@ where
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
-            let left = array::from_fn(|j| PW::from_fn(|k| prev_layer[2 * (first_row + k)][j]));
-            let right = array::from_fn(|j| PW::from_fn(|k| prev_layer[2 * (first_row + k) + 1][j]));
-            let mut packed_digest = c.compress([left, right]);
+            let mut packed_digest = c.compress(pack_children(prev_layer, first_row));
             let tallest_digest = h.hash_iter(
                 matrices_to_inject
                     .iter()
                     .flat_map(|m| m.vertically_packed_row(first_row)),
             );
-            packed_digest = c.compress([packed_digest, tallest_digest]);
+            packed_digest = c.compress(pad_injection(
+                [packed_digest, tallest_digest],
+                default_digest.map(PW::from),
+            ));
             for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
                 *dst = src;
             }
@@ -210,39 +364,56 @@ where
     // If our packing width did not divide next_len, fall back to single-threaded scalar code
     // for the last bit.
     for i in (next_len / width * width)..next_len {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
+        let digest = c.compress(children(prev_layer, i));
         let rows_digest = h.hash_iter(matrices_to_inject.iter().flat_map(|m| m.row(i)));
-        next_digests[i] = c.compress([digest, rows_digest]);
+        next_digests[i] = c.compress(pad_injection([digest, rows_digest], default_digest));
     }
 
     // At this point, we've exceeded the height of the matrices to inject, so we continue the
-    // process above except with default_digest in place of an input digest.
-    for i in next_len..next_len_padded {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
-        next_digests[i] = c.compress([digest, default_digest]);
+    // process above except with default_digest in place of an input digest. Whole chunks of this
+    // padding are again compressed a packing width at a time, leaving scalar code for the nodes
+    // before the first chunk boundary and after the last.
+    let first_padding_chunk = next_len.next_multiple_of(width).min(next_len_padded);
+    let num_padding_chunks = (next_len_padded - first_padding_chunk) / width;
+    let padding_chunks_end = first_padding_chunk + num_padding_chunks * width;
+    next_digests[first_padding_chunk..padding_chunks_end]
+        .par_chunks_exact_mut(width)
+        .enumerate()
+        .for_each(|(i, digests_chunk)| {
+            let first_node = first_padding_chunk + i * width;
+            let packed_default_digest = default_digest.map(PW::from);
+            let packed_digest = c.compress(pack_children(prev_layer, first_node));
+            let packed_digest = c.compress(pad_injection(
+                [packed_digest, packed_default_digest],
+                packed_default_digest,
+            ));
+            for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
+                *dst = src;
+            }
+        });
+
+    for i in (next_len..first_padding_chunk).chain(padding_chunks_end..next_len_padded) {
+        let digest = c.compress(children(prev_layer, i));
+        next_digests[i] = c.compress(pad_injection([digest, default_digest], default_digest));
     }
 
     next_digests
 }
 
-/// Compress `n` digests from the previous layer into `n/2` digests.
-fn compress<P, C, const DIGEST_ELEMS: usize>(
+/// Compress `n` digests from the previous layer into `n/ARITY` digests.
+fn compress<P, C, const DIGEST_ELEMS: usize, const ARITY: usize>(
     prev_layer: &[[P::Value; DIGEST_ELEMS]],
     c: &C,
 ) -> Vec<[P::Value; DIGEST_ELEMS]>
 where
     P: PackedValue,
-    C: PseudoCompressionFunction<[P::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[P; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[P::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[P; DIGEST_ELEMS], ARITY>,
     C: Sync,
 {
     debug_assert!(prev_layer.len().is_power_of_two());
     let width = P::WIDTH;
-    let next_len = prev_layer.len() / 2;
+    let next_len = prev_layer.len() / ARITY;
 
     let default_digest: [P::Value; DIGEST_ELEMS] = [P::Value::default(); DIGEST_ELEMS];
     let mut next_digests = vec![default_digest; next_len];
@@ -252,9 +423,7 @@ where
         .enumerate()
         .for_each(|(i, digests_chunk)| {
             let first_row = i * width;
-            let left = array::from_fn(|j| P::from_fn(|k| prev_layer[2 * (first_row + k)][j]));
-            let right = array::from_fn(|j| P::from_fn(|k| prev_layer[2 * (first_row + k) + 1][j]));
-            let packed_digest = c.compress([left, right]);
+            let packed_digest = c.compress(pack_children(prev_layer, first_row));
             for (dst, src) in digests_chunk.iter_mut().zip(unpack_array(packed_digest)) {
                 *dst = src;
             }
@@ -263,16 +432,41 @@ where
     // If our packing width did not divide next_len, fall back to single-threaded scalar code
     // for the last bit.
     for i in (next_len / width * width)..next_len {
-        let left = prev_layer[2 * i];
-        let right = prev_layer[2 * i + 1];
-        let digest = c.compress([left, right]);
-        next_digests[i] = digest;
+        next_digests[i] = c.compress(children(prev_layer, i));
     }
 
     // Everything has been initialized so we can safely cast.
     next_digests
 }
 
+/// The `ARITY` children, in the previous layer, of node `i`.
+#[inline]
+fn children<T: Copy, const ARITY: usize>(prev_layer: &[T], i: usize) -> [T; ARITY] {
+    array::from_fn(|child| prev_layer[ARITY * i + child])
+}
+
+/// The `ARITY` children, in the previous layer, of nodes `first_node..first_node + P::WIDTH`,
+/// packed so that each packed digest holds the same child of every node.
+#[inline]
+fn pack_children<P: PackedValue, const DIGEST_ELEMS: usize, const ARITY: usize>(
+    prev_layer: &[[P::Value; DIGEST_ELEMS]],
+    first_node: usize,
+) -> [[P; DIGEST_ELEMS]; ARITY] {
+    array::from_fn(|child| {
+        array::from_fn(|j| P::from_fn(|k| prev_layer[ARITY * (first_node + k) + child][j]))
+    })
+}
+
+/// Pads a digest and the digest of the rows injected under it with copies of `default_digest`, up
+/// to `ARITY` inputs to a compression. For a binary tree this is the identity.
+#[inline]
+pub(crate) fn pad_injection<T: Copy, const ARITY: usize>(
+    digests: [T; 2],
+    default_digest: T,
+) -> [T; ARITY] {
+    array::from_fn(|i| digests.get(i).copied().unwrap_or(default_digest))
+}
+
 /// Converts a packed array `[P; N]` into its underlying `P::WIDTH` scalar arrays.
 #[inline]
 fn unpack_array<P: PackedValue, const N: usize>(
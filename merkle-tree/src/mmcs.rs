@@ -1,73 +1,160 @@
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::vec::Vec;
-use core::cmp::Reverse;
+use core::array;
+use core::cmp::{Ordering, Reverse};
 use core::marker::PhantomData;
 
-use itertools::Itertools;
+use itertools::{izip, Itertools};
 use p3_commit::Mmcs;
-use p3_field::{PackedField, PackedValue};
+use p3_field::PackedValue;
+use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::{Dimensions, Matrix};
-use p3_symmetric::{CryptographicHasher, Hash, PseudoCompressionFunction};
-use p3_util::log2_ceil_usize;
+use p3_symmetric::{CryptographicHasher, MerkleCap, PseudoCompressionFunction};
+use p3_util::{log2_ceil_usize, log2_strict_usize};
 use serde::{Deserialize, Serialize};
 
-use crate::FieldMerkleTree;
+use crate::merkle_tree::{cap_layer, heights_fit_arity, pad_injection, padded_height};
+use crate::{FieldMerkleTree, MerkleTreeBackend};
 
 /// A vector commitment scheme backed by a `FieldMerkleTree`.
 ///
 /// Generics:
-/// - `P`: a leaf value TODO
+/// - `P`: a leaf value, or several packed together, so that `H` hashes several rows at once
+/// - `PW`: a digest element, packed as `P` is
 /// - `H`: the leaf hasher
-/// - `C`: the digest compression function
+/// - `C`: the digest compression function, taking `ARITY` digests to one
+/// - `ARITY`: the number of children of each internal node, a power of two
+///
+/// Openings follow the usual `Mmcs` indexing: opening `index` gives row
+/// `index >> (log2_ceil(max_height) - log2_ceil(height))` of each matrix. For `ARITY > 2`, this
+/// requires the heights in a batch to round up to the tallest, rounded up, divided by a power of
+/// `ARITY`, e.g. heights 64 and 16 but not 8 and 4 for `ARITY = 4`. Other heights are rejected.
+///
+/// An opening proof holds the `ARITY - 1` siblings of the opened node at each layer, from the
+/// leaves up, so a higher arity trades fewer layers for more digests per layer.
+///
+/// The commitment is a Merkle cap, of height zero (just the root) by default. A cap of height `k`
+/// holds the `ARITY^k` digests `k` layers below the root, and shortens every opening proof by `k`
+/// layers.
 #[derive(Copy, Clone, Debug)]
-pub struct FieldMerkleTreeMmcs<P, PW, H, C, const DIGEST_ELEMS: usize> {
+pub struct FieldMerkleTreeMmcs<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize = 2> {
     hash: H,
     compress: C,
+    cap_height: usize,
     _phantom: PhantomData<(P, PW)>,
 }
 
-impl<P, PW, H, C, const DIGEST_ELEMS: usize> FieldMerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS> {
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    FieldMerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+{
     pub const fn new(hash: H, compress: C) -> Self {
+        Self::new_with_cap_height(hash, compress, 0)
+    }
+
+    /// Commit to caps of height (at most) `cap_height`, rather than to roots. The cap is lower
+    /// for a batch whose shortest matrices would otherwise be injected above it.
+    pub const fn new_with_cap_height(hash: H, compress: C, cap_height: usize) -> Self {
         Self {
             hash,
             compress,
+            cap_height,
             _phantom: PhantomData,
         }
     }
+
+    /// The positions, as `(layer, node)`, of the siblings in a full proof for `index`, in order.
+    fn sibling_positions(index: usize, num_layers: usize) -> impl Iterator<Item = (usize, usize)> {
+        let log_arity = log2_strict_usize(ARITY);
+        (0..num_layers).flat_map(move |layer| {
+            let node = index >> (layer * log_arity);
+            let first_child = node & !(ARITY - 1);
+            (first_child..first_child + ARITY)
+                .filter(move |&sibling| sibling != node)
+                .map(move |sibling| (layer, sibling))
+        })
+    }
+}
+
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize>
+    FieldMerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
+where
+    P: PackedValue,
+    PW: PackedValue,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+{
+    /// Commit to `inputs` as `commit` does, but with `backend` building the tree, e.g. on a GPU.
+    /// The backend must compute the same digests as this MMCS's hasher and compression function.
+    #[allow(clippy::type_complexity)]
+    pub fn commit_with_backend<B, M>(
+        &self,
+        backend: &B,
+        inputs: Vec<M>,
+    ) -> (
+        MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>,
+        FieldMerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    )
+    where
+        B: MerkleTreeBackend<P::Value, PW::Value, DIGEST_ELEMS, ARITY>,
+        M: Matrix<P::Value>,
+    {
+        let tree = FieldMerkleTree::new_with_backend(backend, inputs);
+        let cap = tree.cap(self.cap_height);
+        (cap, tree)
+    }
+
+    /// Overwrite rows of committed matrices, each update given as `(matrix, row, values)`, and
+    /// return the new commitment. Only the digests above the updated rows are recomputed.
+    #[allow(clippy::type_complexity)]
+    pub fn update_rows(
+        &self,
+        prover_data: &mut FieldMerkleTree<
+            P::Value,
+            PW::Value,
+            RowMajorMatrix<P::Value>,
+            DIGEST_ELEMS,
+            ARITY,
+        >,
+        updates: impl IntoIterator<Item = (usize, usize, Vec<P::Value>)>,
+    ) -> MerkleCap<P::Value, PW::Value, DIGEST_ELEMS> {
+        prover_data.update_rows(&self.hash, &self.compress, updates);
+        prover_data.cap(self.cap_height)
+    }
 }
 
-impl<P, PW, H, C, const DIGEST_ELEMS: usize> Mmcs<P::Scalar>
-    for FieldMerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>
+impl<P, PW, H, C, const DIGEST_ELEMS: usize, const ARITY: usize> Mmcs<P::Value>
+    for FieldMerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS, ARITY>
 where
-    P: PackedField,
+    P: PackedValue,
     PW: PackedValue,
-    H: CryptographicHasher<P::Scalar, [PW::Value; DIGEST_ELEMS]>,
+    H: CryptographicHasher<P::Value, [PW::Value; DIGEST_ELEMS]>,
     H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
     H: Sync,
-    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], 2>,
-    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
+    C: PseudoCompressionFunction<[PW::Value; DIGEST_ELEMS], ARITY>,
+    C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], ARITY>,
     C: Sync,
     PW::Value: Eq,
     [PW::Value; DIGEST_ELEMS]: Serialize + for<'de> Deserialize<'de>,
 {
-    type Commitment = Hash<P::Scalar, PW::Value, DIGEST_ELEMS>;
+    type Commitment = MerkleCap<P::Value, PW::Value, DIGEST_ELEMS>;
     type Proof = Vec<[PW::Value; DIGEST_ELEMS]>;
     type Error = ();
-    type ProverData<M> = FieldMerkleTree<P::Scalar, PW::Value, M, DIGEST_ELEMS>;
+    type ProverData<M> = FieldMerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>;
 
-    fn commit<M: Matrix<P::Scalar>>(
+    fn commit<M: Matrix<P::Value>>(
         &self,
         inputs: Vec<M>,
     ) -> (Self::Commitment, Self::ProverData<M>) {
         let tree = FieldMerkleTree::new::<P, PW, H, C>(&self.hash, &self.compress, inputs);
-        let root = tree.root();
-        (root, tree)
+        let cap = tree.cap(self.cap_height);
+        (cap, tree)
     }
 
-    fn open_batch<M: Matrix<P::Scalar>>(
+    fn open_batch<M: Matrix<P::Value>>(
         &self,
         index: usize,
-        prover_data: &FieldMerkleTree<P::Scalar, PW::Value, M, DIGEST_ELEMS>,
-    ) -> (Vec<Vec<P::Scalar>>, Vec<[PW::Value; DIGEST_ELEMS]>) {
+        prover_data: &FieldMerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS, ARITY>,
+    ) -> (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>) {
         let max_height = self.get_max_height(prover_data);
         let log_max_height = log2_ceil_usize(max_height);
 
@@ -82,14 +169,68 @@ where
             })
             .collect_vec();
 
-        let proof = (0..log_max_height)
-            .map(|i| prover_data.digest_layers[i][(index >> i) ^ 1])
+        let num_layers = cap_layer::<ARITY>(
+            prover_data.leaves.iter().map(|m| m.height()),
+            self.cap_height,
+        );
+        let proof = Self::sibling_positions(index, num_layers)
+            .map(|(layer, sibling)| prover_data.digest_layers[layer][sibling])
             .collect();
 
         (openings, proof)
     }
 
-    fn get_matrices<'a, M: Matrix<P::Scalar>>(
+    /// Leaves out every sibling digest already sent, at the same position in the tree, by an
+    /// earlier proof. Queries close together share most of their upper siblings.
+    fn prune_proofs(&self, indices: &[usize], proofs: Vec<Self::Proof>) -> Vec<Self::Proof> {
+        let mut sent = BTreeSet::new();
+        izip!(indices, proofs)
+            .map(|(&index, proof)| {
+                let num_layers = proof.len() / (ARITY - 1);
+                Self::sibling_positions(index, num_layers)
+                    .zip(proof)
+                    .filter(|&(position, _)| sent.insert(position))
+                    .map(|(_, sibling)| sibling)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn expand_proofs(
+        &self,
+        indices: &[usize],
+        proofs: Vec<Self::Proof>,
+    ) -> Result<Vec<Self::Proof>, Self::Error> {
+        if indices.len() != proofs.len() {
+            return Err(());
+        }
+        // The first proof is never pruned, so it has the full length.
+        let Some(num_layers) = proofs.first().map(|proof| proof.len() / (ARITY - 1)) else {
+            return Ok(proofs);
+        };
+        let mut sent = BTreeMap::new();
+        izip!(indices, proofs)
+            .map(|(&index, pruned)| {
+                let mut pruned = pruned.into_iter();
+                let proof = Self::sibling_positions(index, num_layers)
+                    .map(|position| match sent.get(&position) {
+                        Some(&sibling) => Ok(sibling),
+                        None => {
+                            let sibling = pruned.next().ok_or(())?;
+                            sent.insert(position, sibling);
+                            Ok(sibling)
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if pruned.next().is_some() {
+                    return Err(());
+                }
+                Ok(proof)
+            })
+            .collect()
+    }
+
+    fn get_matrices<'a, M: Matrix<P::Value>>(
         &self,
         prover_data: &'a Self::ProverData<M>,
     ) -> Vec<&'a M> {
@@ -101,45 +242,52 @@ where
         commit: &Self::Commitment,
         dimensions: &[Dimensions],
         mut index: usize,
-        opened_values: &[Vec<P::Scalar>],
+        opened_values: &[Vec<P::Value>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error> {
+        if !heights_fit_arity::<ARITY>(dimensions.iter().map(|dims| dims.height)) {
+            return Err(());
+        }
         let mut heights_tallest_first = dimensions
             .iter()
             .enumerate()
             .sorted_by_key(|(_, dims)| Reverse(dims.height))
             .peekable();
 
-        let mut curr_height_padded = heights_tallest_first
-            .peek()
-            .unwrap()
-            .1
-            .height
-            .next_power_of_two();
+        let mut curr_height_padded =
+            padded_height::<ARITY>(heights_tallest_first.peek().unwrap().1.height);
 
         let mut root = self.hash.hash_iter_slices(
             heights_tallest_first
                 .peeking_take_while(|(_, dims)| {
-                    dims.height.next_power_of_two() == curr_height_padded
+                    padded_height::<ARITY>(dims.height) == curr_height_padded
                 })
                 .map(|(i, _)| opened_values[i].as_slice()),
         );
 
-        for &sibling in proof.iter() {
-            let (left, right) = if index & 1 == 0 {
-                (root, sibling)
-            } else {
-                (sibling, root)
-            };
+        let num_layers =
+            cap_layer::<ARITY>(dimensions.iter().map(|dims| dims.height), self.cap_height);
+        if proof.len() != num_layers * (ARITY - 1) {
+            return Err(());
+        }
 
-            root = self.compress.compress([left, right]);
-            index >>= 1;
-            curr_height_padded >>= 1;
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        for siblings in proof.chunks_exact(ARITY - 1) {
+            let position = index & (ARITY - 1);
+            let children = array::from_fn(|child| match child.cmp(&position) {
+                Ordering::Less => siblings[child],
+                Ordering::Equal => root,
+                Ordering::Greater => siblings[child - 1],
+            });
+
+            root = self.compress.compress(children);
+            index /= ARITY;
+            curr_height_padded /= ARITY;
 
             let next_height = heights_tallest_first
                 .peek()
                 .map(|(_, dims)| dims.height)
-                .filter(|h| h.next_power_of_two() == curr_height_padded);
+                .filter(|&h| padded_height::<ARITY>(h) == curr_height_padded);
             if let Some(next_height) = next_height {
                 let next_height_openings_digest = self.hash.hash_iter_slices(
                     heights_tallest_first
@@ -147,11 +295,126 @@ where
                         .map(|(i, _)| opened_values[i].as_slice()),
                 );
 
-                root = self.compress.compress([root, next_height_openings_digest]);
+                root = self.compress.compress(pad_injection(
+                    [root, next_height_openings_digest],
+                    default_digest,
+                ));
+            }
+        }
+
+        // What's left of the index is the position of the node we reached in the cap.
+        if commit.digests().get(index) == Some(&root) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Recomputes each node on the opened paths once, layer by layer, however many of the paths
+    /// pass through it. Sibling digests are read from the proof of any opening whose path passes
+    /// through a sibling, and only when the sibling isn't itself on an opened path.
+    fn verify_batches(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        openings: &[(usize, &[Vec<P::Value>], &Self::Proof)],
+    ) -> Result<(), Self::Error> {
+        if openings.is_empty() {
+            return Ok(());
+        }
+        if !heights_fit_arity::<ARITY>(dimensions.iter().map(|dims| dims.height)) {
+            return Err(());
+        }
+        let num_layers =
+            cap_layer::<ARITY>(dimensions.iter().map(|dims| dims.height), self.cap_height);
+        if openings
+            .iter()
+            .any(|(_, _, proof)| proof.len() != num_layers * (ARITY - 1))
+        {
+            return Err(());
+        }
+        let log_arity = log2_strict_usize(ARITY);
+
+        let mut heights_tallest_first = dimensions
+            .iter()
+            .enumerate()
+            .sorted_by_key(|(_, dims)| Reverse(dims.height))
+            .peekable();
+
+        let mut curr_height_padded =
+            padded_height::<ARITY>(heights_tallest_first.peek().unwrap().1.height);
+
+        // The digest of every node, in the current layer, on an opened path, along with one of
+        // the openings whose path it is.
+        let tallest_matrices = heights_tallest_first
+            .peeking_take_while(|(_, dims)| {
+                padded_height::<ARITY>(dims.height) == curr_height_padded
+            })
+            .map(|(i, _)| i)
+            .collect_vec();
+        let mut nodes = BTreeMap::new();
+        for (opening, &(index, opened_values, _)) in openings.iter().enumerate() {
+            let digest = self.hash.hash_iter_slices(
+                tallest_matrices
+                    .iter()
+                    .map(|&i| opened_values[i].as_slice()),
+            );
+            insert_consistent(&mut nodes, index, (digest, opening), |(digest, _)| digest)?;
+        }
+
+        let default_digest = [PW::Value::default(); DIGEST_ELEMS];
+        for layer in 0..num_layers {
+            let mut parents = BTreeMap::new();
+            for (&node, &(_, opening)) in &nodes {
+                let parent = node >> log_arity;
+                if parents.contains_key(&parent) {
+                    continue;
+                }
+                let siblings = &openings[opening].2[layer * (ARITY - 1)..][..ARITY - 1];
+                let position = node & (ARITY - 1);
+                let children =
+                    array::from_fn(|child| match nodes.get(&((parent << log_arity) + child)) {
+                        Some(&(digest, _)) => digest,
+                        None => siblings[child - usize::from(child > position)],
+                    });
+                parents.insert(parent, (self.compress.compress(children), opening));
+            }
+            curr_height_padded /= ARITY;
+
+            let next_height = heights_tallest_first
+                .peek()
+                .map(|(_, dims)| dims.height)
+                .filter(|&h| padded_height::<ARITY>(h) == curr_height_padded);
+            if let Some(next_height) = next_height {
+                let injected_matrices = heights_tallest_first
+                    .peeking_take_while(|(_, dims)| dims.height == next_height)
+                    .map(|(i, _)| i)
+                    .collect_vec();
+                let mut injected_digests = BTreeMap::new();
+                for &(index, opened_values, _) in openings {
+                    let digest = self.hash.hash_iter_slices(
+                        injected_matrices
+                            .iter()
+                            .map(|&i| opened_values[i].as_slice()),
+                    );
+                    let parent = index >> ((layer + 1) * log_arity);
+                    insert_consistent(&mut injected_digests, parent, digest, |digest| digest)?;
+                }
+                for (parent, (digest, _)) in &mut parents {
+                    *digest = self.compress.compress(pad_injection(
+                        [*digest, injected_digests[parent]],
+                        default_digest,
+                    ));
+                }
             }
+
+            nodes = parents;
         }
 
-        if commit == &root {
+        if nodes
+            .iter()
+            .all(|(&node, (digest, _))| commit.digests().get(node) == Some(digest))
+        {
             Ok(())
         } else {
             Err(())
@@ -159,23 +422,50 @@ where
     }
 }
 
+/// Records `value` at `node`, unless `node` already has a value, in which case the two must agree
+/// on their digest: openings which meet at a node must have the same digest there.
+fn insert_consistent<V, D: PartialEq>(
+    nodes: &mut BTreeMap<usize, V>,
+    node: usize,
+    value: V,
+    digest: impl Fn(&V) -> &D,
+) -> Result<(), ()> {
+    match nodes.get(&node) {
+        Some(existing) if digest(existing) != digest(&value) => Err(()),
+        Some(_) => Ok(()),
+        None => {
+            nodes.insert(node, value);
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use alloc::vec::Vec;
 
-    use itertools::Itertools;
+    use itertools::{izip, Itertools};
     use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
-    use p3_commit::Mmcs;
+    use p3_blake3::Blake3;
+    use p3_commit::{Mmcs, SaltedMmcs};
     use p3_field::{AbstractField, Field};
+    use p3_goldilocks::Goldilocks;
+    use p3_keccak::{KeccakF, VECTOR_LEN};
     use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::row_fn::RowFnMatrix;
     use p3_matrix::{Dimensions, Matrix};
     use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
     use p3_symmetric::{
-        CryptographicHasher, PaddingFreeSponge, PseudoCompressionFunction, TruncatedPermutation,
+        CompressionFunctionFromHasher, CryptographicHasher, PaddingFreeSponge,
+        PseudoCompressionFunction, SerializingHasher32, SerializingHasher64, TruncatedPermutation,
+        TruncatingHasher,
     };
+    use rand::rngs::StdRng;
     use rand::thread_rng;
 
     use super::FieldMerkleTreeMmcs;
+    use crate::PackedCpuBackend;
 
     type F = BabyBear;
 
@@ -428,6 +718,48 @@ mod tests {
         .expect_err("expected verification to fail");
     }
 
+    #[test]
+    fn prune_and_expand_proofs() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, 64, 3);
+        let dims = [mat.dimensions()];
+        let (commit, prover_data) = mmcs.commit_matrix(mat);
+
+        // Repeated and neighbouring indices share siblings.
+        let indices = [5, 4, 5, 63, 0, 6];
+        let (opened_values, proofs): (Vec<_>, Vec<_>) = indices
+            .iter()
+            .map(|&index| mmcs.open_batch(index, &prover_data))
+            .unzip();
+
+        let pruned = mmcs.prune_proofs(&indices, proofs.clone());
+        let full_len: usize = proofs.iter().map(Vec::len).sum();
+        let pruned_len: usize = pruned.iter().map(Vec::len).sum();
+        assert!(pruned_len < full_len);
+        assert!(pruned[2].is_empty());
+
+        let expanded = mmcs.expand_proofs(&indices, pruned.clone()).unwrap();
+        assert_eq!(expanded, proofs);
+        for (&index, values, proof) in izip!(&indices, &opened_values, &expanded) {
+            mmcs.verify_batch(&commit, &dims, index, values, proof)
+                .unwrap();
+        }
+
+        // A truncated pruned proof can't be expanded.
+        let mut truncated = pruned;
+        truncated[3].pop();
+        assert!(mmcs.expand_proofs(&indices, truncated).is_err());
+    }
+
     #[test]
     fn size_gaps() {
         let mut rng = thread_rng();
@@ -506,4 +838,534 @@ mod tests {
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
             .expect("expected verification to succeed");
     }
+
+    type MyHash4 = PaddingFreeSponge<Perm, 16, 8, 4>;
+    type MyCompress4 = TruncatedPermutation<Perm, 4, 4, 16>;
+    type MyMmcs4 = FieldMerkleTreeMmcs<
+        <F as Field>::Packing,
+        <F as Field>::Packing,
+        MyHash4,
+        MyCompress4,
+        4,
+        4,
+    >;
+
+    #[test]
+    fn commit_and_open_arity_4() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash4::new(perm.clone());
+        let compress = MyCompress4::new(perm);
+        let mmcs = MyMmcs4::new(hash.clone(), compress.clone());
+        let default_digest = [F::zero(); 4];
+
+        let large_mat = RowMajorMatrix::<F>::rand(&mut rng, 16, 2);
+        let small_mat = RowMajorMatrix::<F>::rand(&mut rng, 3, 5);
+        let dims = [large_mat.dimensions(), small_mat.dimensions()];
+        let (commit, prover_data) = mmcs.commit(vec![large_mat.clone(), small_mat.clone()]);
+
+        // The small matrix, padded to height 4, is injected into the layer above the leaves.
+        let leaf_hashes = large_mat
+            .rows()
+            .map(|row| hash.hash_iter(row))
+            .collect_vec();
+        let layer_1 = (0..4)
+            .map(|i| {
+                let digest = compress.compress(core::array::from_fn(|j| leaf_hashes[4 * i + j]));
+                let rows_digest = if i < 3 {
+                    hash.hash_iter(small_mat.row(i))
+                } else {
+                    default_digest
+                };
+                compress.compress([digest, rows_digest, default_digest, default_digest])
+            })
+            .collect_vec();
+        let expected_result = compress.compress([layer_1[0], layer_1[1], layer_1[2], layer_1[3]]);
+        assert_eq!(commit, expected_result);
+
+        let indices = [0, 1, 6, 11, 11];
+        let (opened_values, proofs): (Vec<_>, Vec<_>) = indices
+            .iter()
+            .map(|&index| mmcs.open_batch(index, &prover_data))
+            .unzip();
+        for (&index, values, proof) in izip!(&indices, &opened_values, &proofs) {
+            assert_eq!(proof.len(), 2 * 3);
+            assert_eq!(values[1], small_mat.row(index >> 2).collect_vec());
+            mmcs.verify_batch(&commit, &dims, index, values, proof)
+                .unwrap();
+        }
+
+        let mut tampered = proofs[2].clone();
+        tampered[4][0] += F::one();
+        assert!(mmcs
+            .verify_batch(&commit, &dims, 6, &opened_values[2], &tampered)
+            .is_err());
+
+        let pruned = mmcs.prune_proofs(&indices, proofs.clone());
+        assert_eq!(pruned[1].len(), 1);
+        assert!(pruned[4].is_empty());
+        assert_eq!(mmcs.expand_proofs(&indices, pruned).unwrap(), proofs);
+    }
+
+    #[test]
+    fn arity_4_opens_rows_by_binary_index() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let mmcs = MyMmcs4::new(MyHash4::new(perm.clone()), MyCompress4::new(perm));
+
+        // Heights 32 and 8 round up to 64 and 16 leaves, but openings still index the 32 rows of
+        // the tallest matrix, and the shorter one loses two bits of the index.
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 32, 3),
+            RowMajorMatrix::<F>::rand(&mut rng, 8, 2),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats.clone());
+        for index in 0..32 {
+            let (values, proof) = mmcs.open_batch(index, &prover_data);
+            assert_eq!(values[0], mats[0].row(index).collect_vec());
+            assert_eq!(values[1], mats[1].row(index >> 2).collect_vec());
+            mmcs.verify_batch(&commit, &dims, index, &values, &proof)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "power of the arity")]
+    fn arity_4_rejects_heights_one_bit_apart() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let mmcs = MyMmcs4::new(MyHash4::new(perm.clone()), MyCompress4::new(perm));
+
+        // Row `index >> 1` of the shorter matrix can't be injected a whole number of layers up.
+        let _ = mmcs.commit(vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 8, 1),
+            RowMajorMatrix::<F>::rand(&mut rng, 4, 1),
+        ]);
+    }
+
+    #[test]
+    fn salted_openings_verify() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs =
+            SaltedMmcs::<F, MyMmcs, StdRng, 4>::from_rng(MyMmcs::new(hash, compress), &mut rng);
+
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, 32, 3);
+        let dims = [mat.dimensions()];
+        let (commit, prover_data) = mmcs.commit_matrix(mat.clone());
+        assert_eq!(mmcs.get_matrices(&prover_data), vec![&mat]);
+
+        // Each commitment is salted afresh.
+        let (recommit, _) = mmcs.commit_matrix(mat.clone());
+        assert_ne!(commit, recommit);
+        let (clone_commit, _) = mmcs.clone().commit_matrix(mat.clone());
+        assert_ne!(commit, clone_commit);
+
+        let (opened_values, proof) = mmcs.open_batch(5, &prover_data);
+        assert_eq!(opened_values, vec![mat.row(5).collect_vec()]);
+        assert_eq!(proof.0[0].len(), 4);
+        mmcs.verify_batch(&commit, &dims, 5, &opened_values, &proof)
+            .unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.0[0][0] += F::one();
+        assert!(mmcs
+            .verify_batch(&commit, &dims, 5, &opened_values, &tampered)
+            .is_err());
+
+        let mut truncated = proof;
+        truncated.0[0].pop();
+        assert!(mmcs
+            .verify_batch(&commit, &dims, 5, &opened_values, &truncated)
+            .is_err());
+    }
+
+    #[test]
+    fn cap_commitments() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let root_mmcs = MyMmcs::new(hash.clone(), compress.clone());
+        let cap_mmcs = MyMmcs::new_with_cap_height(hash.clone(), compress.clone(), 2);
+
+        let large_mat = RowMajorMatrix::<F>::rand(&mut rng, 64, 3);
+        let small_mat = RowMajorMatrix::<F>::rand(&mut rng, 16, 2);
+        let dims = [large_mat.dimensions(), small_mat.dimensions()];
+        let (root, _) = root_mmcs.commit(vec![large_mat.clone(), small_mat.clone()]);
+        let (cap, prover_data) = cap_mmcs.commit(vec![large_mat.clone(), small_mat.clone()]);
+
+        // The cap hashes to the root.
+        assert_eq!(cap.len(), 4);
+        let cap_digests = cap.digests();
+        assert_eq!(
+            root,
+            compress.compress([
+                compress.compress([cap_digests[0], cap_digests[1]]),
+                compress.compress([cap_digests[2], cap_digests[3]]),
+            ])
+        );
+
+        for index in [0, 17, 63] {
+            let (opened_values, proof) = cap_mmcs.open_batch(index, &prover_data);
+            assert_eq!(proof.len(), 6 - 2);
+            cap_mmcs
+                .verify_batch(&cap, &dims, index, &opened_values, &proof)
+                .unwrap();
+            assert!(root_mmcs
+                .verify_batch(&root, &dims, index, &opened_values, &proof)
+                .is_err());
+        }
+
+        // A cap can't rise above the shortest matrix, which is injected 4 layers below the root.
+        let tiny_mat = RowMajorMatrix::<F>::rand(&mut rng, 4, 1);
+        let low_cap_mmcs = MyMmcs::new_with_cap_height(hash, compress, 3);
+        let (low_cap, prover_data) = low_cap_mmcs.commit(vec![large_mat, tiny_mat]);
+        assert_eq!(low_cap.len(), 4);
+        let (opened_values, proof) = low_cap_mmcs.open_batch(9, &prover_data);
+        assert_eq!(proof.len(), 6 - 2);
+        low_cap_mmcs
+            .verify_batch(
+                &low_cap,
+                &[
+                    dims[0],
+                    Dimensions {
+                        width: 1,
+                        height: 4,
+                    },
+                ],
+                9,
+                &opened_values,
+                &proof,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn packed_and_scalar_trees_agree() {
+        type ScalarMmcs = FieldMerkleTreeMmcs<F, F, MyHash, MyCompress, 8>;
+
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let packed_mmcs = MyMmcs::new(hash.clone(), compress.clone());
+        let scalar_mmcs = ScalarMmcs::new(hash, compress);
+
+        // Heights which neither fill their padded layers nor divide into packing-width chunks.
+        for heights in [vec![1000, 70, 8], vec![37, 3], vec![5], vec![64, 33]] {
+            let mats = heights
+                .iter()
+                .map(|&height| RowMajorMatrix::<F>::rand(&mut rng, height, 3))
+                .collect_vec();
+            let (packed_commit, _) = packed_mmcs.commit(mats.clone());
+            let (scalar_commit, _) = scalar_mmcs.commit(mats);
+            assert_eq!(packed_commit, scalar_commit);
+        }
+    }
+
+    #[test]
+    fn commit_rows_on_demand() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        // Row r is (r, r^2, ..., r^5), computed only when the tree hashes or opens it.
+        let row_fn = |r: usize| -> Vec<F> {
+            F::from_canonical_usize(r)
+                .powers()
+                .skip(1)
+                .take(5)
+                .collect()
+        };
+        let lazy = RowFnMatrix::new(100, 5, row_fn);
+        let dense = RowMajorMatrix::new((0..100).flat_map(row_fn).collect(), 5);
+        let dims = [dense.dimensions()];
+
+        let (commit, prover_data) = mmcs.commit_matrix(lazy);
+        let (dense_commit, _) = mmcs.commit_matrix(dense);
+        assert_eq!(commit, dense_commit);
+
+        let (opened_values, proof) = mmcs.open_batch(42, &prover_data);
+        assert_eq!(opened_values, vec![row_fn(42)]);
+        mmcs.verify_batch(&commit, &dims, 42, &opened_values, &proof)
+            .unwrap();
+    }
+
+    #[test]
+    fn prover_data_round_trips() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 32, 3),
+            RowMajorMatrix::<F>::rand(&mut rng, 8, 5),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let bytes = postcard::to_allocvec(&prover_data).unwrap();
+        let reloaded: <MyMmcs as Mmcs<F>>::ProverData<RowMajorMatrix<F>> =
+            postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(reloaded.root(), prover_data.root());
+
+        for index in [0, 13, 31] {
+            let opening = mmcs.open_batch(index, &reloaded);
+            assert_eq!(opening, mmcs.open_batch(index, &prover_data));
+            mmcs.verify_batch(&commit, &dims, index, &opening.0, &opening.1)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn verify_batches_shares_paths() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new_with_cap_height(hash, compress, 1);
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 100, 3),
+            RowMajorMatrix::<F>::rand(&mut rng, 20, 2),
+            RowMajorMatrix::<F>::rand(&mut rng, 3, 4),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let indices = [3, 2, 3, 77, 64, 70];
+        let (opened_values, proofs): (Vec<_>, Vec<_>) = indices
+            .iter()
+            .map(|&index| mmcs.open_batch(index, &prover_data))
+            .unzip();
+        let openings = |opened_values: &[Vec<Vec<F>>]| {
+            izip!(&indices, opened_values, &proofs)
+                .map(|(&index, values, proof)| (index, values.as_slice(), proof))
+                .collect_vec()
+        };
+        mmcs.verify_batches(&commit, &dims, &openings(&opened_values))
+            .unwrap();
+
+        // A tampered value in a matrix injected above the leaves.
+        let mut tampered = opened_values.clone();
+        tampered[4][1][0] += F::one();
+        assert!(mmcs
+            .verify_batches(&commit, &dims, &openings(&tampered))
+            .is_err());
+
+        // Two openings of the same row must agree, even if each is otherwise valid.
+        let mut conflicting = opened_values.clone();
+        conflicting[2] = opened_values[1].clone();
+        assert!(mmcs
+            .verify_batches(&commit, &dims, &openings(&conflicting))
+            .is_err());
+    }
+
+    #[test]
+    fn update_rows_matches_recommit() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new_with_cap_height(hash, compress, 1);
+
+        let mut mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 70, 3),
+            RowMajorMatrix::<F>::rand(&mut rng, 16, 2),
+            RowMajorMatrix::<F>::rand(&mut rng, 70, 1),
+        ];
+        let (_, mut prover_data) = mmcs.commit(mats.clone());
+
+        let updates = vec![
+            (0, 5, vec![F::one(), F::two(), F::zero()]),
+            (1, 15, vec![F::zero(), F::one()]),
+            (2, 69, vec![F::two()]),
+            (0, 4, vec![F::zero(); 3]),
+        ];
+        for (matrix, r, values) in &updates {
+            mats[*matrix].row_mut(*r).copy_from_slice(values);
+        }
+        let updated_commit = mmcs.update_rows(&mut prover_data, updates);
+
+        let (recommit, recommitted_data) = mmcs.commit(mats);
+        assert_eq!(updated_commit, recommit);
+        assert_eq!(
+            mmcs.open_batch(69, &prover_data),
+            mmcs.open_batch(69, &recommitted_data)
+        );
+    }
+
+    #[test]
+    fn commit_with_backend() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear,
+            &mut rng,
+        );
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash.clone(), compress.clone());
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 37, 5),
+            RowMajorMatrix::<F>::rand(&mut rng, 9, 2),
+        ];
+        let (commit, _) = mmcs.commit(mats.clone());
+
+        // Any backend computing the same digests gives the same commitment, e.g. one which doesn't
+        // pack.
+        let scalar_backend = PackedCpuBackend::<F, F, _, _>::new(&hash, &compress);
+        let (backend_commit, prover_data) = mmcs.commit_with_backend(&scalar_backend, mats);
+        assert_eq!(backend_commit, commit);
+
+        let (opened_values, proof) = mmcs.open_batch(20, &prover_data);
+        let dims = prover_data
+            .leaves
+            .iter()
+            .map(|m| m.dimensions())
+            .collect_vec();
+        mmcs.verify_batch(&commit, &dims, 20, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn vectorized_keccak_tree_matches_scalar() {
+        type U64Hash = PaddingFreeSponge<KeccakF, 25, 17, 4>;
+        type FieldHash = SerializingHasher64<U64Hash>;
+        type KeccakCompress = TruncatedPermutation<KeccakF, 2, 4, 25>;
+        type ScalarMmcs = FieldMerkleTreeMmcs<Goldilocks, u64, FieldHash, KeccakCompress, 4>;
+        type VectorizedMmcs = FieldMerkleTreeMmcs<
+            [Goldilocks; VECTOR_LEN],
+            [u64; VECTOR_LEN],
+            FieldHash,
+            KeccakCompress,
+            4,
+        >;
+
+        let hash = FieldHash::new(U64Hash::new(KeccakF));
+        let compress = KeccakCompress::new(KeccakF);
+        let scalar_mmcs = ScalarMmcs::new(hash, compress.clone());
+        let vectorized_mmcs = VectorizedMmcs::new(hash, compress);
+
+        let mut rng = thread_rng();
+        let mats = vec![
+            RowMajorMatrix::<Goldilocks>::rand(&mut rng, 37, 7),
+            RowMajorMatrix::<Goldilocks>::rand(&mut rng, 9, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (scalar_commit, _) = scalar_mmcs.commit(mats.clone());
+        let (vectorized_commit, prover_data) = vectorized_mmcs.commit(mats);
+        assert_eq!(vectorized_commit, scalar_commit);
+
+        let (opened_values, proof) = vectorized_mmcs.open_batch(30, &prover_data);
+        scalar_mmcs
+            .verify_batch(&scalar_commit, &dims, 30, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn batched_blake3_tree_matches_scalar() {
+        type FieldHash = SerializingHasher32<Blake3>;
+        type Blake3Compress = CompressionFunctionFromHasher<u32, Blake3, 2, 8>;
+        type ScalarMmcs = FieldMerkleTreeMmcs<F, u32, FieldHash, Blake3Compress, 8>;
+        type BatchedMmcs = FieldMerkleTreeMmcs<[F; 8], [u32; 8], FieldHash, Blake3Compress, 8>;
+
+        let hash = FieldHash::new(Blake3);
+        let compress = Blake3Compress::new(Blake3);
+        let scalar_mmcs = ScalarMmcs::new(hash, compress.clone());
+        let batched_mmcs = BatchedMmcs::new(hash, compress);
+
+        let mut rng = thread_rng();
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 70, 300),
+            RowMajorMatrix::<F>::rand(&mut rng, 20, 5),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (scalar_commit, _) = scalar_mmcs.commit(mats.clone());
+        let (batched_commit, prover_data) = batched_mmcs.commit(mats);
+        assert_eq!(batched_commit, scalar_commit);
+
+        let (opened_values, proof) = batched_mmcs.open_batch(66, &prover_data);
+        scalar_mmcs
+            .verify_batch(&scalar_commit, &dims, 66, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
+
+    #[test]
+    fn truncating_hasher_tree() {
+        type Truncating = TruncatingHasher<Blake3, 32>;
+        type TruncatingMmcs = FieldMerkleTreeMmcs<F, F, Truncating, Truncating, 4>;
+
+        let hasher = Truncating::new(Blake3);
+        let mmcs = TruncatingMmcs::new(hasher, hasher);
+
+        // A leaf with the same elements as two concatenated digests hashes differently.
+        let elems: [F; 8] = core::array::from_fn(|i| F::from_canonical_usize(i));
+        let leaf: [F; 4] = hasher.hash_slice(&elems);
+        let node = hasher.compress([
+            elems[..4].try_into().unwrap(),
+            elems[4..].try_into().unwrap(),
+        ]);
+        assert_ne!(leaf, node);
+
+        let mut rng = thread_rng();
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut rng, 32, 7),
+            RowMajorMatrix::<F>::rand(&mut rng, 8, 3),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+        let (opened_values, proof) = mmcs.open_batch(21, &prover_data);
+        mmcs.verify_batch(&commit, &dims, 21, &opened_values, &proof)
+            .expect("expected verification to succeed");
+    }
 }
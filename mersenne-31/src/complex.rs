@@ -178,6 +178,11 @@ mod tests {
         );
     }
 
+    #[test]
+    fn canonical_bytes() {
+        p3_field_testing::test_ef_canonical_bytes::<F, Fi>();
+    }
+
     test_field!(p3_field::extension::Complex<crate::Mersenne31>);
     test_two_adic_field!(p3_field::extension::Complex<crate::Mersenne31>);
 }
@@ -1,14 +1,15 @@
 //! MDS matrices over the Mersenne31 field, and permutations defined by them.
 //!
 //! NB: Not all sizes have fast implementations of their permutations.
-//! Supported sizes: 8, 12, 16, 32, 64.
+//! Supported sizes: 8, 12, 16, 24, 32, 64.
 //! Sizes 8 and 12 are from Plonky2, size 16 was found as part of concurrent
-//! work by Angus Gruen and Hamish Ivey-Law. Other sizes are from Ulrich Haböck's
-//! database.
+//! work by Angus Gruen and Hamish Ivey-Law. Size 24 is the Cauchy matrix
+//! `1 / (i + j + 1)`, which is MDS for any field with more than 48 elements.
+//! Other sizes are from Ulrich Haböck's database.
 
-use p3_field::AbstractField;
+use p3_field::{AbstractField, PrimeField64};
 use p3_mds::karatsuba_convolution::Convolve;
-use p3_mds::util::{dot_product, first_row_to_first_col};
+use p3_mds::util::{cauchy_matrix, dot_product, first_row_to_first_col, matmul_delayed_reduction};
 use p3_mds::MdsPermutation;
 use p3_symmetric::Permutation;
 
@@ -193,6 +194,26 @@ impl Permutation<[Mersenne31; 16]> for MdsMatrixMersenne31 {
 }
 impl MdsPermutation<Mersenne31, 16> for MdsMatrixMersenne31 {}
 
+const MATRIX_CAUCHY_MDS_24: [[u64; 24]; 24] = cauchy_matrix(Mersenne31::ORDER_U64);
+
+impl Permutation<[Mersenne31; 24]> for MdsMatrixMersenne31 {
+    /// Mersenne31 has no multiplicative subgroup of order 24, so there is no circulant Cauchy
+    /// matrix of this size; this sums the unreduced products and reduces once per output.
+    fn permute(&self, input: [Mersenne31; 24]) -> [Mersenne31; 24] {
+        matmul_delayed_reduction(
+            &MATRIX_CAUCHY_MDS_24,
+            input.map(|x| x.value),
+            Mersenne31::ORDER_U64,
+        )
+        .map(Mersenne31::new)
+    }
+
+    fn permute_mut(&self, input: &mut [Mersenne31; 24]) {
+        *input = self.permute(*input);
+    }
+}
+impl MdsPermutation<Mersenne31, 24> for MdsMatrixMersenne31 {}
+
 #[rustfmt::skip]
 const MATRIX_CIRC_MDS_32_MERSENNE31_ROW: [i64; 32] = [
     0x1896DC78, 0x559D1E29, 0x04EBD732, 0x3FF449D7,
@@ -325,6 +346,29 @@ mod tests {
         assert_eq!(output, expected);
     }
 
+    #[test]
+    fn mersenne24() {
+        let input: [Mersenne31; 24] = [
+            511025150, 1272686665, 1168723365, 280080820, 794472658, 1966976506, 1296908414,
+            1018022223, 1343724115, 1247370366, 140722156, 1300515102, 28278034, 1951672655,
+            1798451156, 1007668780, 556958498, 1182801014, 503221913, 411767315, 2138131003,
+            1540063683, 1009883194, 1161732570,
+        ]
+        .map(Mersenne31::from_canonical_u64);
+
+        let output = MdsMatrixMersenne31.permute(input);
+
+        let expected: [Mersenne31; 24] = [
+            1485382375, 839436679, 2123233743, 687014474, 21299792, 2041315259, 533656338,
+            644013586, 216533255, 1219651515, 679776140, 1104330492, 1455301232, 236611252,
+            1153643414, 1455805702, 2010231973, 1378080339, 269514002, 1213874534, 1633430041,
+            2005577955, 1637930218, 2078446692,
+        ]
+        .map(Mersenne31::from_canonical_u64);
+
+        assert_eq!(output, expected);
+    }
+
     #[test]
     fn mersenne32() {
         let input: [Mersenne31; 32] = [
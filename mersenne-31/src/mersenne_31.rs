@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use core::fmt;
 use core::fmt::{Debug, Display, Formatter};
 use core::hash::{Hash, Hasher};
@@ -11,13 +12,13 @@ use p3_field::{
 };
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// The Mersenne31 prime
 const P: u32 = (1 << 31) - 1;
 
 /// The prime field `F_p` where `p = 2^31 - 1`.
-#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default)]
 pub struct Mersenne31 {
     /// Not necessarily canonical, but must fit in 31 bits.
     pub(crate) value: u32,
@@ -31,6 +32,27 @@ impl Mersenne31 {
     }
 }
 
+impl Serialize for Mersenne31 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.as_canonical_u32())
+    }
+}
+
+impl<'de> Deserialize<'de> for Mersenne31 {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let val = u32::deserialize(d)?;
+        // Reject unreduced values, so that each element has a single encoding. In particular,
+        // `P` itself, which would otherwise be a second encoding of zero.
+        if val < P {
+            Ok(Self::new(val))
+        } else {
+            Err(serde::de::Error::custom(
+                "value is not less than the field order",
+            ))
+        }
+    }
+}
+
 impl PartialEq for Mersenne31 {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -269,6 +291,19 @@ impl PrimeField for Mersenne31 {
     fn as_canonical_biguint(&self) -> BigUint {
         <Self as PrimeField32>::as_canonical_u32(self).into()
     }
+
+    fn num_canonical_bytes() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.as_canonical_u32().to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u32::from_le_bytes(bytes.try_into().ok()?);
+        (value < Self::ORDER_U32).then(|| Self::from_canonical_u32(value))
+    }
 }
 
 impl PrimeField32 for Mersenne31 {
@@ -431,7 +466,7 @@ pub(crate) const fn to_mersenne31_array<const N: usize>(input: [u32; N]) -> [Mer
 #[cfg(test)]
 mod tests {
     use p3_field::{AbstractField, Field, PrimeField32};
-    use p3_field_testing::test_field;
+    use p3_field_testing::{test_field, test_prime_field};
 
     use crate::Mersenne31;
 
@@ -487,4 +522,5 @@ mod tests {
     }
 
     test_field!(crate::Mersenne31);
+    test_prime_field!(crate::Mersenne31);
 }
@@ -9,6 +9,7 @@ use alloc::vec::Vec;
 use p3_field::{AbstractField, PrimeField32};
 use p3_mds::MdsPermutation;
 use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{CryptographicPermutation, Permutation};
 use sha3::digest::{ExtendableOutput, Update};
 use sha3::{Shake128, Shake128Reader};
 
@@ -17,7 +18,7 @@ use crate::util::get_random_u32;
 // The Monolith-31 permutation over Mersenne31.
 // NUM_FULL_ROUNDS is the number of rounds - 1
 // (used to avoid const generics because we need an array of length NUM_FULL_ROUNDS)
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MonolithMersenne31<Mds, const WIDTH: usize, const NUM_FULL_ROUNDS: usize>
 where
     Mds: MdsPermutation<Mersenne31, WIDTH>,
@@ -178,10 +179,29 @@ where
     }
 }
 
+impl<Mds, const WIDTH: usize, const NUM_FULL_ROUNDS: usize> Permutation<[Mersenne31; WIDTH]>
+    for MonolithMersenne31<Mds, WIDTH, NUM_FULL_ROUNDS>
+where
+    Mds: MdsPermutation<Mersenne31, WIDTH>,
+{
+    fn permute_mut(&self, state: &mut [Mersenne31; WIDTH]) {
+        self.permutation(state);
+    }
+}
+
+impl<Mds, const WIDTH: usize, const NUM_FULL_ROUNDS: usize>
+    CryptographicPermutation<[Mersenne31; WIDTH]>
+    for MonolithMersenne31<Mds, WIDTH, NUM_FULL_ROUNDS>
+where
+    Mds: MdsPermutation<Mersenne31, WIDTH>,
+{
+}
+
 #[cfg(test)]
 mod tests {
     use p3_field::AbstractField;
     use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::{CryptographicHasher, PaddingFreeSponge, Permutation};
 
     use crate::monolith::MonolithMersenne31;
     use crate::monolith_mds::MonolithMdsMatrixMersenne31;
@@ -214,4 +234,17 @@ mod tests {
         assert_eq!(input[14], Mersenne31::from_canonical_u64(1989726444));
         assert_eq!(input[15], Mersenne31::from_canonical_u64(1349325635));
     }
+
+    #[test]
+    fn test_monolith_31_sponge() {
+        let mds = MonolithMdsMatrixMersenne31::<6>;
+        let monolith: MonolithMersenne31<_, 16, 5> = MonolithMersenne31::new(mds);
+        let sponge = PaddingFreeSponge::<_, 16, 8, 8>::new(monolith.clone());
+
+        let input: [Mersenne31; 8] = core::array::from_fn(Mersenne31::from_canonical_usize);
+        let mut state = [Mersenne31::zero(); 16];
+        state[..8].copy_from_slice(&input);
+        let expected = monolith.permute(state);
+        assert_eq!(sponge.hash_iter(input), expected[..8]);
+    }
 }
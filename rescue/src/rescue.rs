@@ -18,25 +18,52 @@ use crate::util::shake256_hash;
 /// The Rescue-XLIX permutation.
 #[derive(Clone, Debug)]
 pub struct Rescue<F, Mds, Sbox, const WIDTH: usize> {
-    num_rounds: usize,
     mds: Mds,
     sbox: Sbox,
-    round_constants: Vec<F>,
+    /// Two arrays of constants per round, added after the S-box and inverse S-box layers
+    /// respectively.
+    round_constants: Vec<[F; WIDTH]>,
 }
 
 impl<F, Mds, Sbox, const WIDTH: usize> Rescue<F, Mds, Sbox, WIDTH>
 where
     F: PrimeField,
 {
+    /// Create a new Rescue configuration.
+    ///
+    /// # Panics
+    /// Number of constants must be `2 * WIDTH * num_rounds`; panics otherwise.
     pub fn new(num_rounds: usize, round_constants: Vec<F>, mds: Mds, sbox: Sbox) -> Self {
+        assert_eq!(round_constants.len(), 2 * WIDTH * num_rounds);
+        let round_constants = round_constants
+            .chunks_exact(WIDTH)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
         Self {
-            num_rounds,
             mds,
             sbox,
             round_constants,
         }
     }
 
+    /// Create a Rescue-Prime configuration with the number of rounds and the round constants
+    /// prescribed by the Rescue-Prime specification for the given capacity and security level.
+    pub fn new_rescue_prime(
+        capacity: usize,
+        sec_level: usize,
+        alpha: u64,
+        mds: Mds,
+        sbox: Sbox,
+    ) -> Self
+    where
+        F: PrimeField64,
+    {
+        let num_rounds = Self::num_rounds(capacity, sec_level, alpha);
+        let round_constants =
+            Self::get_round_constants_rescue_prime(num_rounds, capacity, sec_level);
+        Self::new(num_rounds, round_constants, mds, sbox)
+    }
+
     fn num_rounds(capacity: usize, sec_level: usize, alpha: u64) -> usize {
         let rate = WIDTH - capacity;
         let dcon = |n: usize| {
@@ -109,38 +136,28 @@ where
     Sbox: SboxLayers<AF, WIDTH>,
 {
     fn permute_mut(&self, state: &mut [AF; WIDTH]) {
-        for round in 0..self.num_rounds {
-            // S-box
+        for round_constants in self.round_constants.chunks_exact(2) {
             self.sbox.sbox_layer(state);
-
-            // MDS
             self.mds.permute_mut(state);
+            add_round_constants(state, &round_constants[0]);
 
-            // Constants
-            for (state_item, &round_constant) in state
-                .iter_mut()
-                .zip(&self.round_constants[round * WIDTH * 2..])
-            {
-                *state_item += AF::from_f(round_constant);
-            }
-
-            // Inverse S-box
             self.sbox.inverse_sbox_layer(state);
-
-            // MDS
             self.mds.permute_mut(state);
-
-            // Constants
-            for (state_item, &round_constant) in state
-                .iter_mut()
-                .zip(&self.round_constants[round * WIDTH * 2 + WIDTH..])
-            {
-                *state_item += AF::from_f(round_constant);
-            }
+            add_round_constants(state, &round_constants[1]);
         }
     }
 }
 
+#[inline]
+fn add_round_constants<AF: AbstractField, const WIDTH: usize>(
+    state: &mut [AF; WIDTH],
+    round_constants: &[AF::F; WIDTH],
+) {
+    for (state_item, &round_constant) in state.iter_mut().zip(round_constants) {
+        *state_item += AF::from_f(round_constant);
+    }
+}
+
 impl<AF, Mds, Sbox, const WIDTH: usize> CryptographicPermutation<[AF; WIDTH]>
     for Rescue<AF::F, Mds, Sbox, WIDTH>
 where
@@ -166,13 +183,10 @@ mod tests {
         Rescue<Mersenne31, MdsMatrixMersenne31, BasicSboxLayer<Mersenne31>, WIDTH>;
 
     fn new_rescue_prime_m31_default() -> RescuePrimeM31Default {
-        let num_rounds = RescuePrimeM31Default::num_rounds(6, 128, ALPHA);
-        let round_constants =
-            RescuePrimeM31Default::get_round_constants_rescue_prime(num_rounds, 6, 128);
         let mds = MdsMatrixMersenne31 {};
         let sbox = BasicSboxLayer::for_alpha(ALPHA);
 
-        RescuePrimeM31Default::new(num_rounds, round_constants, mds, sbox)
+        RescuePrimeM31Default::new_rescue_prime(6, 128, ALPHA, mds, sbox)
     }
 
     const NUM_TESTS: usize = 3;
@@ -44,8 +44,13 @@ where
     AF::F: PrimeField,
 {
     fn sbox_layer(&self, state: &mut [AF; WIDTH]) {
-        for x in state.iter_mut() {
-            *x = x.exp_u64(self.alpha);
+        // The usual choices of alpha get their shortest addition chains. The inverse powers get
+        // theirs from the field's `exp_u64_generic`.
+        match self.alpha {
+            3 => state.iter_mut().for_each(|x| *x = x.exp_const_u64::<3>()),
+            5 => state.iter_mut().for_each(|x| *x = x.exp_const_u64::<5>()),
+            7 => state.iter_mut().for_each(|x| *x = x.exp_const_u64::<7>()),
+            alpha => state.iter_mut().for_each(|x| *x = x.exp_u64(alpha)),
         }
     }
 
@@ -66,21 +66,27 @@ where
     }
 }
 
-impl<T, H, const N: usize, const CHUNK: usize> PseudoCompressionFunction<[T; CHUNK], N>
+/// Besides `[T; CHUNK]`, this compresses `[U; CHUNK]` for any other `U` which the hasher hashes to
+/// `[U; CHUNK]`, such as several values of `T` packed together.
+impl<T, U, H, const N: usize, const CHUNK: usize> PseudoCompressionFunction<[U; CHUNK], N>
     for CompressionFunctionFromHasher<T, H, N, CHUNK>
 where
     T: Clone,
+    U: Clone,
     H: CryptographicHasher<T, [T; CHUNK]>,
+    H: CryptographicHasher<U, [U; CHUNK]>,
 {
-    fn compress(&self, input: [[T; CHUNK]; N]) -> [T; CHUNK] {
-        self.hasher.hash_iter(input.into_iter().flatten())
+    fn compress(&self, input: [[U; CHUNK]; N]) -> [U; CHUNK] {
+        CryptographicHasher::<U, [U; CHUNK]>::hash_iter(&self.hasher, input.into_iter().flatten())
     }
 }
 
-impl<T, H, const N: usize, const CHUNK: usize> CompressionFunction<[T; CHUNK], N>
+impl<T, U, H, const N: usize, const CHUNK: usize> CompressionFunction<[U; CHUNK], N>
     for CompressionFunctionFromHasher<T, H, N, CHUNK>
 where
     T: Clone,
+    U: Clone,
     H: CryptographicHasher<T, [T; CHUNK]>,
+    H: CryptographicHasher<U, [U; CHUNK]>,
 {
 }
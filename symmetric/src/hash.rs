@@ -1,4 +1,6 @@
+use alloc::vec::Vec;
 use core::borrow::Borrow;
+use core::iter::Flatten;
 use core::marker::PhantomData;
 
 use serde::{Deserialize, Serialize};
@@ -56,3 +58,69 @@ impl<F, W, const DIGEST_ELEMS: usize> AsRef<[W; DIGEST_ELEMS]> for Hash<F, W, DI
         &self.value
     }
 }
+
+/// A Merkle cap: the digests of one layer of a Merkle tree, committed to in place of its root so
+/// that openings stop short of the root. As with `Hash`, the phantom type parameter associates the
+/// digests with a particular field.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(serialize = "[W; DIGEST_ELEMS]: Serialize"))]
+#[serde(bound(deserialize = "[W; DIGEST_ELEMS]: Deserialize<'de>"))]
+pub struct MerkleCap<F, W, const DIGEST_ELEMS: usize> {
+    digests: Vec<[W; DIGEST_ELEMS]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> MerkleCap<F, W, DIGEST_ELEMS> {
+    /// The digests of the cap's layer, from left to right.
+    pub fn digests(&self) -> &[[W; DIGEST_ELEMS]] {
+        &self.digests
+    }
+
+    /// The number of digests in the cap.
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+}
+
+impl<F, W, const DIGEST_ELEMS: usize> From<Vec<[W; DIGEST_ELEMS]>>
+    for MerkleCap<F, W, DIGEST_ELEMS>
+{
+    fn from(digests: Vec<[W; DIGEST_ELEMS]>) -> Self {
+        Self {
+            digests,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// The cap of height zero, i.e. just the root.
+impl<F, W, const DIGEST_ELEMS: usize> From<Hash<F, W, DIGEST_ELEMS>>
+    for MerkleCap<F, W, DIGEST_ELEMS>
+{
+    fn from(root: Hash<F, W, DIGEST_ELEMS>) -> Self {
+        Self::from(alloc::vec![root.value])
+    }
+}
+
+/// A cap equals a digest if it consists of that digest alone, i.e. if it is a root.
+impl<F, W: PartialEq, const DIGEST_ELEMS: usize> PartialEq<[W; DIGEST_ELEMS]>
+    for MerkleCap<F, W, DIGEST_ELEMS>
+{
+    fn eq(&self, other: &[W; DIGEST_ELEMS]) -> bool {
+        self.digests.len() == 1 && self.digests[0] == *other
+    }
+}
+
+/// Iterates over the elements of every digest in the cap, in order.
+impl<F, W, const DIGEST_ELEMS: usize> IntoIterator for MerkleCap<F, W, DIGEST_ELEMS> {
+    type Item = W;
+    type IntoIter = Flatten<alloc::vec::IntoIter<[W; DIGEST_ELEMS]>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.digests.into_iter().flatten()
+    }
+}
@@ -1,4 +1,4 @@
-use p3_field::{PackedField, PackedValue, PrimeField32, PrimeField64};
+use p3_field::{PackedValue, PrimeField32, PrimeField64};
 
 use crate::CryptographicHasher;
 
@@ -45,8 +45,8 @@ where
 
 impl<P, PW, Inner> CryptographicHasher<P, [PW; 8]> for SerializingHasher32<Inner>
 where
-    P: PackedField,
-    P::F: PrimeField32,
+    P: PackedValue,
+    P::Value: PrimeField32,
     PW: PackedValue<Value = u32>,
     Inner: CryptographicHasher<PW, [PW; 8]>,
 {
@@ -81,8 +81,8 @@ where
 
 impl<P, PW, Inner> CryptographicHasher<P, [PW; 4]> for SerializingHasher64<Inner>
 where
-    P: PackedField,
-    P::F: PrimeField64,
+    P: PackedValue,
+    P::Value: PrimeField64,
     PW: PackedValue<Value = u64>,
     Inner: CryptographicHasher<PW, [PW; 4]>,
 {
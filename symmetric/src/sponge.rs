@@ -9,7 +9,10 @@ use crate::permutation::CryptographicPermutation;
 
 /// A padding-free, overwrite-mode sponge function.
 ///
-/// `WIDTH` is the sponge's rate plus the sponge's capacity.
+/// `WIDTH` is the sponge's rate plus the sponge's capacity. It's generic over any permutation of
+/// `[T; WIDTH]`, so a new permutation gets a hasher by instantiating this rather than writing its
+/// own. The parameters are checked at compile time: the capacity `WIDTH - RATE` must be nonzero,
+/// and the output can be at most `WIDTH` elements.
 #[derive(Clone, Debug)]
 pub struct PaddingFreeSponge<P, const WIDTH: usize, const RATE: usize, const OUT: usize> {
     permutation: P,
@@ -18,9 +21,30 @@ pub struct PaddingFreeSponge<P, const WIDTH: usize, const RATE: usize, const OUT
 impl<P, const WIDTH: usize, const RATE: usize, const OUT: usize>
     PaddingFreeSponge<P, WIDTH, RATE, OUT>
 {
+    const VALID_PARAMS: () = assert!(
+        0 < RATE && RATE < WIDTH && OUT <= WIDTH,
+        "a sponge needs 0 < RATE < WIDTH and OUT <= WIDTH"
+    );
+
     pub const fn new(permutation: P) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_PARAMS;
         Self { permutation }
     }
+
+    /// Start hashing an input which is given a piece at a time. Absorbing the pieces in order and
+    /// then squeezing gives the same digest as `hash_iter` on their concatenation.
+    pub fn absorber<T>(&self) -> SpongeAbsorber<'_, T, P, WIDTH, RATE, OUT>
+    where
+        T: Default + Copy,
+        P: CryptographicPermutation<[T; WIDTH]>,
+    {
+        SpongeAbsorber {
+            permutation: &self.permutation,
+            state: [T::default(); WIDTH],
+            absorbed: 0,
+        }
+    }
 }
 
 impl<T, P, const WIDTH: usize, const RATE: usize, const OUT: usize> CryptographicHasher<T, [T; OUT]>
@@ -33,7 +57,6 @@ where
     where
         I: IntoIterator<Item = T>,
     {
-        // static_assert(RATE < WIDTH)
         let mut state = [T::default(); WIDTH];
         for input_chunk in &input.into_iter().chunks(RATE) {
             state.iter_mut().zip(input_chunk).for_each(|(s, i)| *s = i);
@@ -43,6 +66,43 @@ where
     }
 }
 
+/// The state of a `PaddingFreeSponge` part way through absorbing an input.
+#[derive(Clone, Debug)]
+pub struct SpongeAbsorber<'a, T, P, const WIDTH: usize, const RATE: usize, const OUT: usize> {
+    permutation: &'a P,
+    state: [T; WIDTH],
+    /// The number of elements absorbed into the rate since the last permutation.
+    absorbed: usize,
+}
+
+impl<'a, T, P, const WIDTH: usize, const RATE: usize, const OUT: usize>
+    SpongeAbsorber<'a, T, P, WIDTH, RATE, OUT>
+where
+    T: Default + Copy,
+    P: CryptographicPermutation<[T; WIDTH]>,
+{
+    pub fn absorb<I>(&mut self, input: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for x in input {
+            self.state[self.absorbed] = x;
+            self.absorbed += 1;
+            if self.absorbed == RATE {
+                self.permutation.permute_mut(&mut self.state);
+                self.absorbed = 0;
+            }
+        }
+    }
+
+    pub fn squeeze(mut self) -> [T; OUT] {
+        if self.absorbed > 0 {
+            self.permutation.permute_mut(&mut self.state);
+        }
+        self.state[..OUT].try_into().unwrap()
+    }
+}
+
 /// A padding-free, overwrite-mode sponge function that operates natively over PF but accepts elements
 /// of F: PrimeField32.
 ///
@@ -0,0 +1,78 @@
+use core::iter;
+
+use p3_field::PrimeField64;
+
+use crate::{CryptographicHasher, PseudoCompressionFunction};
+
+/// The byte prepended to the input when hashing a leaf.
+const LEAF_PREFIX: u8 = 0;
+
+/// The byte prepended to the input when compressing two nodes.
+const NODE_PREFIX: u8 = 1;
+
+/// Adapts a byte hasher with an `OUT`-byte digest to hash field elements to `[F; N]`, and to
+/// compress pairs of such digests, so that any such hasher can build Merkle trees over field
+/// digests.
+///
+/// Inputs are serialized as 8-byte little-endian encodings, and each of the `N` output elements is
+/// reduced from 8 bytes of the inner digest, so `8 * N` must be at most `OUT`. Leaves and nodes are
+/// hashed with different prefixes, so a leaf digest can't collide with a node digest.
+#[derive(Copy, Clone, Debug)]
+pub struct TruncatingHasher<Inner, const OUT: usize> {
+    inner: Inner,
+}
+
+impl<Inner, const OUT: usize> TruncatingHasher<Inner, OUT> {
+    pub const fn new(inner: Inner) -> Self {
+        Self { inner }
+    }
+
+    fn hash_prefixed<F, I>(&self, prefix: u8, input: I) -> [u8; OUT]
+    where
+        F: PrimeField64,
+        I: IntoIterator<Item = F>,
+        Inner: CryptographicHasher<u8, [u8; OUT]>,
+    {
+        self.inner.hash_iter(
+            iter::once(prefix).chain(
+                input
+                    .into_iter()
+                    .flat_map(|x| x.as_canonical_u64().to_le_bytes()),
+            ),
+        )
+    }
+}
+
+fn truncate<F: PrimeField64, const N: usize, const OUT: usize>(digest: [u8; OUT]) -> [F; N] {
+    assert!(8 * N <= OUT, "the inner digest is too short for N elements");
+    core::array::from_fn(|i| {
+        F::from_wrapped_u64(u64::from_le_bytes(
+            digest[8 * i..8 * (i + 1)].try_into().unwrap(),
+        ))
+    })
+}
+
+impl<F, Inner, const N: usize, const OUT: usize> CryptographicHasher<F, [F; N]>
+    for TruncatingHasher<Inner, OUT>
+where
+    F: PrimeField64,
+    Inner: CryptographicHasher<u8, [u8; OUT]>,
+{
+    fn hash_iter<I>(&self, input: I) -> [F; N]
+    where
+        I: IntoIterator<Item = F>,
+    {
+        truncate(self.hash_prefixed(LEAF_PREFIX, input))
+    }
+}
+
+impl<F, Inner, const N: usize, const OUT: usize> PseudoCompressionFunction<[F; N], 2>
+    for TruncatingHasher<Inner, OUT>
+where
+    F: PrimeField64,
+    Inner: CryptographicHasher<u8, [u8; OUT]> + Clone,
+{
+    fn compress(&self, input: [[F; N]; 2]) -> [F; N] {
+        truncate(self.hash_prefixed(NODE_PREFIX, input.into_iter().flatten()))
+    }
+}
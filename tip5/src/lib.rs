@@ -0,0 +1,7 @@
+//! The Tip5 permutation over Goldilocks, and hash functions built from it.
+
+#![no_std]
+
+mod tip5;
+
+pub use tip5::*;
@@ -0,0 +1,171 @@
+//! The Tip5 permutation, see: https://eprint.iacr.org/2023/107
+
+use core::array;
+
+use p3_field::{AbstractField, PrimeField64};
+use p3_goldilocks::Goldilocks;
+use p3_mds::util::apply_circulant;
+use p3_symmetric::{CryptographicPermutation, Permutation};
+use rand::distributions::Standard;
+use rand::Rng;
+
+/// The width of the Tip5 state.
+pub const TIP5_WIDTH: usize = 16;
+
+/// The number of state elements which go through the split-and-lookup S-box, rather than the
+/// power map.
+pub const NUM_SPLIT_AND_LOOKUP: usize = 4;
+
+/// The number of rounds.
+pub const NUM_ROUNDS: usize = 5;
+
+/// The first row of the circulant MDS matrix. The Tip5 paper gives its first column,
+/// `[61402, 1108, 28750, ..., 17845]`.
+const MDS_MATRIX_CIRC_ROW: [u64; TIP5_WIDTH] = [
+    61402, 17845, 26798, 59689, 12021, 40901, 41351, 27521, 56951, 12034, 53865, 43244, 7454,
+    33823, 28750, 1108,
+];
+
+/// The byte S-box `x -> (x + 1)^3 - 1` over `Z/257`, which permutes `0..256`.
+const LOOKUP_TABLE: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let x = i as u32 + 1;
+        table[i] = ((x * x % 257) * x % 257 - 1) as u8;
+        i += 1;
+    }
+    table
+};
+
+/// `2^64 mod p`, the Montgomery radix of Goldilocks, and its inverse.
+const MONTY_R: u64 = 0xFFFF_FFFF;
+const MONTY_R_INV: u64 = 0xFFFF_FFFE_0000_0001;
+
+/// The Tip5 permutation over Goldilocks, with a state of 16 elements.
+///
+/// Each of the five rounds applies the split-and-lookup S-box to the first four elements and
+/// `x -> x^7` to the rest, multiplies the state by a circulant MDS matrix, and adds round
+/// constants.
+///
+/// This follows the specification in the Tip5 paper, but it hasn't been checked against Triton
+/// VM's implementation: no reference round constants or test vectors are bundled, so nothing here
+/// establishes that it computes the same permutation, even given the same round constants.
+#[derive(Clone, Debug)]
+pub struct Tip5 {
+    round_constants: [[Goldilocks; TIP5_WIDTH]; NUM_ROUNDS],
+}
+
+impl Tip5 {
+    pub const fn new(round_constants: [[Goldilocks; TIP5_WIDTH]; NUM_ROUNDS]) -> Self {
+        Self { round_constants }
+    }
+
+    pub fn new_from_rng<R: Rng>(rng: &mut R) -> Self {
+        Self::new(array::from_fn(|_| array::from_fn(|_| rng.sample(Standard))))
+    }
+
+    /// Decompose the Montgomery form of `x` into bytes, apply the lookup table to each, and
+    /// recompose them. This maps field elements to field elements.
+    fn split_and_lookup(x: Goldilocks) -> Goldilocks {
+        let monty =
+            (x.as_canonical_u64() as u128 * MONTY_R as u128 % Goldilocks::ORDER_U64 as u128) as u64;
+        let looked_up = monty.to_le_bytes().map(|byte| LOOKUP_TABLE[byte as usize]);
+        Goldilocks::from_wrapped_u64(u64::from_le_bytes(looked_up))
+            * Goldilocks::from_canonical_u64(MONTY_R_INV)
+    }
+
+    fn sbox_layer(state: &mut [Goldilocks; TIP5_WIDTH]) {
+        let (lookup, power) = state.split_at_mut(NUM_SPLIT_AND_LOOKUP);
+        lookup
+            .iter_mut()
+            .for_each(|x| *x = Self::split_and_lookup(*x));
+        power.iter_mut().for_each(|x| *x = x.exp_const_u64::<7>());
+    }
+}
+
+impl Permutation<[Goldilocks; TIP5_WIDTH]> for Tip5 {
+    fn permute_mut(&self, state: &mut [Goldilocks; TIP5_WIDTH]) {
+        for round_constants in &self.round_constants {
+            Self::sbox_layer(state);
+            *state = apply_circulant(&MDS_MATRIX_CIRC_ROW, *state);
+            for (x, rc) in state.iter_mut().zip(round_constants) {
+                *x += *rc;
+            }
+        }
+    }
+}
+
+impl CryptographicPermutation<[Goldilocks; TIP5_WIDTH]> for Tip5 {}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::Field;
+    use p3_symmetric::{CryptographicHasher, PaddingFreeSponge};
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+
+    type F = Goldilocks;
+
+    #[test]
+    fn lookup_table_is_a_permutation() {
+        let mut seen = [false; 256];
+        for byte in LOOKUP_TABLE {
+            assert!(!seen[byte as usize]);
+            seen[byte as usize] = true;
+        }
+        assert_eq!(LOOKUP_TABLE[0], 0);
+        assert_eq!(LOOKUP_TABLE[255], 255);
+    }
+
+    #[test]
+    fn monty_radix() {
+        let r = F::from_canonical_u64(MONTY_R);
+        assert_eq!(r, F::two().exp_u64(64));
+        assert_eq!(r.inverse(), F::from_canonical_u64(MONTY_R_INV));
+    }
+
+    #[test]
+    fn split_and_lookup_fixes_zero_and_one() {
+        // The Montgomery form of 1 is R, whose bytes are all 0 or 255.
+        assert_eq!(Tip5::split_and_lookup(F::zero()), F::zero());
+        assert_eq!(Tip5::split_and_lookup(F::one()), F::one());
+    }
+
+    #[test]
+    fn mds_matches_first_column() {
+        let first_column = [
+            61402, 1108, 28750, 33823, 7454, 43244, 53865, 12034, 56951, 27521, 41351, 40901,
+            12021, 59689, 26798, 17845,
+        ];
+        let input: [F; TIP5_WIDTH] = thread_rng().gen();
+        let output = apply_circulant(&MDS_MATRIX_CIRC_ROW, input);
+        for (i, out) in output.into_iter().enumerate() {
+            let expected = (0..TIP5_WIDTH)
+                .map(|j| {
+                    F::from_canonical_u64(first_column[(i + TIP5_WIDTH - j) % TIP5_WIDTH])
+                        * input[j]
+                })
+                .sum::<F>();
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn tip5_sponge() {
+        let tip5 = Tip5::new_from_rng(&mut thread_rng());
+        let sponge = PaddingFreeSponge::<_, TIP5_WIDTH, 10, 5>::new(tip5.clone());
+
+        let input: [F; 10] = thread_rng().gen();
+        let mut state = [F::zero(); TIP5_WIDTH];
+        state[..10].copy_from_slice(&input);
+        assert_eq!(sponge.hash_iter(input), tip5.permute(state)[..5]);
+
+        let input: [F; 23] = thread_rng().gen();
+        let mut absorber = sponge.absorber();
+        absorber.absorb(input[..7].iter().copied());
+        absorber.absorb(input[7..].iter().copied());
+        assert_eq!(absorber.squeeze(), sponge.hash_iter(input));
+    }
+}
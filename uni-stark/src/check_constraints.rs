@@ -1,6 +1,6 @@
 use alloc::vec::Vec;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues};
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, PairBuilder};
 use p3_field::Field;
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 use p3_matrix::stack::VerticalPair;
@@ -14,6 +14,7 @@ where
     A: for<'a> Air<DebugConstraintBuilder<'a, F>>,
 {
     let height = main.height();
+    let preprocessed_trace = air.preprocessed_trace();
 
     (0..height).for_each(|i| {
         let i_next = (i + 1) % height;
@@ -25,9 +26,19 @@ where
             RowMajorMatrixView::new_row(&*next),
         );
 
+        let (preprocessed_local, preprocessed_next) = preprocessed_trace
+            .as_ref()
+            .map(|p| (p.row_slice(i).to_vec(), p.row_slice(i_next).to_vec()))
+            .unwrap_or_default();
+        let preprocessed = VerticalPair::new(
+            RowMajorMatrixView::new_row(&preprocessed_local),
+            RowMajorMatrixView::new_row(&preprocessed_next),
+        );
+
         let mut builder = DebugConstraintBuilder {
             row_index: i,
             main,
+            preprocessed,
             public_values,
             is_first_row: F::from_bool(i == 0),
             is_last_row: F::from_bool(i == height - 1),
@@ -44,6 +55,7 @@ where
 pub struct DebugConstraintBuilder<'a, F: Field> {
     row_index: usize,
     main: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+    preprocessed: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
     public_values: &'a [F],
     is_first_row: F,
     is_last_row: F,
@@ -106,3 +118,9 @@ impl<'a, F: Field> AirBuilderWithPublicValues for DebugConstraintBuilder<'a, F>
         self.public_values
     }
 }
+
+impl<'a, F: Field> PairBuilder for DebugConstraintBuilder<'a, F> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
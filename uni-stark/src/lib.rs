@@ -6,6 +6,7 @@ extern crate alloc;
 
 mod config;
 mod folder;
+mod preprocessed;
 mod proof;
 mod prover;
 mod symbolic_builder;
@@ -21,6 +22,7 @@ mod check_constraints;
 pub use check_constraints::*;
 pub use config::*;
 pub use folder::*;
+pub use preprocessed::*;
 pub use proof::*;
 pub use prover::*;
 pub use symbolic_builder::*;
@@ -28,3 +30,8 @@ pub use symbolic_expression::*;
 pub use symbolic_variable::*;
 pub use verifier::*;
 pub use zerofier_coset::*;
+
+// The index of each commitment stage, observed along with the stage's commitment.
+const PREPROCESSED_STAGE: usize = 0;
+const TRACE_STAGE: usize = 1;
+const QUOTIENT_STAGE: usize = 2;
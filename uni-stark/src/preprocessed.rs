@@ -0,0 +1,90 @@
+use alloc::vec;
+
+use p3_air::BaseAir;
+use p3_commit::Pcs;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::proof::{Com, PcsProverData};
+use crate::{StarkGenericConfig, Val};
+
+/// The prover's side of a committed preprocessed trace, which can be computed once with
+/// `setup_preprocessed` and reused for every proof of the same AIR and degree.
+pub struct PreprocessedProverData<SC: StarkGenericConfig> {
+    pub(crate) width: usize,
+    pub(crate) degree_bits: usize,
+    pub(crate) commitment: Com<SC>,
+    pub(crate) prover_data: PcsProverData<SC>,
+}
+
+/// The verifier's side of a committed preprocessed trace: its commitment, which stands in for the
+/// fixed columns themselves.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PreprocessedVerifierKey<SC: StarkGenericConfig> {
+    pub(crate) width: usize,
+    pub(crate) degree_bits: usize,
+    pub(crate) commitment: Com<SC>,
+}
+
+impl<SC: StarkGenericConfig> Clone for PreprocessedVerifierKey<SC> {
+    fn clone(&self) -> Self {
+        Self {
+            width: self.width,
+            degree_bits: self.degree_bits,
+            commitment: self.commitment.clone(),
+        }
+    }
+}
+
+/// Commit to the preprocessed trace of `air`, if it has one, over the trace domain of size
+/// `2^degree_bits`.
+///
+/// # Panics
+/// Panics if the preprocessed trace doesn't have `2^degree_bits` rows.
+#[instrument(skip_all)]
+pub fn setup_preprocessed<SC, A>(
+    config: &SC,
+    air: &A,
+    degree_bits: usize,
+) -> Option<(PreprocessedProverData<SC>, PreprocessedVerifierKey<SC>)>
+where
+    SC: StarkGenericConfig,
+    A: BaseAir<Val<SC>>,
+{
+    let preprocessed = air.preprocessed_trace()?;
+    assert_eq!(
+        preprocessed.height(),
+        1 << degree_bits,
+        "the preprocessed trace must be as tall as the main trace"
+    );
+    Some(commit_preprocessed(config, preprocessed, degree_bits))
+}
+
+/// Commit to a preprocessed trace of `2^degree_bits` rows.
+pub(crate) fn commit_preprocessed<SC: StarkGenericConfig>(
+    config: &SC,
+    preprocessed: RowMajorMatrix<Val<SC>>,
+    degree_bits: usize,
+) -> (PreprocessedProverData<SC>, PreprocessedVerifierKey<SC>) {
+    let degree = 1 << degree_bits;
+    let width = preprocessed.width();
+
+    let pcs = config.pcs();
+    let domain = pcs.natural_domain_for_degree(degree);
+    let (commitment, prover_data) = pcs.commit(vec![(domain, preprocessed)]);
+    let vk = PreprocessedVerifierKey {
+        width,
+        degree_bits,
+        commitment: commitment.clone(),
+    };
+    let prover_data = PreprocessedProverData {
+        width,
+        degree_bits,
+        commitment,
+        prover_data,
+    };
+    (prover_data, vk)
+}
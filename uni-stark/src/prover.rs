@@ -15,8 +15,9 @@ use tracing::{info_span, instrument};
 
 use crate::symbolic_builder::{get_log_quotient_degree, SymbolicAirBuilder};
 use crate::{
-    Commitments, Domain, OpenedValues, PackedChallenge, PackedVal, Proof, ProverConstraintFolder,
-    StarkGenericConfig, Val,
+    setup_preprocessed, Commitments, Domain, OpenedValues, PackedChallenge, PackedVal,
+    PreprocessedProverData, Proof, ProverConstraintFolder, StarkGenericConfig, Val,
+    PREPROCESSED_STAGE, QUOTIENT_STAGE, TRACE_STAGE,
 };
 
 #[instrument(skip_all)]
@@ -32,6 +33,41 @@ pub fn prove<
     trace: RowMajorMatrix<Val<SC>>,
     public_values: &Vec<Val<SC>>,
 ) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    let degree_bits = log2_strict_usize(trace.height());
+    let preprocessed = setup_preprocessed(config, air, degree_bits).map(|(data, _)| data);
+    prove_with_preprocessed(
+        config,
+        air,
+        challenger,
+        trace,
+        public_values,
+        preprocessed.as_ref(),
+    )
+}
+
+/// Like `prove`, but with the AIR's preprocessed trace, if any, already committed by
+/// `setup_preprocessed`.
+///
+/// # Panics
+/// Panics if `preprocessed` was committed for a degree other than the height of `trace`.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_preprocessed<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed: Option<&PreprocessedProverData<SC>>,
+) -> Proof<SC>
 where
     SC: StarkGenericConfig,
     A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
@@ -42,27 +78,38 @@ where
     let degree = trace.height();
     let log_degree = log2_strict_usize(degree);
 
-    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(air, 0, public_values.len());
+    let preprocessed_width = preprocessed.map_or(0, |p| p.width);
+    if let Some(preprocessed) = preprocessed {
+        assert_eq!(
+            preprocessed.degree_bits, log_degree,
+            "the preprocessed trace was committed for another degree than the trace's"
+        );
+    }
+    let log_quotient_degree =
+        get_log_quotient_degree::<Val<SC>, A>(air, preprocessed_width, public_values.len());
     let quotient_degree = 1 << log_quotient_degree;
 
     let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(degree);
 
-    let (trace_commit, trace_data) =
-        info_span!("commit to trace data").in_scope(|| pcs.commit(vec![(trace_domain, trace)]));
-
     // Observe the instance.
-    challenger.observe(Val::<SC>::from_canonical_usize(log_degree));
+    challenger.observe_usize(log_degree);
+    challenger.observe_slice(public_values);
+    if let Some(preprocessed) = preprocessed {
+        pcs.observe_stage(PREPROCESSED_STAGE, &preprocessed.commitment, challenger);
+    }
     // TODO: Might be best practice to include other instance data here; see verifier comment.
 
-    challenger.observe(trace_commit.clone());
-    challenger.observe_slice(public_values);
+    let (trace_commit, trace_data) = info_span!("commit to trace data")
+        .in_scope(|| pcs.commit_stage(TRACE_STAGE, vec![(trace_domain, trace)], challenger));
     let alpha: SC::Challenge = challenger.sample_ext_element();
 
     let quotient_domain =
         trace_domain.create_disjoint_domain(1 << (log_degree + log_quotient_degree));
 
     let trace_on_quotient_domain = pcs.get_evaluations_on_domain(&trace_data, 0, quotient_domain);
+    let preprocessed_on_quotient_domain =
+        preprocessed.map(|p| pcs.get_evaluations_on_domain(&p.prover_data, 0, quotient_domain));
 
     let quotient_values = quotient_values(
         air,
@@ -70,15 +117,21 @@ where
         trace_domain,
         quotient_domain,
         trace_on_quotient_domain,
+        preprocessed_on_quotient_domain,
         alpha,
     );
     let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
     let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
     let qc_domains = quotient_domain.split_domains(quotient_degree);
 
-    let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
-        .in_scope(|| pcs.commit(izip!(qc_domains, quotient_chunks).collect_vec()));
-    challenger.observe(quotient_commit.clone());
+    let (quotient_commit, quotient_data) =
+        info_span!("commit to quotient poly chunks").in_scope(|| {
+            pcs.commit_stage(
+                QUOTIENT_STAGE,
+                izip!(qc_domains.clone(), quotient_chunks).collect_vec(),
+                challenger,
+            )
+        });
 
     let commitments = Commitments {
         trace: trace_commit,
@@ -86,25 +139,44 @@ where
     };
 
     let zeta: SC::Challenge = challenger.sample();
-    let zeta_next = trace_domain.next_point(zeta).unwrap();
 
-    let (opened_values, opening_proof) = pcs.open(
-        vec![
-            (&trace_data, vec![vec![zeta, zeta_next]]),
+    let mut rounds = vec![
+        // open the trace at zeta and its next point
+        (&trace_data, vec![(trace_domain, vec![0, 1])]),
+        (
+            &quotient_data,
+            // open every chunk at zeta
+            qc_domains
+                .into_iter()
+                .map(|domain| (domain, vec![0]))
+                .collect_vec(),
+        ),
+    ];
+    if let Some(preprocessed) = preprocessed {
+        // open the preprocessed trace at zeta and its next point, like the main trace
+        rounds.push((&preprocessed.prover_data, vec![(trace_domain, vec![0, 1])]));
+    }
+    let (opened_values, opening_proof) = pcs.open_rotations(rounds, zeta, challenger);
+    let trace_local = opened_values[0][0][0].clone().into_vec();
+    let trace_next = opened_values[0][0][1].clone().into_vec();
+    let quotient_chunks = opened_values[1]
+        .iter()
+        .map(|v| v[0].clone().into_vec())
+        .collect_vec();
+    let (preprocessed_local, preprocessed_next) = opened_values
+        .round(2)
+        .map(|round| {
             (
-                &quotient_data,
-                // open every chunk at zeta
-                (0..quotient_degree).map(|_| vec![zeta]).collect_vec(),
-            ),
-        ],
-        challenger,
-    );
-    let trace_local = opened_values[0][0][0].clone();
-    let trace_next = opened_values[0][0][1].clone();
-    let quotient_chunks = opened_values[1].iter().map(|v| v[0].clone()).collect_vec();
+                round[0][0].clone().into_vec(),
+                round[0][1].clone().into_vec(),
+            )
+        })
+        .unwrap_or_default();
     let opened_values = OpenedValues {
         trace_local,
         trace_next,
+        preprocessed_local,
+        preprocessed_next,
         quotient_chunks,
     };
     Proof {
@@ -116,21 +188,26 @@ where
 }
 
 #[instrument(name = "compute quotient polynomial", skip_all)]
-fn quotient_values<SC, A, Mat>(
+fn quotient_values<SC, A, Mat, PreprocessedMat>(
     air: &A,
     public_values: &Vec<Val<SC>>,
     trace_domain: Domain<SC>,
     quotient_domain: Domain<SC>,
     trace_on_quotient_domain: Mat,
+    preprocessed_on_quotient_domain: Option<PreprocessedMat>,
     alpha: SC::Challenge,
 ) -> Vec<SC::Challenge>
 where
     SC: StarkGenericConfig,
     A: for<'a> Air<ProverConstraintFolder<'a, SC>>,
     Mat: Matrix<Val<SC>> + Sync,
+    PreprocessedMat: Matrix<Val<SC>> + Sync,
 {
     let quotient_size = quotient_domain.size();
     let width = trace_on_quotient_domain.width();
+    let preprocessed_width = preprocessed_on_quotient_domain
+        .as_ref()
+        .map_or(0, |p| p.width());
     let mut sels = trace_domain.selectors_on_coset(quotient_domain);
 
     let qdb = log2_strict_usize(quotient_domain.size()) - log2_strict_usize(trace_domain.size());
@@ -163,10 +240,22 @@ where
                     .collect_vec(),
                 width,
             );
+            let preprocessed = RowMajorMatrix::new(
+                preprocessed_on_quotient_domain
+                    .iter()
+                    .flat_map(|p| {
+                        iter::empty()
+                            .chain(p.vertically_packed_row::<PackedVal<SC>>(i_start))
+                            .chain(p.vertically_packed_row::<PackedVal<SC>>(i_start + next_step))
+                    })
+                    .collect_vec(),
+                preprocessed_width,
+            );
 
             let accumulator = PackedChallenge::<SC>::zero();
             let mut folder = ProverConstraintFolder {
                 main,
+                preprocessed,
                 public_values,
                 is_first_row,
                 is_last_row,
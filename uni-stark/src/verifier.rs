@@ -8,11 +8,21 @@ use p3_commit::{Pcs, PolynomialSpace};
 use p3_field::{AbstractExtensionField, AbstractField, Field};
 use p3_matrix::dense::RowMajorMatrixView;
 use p3_matrix::stack::VerticalPair;
+use p3_matrix::Matrix;
 use tracing::instrument;
 
+use crate::preprocessed::commit_preprocessed;
 use crate::symbolic_builder::{get_log_quotient_degree, SymbolicAirBuilder};
-use crate::{Proof, StarkGenericConfig, Val, VerifierConstraintFolder};
-
+use crate::{
+    PreprocessedVerifierKey, Proof, StarkGenericConfig, Val, VerifierConstraintFolder,
+    PREPROCESSED_STAGE, QUOTIENT_STAGE, TRACE_STAGE,
+};
+
+/// Verify `proof`, committing to the AIR's preprocessed trace, if any, over the trace domain the
+/// proof claims.
+///
+/// That commitment is recomputed on every call. A verifier checking many proofs should commit once
+/// with `setup_preprocessed`, and pass the verifier key to `verify_with_preprocessed` instead.
 #[instrument(skip_all)]
 pub fn verify<SC, A>(
     config: &SC,
@@ -21,6 +31,43 @@ pub fn verify<SC, A>(
     proof: &Proof<SC>,
     public_values: &Vec<Val<SC>>,
 ) -> Result<(), VerificationError>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let preprocessed_vk = match air.preprocessed_trace() {
+        None => None,
+        Some(preprocessed) => {
+            // The claimed degree is unchecked until now, so reject it rather than let a bad one
+            // overflow the shift, or fail the height check in `commit_preprocessed`.
+            let degree_bits = proof.degree_bits;
+            if degree_bits >= usize::BITS as usize || preprocessed.height() != 1 << degree_bits {
+                return Err(VerificationError::InvalidProofShape);
+            }
+            Some(commit_preprocessed(config, preprocessed, degree_bits).1)
+        }
+    };
+    verify_with_preprocessed(
+        config,
+        air,
+        challenger,
+        proof,
+        public_values,
+        preprocessed_vk.as_ref(),
+    )
+}
+
+/// Like `verify`, but with the commitment to the AIR's preprocessed trace, if any, taken from a
+/// verifier key rather than recomputed.
+#[instrument(skip_all)]
+pub fn verify_with_preprocessed<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    public_values: &Vec<Val<SC>>,
+    preprocessed_vk: Option<&PreprocessedVerifierKey<SC>>,
+) -> Result<(), VerificationError>
 where
     SC: StarkGenericConfig,
     A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
@@ -32,9 +79,14 @@ where
         degree_bits,
     } = proof;
 
-    let degree = 1 << degree_bits;
-    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(air, 0, public_values.len());
+    let preprocessed_width = preprocessed_vk.map_or(0, |vk| vk.width);
+    let log_quotient_degree =
+        get_log_quotient_degree::<Val<SC>, A>(air, preprocessed_width, public_values.len());
     let quotient_degree = 1 << log_quotient_degree;
+    if degree_bits + log_quotient_degree >= usize::BITS as usize {
+        return Err(VerificationError::InvalidProofShape);
+    }
+    let degree = 1 << degree_bits;
 
     let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(degree);
@@ -45,6 +97,11 @@ where
     let air_width = <A as BaseAir<Val<SC>>>::width(air);
     let valid_shape = opened_values.trace_local.len() == air_width
         && opened_values.trace_next.len() == air_width
+        && opened_values.preprocessed_local.len() == preprocessed_width
+        && opened_values.preprocessed_next.len() == preprocessed_width
+        && preprocessed_vk
+            .iter()
+            .all(|vk| vk.degree_bits == *degree_bits)
         && opened_values.quotient_chunks.len() == quotient_degree
         && opened_values
             .quotient_chunks
@@ -55,46 +112,58 @@ where
     }
 
     // Observe the instance.
-    challenger.observe(Val::<SC>::from_canonical_usize(proof.degree_bits));
+    challenger.observe_usize(proof.degree_bits);
+    challenger.observe_slice(public_values);
+    if let Some(vk) = preprocessed_vk {
+        pcs.observe_stage(PREPROCESSED_STAGE, &vk.commitment, challenger);
+    }
     // TODO: Might be best practice to include other instance data here in the transcript, like some
     // encoding of the AIR. This protects against transcript collisions between distinct instances.
     // Practically speaking though, the only related known attack is from failing to include public
     // values. It's not clear if failing to include other instance data could enable a transcript
     // collision, since most such changes would completely change the set of satisfying witnesses.
 
-    challenger.observe(commitments.trace.clone());
-    challenger.observe_slice(public_values);
+    pcs.observe_stage(TRACE_STAGE, &commitments.trace, challenger);
     let alpha: SC::Challenge = challenger.sample_ext_element();
-    challenger.observe(commitments.quotient_chunks.clone());
+    pcs.observe_stage(QUOTIENT_STAGE, &commitments.quotient_chunks, challenger);
 
     let zeta: SC::Challenge = challenger.sample();
     let zeta_next = trace_domain.next_point(zeta).unwrap();
 
-    pcs.verify(
-        vec![
-            (
-                commitments.trace.clone(),
-                vec![(
-                    trace_domain,
-                    vec![
-                        (zeta, opened_values.trace_local.clone()),
-                        (zeta_next, opened_values.trace_next.clone()),
-                    ],
-                )],
-            ),
-            (
-                commitments.quotient_chunks.clone(),
-                quotient_chunks_domains
-                    .iter()
-                    .zip(&opened_values.quotient_chunks)
-                    .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
-                    .collect_vec(),
-            ),
-        ],
-        opening_proof,
-        challenger,
-    )
-    .map_err(|_| VerificationError::InvalidOpeningArgument)?;
+    let mut rounds = vec![
+        (
+            commitments.trace.clone(),
+            vec![(
+                trace_domain,
+                vec![
+                    (zeta, opened_values.trace_local.clone().into()),
+                    (zeta_next, opened_values.trace_next.clone().into()),
+                ],
+            )],
+        ),
+        (
+            commitments.quotient_chunks.clone(),
+            quotient_chunks_domains
+                .iter()
+                .zip(&opened_values.quotient_chunks)
+                .map(|(domain, values)| (*domain, vec![(zeta, values.clone().into())]))
+                .collect_vec(),
+        ),
+    ];
+    if let Some(vk) = preprocessed_vk {
+        rounds.push((
+            vk.commitment.clone(),
+            vec![(
+                trace_domain,
+                vec![
+                    (zeta, opened_values.preprocessed_local.clone().into()),
+                    (zeta_next, opened_values.preprocessed_next.clone().into()),
+                ],
+            )],
+        ));
+    }
+    pcs.verify(rounds, opening_proof, challenger)
+        .map_err(|_| VerificationError::InvalidOpeningArgument)?;
 
     let zps = quotient_chunks_domains
         .iter()
@@ -131,8 +200,14 @@ where
         RowMajorMatrixView::new_row(&opened_values.trace_next),
     );
 
+    let preprocessed = VerticalPair::new(
+        RowMajorMatrixView::new_row(&opened_values.preprocessed_local),
+        RowMajorMatrixView::new_row(&opened_values.preprocessed_next),
+    );
+
     let mut folder = VerifierConstraintFolder {
         main,
+        preprocessed,
         public_values,
         is_first_row: sels.is_first_row,
         is_last_row: sels.is_last_row,
@@ -3,11 +3,11 @@ use std::borrow::Borrow;
 use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
 use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
 use p3_challenger::DuplexChallenger;
-use p3_commit::ExtensionMmcs;
+use p3_commit::{ExtensionMmcs, PcsRoute, RoutedPcs, TwoAdicMultiplicativeCoset};
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field, PrimeField64};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, LowDegreeTest, TwoAdicFriPcs};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_merkle_tree::FieldMerkleTreeMmcs;
@@ -110,7 +110,7 @@ type ValMmcs =
 type Challenge = BinomialExtensionField<Val, 4>;
 type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
-type Dft = Radix2DitParallel;
+type Dft = Radix2DitParallel<Val>;
 type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
 
@@ -125,12 +125,16 @@ fn test_public_value() {
     let compress = MyCompress::new(perm.clone());
     let val_mmcs = ValMmcs::new(hash, compress);
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
-    let dft = Dft {};
+    let dft = Dft::default();
     let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
     let fri_config = FriConfig {
         log_blowup: 2,
         num_queries: 28,
         proof_of_work_bits: 8,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings: false,
         mmcs: challenge_mmcs,
     };
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
@@ -146,6 +150,99 @@ fn test_public_value() {
     verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
 }
 
+#[test]
+fn test_verify_with_other_public_value() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear,
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings: false,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    let config = MyConfig::new(pcs);
+    let mut challenger = Challenger::new(perm.clone());
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+
+    // A proof for one claimed output doesn't verify against another.
+    let other_pis = [0, 1, 22].map(BabyBear::from_canonical_u64).to_vec();
+    let mut challenger = Challenger::new(perm);
+    assert!(verify(
+        &config,
+        &FibonacciAir {},
+        &mut challenger,
+        &proof,
+        &other_pis
+    )
+    .is_err());
+}
+
+type Router = fn(&[(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)]) -> PcsRoute;
+type MyRoutedConfig = StarkConfig<RoutedPcs<Pcs, Pcs, Router>, Challenge, Challenger>;
+
+/// Routes the quotient, the only batch of more than one matrix, to the second PCS.
+fn route_quotient_to_second(
+    evaluations: &[(TwoAdicMultiplicativeCoset<Val>, RowMajorMatrix<Val>)],
+) -> PcsRoute {
+    if evaluations.len() > 1 {
+        PcsRoute::Second
+    } else {
+        PcsRoute::First
+    }
+}
+
+#[test]
+fn test_routed_pcs() {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear,
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let pcs = |max_log_arity| {
+        let fri_config = FriConfig {
+            log_blowup: 2,
+            num_queries: 28,
+            proof_of_work_bits: 8,
+            max_log_arity,
+            log_final_poly_len: 0,
+            ldt: LowDegreeTest::Fri,
+            prune_openings: false,
+            mmcs: challenge_mmcs.clone(),
+        };
+        Pcs::new(Dft::default(), val_mmcs.clone(), fri_config)
+    };
+    let config = MyRoutedConfig::new(RoutedPcs::new(
+        pcs(1),
+        pcs(2),
+        route_quotient_to_second as Router,
+    ));
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let pis = [0, 1, 21].map(BabyBear::from_canonical_u64).to_vec();
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
+}
+
 #[cfg(debug_assertions)]
 #[test]
 #[should_panic(expected = "assertion `left == right` failed: constraints had nonzero value")]
@@ -159,11 +256,15 @@ fn test_incorrect_public_value() {
     let compress = MyCompress::new(perm.clone());
     let val_mmcs = ValMmcs::new(hash, compress);
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
-    let dft = Dft {};
+    let dft = Dft::default();
     let fri_config = FriConfig {
         log_blowup: 2,
         num_queries: 28,
         proof_of_work_bits: 8,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings: false,
         mmcs: challenge_mmcs,
     };
     let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
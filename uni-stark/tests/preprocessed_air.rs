@@ -0,0 +1,218 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, PairBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, LowDegreeTest, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::FieldMerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove, prove_with_preprocessed, setup_preprocessed, verify, verify_with_preprocessed, Proof,
+    StarkConfig, VerificationError,
+};
+use rand::thread_rng;
+
+/// Sums a fixed "ROM" column, held in the preprocessed trace, into a running total in the main
+/// trace, and checks the total against a public value.
+struct RomSumAir {
+    rom: Vec<u64>,
+}
+
+impl RomSumAir {
+    fn generate_trace<F: Field>(&self) -> RowMajorMatrix<F> {
+        let mut acc = F::zero();
+        let values = self
+            .rom
+            .iter()
+            .map(|&x| {
+                let current = acc;
+                acc += F::from_canonical_u64(x);
+                current
+            })
+            .collect();
+        RowMajorMatrix::new_col(values)
+    }
+
+    fn total<F: Field>(&self) -> F {
+        self.rom.iter().map(|&x| F::from_canonical_u64(x)).sum()
+    }
+}
+
+impl<F: Field> BaseAir<F> for RomSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            self.rom.iter().map(|&x| F::from_canonical_u64(x)).collect(),
+        ))
+    }
+}
+
+impl<AB: PairBuilder + AirBuilderWithPublicValues> Air<AB> for RomSumAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let preprocessed = builder.preprocessed();
+        let total = builder.public_values()[0];
+
+        let (acc, acc_next) = (main.get(0, 0), main.get(1, 0));
+        let rom = preprocessed.get(0, 0);
+
+        builder.when_first_row().assert_zero(acc);
+        builder.when_transition().assert_eq(acc + rom, acc_next);
+        builder.when_last_row().assert_eq(acc + rom, total);
+    }
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    FieldMerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn setup() -> (MyConfig, Perm) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear,
+        &mut thread_rng(),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 2,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        max_log_arity: 1,
+        log_final_poly_len: 0,
+        ldt: LowDegreeTest::Fri,
+        prune_openings: false,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_config);
+    (MyConfig::new(pcs), perm)
+}
+
+#[test]
+fn prove_and_verify_preprocessed() {
+    let (config, perm) = setup();
+    let air = RomSumAir {
+        rom: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    let trace = air.generate_trace::<Val>();
+    let pis = vec![air.total::<Val>()];
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &pis);
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &air, &mut challenger, &proof, &pis).expect("verification failed");
+}
+
+#[test]
+fn prove_and_verify_with_verifier_key() {
+    let (config, perm) = setup();
+    let air = RomSumAir {
+        rom: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    let trace = air.generate_trace::<Val>();
+    let degree_bits = 3;
+    assert_eq!(trace.height(), 1 << degree_bits);
+    let pis = vec![air.total::<Val>()];
+    let (prover_data, vk) = setup_preprocessed(&config, &air, degree_bits).unwrap();
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_preprocessed(
+        &config,
+        &air,
+        &mut challenger,
+        trace,
+        &pis,
+        Some(&prover_data),
+    );
+
+    let mut challenger = Challenger::new(perm.clone());
+    verify_with_preprocessed(&config, &air, &mut challenger, &proof, &pis, Some(&vk))
+        .expect("verification failed");
+
+    // The proof is bound to the committed ROM, so a key for another ROM with the same total
+    // rejects it.
+    let other_air = RomSumAir {
+        rom: vec![6, 2, 9, 5, 1, 4, 1, 3],
+    };
+    let (_, other_vk) = setup_preprocessed(&config, &other_air, degree_bits).unwrap();
+    let mut challenger = Challenger::new(perm);
+    assert!(verify_with_preprocessed(
+        &config,
+        &other_air,
+        &mut challenger,
+        &proof,
+        &pis,
+        Some(&other_vk)
+    )
+    .is_err());
+}
+
+#[test]
+fn verify_rejects_wrong_degree_bits() {
+    let (config, perm) = setup();
+    let air = RomSumAir {
+        rom: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    let trace = air.generate_trace::<Val>();
+    let pis = vec![air.total::<Val>()];
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, trace, &pis);
+
+    // The degree bits are the last field of the proof, and a single byte for small values.
+    let mut bytes = postcard::to_allocvec(&proof).expect("unable to serialize proof");
+    assert_eq!(bytes.pop(), Some(3));
+    for degree_bits in [4_usize, 200] {
+        let mut tampered = bytes.clone();
+        tampered.extend(postcard::to_allocvec(&degree_bits).unwrap());
+        let tampered: Proof<MyConfig> =
+            postcard::from_bytes(&tampered).expect("unable to deserialize proof");
+        let mut challenger = Challenger::new(perm.clone());
+        assert!(matches!(
+            verify(&config, &air, &mut challenger, &tampered, &pis),
+            Err(VerificationError::InvalidProofShape)
+        ));
+    }
+}
+
+#[test]
+#[should_panic(expected = "another degree")]
+fn prove_rejects_preprocessed_data_of_another_degree() {
+    let (config, perm) = setup();
+    let air = RomSumAir {
+        rom: vec![3, 1, 4, 1, 5, 9, 2, 6],
+    };
+    let taller_air = RomSumAir {
+        rom: [3, 1, 4, 1, 5, 9, 2, 6].repeat(2),
+    };
+    let (prover_data, _) = setup_preprocessed(&config, &taller_air, 4).unwrap();
+    let trace = air.generate_trace::<Val>();
+    let pis = vec![air.total::<Val>()];
+    let mut challenger = Challenger::new(perm);
+    prove_with_preprocessed(
+        &config,
+        &air,
+        &mut challenger,
+        trace,
+        &pis,
+        Some(&prover_data),
+    );
+}